@@ -35,13 +35,14 @@ use aptos_types::{
 use aptos_vm::data_cache::{IntoMoveResolver, StorageAdapter, StorageAdapterOwned};
 use futures::{channel::oneshot, SinkExt};
 use itertools::Itertools;
-use move_core_types::language_storage::{ModuleId, StructTag};
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag};
 use std::sync::RwLock;
 use std::{collections::HashMap, sync::Arc};
 use storage_interface::{
     state_view::{DbStateView, DbStateViewAtVersion, LatestDbStateCheckpointView},
-    DbReader, Order, MAX_REQUEST_LIMIT,
+    DbReader, Order, StateValueChange, MAX_REQUEST_LIMIT,
 };
+use tokio::sync::{watch, OwnedSemaphorePermit, Semaphore};
 
 // Context holds application scope context
 #[derive(Clone)]
@@ -52,6 +53,8 @@ pub struct Context {
     pub node_config: NodeConfig,
     gas_estimation: Arc<RwLock<GasEstimationCache>>,
     gas_schedule_cache: Arc<RwLock<GasScheduleCache>>,
+    commit_notifications: Option<watch::Receiver<Version>>,
+    subscription_permits: Arc<Semaphore>,
 }
 
 impl std::fmt::Debug for Context {
@@ -71,7 +74,6 @@ impl Context {
             chain_id,
             db,
             mp_sender,
-            node_config,
             gas_estimation: Arc::new(RwLock::new(GasEstimationCache {
                 last_updated_version: None,
                 last_updated_epoch: None,
@@ -83,9 +85,34 @@ impl Context {
                 last_updated_epoch: None,
                 gas_schedule_params: None,
             })),
+            commit_notifications: None,
+            subscription_permits: Arc::new(Semaphore::new(node_config.api.max_subscriptions)),
+            node_config,
         }
     }
 
+    /// Provides the latest committed version as it changes, which enables `/subscribe`.
+    pub fn with_commit_notifications(
+        mut self,
+        commit_notifications: watch::Receiver<Version>,
+    ) -> Self {
+        self.commit_notifications = Some(commit_notifications);
+        self
+    }
+
+    pub fn commit_notifications(&self) -> Option<watch::Receiver<Version>> {
+        self.commit_notifications.clone()
+    }
+
+    /// Reserves a slot for a new subscription, if there is one left.
+    pub fn try_acquire_subscription_permit(&self) -> Option<OwnedSemaphorePermit> {
+        self.subscription_permits.clone().try_acquire_owned().ok()
+    }
+
+    pub fn subscription_buffer_size(&self) -> usize {
+        self.node_config.api.subscription_buffer_size
+    }
+
     pub fn max_transactions_page_size(&self) -> u16 {
         self.node_config.api.max_transactions_page_size
     }
@@ -740,6 +767,28 @@ impl Context {
         }
     }
 
+    pub fn get_events_by_type(
+        &self,
+        type_tag: &TypeTag,
+        start_version: u64,
+        limit: u16,
+        ledger_version: u64,
+    ) -> Result<Vec<EventWithVersion>> {
+        self.db
+            .get_events_by_type(type_tag, start_version, limit as u64, ledger_version)
+    }
+
+    pub fn get_state_value_changes(
+        &self,
+        state_key: &StateKey,
+        start_version: u64,
+        limit: u16,
+        ledger_version: u64,
+    ) -> Result<Vec<StateValueChange>> {
+        self.db
+            .get_state_value_changes(state_key, start_version, limit as u64, ledger_version)
+    }
+
     pub fn estimate_gas_price<E: InternalError>(
         &self,
         ledger_info: &LedgerInfo,
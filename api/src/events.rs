@@ -8,7 +8,8 @@ use crate::failpoint::fail_point_poem;
 use crate::page::Page;
 use crate::response::BadRequestError;
 use crate::response::{
-    BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404, InternalError,
+    api_disabled, BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404,
+    InternalError,
 };
 use crate::ApiTags;
 use anyhow::Context as AnyhowContext;
@@ -16,7 +17,8 @@ use aptos_api_types::{
     verify_field_identifier, Address, AptosErrorCode, AsConverter, IdentifierWrapper, LedgerInfo,
     MoveStructTag, VerifyInputWithRecursion, VersionedEvent, U64,
 };
-use aptos_types::event::EventKey;
+use aptos_types::{contract_event::EventWithVersion, event::EventKey};
+use move_core_types::language_storage::{StructTag, TypeTag};
 use poem_openapi::param::Query;
 use poem_openapi::{param::Path, OpenApi};
 use std::sync::Arc;
@@ -135,6 +137,108 @@ impl EventsApi {
         let key = account.find_event_key(event_handle.0, field_name.0.into())?;
         self.list(account.latest_ledger_info, accept_type, page, key)
     }
+
+    /// Get events by type
+    ///
+    /// This API returns events of the given Move type emitted to any account, in
+    /// the order they were committed. It is only available on nodes that maintain
+    /// the optional event by type index.
+    ///
+    /// Events are paged by ledger version. A page never splits the events of a
+    /// single transaction, so the next page can be fetched by setting `start_version`
+    /// to one past the version of the last event returned. A transaction that emitted
+    /// more than `limit` events of the type is returned whole in a page of its own.
+    #[oai(
+        path = "/events/by_type/:event_type",
+        method = "get",
+        operation_id = "get_events_by_type",
+        tag = "ApiTags::Events"
+    )]
+    async fn get_events_by_type(
+        &self,
+        accept_type: AcceptType,
+        /// Move type of the events e.g. `0x1::coin::DepositEvent`
+        event_type: Path<MoveStructTag>,
+        /// Ledger version to start looking for events at, inclusive.
+        ///
+        /// If unspecified, defaults to the oldest ledger version that is not pruned
+        start_version: Query<Option<U64>>,
+        /// Ledger version to stop looking for events at, inclusive.
+        ///
+        /// If unspecified, defaults to the latest ledger version
+        end_version: Query<Option<U64>>,
+        /// Max number of events to retrieve.
+        ///
+        /// If unspecified, defaults to default page size
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<Vec<VersionedEvent>> {
+        event_type
+            .0
+            .verify(0)
+            .context("'event_type' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        fail_point_poem("endpoint_get_events_by_type")?;
+        self.context
+            .check_api_output_enabled("Get events by type", &accept_type)?;
+        if !self.context.node_config.storage.enable_event_by_type_index {
+            return Err(api_disabled("Get events by type"));
+        }
+        let latest_ledger_info = self.context.get_latest_ledger_info()?;
+        let type_tag = TypeTag::Struct(Box::new(
+            StructTag::try_from(event_type.0)
+                .context("'event_type' invalid")
+                .map_err(|err| {
+                    BasicErrorWith404::bad_request_with_code(
+                        err,
+                        AptosErrorCode::InvalidInput,
+                        &latest_ledger_info,
+                    )
+                })?,
+        ));
+
+        let page = Page::new(
+            start_version.0.map(|v| v.0),
+            limit.0,
+            self.context.max_events_page_size(),
+        );
+        let ledger_version = latest_ledger_info.version();
+        let start_version = page
+            .start_option()
+            .unwrap_or(latest_ledger_info.oldest_ledger_version.0);
+        let end_version = end_version
+            .0
+            .map_or(ledger_version, |v| std::cmp::min(v.0, ledger_version));
+        if start_version > end_version {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                &format!(
+                    "Given start_version ({}) is higher than the end version ({})",
+                    start_version, end_version
+                ),
+                AptosErrorCode::InvalidInput,
+                &latest_ledger_info,
+            ));
+        }
+
+        let events = self
+            .context
+            .get_events_by_type(
+                &type_tag,
+                start_version,
+                page.limit(&latest_ledger_info)?,
+                end_version,
+            )
+            .context(format!("Failed to find events by type {}", type_tag))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &latest_ledger_info,
+                )
+            })?;
+        self.render(latest_ledger_info, accept_type, events)
+    }
 }
 
 impl EventsApi {
@@ -163,7 +267,16 @@ impl EventsApi {
                     &latest_ledger_info,
                 )
             })?;
+        self.render(latest_ledger_info, accept_type, events)
+    }
 
+    /// Render events in the requested output format
+    fn render(
+        &self,
+        latest_ledger_info: LedgerInfo,
+        accept_type: AcceptType,
+        events: Vec<EventWithVersion>,
+    ) -> BasicResultWith404<Vec<VersionedEvent>> {
         match accept_type {
             AcceptType::Json => {
                 let resolver = self.context.move_resolver_poem(&latest_ledger_info)?;
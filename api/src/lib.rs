@@ -21,9 +21,11 @@ mod response;
 mod runtime;
 mod set_failpoints;
 mod state;
+mod subscriptions;
 #[cfg(test)]
 pub mod tests;
 mod transactions;
+mod view_function;
 
 /// API categories for the OpenAPI spec
 #[derive(Tags)]
@@ -39,11 +41,17 @@ pub enum ApiTags {
     /// General information
     General,
 
+    /// Push based subscriptions to commits
+    Subscriptions,
+
     /// Access to tables
     Tables,
 
     /// Access to transactions
     Transactions,
+
+    /// View functions
+    View,
 }
 
 // Note: Many of these exports are just for the test-context crate, which is
@@ -6,13 +6,16 @@ use std::{net::SocketAddr, sync::Arc};
 use crate::{
     accounts::AccountsApi, basic::BasicApi, blocks::BlocksApi, check_size::PostSizeLimit,
     context::Context, error_converter::convert_error, events::EventsApi, index::IndexApi,
-    log::middleware_log, set_failpoints, state::StateApi, transactions::TransactionsApi,
+    log::middleware_log, set_failpoints, state::StateApi, subscriptions::SubscriptionsApi,
+    transactions::TransactionsApi, view_function::ViewFunctionApi,
 };
 use anyhow::Context as AnyhowContext;
 use aptos_config::config::NodeConfig;
 use aptos_logger::info;
 use aptos_mempool::MempoolClientSender;
-use aptos_types::chain_id::ChainId;
+use aptos_types::{chain_id::ChainId, transaction::Version};
+use event_notifications::EventNotificationListener;
+use futures::StreamExt;
 use poem::{
     http::{header, Method},
     listener::{Listener, RustlsCertificate, RustlsConfig, TcpListener},
@@ -22,7 +25,10 @@ use poem::{
 use poem_openapi::{ContactObject, LicenseObject, OpenApiService};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use storage_interface::DbReader;
-use tokio::runtime::{Builder, Handle, Runtime};
+use tokio::{
+    runtime::{Builder, Handle, Runtime},
+    sync::watch,
+};
 
 const VERSION: &str = include_str!("../doc/.version");
 
@@ -32,6 +38,7 @@ pub fn bootstrap(
     chain_id: ChainId,
     db: Arc<dyn DbReader>,
     mp_sender: MempoolClientSender,
+    commit_listener: Option<EventNotificationListener>,
 ) -> anyhow::Result<Runtime> {
     let runtime = Builder::new_multi_thread()
         .thread_name_fn(|| {
@@ -44,7 +51,18 @@ pub fn bootstrap(
         .build()
         .context("[api] failed to create runtime")?;
 
-    let context = Context::new(chain_id, db, mp_sender, config.clone());
+    let mut context = Context::new(chain_id, db.clone(), mp_sender, config.clone());
+    if let Some(commit_listener) = commit_listener {
+        let latest_version = db
+            .get_latest_ledger_info_option()
+            .context("[api] failed to read the latest ledger info")?
+            .map_or(0, |li| li.ledger_info().version());
+        context = context.with_commit_notifications(watch_commits(
+            runtime.handle(),
+            commit_listener,
+            latest_version,
+        ));
+    }
 
     attach_poem_to_runtime(runtime.handle(), context, config, false)
         .context("Failed to attach poem to runtime")?;
@@ -52,6 +70,24 @@ pub fn bootstrap(
     Ok(runtime)
 }
 
+/// Tracks the latest committed version from the commit notifications of state sync. Every block
+/// emits a `NewBlockEvent`, so listening to that event stream is enough to see every commit.
+fn watch_commits(
+    runtime_handle: &Handle,
+    mut commit_listener: EventNotificationListener,
+    latest_version: Version,
+) -> watch::Receiver<Version> {
+    let (sender, receiver) = watch::channel(latest_version);
+    runtime_handle.spawn(async move {
+        while let Some(notification) = commit_listener.next().await {
+            if sender.send(notification.version).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
 // TODOs regarding spec generation:
 // TODO: https://github.com/aptos-labs/aptos-core/issues/2280
 // TODO: https://github.com/poem-web/poem/issues/321
@@ -69,7 +105,9 @@ pub fn get_api_service(
         EventsApi,
         IndexApi,
         StateApi,
+        SubscriptionsApi,
         TransactionsApi,
+        ViewFunctionApi,
     ),
     (),
 > {
@@ -93,7 +131,13 @@ pub fn get_api_service(
         StateApi {
             context: context.clone(),
         },
-        TransactionsApi { context },
+        SubscriptionsApi {
+            context: context.clone(),
+        },
+        TransactionsApi {
+            context: context.clone(),
+        },
+        ViewFunctionApi { context },
     );
 
     let version = VERSION.to_string();
@@ -237,6 +281,7 @@ mod tests {
             ChainId::test(),
             context.db.clone(),
             context.mempool.ac_client.clone(),
+            None,
         );
         assert!(ret.is_ok());
 
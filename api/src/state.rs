@@ -1,9 +1,10 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::page::determine_limit;
 use crate::response::{
     api_disabled, build_not_found, module_not_found, resource_not_found, table_item_not_found,
-    StdApiError,
+    version_pruned, StdApiError,
 };
 use crate::{
     accept_type::AcceptType,
@@ -18,12 +19,13 @@ use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
     verify_module_identifier, Address, AptosErrorCode, AsConverter, IdentifierWrapper, LedgerInfo,
     MoveModuleBytecode, MoveResource, MoveStructTag, MoveValue, RawTableItemRequest,
-    TableItemRequest, VerifyInput, VerifyInputWithRecursion, U64,
+    ResourceChange, ResourceChangeBcs, StateValueWithProof, TableItemRequest, VerifyInput,
+    VerifyInputWithRecursion, U64,
 };
 use aptos_state_view::StateView;
 use aptos_types::{
     access_path::AccessPath,
-    state_store::{state_key::StateKey, table::TableHandle},
+    state_store::{state_key::StateKey, state_value::StateValue, table::TableHandle},
 };
 use aptos_vm::data_cache::AsMoveResolver;
 use move_core_types::language_storage::{ModuleId, ResourceKey, StructTag};
@@ -66,6 +68,12 @@ impl StateApi {
         ///
         /// If not provided, it will be the latest version
         ledger_version: Query<Option<U64>>,
+        /// If true, return the resource as a BCS encoded `StateValueWithProof`, which proves
+        /// the absence of the resource instead of returning 404 if there is none
+        ///
+        /// Only supported for BCS, and only at ledger versions whose state snapshot is persisted.
+        /// Without a ledger version, the latest such version is used
+        with_proof: Query<Option<bool>>,
     ) -> BasicResultWith404<MoveResource> {
         resource_type
             .0
@@ -77,11 +85,66 @@ impl StateApi {
         fail_point_poem("endpoint_get_account_resource")?;
         self.context
             .check_api_output_enabled("Get account resource", &accept_type)?;
+        let with_proof = verify_with_proof(&accept_type, with_proof.0)?;
         self.resource(
             &accept_type,
             address.0,
             resource_type.0,
             ledger_version.0.map(|inner| inner.0),
+            with_proof,
+        )
+    }
+
+    /// Get account resource changes
+    ///
+    /// Retrieves the changes made to a resource of a given account by the transactions in a range
+    /// of ledger versions, along with the resource before and after each change. This allows
+    /// e.g. following the balance of an account without requesting it at every version.
+    ///
+    /// The range starts at `start_version` and covers `limit` transactions, the next page starts
+    /// at `start_version + limit`. Versions at which the resource didn't change are skipped, so
+    /// a page may be empty.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/accounts/:address/resource/:resource_type/changes",
+        method = "get",
+        operation_id = "get_account_resource_changes",
+        tag = "ApiTags::Accounts"
+    )]
+    async fn get_account_resource_changes(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Name of struct to retrieve the changes of e.g. `0x1::account::Account`
+        resource_type: Path<MoveStructTag>,
+        /// Ledger version to start looking for changes at, inclusive.
+        ///
+        /// If unspecified, defaults to the oldest ledger version that is not pruned
+        start_version: Query<Option<U64>>,
+        /// Number of transactions to look for changes in.
+        ///
+        /// If unspecified, defaults to the max transactions page size
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<Vec<ResourceChange>> {
+        resource_type
+            .0
+            .verify(0)
+            .context("'resource_type' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        fail_point_poem("endpoint_get_account_resource_changes")?;
+        self.context
+            .check_api_output_enabled("Get account resource changes", &accept_type)?;
+        self.resource_changes(
+            &accept_type,
+            address.0,
+            resource_type.0,
+            start_version.0.map(|inner| inner.0),
+            limit.0,
         )
     }
 
@@ -150,6 +213,12 @@ impl StateApi {
         ///
         /// If not provided, it will be the latest version
         ledger_version: Query<Option<U64>>,
+        /// If true, return the table item as a BCS encoded `StateValueWithProof`, which proves
+        /// the absence of the table item instead of returning 404 if there is none
+        ///
+        /// Only supported for BCS, and only at ledger versions whose state snapshot is persisted.
+        /// Without a ledger version, the latest such version is used
+        with_proof: Query<Option<bool>>,
     ) -> BasicResultWith404<MoveValue> {
         table_item_request
             .0
@@ -161,11 +230,13 @@ impl StateApi {
         fail_point_poem("endpoint_get_table_item")?;
         self.context
             .check_api_output_enabled("Get table item", &accept_type)?;
+        let with_proof = verify_with_proof(&accept_type, with_proof.0)?;
         self.table_item(
             &accept_type,
             table_handle.0,
             table_item_request.0,
             ledger_version.0,
+            with_proof,
         )
     }
 
@@ -239,6 +310,7 @@ impl StateApi {
         address: Address,
         resource_type: MoveStructTag,
         ledger_version: Option<u64>,
+        with_proof: bool,
     ) -> BasicResultWith404<MoveResource> {
         let resource_type: StructTag = resource_type
             .try_into()
@@ -246,10 +318,15 @@ impl StateApi {
             .map_err(|err| {
                 BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
             })?;
+        let requested_version = ledger_version;
         let (ledger_info, ledger_version, state_view) = self.preprocess_request(ledger_version)?;
         let resource_key = ResourceKey::new(address.into(), resource_type.clone());
         let access_path = AccessPath::resource_access_path(resource_key);
         let state_key = StateKey::AccessPath(access_path);
+        if with_proof {
+            let proof = self.state_value_with_proof(&state_key, requested_version, &ledger_info)?;
+            return BasicResponse::try_from_bcs((proof, &ledger_info, BasicResponseStatus::Ok));
+        }
         let bytes = state_view
             .get_state_value(&state_key)
             .context(format!("Failed to query DB to check for {:?}", state_key))
@@ -287,6 +364,101 @@ impl StateApi {
         }
     }
 
+    /// Read the changes to a resource in a range of ledger versions
+    ///
+    /// JSON: Convert the values to MoveResource
+    /// BCS: Leave the values encoded as the resource
+    fn resource_changes(
+        &self,
+        accept_type: &AcceptType,
+        address: Address,
+        resource_type: MoveStructTag,
+        start_version: Option<u64>,
+        limit: Option<u16>,
+    ) -> BasicResultWith404<Vec<ResourceChange>> {
+        let resource_type: StructTag = resource_type
+            .try_into()
+            .context("Failed to parse given resource type")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let max_page_size = self.context.max_transactions_page_size();
+        let limit = determine_limit(limit, max_page_size, max_page_size, &ledger_info)?;
+        let start_version = start_version.unwrap_or(ledger_info.oldest_ledger_version.0);
+        if start_version > ledger_info.version() {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                &format!(
+                    "Given start_version ({}) is higher than the current ledger version ({})",
+                    start_version,
+                    ledger_info.version()
+                ),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+        if start_version < ledger_info.oldest_ledger_version.0 {
+            return Err(version_pruned(start_version, &ledger_info));
+        }
+
+        let resource_key = ResourceKey::new(address.into(), resource_type.clone());
+        let state_key = StateKey::AccessPath(AccessPath::resource_access_path(resource_key));
+        let changes = self
+            .context
+            .get_state_value_changes(&state_key, start_version, limit, ledger_info.version())
+            .context(format!("Failed to query DB for changes to {:?}", state_key))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+
+        match accept_type {
+            AcceptType::Json => {
+                let resolver = self.context.move_resolver_poem(&ledger_info)?;
+                let converter = resolver.as_converter(self.context.db.clone());
+                let try_into_resource = |value: Option<StateValue>| {
+                    value
+                        .map(|value| converter.try_into_resource(&resource_type, value.bytes()))
+                        .transpose()
+                };
+                let changes = changes
+                    .into_iter()
+                    .map(|change| {
+                        Ok(ResourceChange {
+                            version: change.version.into(),
+                            before: try_into_resource(change.before)?,
+                            after: try_into_resource(change.after)?,
+                        })
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()
+                    .context("Failed to deserialize resource data retrieved from DB")
+                    .map_err(|err| {
+                        BasicErrorWith404::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            &ledger_info,
+                        )
+                    })?;
+
+                BasicResponse::try_from_json((changes, &ledger_info, BasicResponseStatus::Ok))
+            }
+            AcceptType::Bcs => {
+                let changes: Vec<_> = changes
+                    .into_iter()
+                    .map(|change| ResourceChangeBcs {
+                        version: change.version,
+                        before: change.before.map(StateValue::into_bytes),
+                        after: change.after.map(StateValue::into_bytes),
+                    })
+                    .collect();
+                BasicResponse::try_from_bcs((changes, &ledger_info, BasicResponseStatus::Ok))
+            }
+        }
+    }
+
     /// Retrieve the module
     ///
     /// JSON: Parse ABI and bytecode
@@ -345,6 +517,7 @@ impl StateApi {
         table_handle: Address,
         table_item_request: TableItemRequest,
         ledger_version: Option<U64>,
+        with_proof: bool,
     ) -> BasicResultWith404<MoveValue> {
         // Parse the key and value types for the table
         let key_type = table_item_request
@@ -364,8 +537,9 @@ impl StateApi {
             })?;
 
         // Retrieve local state
+        let requested_version = ledger_version.map(|inner| inner.0);
         let (ledger_info, ledger_version, state_view) =
-            self.preprocess_request(ledger_version.map(|inner| inner.0))?;
+            self.preprocess_request(requested_version)?;
 
         let resolver = state_view.as_move_resolver();
         let converter = resolver.as_converter(self.context.db.clone());
@@ -390,6 +564,10 @@ impl StateApi {
 
         // Retrieve value from the state key
         let state_key = StateKey::table_item(TableHandle(table_handle.into()), raw_key);
+        if with_proof {
+            let proof = self.state_value_with_proof(&state_key, requested_version, &ledger_info)?;
+            return BasicResponse::try_from_bcs((proof, &ledger_info, BasicResponseStatus::Ok));
+        }
         let bytes = state_view
             .get_state_value(&state_key)
             .context(format!(
@@ -476,4 +654,93 @@ impl StateApi {
             }
         }
     }
+
+    /// Read a state value along with the proofs authenticating it. If the state key holds no
+    /// value, the proofs authenticate that instead.
+    ///
+    /// The state tree is persisted asynchronously and only at some block ends, so proofs are only
+    /// available at the versions of persisted state snapshots. Without a requested version, the
+    /// latest of those is used.
+    ///
+    /// The proofs are relative to the epoch change ledger info if the version ends an epoch, so
+    /// they can be checked against a waypoint, and to the latest ledger info otherwise.
+    fn state_value_with_proof(
+        &self,
+        state_key: &StateKey,
+        requested_version: Option<u64>,
+        ledger_info: &LedgerInfo,
+    ) -> Result<StateValueWithProof, BasicErrorWith404> {
+        let internal_error = |err: anyhow::Error| {
+            BasicErrorWith404::internal_with_code(err, AptosErrorCode::InternalError, ledger_info)
+        };
+        let db = &self.context.db;
+        let latest_snapshot_version = db
+            .get_state_snapshot_before(ledger_info.version() + 1)
+            .context("Failed to get the latest state snapshot")
+            .map_err(internal_error)?
+            .map(|(version, _)| version)
+            .ok_or_else(|| internal_error(anyhow::anyhow!("No state snapshot is persisted")))?;
+        let version = match requested_version {
+            None => latest_snapshot_version,
+            Some(version) => {
+                let snapshot_version = db
+                    .get_state_snapshot_before(version + 1)
+                    .context("Failed to get the state snapshot")
+                    .map_err(internal_error)?
+                    .map(|(version, _)| version);
+                if snapshot_version != Some(version) {
+                    return Err(BasicErrorWith404::bad_request_with_code(
+                        format!(
+                            "State proofs are only available at versions with a persisted state \
+                            snapshot, version {} has none. The newest provable version is {}",
+                            version, latest_snapshot_version
+                        ),
+                        AptosErrorCode::InvalidInput,
+                        ledger_info,
+                    ));
+                }
+                version
+            }
+        };
+        let ledger_info_with_signatures = match db.get_epoch_ending_ledger_info(version) {
+            Ok(epoch_change_ledger_info) => epoch_change_ledger_info,
+            Err(_) => db.get_latest_ledger_info().map_err(internal_error)?,
+        };
+        let transaction_info_with_proof = db
+            .get_transaction_by_version(
+                version,
+                ledger_info_with_signatures.ledger_info().version(),
+                false,
+            )
+            .context("Failed to get transaction info with proof")
+            .map_err(internal_error)?
+            .proof;
+        let (state_value, sparse_merkle_proof) = db
+            .get_state_value_with_proof_by_version(state_key, version)
+            .context(format!("Failed to query DB to prove {:?}", state_key))
+            .map_err(internal_error)?;
+
+        Ok(StateValueWithProof {
+            version,
+            state_value,
+            sparse_merkle_proof,
+            transaction_info_with_proof,
+            ledger_info_with_signatures,
+        })
+    }
+}
+
+/// Proofs are BCS types, so they can't be returned as JSON
+fn verify_with_proof(
+    accept_type: &AcceptType,
+    with_proof: Option<bool>,
+) -> Result<bool, BasicErrorWith404> {
+    let with_proof = with_proof.unwrap_or(false);
+    if with_proof && accept_type != &AcceptType::Bcs {
+        return Err(BasicErrorWith404::bad_request_with_code_no_info(
+            "'with_proof' is only supported for BCS",
+            AptosErrorCode::InvalidInput,
+        ));
+    }
+    Ok(with_proof)
 }
@@ -0,0 +1,290 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::accept_type::AcceptType;
+use crate::context::Context;
+use crate::failpoint::fail_point_poem;
+use crate::response::{
+    api_disabled, version_pruned, BadRequestError, BasicErrorWith404, ServiceUnavailableError,
+};
+use crate::ApiTags;
+use anyhow::Context as AnyhowContext;
+use aptos_api_types::{
+    Address, AptosErrorCode, AsConverter, Block, BlockNotification, EventNotification,
+    MoveStructTag, SubscriptionNotification, TransactionNotification, VerifyInputWithRecursion,
+    U64,
+};
+use aptos_logger::warn;
+use aptos_types::{
+    account_address::AccountAddress,
+    contract_event::{ContractEvent, EventWithVersion},
+    event::EventKey,
+    transaction::{Transaction, Version},
+};
+use futures::{channel::mpsc, stream::BoxStream, SinkExt, StreamExt};
+use move_core_types::language_storage::{StructTag, TypeTag};
+use poem_openapi::{param::Query, payload::EventStream, OpenApi};
+use std::{convert::TryFrom, sync::Arc};
+use tokio::sync::watch;
+
+pub struct SubscriptionsApi {
+    pub context: Arc<Context>,
+}
+
+#[OpenApi]
+impl SubscriptionsApi {
+    /// Subscribe to new blocks, transactions and events
+    ///
+    /// Opens a Server-Sent Events stream that pushes a notification as soon as
+    /// something matching the filters is committed, instead of having to poll
+    /// the other APIs. At least one filter must be given:
+    ///
+    /// - `blocks`: every fully committed block
+    /// - `sender`: committed user transactions sent by the account
+    /// - `event_type`: events of the Move type, from any account
+    /// - `event_address` and `event_creation_number`: events of one event handle
+    ///
+    /// Notifications are delivered in commit order. A client that loses its
+    /// connection can resume by passing the version after the last one it saw
+    /// as `start_version`.
+    #[oai(
+        path = "/subscribe",
+        method = "get",
+        operation_id = "subscribe",
+        tag = "ApiTags::Subscriptions"
+    )]
+    async fn subscribe(
+        &self,
+        accept_type: AcceptType,
+        /// Ledger version to start notifying from, inclusive.
+        ///
+        /// If unspecified, only new commits are notified
+        start_version: Query<Option<U64>>,
+        /// Notify about every new block
+        blocks: Query<Option<bool>>,
+        /// Notify about committed transactions sent by this account
+        sender: Query<Option<Address>>,
+        /// Notify about events of this Move type e.g. `0x1::coin::DepositEvent`
+        event_type: Query<Option<MoveStructTag>>,
+        /// Account of the event handle to notify about, requires `event_creation_number`
+        event_address: Query<Option<Address>>,
+        /// Creation number of the event handle to notify about, requires `event_address`
+        event_creation_number: Query<Option<U64>>,
+    ) -> poem::Result<EventStream<BoxStream<'static, SubscriptionNotification>>, BasicErrorWith404>
+    {
+        fail_point_poem("endpoint_subscribe")?;
+        if accept_type == AcceptType::Bcs {
+            return Err(BasicErrorWith404::bad_request_with_code_no_info(
+                "BCS is not supported for subscriptions",
+                AptosErrorCode::BcsNotSupported,
+            ));
+        }
+        self.context
+            .check_api_output_enabled("Subscribe", &accept_type)?;
+        let commits = self
+            .context
+            .commit_notifications()
+            .ok_or_else(|| api_disabled("Subscribe"))?;
+
+        let event_type = event_type
+            .0
+            .map(|event_type| {
+                event_type.verify(0)?;
+                StructTag::try_from(event_type).map(|tag| TypeTag::Struct(Box::new(tag)))
+            })
+            .transpose()
+            .context("'event_type' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        let event_key = match (event_address.0, event_creation_number.0) {
+            (Some(address), Some(creation_number)) => {
+                Some(EventKey::new(creation_number.0, address.into()))
+            }
+            (None, None) => None,
+            _ => {
+                return Err(BasicErrorWith404::bad_request_with_code_no_info(
+                    "'event_address' and 'event_creation_number' must be given together",
+                    AptosErrorCode::InvalidInput,
+                ))
+            }
+        };
+        let filter = SubscriptionFilter {
+            blocks: blocks.0.unwrap_or(false),
+            sender: sender.0.map(Into::into),
+            event_type,
+            event_key,
+        };
+        if filter.is_empty() {
+            return Err(BasicErrorWith404::bad_request_with_code_no_info(
+                "At least one of 'blocks', 'sender', 'event_type' or an event handle must be given",
+                AptosErrorCode::InvalidInput,
+            ));
+        }
+
+        let latest_ledger_info = self.context.get_latest_ledger_info()?;
+        let start_version = start_version
+            .0
+            .map_or(latest_ledger_info.version() + 1, |v| v.0);
+        if start_version < latest_ledger_info.oldest_ledger_version.0 {
+            return Err(version_pruned(start_version, &latest_ledger_info));
+        }
+        let permit = self
+            .context
+            .try_acquire_subscription_permit()
+            .ok_or_else(|| {
+                BasicErrorWith404::service_unavailable_with_code(
+                    "Too many subscriptions, try again later",
+                    AptosErrorCode::InternalError,
+                    &latest_ledger_info,
+                )
+            })?;
+
+        let (mut notification_sender, notification_receiver) =
+            mpsc::channel(self.context.subscription_buffer_size());
+        let context = self.context.clone();
+        tokio::spawn(async move {
+            // Held for as long as the subscription is served.
+            let _permit = permit;
+            let mut commits = commits;
+            let mut next_version = start_version;
+            while let Some(committed_version) = wait_for_commit(&mut commits, next_version).await {
+                while next_version <= committed_version {
+                    let limit = std::cmp::min(
+                        context.max_transactions_page_size() as u64,
+                        committed_version - next_version + 1,
+                    ) as u16;
+                    let notifications = match filter.notifications(
+                        &context,
+                        next_version,
+                        limit,
+                        committed_version,
+                    ) {
+                        Ok(notifications) => notifications,
+                        Err(err) => {
+                            warn!(
+                                start_version = next_version,
+                                error = ?err,
+                                "[api] Failed to collect subscription notifications"
+                            );
+                            return;
+                        }
+                    };
+                    for notification in notifications {
+                        if notification_sender.send(notification).await.is_err() {
+                            // The subscriber went away.
+                            return;
+                        }
+                    }
+                    next_version += limit as u64;
+                }
+            }
+        });
+
+        Ok(EventStream::new(notification_receiver.boxed()))
+    }
+}
+
+/// Waits until `version` is committed and returns the latest committed version, or `None` once
+/// commit notifications stop.
+async fn wait_for_commit(
+    commits: &mut watch::Receiver<Version>,
+    version: Version,
+) -> Option<Version> {
+    loop {
+        let committed_version = *commits.borrow();
+        if committed_version >= version {
+            return Some(committed_version);
+        }
+        commits.changed().await.ok()?;
+    }
+}
+
+/// What a subscriber asked to be notified about
+#[derive(Clone, Debug)]
+struct SubscriptionFilter {
+    blocks: bool,
+    sender: Option<AccountAddress>,
+    event_type: Option<TypeTag>,
+    event_key: Option<EventKey>,
+}
+
+impl SubscriptionFilter {
+    fn is_empty(&self) -> bool {
+        !self.blocks
+            && self.sender.is_none()
+            && self.event_type.is_none()
+            && self.event_key.is_none()
+    }
+
+    fn matches_transaction(&self, txn: &Transaction) -> bool {
+        match (self.sender, txn) {
+            (Some(sender), Transaction::UserTransaction(txn)) => txn.sender() == sender,
+            _ => false,
+        }
+    }
+
+    fn matches_event(&self, event: &ContractEvent) -> bool {
+        self.event_type.as_ref() == Some(event.type_tag())
+            || self.event_key.as_ref() == Some(event.key())
+    }
+
+    /// Collects the notifications for `limit` committed transactions starting at `start_version`
+    fn notifications(
+        &self,
+        context: &Context,
+        start_version: Version,
+        limit: u16,
+        ledger_version: Version,
+    ) -> anyhow::Result<Vec<SubscriptionNotification>> {
+        let txns = context.get_transactions(start_version, limit, ledger_version)?;
+        let resolver = context.move_resolver()?;
+        let converter = resolver.as_converter(context.db.clone());
+
+        let mut timestamp = context.db.get_block_timestamp(start_version)?;
+        let mut notifications = vec![];
+        for txn in txns {
+            let version = txn.version;
+            if let Transaction::BlockMetadata(ref metadata) = txn.transaction {
+                timestamp = metadata.timestamp_usecs();
+            }
+            // Every block ends with a state checkpoint, at which point it is fully committed.
+            let block_ended = matches!(txn.transaction, Transaction::StateCheckpoint(_));
+
+            let events: Vec<_> = txn
+                .events
+                .iter()
+                .filter(|event| self.matches_event(event))
+                .map(|event| EventWithVersion::new(version, event.clone()))
+                .collect();
+            if self.matches_transaction(&txn.transaction) {
+                let transaction = converter.try_into_onchain_transaction(timestamp, txn)?;
+                notifications.push(SubscriptionNotification::TransactionNotification(Box::new(
+                    TransactionNotification { transaction },
+                )));
+            }
+            for event in converter.try_into_versioned_events(&events)? {
+                notifications.push(SubscriptionNotification::EventNotification(
+                    EventNotification { event },
+                ));
+            }
+            if self.blocks && block_ended {
+                let (first_version, last_version, new_block_event) =
+                    context.db.get_block_info_by_version(version)?;
+                notifications.push(SubscriptionNotification::BlockNotification(
+                    BlockNotification {
+                        block: Block {
+                            block_height: new_block_event.height().into(),
+                            block_hash: new_block_event.hash()?.into(),
+                            block_timestamp: new_block_event.proposed_time().into(),
+                            first_version: first_version.into(),
+                            last_version: last_version.into(),
+                            transactions: None,
+                        },
+                    },
+                ));
+            }
+        }
+        Ok(notifications)
+    }
+}
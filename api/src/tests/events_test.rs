@@ -154,3 +154,68 @@ async fn test_get_events_by_struct_type_has_generic_type_parameter() {
     let resp = context.expect_status_code(404).get(path.as_str()).await;
     context.check_golden_output(resp);
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_events_by_type() {
+    let context = new_test_context(current_function_name!());
+
+    let resp = context
+        .get("/events/by_type/0x1::reconfiguration::NewEpochEvent")
+        .await;
+    let events = resp.as_array().unwrap();
+    assert!(!events.is_empty());
+    for event in events {
+        assert_eq!(event["type"], "0x1::reconfiguration::NewEpochEvent");
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_events_by_type_paging_by_version() {
+    let mut context = new_test_context(current_function_name!());
+    for _ in 0..3 {
+        context.commit_block(&[]).await;
+    }
+
+    let resp = context
+        .get("/events/by_type/0x1::block::NewBlockEvent?start_version=1&limit=2")
+        .await;
+    let first_page = resp.as_array().unwrap();
+    assert_eq!(first_page.len(), 2);
+    let last_version: u64 = first_page[1]["version"].as_str().unwrap().parse().unwrap();
+
+    let resp = context
+        .get(
+            format!(
+                "/events/by_type/0x1::block::NewBlockEvent?start_version={}",
+                last_version + 1
+            )
+            .as_str(),
+        )
+        .await;
+    let second_page = resp.as_array().unwrap();
+    assert_eq!(second_page.len(), 1);
+    assert_eq!(second_page[0]["type"], "0x1::block::NewBlockEvent");
+
+    // The end version bounds the page as well.
+    let resp = context
+        .get(
+            format!(
+                "/events/by_type/0x1::block::NewBlockEvent?start_version=1&end_version={}",
+                last_version
+            )
+            .as_str(),
+        )
+        .await;
+    assert_eq!(resp.as_array().unwrap(), first_page);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_events_by_type_invalid_version_range() {
+    let context = new_test_context(current_function_name!());
+
+    let resp = context
+        .expect_status_code(400)
+        .get("/events/by_type/0x1::block::NewBlockEvent?start_version=2&end_version=1")
+        .await;
+    assert_eq!(resp["error_code"], "invalid_input");
+}
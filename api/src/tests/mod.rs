@@ -9,8 +9,10 @@ mod index_test;
 mod invalid_post_request_test;
 mod state_test;
 mod string_resource_test;
+mod subscriptions_test;
 mod transaction_vector_test;
 mod transactions_test;
+mod view_function_test;
 
 use aptos_api_test_context::{new_test_context as super_new_test_context, TestContext};
 
@@ -3,8 +3,20 @@
 
 use super::new_test_context;
 use aptos_api_test_context::{current_function_name, TestContext};
-use aptos_sdk::{transaction_builder::aptos_stdlib::aptos_token_stdlib, types::LocalAccount};
-use move_core_types::account_address::AccountAddress;
+use aptos_api_types::{mime_types::BCS, StateValueWithProof};
+use aptos_sdk::{
+    rest_client::verify_state_value, transaction_builder::aptos_stdlib::aptos_token_stdlib,
+    types::LocalAccount,
+};
+use aptos_types::{
+    access_path::AccessPath,
+    account_config::aptos_test_root_address,
+    state_store::{state_key::StateKey, table::TableHandle},
+    waypoint::Waypoint,
+};
+use move_core_types::{
+    account_address::AccountAddress, language_storage::ResourceKey, parser::parse_struct_tag,
+};
 use move_package::BuildConfig;
 use serde::Serialize;
 use serde_json::{json, Value};
@@ -95,6 +107,238 @@ async fn test_get_account_resource_with_version_too_large() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_with_proof() {
+    let mut context = new_test_context(current_function_name!());
+    let account = &mut context.gen_account();
+    let txn = context.create_user_account(account);
+    context.commit_block(&vec![txn]).await;
+
+    let ledger_info_with_sigs = context.db.get_latest_ledger_info().unwrap();
+    let ledger_version = ledger_info_with_sigs.ledger_info().version();
+    let proof = get_account_resource_with_proof(&context, "0xA550C18", ledger_version).await;
+    assert_eq!(proof.version, ledger_version);
+    assert_eq!(proof.ledger_info_with_signatures, ledger_info_with_sigs);
+    let waypoint = Waypoint::new_any(ledger_info_with_sigs.ledger_info());
+    verify_state_value(&proof, &account_state_key(), &waypoint).unwrap();
+
+    // The proof doesn't verify for another state key.
+    let other_key = StateKey::AccessPath(AccessPath::resource_access_path(ResourceKey::new(
+        account.address(),
+        parse_struct_tag("0x1::account::Account").unwrap(),
+    )));
+    assert!(verify_state_value(&proof, &other_key, &waypoint).is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_with_proof_at_genesis() {
+    let context = new_test_context(current_function_name!());
+
+    // Genesis ends an epoch, so the proof is relative to the genesis ledger info.
+    let proof = get_account_resource_with_proof(&context, "0xA550C18", 0).await;
+    let genesis_ledger_info = context.db.get_epoch_ending_ledger_info(0).unwrap();
+    assert_eq!(proof.ledger_info_with_signatures, genesis_ledger_info);
+    let waypoint = Waypoint::new_epoch_boundary(genesis_ledger_info.ledger_info()).unwrap();
+    verify_state_value(&proof, &account_state_key(), &waypoint).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_with_proof_invalid() {
+    let mut context = new_test_context(current_function_name!());
+    let account = &mut context.gen_account();
+    let txn = context.create_user_account(account);
+    context.commit_block(&vec![txn]).await;
+
+    // JSON can't hold the proofs.
+    let resp = context
+        .expect_status_code(400)
+        .get(&format!(
+            "{}?with_proof=true",
+            get_account_resource("0xA550C18", "0x1::account::Account")
+        ))
+        .await;
+    assert_eq!(resp["error_code"], "invalid_input");
+
+    // The block metadata transaction doesn't end a block, so there is no state snapshot.
+    let req = warp::test::request()
+        .method("GET")
+        .header("Accept", BCS)
+        .path(&format!(
+            "/v1{}&with_proof=true",
+            get_account_resource_with_version("0xA550C18", "0x1::account::Account", 1)
+        ));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_with_proof_unpersisted() {
+    let mut context = new_test_context(current_function_name!());
+    let account = &mut context.gen_account();
+    let txn = context.create_user_account(account);
+    context.commit_block(&vec![txn]).await;
+    let persisted_version = context.get_latest_ledger_info().version();
+    // Like on a live node, the state snapshot of the latest block isn't persisted yet.
+    context.commit_block_ext(&[], false).await;
+    let ledger_version = context.get_latest_ledger_info().version();
+    assert!(ledger_version > persisted_version);
+
+    // The latest version can't be proven, the error names the newest version that can.
+    let req = warp::test::request()
+        .method("GET")
+        .header("Accept", BCS)
+        .path(&format!(
+            "/v1{}&with_proof=true",
+            get_account_resource_with_version("0xA550C18", "0x1::account::Account", ledger_version)
+        ));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 400);
+    assert!(String::from_utf8_lossy(resp.body()).contains(&format!(
+        "The newest provable version is {}",
+        persisted_version
+    )));
+
+    // Without a ledger version, the proof is at the latest persisted snapshot.
+    let req = warp::test::request()
+        .method("GET")
+        .header("Accept", BCS)
+        .path(&format!(
+            "/v1{}?with_proof=true",
+            get_account_resource("0xA550C18", "0x1::account::Account")
+        ));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200, "{:?}", resp.body());
+    let proof: StateValueWithProof = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(proof.version, persisted_version);
+    let ledger_info_with_sigs = context.db.get_latest_ledger_info().unwrap();
+    assert_eq!(proof.ledger_info_with_signatures, ledger_info_with_sigs);
+    let waypoint = Waypoint::new_any(ledger_info_with_sigs.ledger_info());
+    verify_state_value(&proof, &account_state_key(), &waypoint).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_with_proof_not_found() {
+    let context = new_test_context(current_function_name!());
+
+    // The account doesn't exist, so the proof is a non-inclusion proof.
+    let proof = get_account_resource_with_proof(&context, "0xA550C19", 0).await;
+    assert_eq!(proof.state_value, None);
+    let genesis_ledger_info = context.db.get_epoch_ending_ledger_info(0).unwrap();
+    let waypoint = Waypoint::new_epoch_boundary(genesis_ledger_info.ledger_info()).unwrap();
+    let absent_key = StateKey::AccessPath(AccessPath::resource_access_path(ResourceKey::new(
+        AccountAddress::from_hex_literal("0xA550C19").unwrap(),
+        parse_struct_tag("0x1::account::Account").unwrap(),
+    )));
+    verify_state_value(&proof, &absent_key, &waypoint).unwrap();
+
+    // It doesn't prove that an existing resource is absent.
+    assert!(verify_state_value(&proof, &account_state_key(), &waypoint).is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_table_item_with_proof() {
+    let mut context = new_test_context(current_function_name!());
+    let ctx = &mut context;
+    let creator = &mut ctx.gen_account();
+    let txn = ctx.mint_user_account(creator);
+    let collection_builder =
+        ctx.transaction_factory()
+            .payload(aptos_token_stdlib::token_create_collection_script(
+                "collection name".to_owned().into_bytes(),
+                "description".to_owned().into_bytes(),
+                "uri".to_owned().into_bytes(),
+                20_000_000,
+                vec![false, false, false],
+            ));
+    let collection_txn = creator.sign_with_transaction_builder(collection_builder);
+    ctx.commit_block(&vec![txn, collection_txn]).await;
+
+    let collections = ctx
+        .get(&get_account_resource(
+            &creator.address().to_hex_literal(),
+            "0x3::token::Collections",
+        ))
+        .await;
+    let handle = AccountAddress::from_hex_literal(
+        collections["data"]["collection_data"]["handle"]
+            .as_str()
+            .unwrap(),
+    )
+    .unwrap();
+    let ledger_info_with_sigs = ctx.db.get_latest_ledger_info().unwrap();
+    let waypoint = Waypoint::new_any(ledger_info_with_sigs.ledger_info());
+
+    let proof = get_collection_data_with_proof(ctx, handle, "collection name").await;
+    assert!(proof.state_value.is_some());
+    assert_eq!(proof.ledger_info_with_signatures, ledger_info_with_sigs);
+    let collection_key = collection_data_state_key(handle, "collection name");
+    verify_state_value(&proof, &collection_key, &waypoint).unwrap();
+
+    // An absent table item comes with a non-inclusion proof.
+    let proof = get_collection_data_with_proof(ctx, handle, "other collection").await;
+    assert_eq!(proof.state_value, None);
+    verify_state_value(
+        &proof,
+        &collection_data_state_key(handle, "other collection"),
+        &waypoint,
+    )
+    .unwrap();
+    assert!(verify_state_value(&proof, &collection_key, &waypoint).is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_changes() {
+    let mut context = new_test_context(current_function_name!());
+    let account = &mut context.gen_account();
+    let other = &mut context.gen_account();
+    let start_version = context.get_latest_ledger_info().version() + 1;
+    let txn = context.mint_user_account(account);
+    context.commit_block(&vec![txn]).await;
+    let txn = context.account_transfer(account, other, 100);
+    context.commit_block(&vec![txn]).await;
+
+    let path = get_account_resource_changes(
+        &account.address().to_hex_literal(),
+        "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>",
+    );
+    let resp = context
+        .get(&format!("{}?start_version={}", path, start_version))
+        .await;
+    let changes = resp.as_array().unwrap();
+    assert_eq!(changes.len(), 2, "{}", resp);
+
+    // Each block starts with a block metadata transaction.
+    assert_eq!(changes[0]["version"], (start_version + 1).to_string());
+    assert_eq!(changes[0]["before"], Value::Null);
+    assert_eq!(changes[0]["after"]["data"]["coin"]["value"], "10000000");
+    assert_eq!(changes[1]["version"], (start_version + 4).to_string());
+    assert_eq!(changes[1]["before"], changes[0]["after"]);
+    let balance: u64 = changes[1]["after"]["data"]["coin"]["value"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(balance < 10_000_000 - 100);
+
+    // Only the given range of versions is looked at.
+    let resp = context
+        .get(&format!(
+            "{}?start_version={}&limit=3",
+            path,
+            start_version + 3
+        ))
+        .await;
+    assert_eq!(resp, json!([changes[1]]));
+    let resp = context
+        .get(&format!(
+            "{}?start_version={}&limit=1",
+            path,
+            start_version + 5
+        ))
+        .await;
+    assert_eq!(resp, json!([]));
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_account_module() {
     let mut context = new_test_context(current_function_name!());
@@ -309,6 +553,60 @@ fn get_account_resource_with_version(address: &str, struct_tag: &str, version: u
     )
 }
 
+async fn get_account_resource_with_proof(
+    context: &TestContext,
+    address: &str,
+    version: u64,
+) -> StateValueWithProof {
+    let req = warp::test::request()
+        .method("GET")
+        .header("Accept", BCS)
+        .path(&format!(
+            "/v1{}&with_proof=true",
+            get_account_resource_with_version(address, "0x1::account::Account", version)
+        ));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200, "{:?}", resp.body());
+    bcs::from_bytes(resp.body()).unwrap()
+}
+
+fn account_state_key() -> StateKey {
+    StateKey::AccessPath(AccessPath::resource_access_path(ResourceKey::new(
+        aptos_test_root_address(),
+        parse_struct_tag("0x1::account::Account").unwrap(),
+    )))
+}
+
+async fn get_collection_data_with_proof(
+    context: &TestContext,
+    handle: AccountAddress,
+    collection_name: &str,
+) -> StateValueWithProof {
+    let req = warp::test::request()
+        .method("POST")
+        .header("Accept", BCS)
+        .path(&format!("/v1{}?with_proof=true", get_table_item(handle)))
+        .json(&json!({
+            "key_type": "0x1::string::String",
+            "value_type": "0x3::token::CollectionData",
+            "key": collection_name,
+        }));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200, "{:?}", resp.body());
+    bcs::from_bytes(resp.body()).unwrap()
+}
+
+fn collection_data_state_key(handle: AccountAddress, collection_name: &str) -> StateKey {
+    StateKey::table_item(
+        TableHandle(handle),
+        bcs::to_bytes(&collection_name.to_owned()).unwrap(),
+    )
+}
+
+fn get_account_resource_changes(address: &str, struct_tag: &str) -> String {
+    format!("/accounts/{}/resource/{}/changes", address, struct_tag)
+}
+
 fn get_account_module(address: &str, name: &str) -> String {
     format!("/accounts/{}/module/{}", address, name)
 }
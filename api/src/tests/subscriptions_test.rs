@@ -0,0 +1,90 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use super::new_test_context;
+use aptos_api_test_context::{current_function_name, ApiSpecificConfig, TestContext};
+use serde_json::Value;
+use std::time::Duration;
+
+async fn subscribe(context: &TestContext, query: &str) -> reqwest::Response {
+    let ApiSpecificConfig::V1(address) = &context.api_specific_config;
+    let resp = reqwest::get(format!("http://{}/v1/subscribe?{}", address, query))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    resp
+}
+
+/// Reads `count` notifications from a server-sent events stream
+async fn next_notifications(resp: &mut reqwest::Response, count: usize) -> Vec<Value> {
+    let mut buffer = String::new();
+    let mut notifications = vec![];
+    while notifications.len() < count {
+        let chunk = tokio::time::timeout(Duration::from_secs(10), resp.chunk())
+            .await
+            .expect("Timed out waiting for notifications")
+            .unwrap()
+            .expect("Subscription stream ended");
+        buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+        while let Some(end) = buffer.find("\n\n") {
+            let message: String = buffer.drain(..end + 2).collect();
+            for line in message.lines() {
+                if let Some(data) = line.strip_prefix("data:") {
+                    notifications.push(serde_json::from_str(data.trim_start()).unwrap());
+                }
+            }
+        }
+    }
+    notifications
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_subscribe_to_blocks_and_sender() {
+    let mut context = new_test_context(current_function_name!());
+    let root = context.root_account().address().to_hex_literal();
+    let mut resp = subscribe(&context, &format!("blocks=true&sender={}", root)).await;
+
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&[txn.clone()]).await;
+
+    let notifications = next_notifications(&mut resp, 2).await;
+    assert_eq!(notifications[0]["type"], "transaction_notification");
+    assert_eq!(
+        notifications[0]["transaction"]["hash"],
+        txn.committed_hash().to_hex_literal()
+    );
+    assert_eq!(notifications[1]["type"], "block_notification");
+    assert_eq!(
+        notifications[1]["block"]["last_version"],
+        context.get_latest_ledger_info().version().to_string()
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_subscribe_to_event_type_from_start_version() {
+    let context = new_test_context(current_function_name!());
+    let mut resp = subscribe(
+        &context,
+        "start_version=0&event_type=0x1::reconfiguration::NewEpochEvent",
+    )
+    .await;
+
+    let notifications = next_notifications(&mut resp, 1).await;
+    assert_eq!(notifications[0]["type"], "event_notification");
+    assert_eq!(notifications[0]["event"]["version"], "0");
+    assert_eq!(
+        notifications[0]["event"]["type"],
+        "0x1::reconfiguration::NewEpochEvent"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_subscribe_without_filter() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .expect_status_code(400)
+        .get("/subscribe?start_version=0")
+        .await;
+    assert_eq!(resp["error_code"], "invalid_input");
+}
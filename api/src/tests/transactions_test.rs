@@ -1134,6 +1134,127 @@ async fn test_gas_estimation() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_with_overrides() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.mint_user_account(&account);
+    context.commit_block(&vec![txn]).await;
+
+    // The account only has 10_000_000 coins.
+    let request = simulate_transfer_request(&account, 20_000_000, json!([]));
+    let resp = context.post("/transactions/simulate", request).await;
+    assert_eq!(resp[0]["success"], false, "{}", pretty(&resp));
+
+    let coin_store_type = "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>";
+    let mut coin_store = context
+        .get(&format!(
+            "/accounts/{}/resource/{}",
+            account.address().to_hex_literal(),
+            coin_store_type
+        ))
+        .await;
+    coin_store["data"]["coin"]["value"] = json!("30000000");
+    let request = simulate_transfer_request(
+        &account,
+        20_000_000,
+        json!([{
+            "type": "resource_override",
+            "address": account.address().to_hex_literal(),
+            "resource_type": coin_store_type,
+            "data": coin_store["data"],
+        }]),
+    );
+    let resp = context.post("/transactions/simulate", request).await;
+    assert_eq!(resp[0]["success"], true, "{}", pretty(&resp));
+
+    let gas_used: u64 = resp[0]["gas_used"].as_str().unwrap().parse().unwrap();
+    let mut gas_breakdown_total = 0;
+    for category in ["intrinsic", "execution", "io", "storage"] {
+        let gas: u64 = resp[0]["gas_breakdown"][category]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(gas > 0, "{} gas is 0", category);
+        gas_breakdown_total += gas;
+    }
+    assert!(gas_breakdown_total <= gas_used);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_with_overrides_at_ledger_version() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let version = context.get_latest_ledger_info().version();
+    let txn = context.mint_user_account(&account);
+    context.commit_block(&vec![txn]).await;
+
+    let request = simulate_transfer_request(&account, 100, json!([]));
+    let resp = context
+        .post("/transactions/simulate", request.clone())
+        .await;
+    assert_eq!(resp[0]["success"], true, "{}", pretty(&resp));
+
+    // The account didn't exist yet.
+    let resp = context
+        .post(
+            &format!("/transactions/simulate?ledger_version={}", version),
+            request,
+        )
+        .await;
+    assert_eq!(resp[0]["success"], false, "{}", pretty(&resp));
+    assert_eq!(resp[0]["version"], version.to_string());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_with_invalid_overrides() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let request = simulate_transfer_request(
+        &account,
+        100,
+        json!([{
+            "type": "resource_override",
+            "address": account.address().to_hex_literal(),
+            "resource_type": "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>",
+            "data": {"coin": {"value": "100"}},
+        }]),
+    );
+    let resp = context
+        .expect_status_code(400)
+        .post("/transactions/simulate", request)
+        .await;
+    assert_eq!(resp["error_code"], "invalid_input");
+}
+
+fn simulate_transfer_request(
+    sender: &LocalAccount,
+    amount: u64,
+    state_overrides: serde_json::Value,
+) -> serde_json::Value {
+    json!({
+        "sender": sender.address().to_hex_literal(),
+        "sequence_number": sender.sequence_number().to_string(),
+        "gas_unit_price": "0",
+        "max_gas_amount": "1000000",
+        "expiration_timestamp_secs": "16373698888888",
+        "payload": {
+            "type": "entry_function_payload",
+            "function": "0x1::coin::transfer",
+            "type_arguments": ["0x1::aptos_coin::AptosCoin"],
+            "arguments": ["0xA550C18", amount.to_string()],
+        },
+        // Simulated transactions must not be signed
+        "signature": {
+            "type": "ed25519_signature",
+            "public_key": format!("0x{}", hex::encode(sender.public_key().to_bytes())),
+            "signature": format!("0x{}", hex::encode([0u8; 64])),
+        },
+        "state_overrides": state_overrides,
+    })
+}
+
 fn gen_string(len: u64) -> String {
     let mut rng = thread_rng();
     std::iter::repeat(())
@@ -0,0 +1,84 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use super::new_test_context;
+use aptos_api_test_context::current_function_name;
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_coin_balance() {
+    let context = new_test_context(current_function_name!());
+    let root = context.root_account().address().to_hex_literal();
+
+    let coin_store = context
+        .get(&format!(
+            "/accounts/{}/resource/0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>",
+            root
+        ))
+        .await;
+    let resp = context
+        .post(
+            "/view",
+            json!({
+                "function": "0x1::coin::balance",
+                "type_arguments": ["0x1::aptos_coin::AptosCoin"],
+                "arguments": [root],
+            }),
+        )
+        .await;
+
+    assert_eq!(resp, json!([coin_store["data"]["coin"]["value"]]));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_struct_return_value() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .post(
+            "/view",
+            json!({
+                "function": "0x1::coin::name",
+                "type_arguments": ["0x1::aptos_coin::AptosCoin"],
+                "arguments": [],
+            }),
+        )
+        .await;
+
+    assert_eq!(resp, json!(["Aptos Coin"]));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_at_ledger_version() {
+    let context = new_test_context(current_function_name!());
+    let root = context.root_account().address().to_hex_literal();
+    let resp = context
+        .post(
+            "/view?ledger_version=0",
+            json!({
+                "function": "0x1::account::get_sequence_number",
+                "type_arguments": [],
+                "arguments": [root],
+            }),
+        )
+        .await;
+
+    assert_eq!(resp, json!(["0"]));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_function_taking_signer() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .expect_status_code(400)
+        .post(
+            "/view",
+            json!({
+                "function": "0x1::coin::register",
+                "type_arguments": ["0x1::aptos_coin::AptosCoin"],
+                "arguments": [],
+            }),
+        )
+        .await;
+
+    assert_eq!(resp["error_code"], json!("invalid_input"));
+}
@@ -19,27 +19,32 @@ use crate::{
     },
     ApiTags,
 };
-use anyhow::{anyhow, Context as AnyhowContext};
+use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
     verify_function_identifier, verify_module_identifier, Address, AptosError, AptosErrorCode,
-    AsConverter, EncodeSubmissionRequest, GasEstimation, GasEstimationBcs, HashValue,
-    HexEncodedBytes, LedgerInfo, MoveType, PendingTransaction, SubmitTransactionRequest,
-    Transaction, TransactionData, TransactionOnChainData, TransactionsBatchSingleSubmissionFailure,
-    TransactionsBatchSubmissionResult, UserTransaction, VerifyInput, VerifyInputWithRecursion,
-    MAX_RECURSIVE_TYPES_ALLOWED, U64,
+    AsConverter, EncodeSubmissionRequest, GasBreakdown, GasEstimation, GasEstimationBcs, HashValue,
+    HexEncodedBytes, LedgerInfo, MoveType, PendingTransaction, SimulateTransactionRequest,
+    SubmitTransactionRequest, Transaction, TransactionData, TransactionOnChainData,
+    TransactionsBatchSingleSubmissionFailure, TransactionsBatchSubmissionResult, UserTransaction,
+    VerifyInput, VerifyInputWithRecursion, MAX_RECURSIVE_TYPES_ALLOWED, U64,
 };
 use aptos_crypto::{hash::CryptoHash, signing_message};
+use aptos_state_view::{account_with_state_view::AsAccountWithStateView, StateView};
 use aptos_types::{
     account_config::CoinStoreResource,
     account_view::AccountView,
     mempool_status::MempoolStatusCode,
     transaction::{
         ExecutionStatus, RawTransaction, RawTransactionWithData, SignedTransaction,
-        TransactionPayload, TransactionStatus,
+        TransactionOutput, TransactionPayload, TransactionStatus,
     },
     vm_status::StatusCode,
+    write_set::WriteSetMut,
+};
+use aptos_vm::{
+    data_cache::{AsMoveResolver, StateViewCache},
+    AptosVM,
 };
-use aptos_vm::AptosVM;
 use poem_openapi::{
     param::{Path, Query},
     payload::Json,
@@ -71,7 +76,19 @@ generate_success_response!(
 type SubmitTransactionsBatchResult<T> =
     poem::Result<SubmitTransactionsBatchResponse<T>, SubmitTransactionError>;
 
-type SimulateTransactionResult<T> = poem::Result<BasicResponse<T>, SubmitTransactionError>;
+generate_error_response!(
+    SimulateTransactionError,
+    (400, BadRequest),
+    (403, Forbidden),
+    (404, NotFound),
+    (410, Gone),
+    (413, PayloadTooLarge),
+    (500, Internal),
+    (503, ServiceUnavailable),
+    (507, InsufficientStorage)
+);
+
+type SimulateTransactionResult<T> = poem::Result<BasicResponse<T>, SimulateTransactionError>;
 
 // TODO: Consider making both content types accept either
 // SubmitTransactionRequest or SignedTransaction, the way
@@ -100,6 +117,27 @@ impl VerifyInput for SubmitTransactionPost {
     }
 }
 
+// We need a custom type here because we use different types for each of the
+// content types possible for the POST data.
+#[derive(ApiRequest, Debug)]
+pub enum SimulateTransactionPost {
+    #[oai(content_type = "application/json")]
+    Json(Json<SimulateTransactionRequest>),
+
+    // State overrides can only be given as JSON.
+    #[oai(content_type = "application/x.aptos.signed_transaction+bcs")]
+    Bcs(Bcs),
+}
+
+impl VerifyInput for SimulateTransactionPost {
+    fn verify(&self) -> anyhow::Result<()> {
+        match self {
+            SimulateTransactionPost::Json(inner) => inner.0.verify(),
+            SimulateTransactionPost::Bcs(_) => Ok(()),
+        }
+    }
+}
+
 // We need a custom type here because we use different types for each of the
 // content types possible for the POST data.
 #[derive(ApiRequest, Debug)]
@@ -391,10 +429,17 @@ impl TransactionsApi {
     ///
     /// To use this, you must:
     /// - Create a SignedTransaction with a zero-padded signature.
-    /// - Submit a SubmitTransactionRequest containing a UserTransactionRequest containing that signature.
+    /// - Submit a SimulateTransactionRequest containing a UserTransactionRequest containing that signature.
+    ///
+    /// The transaction can be simulated at a past ledger version, and on top of replaced
+    /// resources and modules given as `state_overrides`, e.g. to give an account a fake coin
+    /// balance or to try out a module upgrade. Resource overrides are converted using the modules
+    /// at the ledger version, so they can't be of types defined by module overrides. With JSON,
+    /// the gas used is also broken down into intrinsic, execution, IO and storage costs.
     ///
     /// To use this endpoint with BCS, you must submit a SignedTransaction
     /// encoded as BCS. See SignedTransaction in types/src/transaction/mod.rs.
+    /// State overrides can't be given with BCS.
     #[oai(
         path = "/transactions/simulate",
         method = "post",
@@ -404,6 +449,10 @@ impl TransactionsApi {
     async fn simulate_transaction(
         &self,
         accept_type: AcceptType,
+        /// Ledger version to simulate the transaction at
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
         /// If set to true, the max gas value in the transaction will be ignored
         /// and the maximum possible gas will be used
         estimate_max_gas_amount: Query<Option<bool>>,
@@ -413,12 +462,12 @@ impl TransactionsApi {
         /// If set to true, the transaction will use a higher price than the original
         /// estimate.
         estimate_prioritized_gas_unit_price: Query<Option<bool>>,
-        data: SubmitTransactionPost,
+        data: SimulateTransactionPost,
     ) -> SimulateTransactionResult<Vec<UserTransaction>> {
         data.verify()
             .context("Simulated transaction invalid")
             .map_err(|err| {
-                SubmitTransactionError::bad_request_with_code_no_info(
+                SimulateTransactionError::bad_request_with_code_no_info(
                     err,
                     AptosErrorCode::InvalidInput,
                 )
@@ -429,8 +478,72 @@ impl TransactionsApi {
         if !self.context.node_config.api.transaction_simulation_enabled {
             return Err(api_disabled("Simulate transaction"));
         }
-        let ledger_info = self.context.get_latest_ledger_info()?;
-        let mut signed_transaction = self.get_signed_transaction(&ledger_info, data)?;
+        let (ledger_info, version) = self
+            .context
+            .get_latest_ledger_info_and_verify_lookup_version(
+                ledger_version.0.map(|inner| inner.0),
+            )?;
+        let state_view = self.context.state_view_at_version(version).map_err(|err| {
+            SimulateTransactionError::internal_with_code(
+                err,
+                AptosErrorCode::InternalError,
+                &ledger_info,
+            )
+        })?;
+        let resolver = state_view.as_move_resolver();
+        let converter = resolver.as_converter(self.context.db.clone());
+        let (mut signed_transaction, state_overrides) = match data {
+            SimulateTransactionPost::Json(request) => {
+                let SimulateTransactionRequest {
+                    transaction,
+                    state_overrides,
+                } = request.0;
+                let signed_transaction = converter
+                    .try_into_signed_transaction_poem(transaction, self.context.chain_id())
+                    .context("Failed to create SignedTransaction from SubmitTransactionRequest")
+                    .map_err(|err| {
+                        SimulateTransactionError::bad_request_with_code(
+                            err,
+                            AptosErrorCode::InvalidInput,
+                            &ledger_info,
+                        )
+                    })?;
+                (signed_transaction, state_overrides)
+            }
+            SimulateTransactionPost::Bcs(data) => (
+                self.get_signed_transaction(&ledger_info, SubmitTransactionPost::Bcs(data))?,
+                vec![],
+            ),
+        };
+
+        let write_ops = state_overrides
+            .into_iter()
+            .enumerate()
+            .map(|(index, state_override)| {
+                converter
+                    .try_into_state_override(state_override)
+                    .context(format!(
+                        "Failed to apply state override at position {}",
+                        index
+                    ))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map_err(|err| {
+                SimulateTransactionError::bad_request_with_code(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                )
+            })?;
+        let write_set = WriteSetMut::new(write_ops).freeze().map_err(|err| {
+            SimulateTransactionError::internal_with_code(
+                err,
+                AptosErrorCode::InternalError,
+                &ledger_info,
+            )
+        })?;
+        let mut overridden_state_view = StateViewCache::new(&state_view);
+        overridden_state_view.push_write_set(&write_set);
 
         let estimated_gas_unit_price = match (
             estimate_gas_unit_price.0.unwrap_or_default(),
@@ -457,37 +570,24 @@ impl TransactionsApi {
                 / u64::from(gas_params.txn.gas_unit_scaling_factor);
             let max_number_of_gas_units = u64::from(gas_params.txn.maximum_number_of_gas_units);
 
-            // Retrieve account balance to determine max gas available
-            let account_state = self
-                .context
-                .get_account_state(
-                    signed_transaction.sender(),
-                    ledger_info.version(),
-                    &ledger_info,
-                )?
-                .ok_or_else(|| {
-                    SubmitTransactionError::bad_request_with_code(
-                        "Account not found",
-                        AptosErrorCode::InvalidInput,
-                        &ledger_info,
-                    )
-                })?;
-            let coin_store: CoinStoreResource = account_state
+            // Retrieve account balance to determine max gas available, including any override
+            let sender = signed_transaction.sender();
+            let coin_store: CoinStoreResource = overridden_state_view
+                .as_account_with_state_view(&sender)
                 .get_coin_store_resource()
-                .and_then(|inner| {
-                    inner.ok_or_else(|| {
-                        anyhow!(
-                            "No coin store found for account {}",
-                            signed_transaction.sender()
-                        )
-                    })
-                })
                 .map_err(|err| {
-                    SubmitTransactionError::internal_with_code(
+                    SimulateTransactionError::internal_with_code(
                         format!("Failed to get coin store resource {}", err),
                         AptosErrorCode::InternalError,
                         &ledger_info,
                     )
+                })?
+                .ok_or_else(|| {
+                    SimulateTransactionError::bad_request_with_code(
+                        format!("No coin store found for account {}", sender),
+                        AptosErrorCode::InvalidInput,
+                        &ledger_info,
+                    )
                 })?;
 
             let gas_unit_price =
@@ -522,8 +622,13 @@ impl TransactionsApi {
             );
         }
 
-        self.simulate(&accept_type, ledger_info, signed_transaction)
-            .await
+        self.simulate(
+            &accept_type,
+            ledger_info,
+            version,
+            &overridden_state_view,
+            signed_transaction,
+        )
     }
 
     /// Encode submission
@@ -825,22 +930,18 @@ impl TransactionsApi {
     }
 
     /// Parses a single signed transaction
-    fn get_signed_transaction(
+    fn get_signed_transaction<E: BadRequestError + InternalError>(
         &self,
         ledger_info: &LedgerInfo,
         data: SubmitTransactionPost,
-    ) -> Result<SignedTransaction, SubmitTransactionError> {
+    ) -> Result<SignedTransaction, E> {
         match data {
             SubmitTransactionPost::Bcs(data) => {
                 let signed_transaction: SignedTransaction =
                     bcs::from_bytes_with_limit(&data.0, MAX_RECURSIVE_TYPES_ALLOWED as usize)
                         .context("Failed to deserialize input into SignedTransaction")
                         .map_err(|err| {
-                            SubmitTransactionError::bad_request_with_code(
-                                err,
-                                AptosErrorCode::InvalidInput,
-                                ledger_info,
-                            )
+                            E::bad_request_with_code(err, AptosErrorCode::InvalidInput, ledger_info)
                         })?;
                 // Verify the signed transaction
                 match signed_transaction.payload() {
@@ -848,7 +949,7 @@ impl TransactionsApi {
                         verify_module_identifier(entry_function.module().name().as_str())
                             .context("Transaction entry function module invalid")
                             .map_err(|err| {
-                                SubmitTransactionError::bad_request_with_code(
+                                E::bad_request_with_code(
                                     err,
                                     AptosErrorCode::InvalidInput,
                                     ledger_info,
@@ -858,7 +959,7 @@ impl TransactionsApi {
                         verify_function_identifier(entry_function.function().as_str())
                             .context("Transaction entry function name invalid")
                             .map_err(|err| {
-                                SubmitTransactionError::bad_request_with_code(
+                                E::bad_request_with_code(
                                     err,
                                     AptosErrorCode::InvalidInput,
                                     ledger_info,
@@ -869,7 +970,7 @@ impl TransactionsApi {
                             arg.verify(0)
                                 .context("Transaction entry function type arg invalid")
                                 .map_err(|err| {
-                                    SubmitTransactionError::bad_request_with_code(
+                                    E::bad_request_with_code(
                                         err,
                                         AptosErrorCode::InvalidInput,
                                         ledger_info,
@@ -879,7 +980,7 @@ impl TransactionsApi {
                     }
                     TransactionPayload::Script(script) => {
                         if script.code().is_empty() {
-                            return Err(SubmitTransactionError::bad_request_with_code(
+                            return Err(E::bad_request_with_code(
                                 "Script payload bytecode must not be empty",
                                 AptosErrorCode::InvalidInput,
                                 ledger_info,
@@ -891,7 +992,7 @@ impl TransactionsApi {
                             arg.verify(0)
                                 .context("Transaction script function type arg invalid")
                                 .map_err(|err| {
-                                    SubmitTransactionError::bad_request_with_code(
+                                    E::bad_request_with_code(
                                         err,
                                         AptosErrorCode::InvalidInput,
                                         ledger_info,
@@ -912,11 +1013,7 @@ impl TransactionsApi {
                 .try_into_signed_transaction_poem(data.0, self.context.chain_id())
                 .context("Failed to create SignedTransaction from SubmitTransactionRequest")
                 .map_err(|err| {
-                    SubmitTransactionError::bad_request_with_code(
-                        err,
-                        AptosErrorCode::InvalidInput,
-                        ledger_info,
-                    )
+                    E::bad_request_with_code(err, AptosErrorCode::InvalidInput, ledger_info)
                 }),
         }
     }
@@ -1123,19 +1220,21 @@ impl TransactionsApi {
     // TODO: This function leverages a lot of types from aptos_types, use the
     // local API types and just return those directly, instead of converting
     // from these types in render_transactions.
-    /// Simulate a transaction in the VM
+    /// Simulate a transaction in the VM on top of `state_view`, the state at `version`
     ///
     /// Note: this returns a `Vec<UserTransaction>`, but for backwards compatibility, this can't
     /// be removed even though, there is only one possible transaction
-    pub async fn simulate(
+    fn simulate<S: StateView>(
         &self,
         accept_type: &AcceptType,
         ledger_info: LedgerInfo,
+        version: u64,
+        state_view: &S,
         txn: SignedTransaction,
     ) -> SimulateTransactionResult<Vec<UserTransaction>> {
         // Transactions shouldn't have a valid signature or this could be used to attack
         if txn.signature_is_valid() {
-            return Err(SubmitTransactionError::bad_request_with_code(
+            return Err(SimulateTransactionError::bad_request_with_code(
                 "Simulated transactions must have a non-valid signature",
                 AptosErrorCode::InvalidInput,
                 &ledger_info,
@@ -1143,43 +1242,16 @@ impl TransactionsApi {
         }
 
         // Simulate transaction
-        let move_resolver = self.context.move_resolver_poem(&ledger_info)?;
-        let (_, output_ext) = AptosVM::simulate_signed_transaction(&txn, &move_resolver);
-        let version = ledger_info.version();
+        let (_, output_ext, gas_breakdown) =
+            AptosVM::simulate_signed_transaction_with_gas_breakdown(&txn, state_view);
 
         // Apply transaction outputs to build up a transaction
         // TODO: while `into_transaction_output_with_status()` should never fail
         // to apply deltas, we should propagate errors properly. Fix this when
         // VM error handling is fixed.
-        let output = output_ext.into_transaction_output(&move_resolver);
-
-        // Ensure that all known statuses return their values in the output (even if they aren't supposed to)
-        let exe_status = match output.status().clone() {
-            TransactionStatus::Keep(exec_status) => exec_status,
-            TransactionStatus::Discard(status) => ExecutionStatus::MiscellaneousError(Some(status)),
-            _ => ExecutionStatus::MiscellaneousError(None),
-        };
+        let output = output_ext.into_transaction_output(state_view);
 
-        // Build up a transaction from the outputs
-        // All state hashes are invalid, and will be filled with 0s
-        let txn = aptos_types::transaction::Transaction::UserTransaction(txn);
-        let zero_hash = aptos_crypto::HashValue::zero();
-        let info = aptos_types::transaction::TransactionInfo::new(
-            txn.hash(),
-            zero_hash,
-            zero_hash,
-            None,
-            output.gas_used(),
-            exe_status,
-        );
-        let simulated_txn = TransactionOnChainData {
-            version,
-            transaction: txn,
-            info,
-            events: output.events().to_vec(),
-            accumulator_root_hash: zero_hash,
-            changes: output.write_set().clone(),
-        };
+        let simulated_txn = simulated_transaction(txn, &output, version);
 
         match accept_type {
             AcceptType::Json => {
@@ -1192,9 +1264,17 @@ impl TransactionsApi {
                 let mut user_transactions = Vec::new();
                 for transaction in transactions.into_iter() {
                     match transaction {
-                        Transaction::UserTransaction(user_txn) => user_transactions.push(*user_txn),
+                        Transaction::UserTransaction(mut user_txn) => {
+                            user_txn.info.gas_breakdown = Some(GasBreakdown {
+                                intrinsic: u64::from(gas_breakdown.intrinsic).into(),
+                                execution: u64::from(gas_breakdown.execution).into(),
+                                io: u64::from(gas_breakdown.io).into(),
+                                storage: u64::from(gas_breakdown.storage).into(),
+                            });
+                            user_transactions.push(*user_txn)
+                        }
                         _ => {
-                            return Err(SubmitTransactionError::internal_with_code(
+                            return Err(SimulateTransactionError::internal_with_code(
                                 "Simulation transaction resulted in a non-UserTransaction",
                                 AptosErrorCode::InternalError,
                                 &ledger_info,
@@ -1292,3 +1372,38 @@ fn override_gas_parameters(
     // TODO: Check that signature is null, this would just be helpful for downstream use
     SignedTransaction::new_with_authenticator(raw_txn, signed_txn.authenticator())
 }
+
+/// Builds the transaction a simulation would have committed at `version`
+///
+/// All state hashes are invalid, and will be filled with 0s
+fn simulated_transaction(
+    txn: SignedTransaction,
+    output: &TransactionOutput,
+    version: u64,
+) -> TransactionOnChainData {
+    // Ensure that all known statuses return their values in the output (even if they aren't supposed to)
+    let exe_status = match output.status().clone() {
+        TransactionStatus::Keep(exec_status) => exec_status,
+        TransactionStatus::Discard(status) => ExecutionStatus::MiscellaneousError(Some(status)),
+        _ => ExecutionStatus::MiscellaneousError(None),
+    };
+
+    let txn = aptos_types::transaction::Transaction::UserTransaction(txn);
+    let zero_hash = aptos_crypto::HashValue::zero();
+    let info = aptos_types::transaction::TransactionInfo::new(
+        txn.hash(),
+        zero_hash,
+        zero_hash,
+        None,
+        output.gas_used(),
+        exe_status,
+    );
+    TransactionOnChainData {
+        version,
+        transaction: txn,
+        info,
+        events: output.events().to_vec(),
+        accumulator_root_hash: zero_hash,
+        changes: output.write_set().clone(),
+    }
+}
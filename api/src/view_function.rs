@@ -0,0 +1,149 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    accept_type::AcceptType,
+    failpoint::fail_point_poem,
+    response::{
+        BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404,
+        InternalError,
+    },
+    ApiTags, Context,
+};
+use anyhow::Context as AnyhowContext;
+use aptos_api_types::{AptosErrorCode, AsConverter, MoveValue, VerifyInput, ViewRequest, U64};
+use aptos_vm::{data_cache::AsMoveResolver, AptosVM};
+use poem_openapi::{param::Query, payload::Json, OpenApi};
+use std::sync::Arc;
+
+/// API for executing Move view functions
+pub struct ViewFunctionApi {
+    pub context: Arc<Context>,
+}
+
+#[OpenApi]
+impl ViewFunctionApi {
+    /// Execute view function of a module
+    ///
+    /// Execute a public Move function with the given arguments against the state at a specific
+    /// ledger version, and return the values it returns. Nothing the function writes is
+    /// committed. Functions taking a `signer` can't be executed this way. Execution is limited
+    /// to the gas configured on the node. If the ledger version is not specified in the request,
+    /// the latest ledger version is used.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/view",
+        method = "post",
+        operation_id = "view",
+        tag = "ApiTags::View"
+    )]
+    async fn view_function(
+        &self,
+        accept_type: AcceptType,
+        /// View function request with type arguments and arguments
+        request: Json<ViewRequest>,
+        /// Ledger version to get state of account
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<Vec<MoveValue>> {
+        request
+            .0
+            .verify()
+            .context("'request' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        fail_point_poem("endpoint_view_function")?;
+        self.context
+            .check_api_output_enabled("View function", &accept_type)?;
+        self.view(
+            &accept_type,
+            request.0,
+            ledger_version.0.map(|inner| inner.0),
+        )
+    }
+}
+
+impl ViewFunctionApi {
+    /// Execute the function in a throwaway VM session at the ledger version
+    ///
+    /// JSON: Convert return values to MoveValues
+    /// BCS: Return the list of BCS encoded return values
+    fn view(
+        &self,
+        accept_type: &AcceptType,
+        request: ViewRequest,
+        ledger_version: Option<u64>,
+    ) -> BasicResultWith404<Vec<MoveValue>> {
+        let (ledger_info, requested_version) = self
+            .context
+            .get_latest_ledger_info_and_verify_lookup_version(ledger_version)?;
+        let state_view = self
+            .context
+            .state_view_at_version(requested_version)
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+        let resolver = state_view.as_move_resolver();
+        let converter = resolver.as_converter(self.context.db.clone());
+
+        let (entry_function, return_types) = converter
+            .try_into_view_function(request)
+            .context("Failed to convert view request")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                )
+            })?;
+        let (module_id, function_name, type_args, args) = entry_function.into_inner();
+
+        let return_values = AptosVM::execute_view_function(
+            &state_view,
+            module_id,
+            function_name,
+            type_args,
+            args,
+            self.context.node_config.api.max_gas_view_function,
+        )
+        .map_err(|vm_status| {
+            BasicErrorWith404::bad_request_with_vm_status(
+                format!("Failed to execute function: {:?}", vm_status),
+                AptosErrorCode::VmError,
+                vm_status.status_code(),
+                &ledger_info,
+            )
+        })?;
+
+        match accept_type {
+            AcceptType::Json => {
+                let move_values = return_types
+                    .iter()
+                    .zip(return_values.iter())
+                    .map(|(typ, bytes)| converter.try_into_move_value(typ, bytes))
+                    .collect::<anyhow::Result<Vec<_>>>()
+                    .context("Failed to deserialize view function return values")
+                    .map_err(|err| {
+                        BasicErrorWith404::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            &ledger_info,
+                        )
+                    })?;
+
+                BasicResponse::try_from_json((move_values, &ledger_info, BasicResponseStatus::Ok))
+            }
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((return_values, &ledger_info, BasicResponseStatus::Ok))
+            }
+        }
+    }
+}
@@ -8,7 +8,7 @@ use aptos_api_types::{
     X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION,
 };
 use aptos_config::config::{
-    NodeConfig, RocksdbConfigs, BUFFERED_STATE_TARGET_ITEMS,
+    NodeConfig, PrunerConfig, RocksdbConfigs, BUFFERED_STATE_TARGET_ITEMS,
     DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD, NO_OP_STORAGE_PRUNER_CONFIG,
 };
 use aptos_crypto::{hash::HashValue, SigningKey};
@@ -27,7 +27,7 @@ use aptos_types::{
     block_metadata::BlockMetadata,
     chain_id::ChainId,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
-    transaction::{Transaction, TransactionStatus},
+    transaction::{Transaction, TransactionStatus, Version},
 };
 use aptos_vm::AptosVM;
 use aptosdb::AptosDB;
@@ -36,7 +36,7 @@ use executor::{block_executor::BlockExecutor, db_bootstrapper};
 use executor_types::BlockExecutorTrait;
 use hyper::{HeaderMap, Response};
 use mempool_notifications::MempoolNotificationSender;
-use storage_interface::DbReaderWriter;
+use storage_interface::{DbReader, DbReaderWriter};
 
 use aptos_config::keys::ConfigKey;
 use aptos_crypto::ed25519::Ed25519PrivateKey;
@@ -45,6 +45,7 @@ use rand::SeedableRng;
 use serde_json::{json, Value};
 use std::{boxed::Box, iter::once, net::SocketAddr, sync::Arc, time::Duration};
 use storage_interface::state_view::DbStateView;
+use tokio::sync::watch;
 use vm_validator::vm_validator::VMValidator;
 use warp::{http::header::CONTENT_TYPE, Filter, Rejection, Reply};
 use warp_reverse_proxy::reverse_proxy_filter;
@@ -85,6 +86,19 @@ impl ApiSpecificConfig {
 }
 
 pub fn new_test_context(test_name: String, use_db_with_indexer: bool) -> TestContext {
+    new_test_context_impl(test_name, use_db_with_indexer, NO_OP_STORAGE_PRUNER_CONFIG)
+}
+
+/// Same as `new_test_context` without the indexer, but the DB prunes according to `pruner_config`.
+pub fn new_test_context_with_pruner(test_name: String, pruner_config: PrunerConfig) -> TestContext {
+    new_test_context_impl(test_name, false, pruner_config)
+}
+
+fn new_test_context_impl(
+    test_name: String,
+    use_db_with_indexer: bool,
+    pruner_config: PrunerConfig,
+) -> TestContext {
     let tmp_dir = TempPath::new();
     tmp_dir.create_as_dir().unwrap();
 
@@ -103,36 +117,39 @@ pub fn new_test_context(test_name: String, use_db_with_indexer: bool) -> TestCon
     let (validator_identity, _, _, _) = validators[0].get_key_objects(None).unwrap();
     let validator_owner = validator_identity.account_address.unwrap();
 
-    let (db, db_rw) = if use_db_with_indexer {
-        DbReaderWriter::wrap(AptosDB::new_for_test_with_indexer(&tmp_dir))
+    let mut aptos_db = if use_db_with_indexer {
+        AptosDB::new_for_test_with_indexer(&tmp_dir)
     } else {
-        DbReaderWriter::wrap(
-            AptosDB::open(
-                &tmp_dir,
-                false,                       /* readonly */
-                NO_OP_STORAGE_PRUNER_CONFIG, /* pruner */
-                RocksdbConfigs::default(),
-                false, /* indexer */
-                BUFFERED_STATE_TARGET_ITEMS,
-                DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
-            )
-            .unwrap(),
+        AptosDB::open(
+            &tmp_dir,
+            false, /* readonly */
+            pruner_config,
+            RocksdbConfigs::default(),
+            false, /* indexer */
+            BUFFERED_STATE_TARGET_ITEMS,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
         )
+        .unwrap()
     };
+    aptos_db.enable_event_by_type_index();
+    let (db, db_rw) = DbReaderWriter::wrap(aptos_db);
     let ret =
         db_bootstrapper::maybe_bootstrap::<AptosVM>(&db_rw, &genesis, genesis_waypoint).unwrap();
     assert!(ret);
 
     let mempool = MockSharedMempool::new_in_runtime(&db_rw, VMValidator::new(db.clone()));
 
-    let node_config = NodeConfig::default();
+    let mut node_config = NodeConfig::default();
+    node_config.storage.enable_event_by_type_index = true;
 
+    let (commit_sender, commit_receiver) = watch::channel(db.get_latest_version().unwrap());
     let context = Context::new(
         ChainId::test(),
         db.clone(),
         mempool.ac_client.clone(),
         node_config.clone(),
-    );
+    )
+    .with_commit_notifications(commit_receiver);
 
     // Configure the testing depending on which API version we're testing.
     let runtime_handle = tokio::runtime::Handle::current();
@@ -151,6 +168,7 @@ pub fn new_test_context(test_name: String, use_db_with_indexer: bool) -> TestCon
         test_name,
         api_specific_config,
     )
+    .with_commit_sender(commit_sender)
 }
 
 #[derive(Clone)]
@@ -167,6 +185,7 @@ pub struct TestContext {
     golden_output: Option<GoldenOutputs>,
     fake_time_usecs: u64,
     pub api_specific_config: ApiSpecificConfig,
+    commit_sender: Option<Arc<watch::Sender<Version>>>,
 }
 
 impl TestContext {
@@ -194,9 +213,16 @@ impl TestContext {
             golden_output: None,
             fake_time_usecs: 0,
             api_specific_config,
+            commit_sender: None,
         }
     }
 
+    /// Notifies the API of every block committed through [`TestContext::commit_block`].
+    pub fn with_commit_sender(mut self, commit_sender: watch::Sender<Version>) -> Self {
+        self.commit_sender = Some(Arc::new(commit_sender));
+        self
+    }
+
     pub fn set_fake_time_usecs(&mut self, fake_time_usecs: u64) {
         self.fake_time_usecs = fake_time_usecs;
     }
@@ -384,6 +410,12 @@ impl TestContext {
     }
 
     pub async fn commit_block(&mut self, signed_txns: &[SignedTransaction]) {
+        self.commit_block_ext(signed_txns, true).await
+    }
+
+    /// Same as `commit_block`, but the state snapshot is only persisted right away if
+    /// `sync_commit` is set, like it is on a live node.
+    pub async fn commit_block_ext(&mut self, signed_txns: &[SignedTransaction], sync_commit: bool) {
         let metadata = self.new_block_metadata();
         let timestamp = metadata.timestamp_usecs();
         let txns: Vec<Transaction> = std::iter::once(Transaction::BlockMetadata(metadata.clone()))
@@ -418,9 +450,10 @@ impl TestContext {
         }
 
         self.executor
-            .commit_blocks(
+            .commit_blocks_ext(
                 vec![metadata.id()],
                 self.new_ledger_info(&metadata, result.root_hash(), txns.len()),
+                sync_commit,
             )
             .unwrap();
 
@@ -429,6 +462,12 @@ impl TestContext {
             .notify_new_commit(txns, timestamp, 1000)
             .await
             .unwrap();
+
+        if let Some(commit_sender) = &self.commit_sender {
+            commit_sender
+                .send(self.db.get_latest_version().unwrap())
+                .unwrap();
+        }
     }
 
     // TODO: Add support for generic_type_params if necessary.
@@ -8,10 +8,11 @@ use crate::{
         WriteResource, WriteTableItem,
     },
     Bytecode, DirectWriteSet, EntryFunctionId, EntryFunctionPayload, Event, HexEncodedBytes,
-    MoveFunction, MoveModuleBytecode, MoveResource, MoveScriptBytecode, MoveValue,
-    PendingTransaction, ScriptPayload, ScriptWriteSet, SubmitTransactionRequest, Transaction,
-    TransactionInfo, TransactionOnChainData, TransactionPayload, UserTransactionRequest,
-    VersionedEvent, WriteSet, WriteSetChange, WriteSetPayload,
+    ModuleOverride, MoveFunction, MoveFunctionVisibility, MoveModuleBytecode, MoveResource,
+    MoveScriptBytecode, MoveType, MoveValue, PendingTransaction, ResourceOverride, ScriptPayload,
+    ScriptWriteSet, StateOverride, SubmitTransactionRequest, Transaction, TransactionInfo,
+    TransactionOnChainData, TransactionPayload, UserTransactionRequest, VersionedEvent,
+    ViewRequest, WriteSet, WriteSetChange, WriteSetPayload,
 };
 use anyhow::{bail, ensure, format_err, Context as AnyhowContext, Result};
 use aptos_crypto::{hash::CryptoHash, HashValue};
@@ -27,10 +28,10 @@ use aptos_types::{
     write_set::WriteOp,
 };
 use aptos_vm::move_vm_ext::MoveResolverExt;
-use move_binary_format::file_format::FunctionHandleIndex;
+use move_binary_format::{file_format::FunctionHandleIndex, CompiledModule};
 use move_core_types::{
     identifier::Identifier,
-    language_storage::{ModuleId, StructTag, TypeTag},
+    language_storage::{ModuleId, ResourceKey, StructTag, TypeTag},
     value::{MoveStructLayout, MoveTypeLayout},
 };
 use move_resource_viewer::MoveValueAnnotator;
@@ -149,6 +150,7 @@ impl<'a, R: MoveResolverExt + ?Sized> MoveConverter<'a, R> {
                 .collect(),
             block_height: None,
             epoch: None,
+            gas_breakdown: None,
         }
     }
 
@@ -537,6 +539,103 @@ impl<'a, R: MoveResolverExt + ?Sized> MoveConverter<'a, R> {
         Ok(ret)
     }
 
+    /// Converts a view request into the function call to execute, along with the types of the
+    /// values it returns. Only public functions that don't take a signer can be viewed.
+    pub fn try_into_view_function(
+        &self,
+        request: ViewRequest,
+    ) -> Result<(EntryFunction, Vec<TypeTag>)> {
+        let ViewRequest {
+            function,
+            type_arguments,
+            arguments,
+        } = request;
+
+        let module = function.module.clone();
+        let code = self.inner.get_module(&module.clone().into())? as Rc<dyn Bytecode>;
+        let func = code
+            .find_function(function.name.0.as_ident_str())
+            .ok_or_else(|| format_err!("could not find function by {}", function))?;
+        ensure!(
+            func.visibility == MoveFunctionVisibility::Public,
+            "function {} is not public",
+            function
+        );
+        ensure!(
+            !func.params.iter().any(|param| param.is_signer()),
+            "function {} takes a signer, it can't be viewed",
+            function
+        );
+        ensure!(
+            func.generic_type_params.len() == type_arguments.len(),
+            "expect {} type arguments for function {}, but got {}",
+            func.generic_type_params.len(),
+            function,
+            type_arguments.len()
+        );
+
+        let return_types = func
+            .return_
+            .iter()
+            .map(|typ| match typ.substitute_type_params(&type_arguments)? {
+                // Referenced values are returned by value
+                MoveType::Reference { to, .. } => (*to).try_into(),
+                typ => typ.try_into(),
+            })
+            .collect::<Result<_>>()?;
+        let args = self
+            .try_into_vm_values(func, arguments)?
+            .iter()
+            .map(bcs::to_bytes)
+            .collect::<Result<_, bcs::Error>>()?;
+
+        let entry_function = EntryFunction::new(
+            module.into(),
+            function.name.into(),
+            type_arguments
+                .into_iter()
+                .map(|v| v.try_into())
+                .collect::<Result<_>>()?,
+            args,
+        );
+        Ok((entry_function, return_types))
+    }
+
+    /// Converts a state override into the write it makes. Resources are converted with the
+    /// modules of the underlying state, so they can't use types of overridden modules.
+    pub fn try_into_state_override(
+        &self,
+        state_override: StateOverride,
+    ) -> Result<(StateKey, WriteOp)> {
+        Ok(match state_override {
+            StateOverride::ResourceOverride(ResourceOverride {
+                address,
+                resource_type,
+                data,
+            }) => {
+                let struct_tag: StructTag = resource_type.try_into()?;
+                let state_key = StateKey::AccessPath(AccessPath::resource_access_path(
+                    ResourceKey::new(address.into(), struct_tag.clone()),
+                ));
+                match data {
+                    Some(data) => {
+                        let value =
+                            self.try_into_vm_value(&TypeTag::Struct(Box::new(struct_tag)), data)?;
+                        (state_key, WriteOp::Modification(bcs::to_bytes(&value)?))
+                    }
+                    None => (state_key, WriteOp::Deletion),
+                }
+            }
+            StateOverride::ModuleOverride(ModuleOverride { bytecode }) => {
+                let module = CompiledModule::deserialize(&bytecode.0)
+                    .context("Failed to deserialize module bytecode")?;
+                let state_key =
+                    StateKey::AccessPath(AccessPath::code_access_path(module.self_id()));
+                (state_key, WriteOp::Modification(bytecode.0))
+            }
+        })
+    }
+
     pub fn try_into_vm_values(
         &self,
         func: MoveFunction,
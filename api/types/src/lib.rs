@@ -14,8 +14,13 @@ mod index;
 mod ledger_info;
 pub mod mime_types;
 mod move_types;
+mod resource_change;
+mod simulation;
+mod state_proof;
+mod subscription;
 mod table;
 mod transaction;
+mod view;
 mod wrappers;
 
 pub use account::AccountData;
@@ -35,8 +40,16 @@ pub use move_types::{
     MoveScriptBytecode, MoveStruct, MoveStructField, MoveStructTag, MoveType, MoveValue,
     MAX_RECURSIVE_TYPES_ALLOWED, U128, U64,
 };
+pub use resource_change::{ResourceChange, ResourceChangeBcs};
 use serde::{Deserialize, Deserializer};
+pub use simulation::{
+    GasBreakdown, ModuleOverride, ResourceOverride, SimulateTransactionRequest, StateOverride,
+};
+pub use state_proof::StateValueWithProof;
 use std::str::FromStr;
+pub use subscription::{
+    BlockNotification, EventNotification, SubscriptionNotification, TransactionNotification,
+};
 pub use table::{RawTableItemRequest, TableItemRequest};
 pub use transaction::{
     AccountSignature, BlockMetadataTransaction, DeleteModule, DeleteResource, DeleteTableItem,
@@ -49,6 +62,7 @@ pub use transaction::{
     UserCreateSigningMessageRequest, UserTransaction, UserTransactionRequest, VersionedEvent,
     WriteModule, WriteResource, WriteSet, WriteSetChange, WriteSetPayload, WriteTableItem,
 };
+pub use view::ViewRequest;
 pub use wrappers::{EventGuid, IdentifierWrapper, StateKeyWrapper};
 
 pub fn deserialize_from_string<'de, D, T>(deserializer: D) -> Result<T, D::Error>
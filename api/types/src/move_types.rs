@@ -679,6 +679,38 @@ impl MoveType {
             _ => false,
         }
     }
+
+    /// Replaces generic type params with the given type arguments, e.g. to get the concrete
+    /// return types of a generic function
+    pub fn substitute_type_params(&self, type_args: &[MoveType]) -> anyhow::Result<MoveType> {
+        Ok(match self {
+            MoveType::GenericTypeParam { index } => {
+                type_args.get(*index as usize).cloned().ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Type param T{} is out of bounds of the {} type arguments",
+                        index,
+                        type_args.len()
+                    )
+                })?
+            }
+            MoveType::Vector { items } => MoveType::Vector {
+                items: Box::new(items.substitute_type_params(type_args)?),
+            },
+            MoveType::Struct(struct_tag) => MoveType::Struct(MoveStructTag {
+                generic_type_params: struct_tag
+                    .generic_type_params
+                    .iter()
+                    .map(|param| param.substitute_type_params(type_args))
+                    .collect::<anyhow::Result<_>>()?,
+                ..struct_tag.clone()
+            }),
+            MoveType::Reference { mutable, to } => MoveType::Reference {
+                mutable: *mutable,
+                to: Box::new(to.substitute_type_params(type_args)?),
+            },
+            other => other.clone(),
+        })
+    }
 }
 
 impl From<TypeTag> for MoveType {
@@ -0,0 +1,30 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{MoveResource, U64};
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+
+/// A change to a resource made by a transaction
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ResourceChange {
+    /// Version of the transaction that changed the resource
+    pub version: U64,
+    /// The resource before the transaction, unset if it didn't exist
+    pub before: Option<MoveResource>,
+    /// The resource after the transaction, unset if it was deleted
+    pub after: Option<MoveResource>,
+}
+
+/// A change to a resource made by a transaction for encoding in BCS
+///
+/// The resources are left BCS encoded as they are stored
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceChangeBcs {
+    /// Version of the transaction that changed the resource
+    pub version: u64,
+    /// The resource before the transaction, unset if it didn't exist
+    pub before: Option<Vec<u8>>,
+    /// The resource after the transaction, unset if it was deleted
+    pub after: Option<Vec<u8>>,
+}
@@ -0,0 +1,80 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    Address, HexEncodedBytes, MoveStructTag, SubmitTransactionRequest, VerifyInput,
+    VerifyInputWithRecursion, U64,
+};
+use poem_openapi::{Object, Union};
+use serde::{Deserialize, Serialize};
+
+/// A request to simulate a transaction, optionally on top of replaced state
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct SimulateTransactionRequest {
+    /// The transaction to simulate, its signature must not be valid
+    #[serde(flatten)]
+    #[oai(flatten)]
+    pub transaction: SubmitTransactionRequest,
+    /// State to replace before simulating the transaction
+    ///
+    /// Later overrides of the same resource or module win
+    #[serde(default)]
+    #[oai(default)]
+    pub state_overrides: Vec<StateOverride>,
+}
+
+impl VerifyInput for SimulateTransactionRequest {
+    fn verify(&self) -> anyhow::Result<()> {
+        self.transaction.verify()?;
+        for state_override in &self.state_overrides {
+            if let StateOverride::ResourceOverride(resource_override) = state_override {
+                resource_override.resource_type.verify(0)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A replacement of a single resource or module for a simulation
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Union)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[oai(one_of, discriminator_name = "type", rename_all = "snake_case")]
+pub enum StateOverride {
+    ResourceOverride(ResourceOverride),
+    ModuleOverride(ModuleOverride),
+}
+
+/// Replaces a resource of an account
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ResourceOverride {
+    pub address: Address,
+    /// The type of the resource e.g. `0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>`
+    pub resource_type: MoveStructTag,
+    /// The fields of the resource in the same format they are returned by the API
+    ///
+    /// The resource is deleted if unset
+    pub data: Option<serde_json::Value>,
+}
+
+/// Publishes a module, replacing any module with the same address and name
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ModuleOverride {
+    /// The compiled module, it is not verified
+    pub bytecode: HexEncodedBytes,
+}
+
+/// Gas used by a transaction, split by what it was used for
+///
+/// Each category is rounded down on its own, so they may add up to slightly less than the
+/// gas used by the transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct GasBreakdown {
+    /// Gas charged for the transaction itself, based on its size
+    pub intrinsic: U64,
+    /// Gas charged for executing instructions and native functions
+    pub execution: U64,
+    /// Gas charged for loading resources from storage
+    pub io: U64,
+    /// Gas charged for the state written by the transaction
+    pub storage: U64,
+}
@@ -0,0 +1,25 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    proof::{SparseMerkleProof, TransactionInfoWithProof},
+    state_store::state_value::StateValue,
+};
+use serde::{Deserialize, Serialize};
+
+/// A state value along with the proofs authenticating it, for encoding in BCS
+///
+/// The `sparse_merkle_proof` proves the value, or that there is no value if `state_value` is
+/// `None`, against the state checkpoint hash of the transaction info at `version`, the
+/// `transaction_info_with_proof` proves that transaction info against the
+/// `ledger_info_with_signatures`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StateValueWithProof {
+    /// The ledger version the state was read at
+    pub version: u64,
+    pub state_value: Option<StateValue>,
+    pub sparse_merkle_proof: SparseMerkleProof,
+    pub transaction_info_with_proof: TransactionInfoWithProof,
+    pub ledger_info_with_signatures: LedgerInfoWithSignatures,
+}
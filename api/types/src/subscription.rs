@@ -0,0 +1,35 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Block, Transaction, VersionedEvent};
+use poem_openapi::{Object, Union};
+use serde::{Deserialize, Serialize};
+
+/// A notification pushed to subscribers of the `/subscribe` stream
+#[derive(Clone, Debug, Serialize, Deserialize, Union)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[oai(one_of, discriminator_name = "type", rename_all = "snake_case")]
+pub enum SubscriptionNotification {
+    BlockNotification(BlockNotification),
+    TransactionNotification(Box<TransactionNotification>),
+    EventNotification(EventNotification),
+}
+
+/// A block that has been fully committed
+#[derive(Clone, Debug, Serialize, Deserialize, Object)]
+pub struct BlockNotification {
+    /// The block, without its transactions
+    pub block: Block,
+}
+
+/// A committed transaction matching the subscription
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TransactionNotification {
+    pub transaction: Transaction,
+}
+
+/// A committed event matching the subscription
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct EventNotification {
+    pub event: VersionedEvent,
+}
@@ -0,0 +1,408 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! A gas meter splitting the gas charged by [`AptosGasMeter`] by what it was charged for, which
+//! transaction simulation reports back to users. It is kept apart from [`AptosGasMeter`] so that
+//! the accounting isn't paid for when executing transactions.
+
+use crate::{
+    algebra::Gas,
+    gas_meter::{AptosGasMeter, TransactionGasMeter},
+    transaction::TransactionGasParameters,
+};
+use aptos_types::{state_store::state_key::StateKey, write_set::WriteOp};
+use move_binary_format::errors::{PartialVMResult, VMResult};
+use move_core_types::{
+    gas_algebra::{InternalGas, NumArgs, NumBytes},
+    language_storage::ModuleId,
+};
+use move_vm_types::{
+    gas::{GasMeter, SimpleInstruction},
+    views::{TypeView, ValueView},
+};
+
+/// The gas charged by a gas meter so far, split by what it was charged for.
+///
+/// Each category is rounded down to external gas units on its own, so the categories may add up
+/// to slightly less than the gas used.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GasBreakdown {
+    /// Gas charged for the transaction itself, based on its size
+    pub intrinsic: Gas,
+    /// Gas charged for executing instructions and native functions
+    pub execution: Gas,
+    /// Gas charged for loading resources from storage
+    pub io: Gas,
+    /// Gas charged for the state written by the transaction
+    pub storage: Gas,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum GasCategory {
+    Intrinsic,
+    Execution,
+    Io,
+    Storage,
+}
+
+/// Wraps an [`AptosGasMeter`], adding up what each of its charges took from the balance.
+pub struct GasBreakdownMeter {
+    base: AptosGasMeter,
+    txn_gas_params: TransactionGasParameters,
+
+    intrinsic_charged: InternalGas,
+    execution_charged: InternalGas,
+    io_charged: InternalGas,
+    storage_charged: InternalGas,
+}
+
+impl GasBreakdownMeter {
+    pub fn new(base: AptosGasMeter, txn_gas_params: TransactionGasParameters) -> Self {
+        Self {
+            base,
+            txn_gas_params,
+            intrinsic_charged: 0.into(),
+            execution_charged: 0.into(),
+            io_charged: 0.into(),
+            storage_charged: 0.into(),
+        }
+    }
+
+    pub fn gas_breakdown(&self) -> GasBreakdown {
+        let txn_params = &self.txn_gas_params;
+        GasBreakdown {
+            intrinsic: self
+                .intrinsic_charged
+                .to_unit_round_down_with_params(txn_params),
+            execution: self
+                .execution_charged
+                .to_unit_round_down_with_params(txn_params),
+            io: self.io_charged.to_unit_round_down_with_params(txn_params),
+            storage: self
+                .storage_charged
+                .to_unit_round_down_with_params(txn_params),
+        }
+    }
+
+    /// Makes a charge on the wrapped meter and books whatever it took from the balance, which is
+    /// all that was left if it ran out of gas, under `category`.
+    #[inline]
+    fn record<T>(
+        &mut self,
+        category: GasCategory,
+        charge: impl FnOnce(&mut AptosGasMeter) -> T,
+    ) -> T {
+        let balance_before = self.base.balance_internal();
+        let res = charge(&mut self.base);
+        let charged = balance_before
+            .checked_sub(self.base.balance_internal())
+            .unwrap_or_else(|| 0.into());
+        let charged_for = match category {
+            GasCategory::Intrinsic => &mut self.intrinsic_charged,
+            GasCategory::Execution => &mut self.execution_charged,
+            GasCategory::Io => &mut self.io_charged,
+            GasCategory::Storage => &mut self.storage_charged,
+        };
+        *charged_for += charged;
+        res
+    }
+}
+
+impl GasMeter for GasBreakdownMeter {
+    #[inline]
+    fn charge_simple_instr(&mut self, instr: SimpleInstruction) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| {
+            base.charge_simple_instr(instr)
+        })
+    }
+
+    #[inline]
+    fn charge_native_function_before_execution(
+        &mut self,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView>,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| {
+            base.charge_native_function_before_execution(ty_args, args)
+        })
+    }
+
+    #[inline]
+    fn charge_native_function(
+        &mut self,
+        amount: InternalGas,
+        ret_vals: Option<impl ExactSizeIterator<Item = impl ValueView>>,
+    ) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| {
+            base.charge_native_function(amount, ret_vals)
+        })
+    }
+
+    #[inline]
+    fn charge_load_resource(
+        &mut self,
+        loaded: Option<(NumBytes, impl ValueView)>,
+    ) -> PartialVMResult<()> {
+        self.record(GasCategory::Io, |base| base.charge_load_resource(loaded))
+    }
+
+    #[inline]
+    fn charge_pop(&mut self, popped_val: impl ValueView) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| base.charge_pop(popped_val))
+    }
+
+    #[inline]
+    fn charge_call(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| {
+            base.charge_call(module_id, func_name, args, num_locals)
+        })
+    }
+
+    #[inline]
+    fn charge_call_generic(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView>,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| {
+            base.charge_call_generic(module_id, func_name, ty_args, args, num_locals)
+        })
+    }
+
+    #[inline]
+    fn charge_ld_const(&mut self, size: NumBytes) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| base.charge_ld_const(size))
+    }
+
+    #[inline]
+    fn charge_ld_const_after_deserialization(
+        &mut self,
+        val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| {
+            base.charge_ld_const_after_deserialization(val)
+        })
+    }
+
+    #[inline]
+    fn charge_copy_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| base.charge_copy_loc(val))
+    }
+
+    #[inline]
+    fn charge_move_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| base.charge_move_loc(val))
+    }
+
+    #[inline]
+    fn charge_store_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| base.charge_store_loc(val))
+    }
+
+    #[inline]
+    fn charge_pack(
+        &mut self,
+        is_generic: bool,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| {
+            base.charge_pack(is_generic, args)
+        })
+    }
+
+    #[inline]
+    fn charge_unpack(
+        &mut self,
+        is_generic: bool,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| {
+            base.charge_unpack(is_generic, args)
+        })
+    }
+
+    #[inline]
+    fn charge_read_ref(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| base.charge_read_ref(val))
+    }
+
+    #[inline]
+    fn charge_write_ref(
+        &mut self,
+        new_val: impl ValueView,
+        old_val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| {
+            base.charge_write_ref(new_val, old_val)
+        })
+    }
+
+    #[inline]
+    fn charge_eq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| base.charge_eq(lhs, rhs))
+    }
+
+    #[inline]
+    fn charge_neq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| base.charge_neq(lhs, rhs))
+    }
+
+    #[inline]
+    fn charge_borrow_global(
+        &mut self,
+        is_mut: bool,
+        is_generic: bool,
+        ty: impl TypeView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| {
+            base.charge_borrow_global(is_mut, is_generic, ty, is_success)
+        })
+    }
+
+    #[inline]
+    fn charge_exists(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        exists: bool,
+    ) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| {
+            base.charge_exists(is_generic, ty, exists)
+        })
+    }
+
+    #[inline]
+    fn charge_move_from(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        val: Option<impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| {
+            base.charge_move_from(is_generic, ty, val)
+        })
+    }
+
+    #[inline]
+    fn charge_move_to(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        val: impl ValueView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| {
+            base.charge_move_to(is_generic, ty, val, is_success)
+        })
+    }
+
+    #[inline]
+    fn charge_vec_pack<'a>(
+        &mut self,
+        ty: impl TypeView + 'a,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| {
+            base.charge_vec_pack(ty, args)
+        })
+    }
+
+    #[inline]
+    fn charge_vec_unpack(
+        &mut self,
+        ty: impl TypeView,
+        expect_num_elements: NumArgs,
+        elems: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| {
+            base.charge_vec_unpack(ty, expect_num_elements, elems)
+        })
+    }
+
+    #[inline]
+    fn charge_vec_len(&mut self, ty: impl TypeView) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| base.charge_vec_len(ty))
+    }
+
+    #[inline]
+    fn charge_vec_borrow(
+        &mut self,
+        is_mut: bool,
+        ty: impl TypeView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| {
+            base.charge_vec_borrow(is_mut, ty, is_success)
+        })
+    }
+
+    #[inline]
+    fn charge_vec_push_back(
+        &mut self,
+        ty: impl TypeView,
+        val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| {
+            base.charge_vec_push_back(ty, val)
+        })
+    }
+
+    #[inline]
+    fn charge_vec_pop_back(
+        &mut self,
+        ty: impl TypeView,
+        val: Option<impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| {
+            base.charge_vec_pop_back(ty, val)
+        })
+    }
+
+    #[inline]
+    fn charge_vec_swap(&mut self, ty: impl TypeView) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| base.charge_vec_swap(ty))
+    }
+
+    #[inline]
+    fn charge_drop_frame(
+        &mut self,
+        locals: impl Iterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.record(GasCategory::Execution, |base| {
+            base.charge_drop_frame(locals)
+        })
+    }
+}
+
+impl TransactionGasMeter for GasBreakdownMeter {
+    fn balance(&self) -> Gas {
+        self.base.balance()
+    }
+
+    fn feature_version(&self) -> u64 {
+        self.base.feature_version()
+    }
+
+    fn charge_intrinsic_gas_for_transaction(&mut self, txn_size: NumBytes) -> VMResult<()> {
+        self.record(GasCategory::Intrinsic, |base| {
+            base.charge_intrinsic_gas_for_transaction(txn_size)
+        })
+    }
+
+    fn charge_write_set_gas<'a>(
+        &mut self,
+        ops: impl IntoIterator<Item = (&'a StateKey, &'a WriteOp)>,
+    ) -> VMResult<()> {
+        self.record(GasCategory::Storage, |base| base.charge_write_set_gas(ops))
+    }
+}
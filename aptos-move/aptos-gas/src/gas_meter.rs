@@ -209,6 +209,10 @@ impl AptosGasMeter {
             .to_unit_round_down_with_params(&self.gas_params.txn)
     }
 
+    pub fn balance_internal(&self) -> InternalGas {
+        self.balance
+    }
+
     #[inline]
     fn charge(&mut self, amount: InternalGas) -> PartialVMResult<()> {
         match self.balance.checked_sub(amount) {
@@ -768,3 +772,40 @@ impl AptosGasMeter {
         self.charge(cost).map_err(|e| e.finish(Location::Undefined))
     }
 }
+
+/// The charges the Aptos VM makes for a transaction on top of those the Move VM makes through
+/// [`GasMeter`]. The VM runs transactions with any such meter, so [`AptosGasMeter`] can be wrapped
+/// to observe what it charges.
+pub trait TransactionGasMeter: GasMeter {
+    fn balance(&self) -> Gas;
+
+    fn feature_version(&self) -> u64;
+
+    fn charge_intrinsic_gas_for_transaction(&mut self, txn_size: NumBytes) -> VMResult<()>;
+
+    fn charge_write_set_gas<'a>(
+        &mut self,
+        ops: impl IntoIterator<Item = (&'a StateKey, &'a WriteOp)>,
+    ) -> VMResult<()>;
+}
+
+impl TransactionGasMeter for AptosGasMeter {
+    fn balance(&self) -> Gas {
+        AptosGasMeter::balance(self)
+    }
+
+    fn feature_version(&self) -> u64 {
+        AptosGasMeter::feature_version(self)
+    }
+
+    fn charge_intrinsic_gas_for_transaction(&mut self, txn_size: NumBytes) -> VMResult<()> {
+        AptosGasMeter::charge_intrinsic_gas_for_transaction(self, txn_size)
+    }
+
+    fn charge_write_set_gas<'a>(
+        &mut self,
+        ops: impl IntoIterator<Item = (&'a StateKey, &'a WriteOp)>,
+    ) -> VMResult<()> {
+        AptosGasMeter::charge_write_set_gas(self, ops)
+    }
+}
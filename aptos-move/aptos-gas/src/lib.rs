@@ -26,6 +26,7 @@ mod params;
 
 mod algebra;
 mod aptos_framework;
+mod gas_breakdown;
 mod gas_meter;
 pub mod gen;
 mod instr;
@@ -35,9 +36,10 @@ mod table;
 mod transaction;
 
 pub use algebra::*;
+pub use gas_breakdown::{GasBreakdown, GasBreakdownMeter};
 pub use gas_meter::{
     AptosGasMeter, AptosGasParameters, FromOnChainGasSchedule, InitialGasSchedule,
-    NativeGasParameters, ToOnChainGasSchedule, LATEST_GAS_FEATURE_VERSION,
+    NativeGasParameters, ToOnChainGasSchedule, TransactionGasMeter, LATEST_GAS_FEATURE_VERSION,
 };
 pub use instr::InstructionGasParameters;
 pub use misc::{AbstractValueSizeGasParameters, MiscGasParameters};
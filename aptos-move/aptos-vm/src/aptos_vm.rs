@@ -25,7 +25,7 @@ use aptos_aggregator::{
     transaction::{ChangeSetExt, TransactionOutputExt},
 };
 use aptos_crypto::HashValue;
-use aptos_gas::AptosGasMeter;
+use aptos_gas::{AptosGasMeter, GasBreakdown, GasBreakdownMeter, TransactionGasMeter};
 use aptos_logger::prelude::*;
 use aptos_module_verifier::module_init::verify_module_init_function;
 use aptos_state_view::StateView;
@@ -47,12 +47,14 @@ use framework::natives::code::PublishRequest;
 use move_binary_format::{
     access::ModuleAccess,
     errors::{verification_error, Location, PartialVMError, VMError, VMResult},
+    file_format::Visibility,
     CompiledModule, IndexKind,
 };
 use move_core_types::{
     account_address::AccountAddress,
     ident_str,
-    language_storage::ModuleId,
+    identifier::Identifier,
+    language_storage::{ModuleId, TypeTag},
     transaction_argument::convert_txn_args,
     value::{serialize_values, MoveValue},
 };
@@ -187,7 +189,7 @@ impl AptosVM {
     pub fn failed_transaction_cleanup<S: MoveResolverExt>(
         &self,
         error_code: VMStatus,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut impl TransactionGasMeter,
         txn_data: &TransactionMetadata,
         storage: &S,
         log_context: &AdapterLogSchema,
@@ -205,7 +207,7 @@ impl AptosVM {
     fn failed_transaction_cleanup_and_keep_vm_status<S: MoveResolverExt>(
         &self,
         error_code: VMStatus,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut impl TransactionGasMeter,
         txn_data: &TransactionMetadata,
         storage: &S,
         log_context: &AdapterLogSchema,
@@ -266,7 +268,7 @@ impl AptosVM {
         &self,
         storage: &S,
         user_txn_change_set_ext: ChangeSetExt,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut impl TransactionGasMeter,
         txn_data: &TransactionMetadata,
         log_context: &AdapterLogSchema,
     ) -> Result<(VMStatus, TransactionOutputExt), VMStatus> {
@@ -331,7 +333,7 @@ impl AptosVM {
         &self,
         storage: &S,
         mut session: SessionExt<S>,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut impl TransactionGasMeter,
         txn_data: &TransactionMetadata,
         payload: &TransactionPayload,
         log_context: &AdapterLogSchema,
@@ -448,7 +450,7 @@ impl AptosVM {
     fn execute_module_initialization<S: MoveResolverExt>(
         &self,
         session: &mut SessionExt<S>,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut impl TransactionGasMeter,
         modules: &[CompiledModule],
         exists: BTreeSet<ModuleId>,
         senders: &[AccountAddress],
@@ -508,7 +510,7 @@ impl AptosVM {
         &self,
         storage: &S,
         mut session: SessionExt<S>,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut impl TransactionGasMeter,
         txn_data: &TransactionMetadata,
         modules: &ModuleBundle,
         log_context: &AdapterLogSchema,
@@ -555,7 +557,7 @@ impl AptosVM {
     fn resolve_pending_code_publish<S: MoveResolverExt>(
         &self,
         session: &mut SessionExt<S>,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut impl TransactionGasMeter,
     ) -> VMResult<()> {
         if let Some(PublishRequest {
             destination,
@@ -931,12 +933,81 @@ impl AptosVM {
         txn: &SignedTransaction,
         state_view: &impl StateView,
     ) -> (VMStatus, TransactionOutputExt) {
+        let vm = AptosVM::new(state_view);
+        let simulation_vm = AptosSimulationVM(vm);
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        let (vm_status, output, _) = simulation_vm.simulate_signed_transaction(
+            &state_view.as_move_resolver(),
+            txn,
+            &log_context,
+        );
+        (vm_status, output)
+    }
+
+    /// Same as [`AptosVM::simulate_signed_transaction`], also returning how the gas used was
+    /// split between intrinsic, execution, IO and storage costs. Nothing is charged for
+    /// transactions that are discarded.
+    pub fn simulate_signed_transaction_with_gas_breakdown(
+        txn: &SignedTransaction,
+        state_view: &impl StateView,
+    ) -> (VMStatus, TransactionOutputExt, GasBreakdown) {
         let vm = AptosVM::new(state_view);
         let simulation_vm = AptosSimulationVM(vm);
         let log_context = AdapterLogSchema::new(state_view.id(), 0);
         simulation_vm.simulate_signed_transaction(&state_view.as_move_resolver(), txn, &log_context)
     }
 
+    /// Executes a public function outside of any transaction and returns its BCS serialized
+    /// return values. The session is never finished, so nothing it writes is ever committed.
+    /// Execution is metered and aborts once `gas_budget` is used up.
+    pub fn execute_view_function(
+        state_view: &impl StateView,
+        module_id: ModuleId,
+        func_name: Identifier,
+        type_args: Vec<TypeTag>,
+        arguments: Vec<Vec<u8>>,
+        gas_budget: u64,
+    ) -> Result<Vec<Vec<u8>>, VMStatus> {
+        let vm = AptosVM::new(state_view);
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        let mut gas_meter = AptosGasMeter::new(
+            vm.0.get_gas_feature_version(),
+            vm.0.get_gas_parameters(&log_context)?.clone(),
+            vm.0.get_storage_gas_parameters(&log_context)?.cloned(),
+            gas_budget,
+        );
+
+        let resolver = state_view.as_move_resolver();
+        let module = vm
+            .0
+            .load_module(&module_id, &resolver)
+            .map_err(|e| e.into_vm_status())?;
+        let is_public_function = module.function_defs.iter().any(|def| {
+            let handle = module.function_handle_at(def.function);
+            module.identifier_at(handle.name) == func_name.as_ident_str()
+                && def.visibility == Visibility::Public
+        });
+        if !is_public_function {
+            return Err(VMStatus::Error(StatusCode::FUNCTION_RESOLUTION_FAILURE));
+        }
+
+        let mut session = vm.0.new_session(&resolver, SessionId::Void);
+        let return_values = session
+            .execute_function_bypass_visibility(
+                &module_id,
+                &func_name,
+                type_args,
+                arguments,
+                &mut gas_meter,
+            )
+            .map_err(|e| e.into_vm_status())?;
+        Ok(return_values
+            .return_values
+            .into_iter()
+            .map(|(bytes, _layout)| bytes)
+            .collect())
+    }
+
     fn run_prologue_with_payload<S: MoveResolverExt>(
         &self,
         session: &mut SessionExt<S>,
@@ -1181,11 +1252,16 @@ impl AptosSimulationVM {
         storage: &S,
         txn: &SignedTransaction,
         log_context: &AdapterLogSchema,
-    ) -> (VMStatus, TransactionOutputExt) {
+    ) -> (VMStatus, TransactionOutputExt, GasBreakdown) {
+        let discard = |err: VMStatus| {
+            let (vm_status, output) = discard_error_vm_status(err);
+            (vm_status, output, GasBreakdown::default())
+        };
+
         // simulation transactions should not carry valid signatures, otherwise malicious fullnodes
         // may execute them without user's explicit permission.
         if txn.signature_is_valid() {
-            return discard_error_vm_status(VMStatus::Error(StatusCode::INVALID_SIGNATURE));
+            return discard(VMStatus::Error(StatusCode::INVALID_SIGNATURE));
         }
 
         // Revalidate the transaction.
@@ -1198,23 +1274,26 @@ impl AptosSimulationVM {
             &txn_data,
             log_context,
         ) {
-            return discard_error_vm_status(err);
+            return discard(err);
         };
 
         let gas_params = match self.0 .0.get_gas_parameters(log_context) {
-            Err(err) => return discard_error_vm_status(err),
+            Err(err) => return discard(err),
             Ok(s) => s,
         };
         let storage_gas_params = match self.0 .0.get_storage_gas_parameters(log_context) {
-            Err(err) => return discard_error_vm_status(err),
+            Err(err) => return discard(err),
             Ok(s) => s,
         };
 
-        let mut gas_meter = AptosGasMeter::new(
-            self.0 .0.get_gas_feature_version(),
-            gas_params.clone(),
-            storage_gas_params.cloned(),
-            txn_data.max_gas_amount(),
+        let mut gas_meter = GasBreakdownMeter::new(
+            AptosGasMeter::new(
+                self.0 .0.get_gas_feature_version(),
+                gas_params.clone(),
+                storage_gas_params.cloned(),
+                txn_data.max_gas_amount(),
+            ),
+            gas_params.txn.clone(),
         );
 
         let result = match txn.payload() {
@@ -1236,11 +1315,11 @@ impl AptosSimulationVM {
         };
 
         match result {
-            Ok(output) => output,
+            Ok((vm_status, output)) => (vm_status, output, gas_meter.gas_breakdown()),
             Err(err) => {
                 let txn_status = TransactionStatus::from(err.clone());
                 if txn_status.is_discarded() {
-                    discard_error_vm_status(err)
+                    discard(err)
                 } else {
                     let (vm_status, output) = self.0.failed_transaction_cleanup_and_keep_vm_status(
                         err,
@@ -1249,7 +1328,7 @@ impl AptosSimulationVM {
                         storage,
                         log_context,
                     );
-                    (vm_status, output)
+                    (vm_status, output, gas_meter.gas_breakdown())
                 }
             }
         }
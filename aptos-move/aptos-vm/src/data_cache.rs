@@ -61,7 +61,7 @@ impl<'a, S: StateView> StateViewCache<'a, S> {
     // Publishes a `WriteSet` computed at the end of a transaction.
     // The effect is to build a layer in front of the `StateView` which keeps
     // track of the data as if the changes were applied immediately.
-    pub(crate) fn push_write_set(&mut self, write_set: &WriteSet) {
+    pub fn push_write_set(&mut self, write_set: &WriteSet) {
         for (ap, write_op) in write_set.iter() {
             match write_op {
                 WriteOp::Modification(blob) | WriteOp::Creation(blob) => {
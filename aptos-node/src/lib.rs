@@ -10,8 +10,8 @@ use aptos_api::bootstrap as bootstrap_api;
 use aptos_build_info::build_information;
 use aptos_config::{
     config::{
-        AptosDataClientConfig, BaseConfig, DataStreamingServiceConfig, NetworkConfig, NodeConfig,
-        PersistableConfig, StorageServiceConfig,
+        AptosDataClientConfig, BaseConfig, DataStreamingServiceConfig, IndexerConfig,
+        NetworkConfig, NodeConfig, PersistableConfig, StorageServiceConfig,
     },
     network_id::NetworkId,
     utils::get_genesis_txn,
@@ -23,8 +23,11 @@ use aptos_logger::{prelude::*, telemetry_log_writer::TelemetryLog, Level, Logger
 use aptos_state_view::account_with_state_view::AsAccountWithStateView;
 use aptos_time_service::TimeService;
 use aptos_types::{
-    account_config::CORE_CODE_ADDRESS, account_view::AccountView, chain_id::ChainId,
-    on_chain_config::ON_CHAIN_CONFIG_REGISTRY, waypoint::Waypoint,
+    account_config::{new_block_event_key, CORE_CODE_ADDRESS},
+    account_view::AccountView,
+    chain_id::ChainId,
+    on_chain_config::ON_CHAIN_CONFIG_REGISTRY,
+    waypoint::Waypoint,
 };
 use aptos_vm::AptosVM;
 use aptosdb::AptosDB;
@@ -112,6 +115,14 @@ pub struct AptosNodeArgs {
     /// only commit a block when there is user transaction in mempool.
     #[clap(long, requires("test"))]
     lazy: bool,
+
+    /// Run an indexer alongside the node
+    ///
+    /// The indexer writes to a SQLite file in the test directory, and serves its GraphQL API on
+    /// port 8090. A testnet started with it keeps running it on restarts. Requires the node to
+    /// be built with the `indexer` feature.
+    #[clap(long, requires("test"))]
+    with_indexer: bool,
 }
 
 impl AptosNodeArgs {
@@ -132,6 +143,7 @@ impl AptosNodeArgs {
                 self.test_dir,
                 self.random_ports,
                 self.lazy,
+                self.with_indexer,
                 &genesis_framework,
                 rng,
             )
@@ -241,18 +253,34 @@ pub fn start(
 }
 
 const EPOCH_LENGTH_SECS: u64 = 60;
+const TEST_INDEXER_GRAPHQL_PORT: u16 = 8090;
+/// Processors run by the indexer of a local testnet, i.e. all of them
+const TEST_INDEXER_PROCESSORS: [&str; 5] = [
+    "default_processor",
+    "coin_processor",
+    "token_processor",
+    "stake_processor",
+    "module_processor",
+];
 
 pub fn load_test_environment<R>(
     config_path: Option<PathBuf>,
     test_dir: Option<PathBuf>,
     random_ports: bool,
     lazy: bool,
+    with_indexer: bool,
     framework: &ReleaseBundle,
     rng: R,
 ) -> anyhow::Result<()>
 where
     R: ::rand::RngCore + ::rand::CryptoRng,
 {
+    if with_indexer && !cfg!(feature = "indexer") {
+        return Err(anyhow!(
+            "Running an indexer requires the node to be built with the `indexer` feature"
+        ));
+    }
+
     // If there wasn't a testnet directory given, create a temp one
     let test_dir = if let Some(test_dir) = test_dir {
         test_dir
@@ -272,8 +300,17 @@ where
 
     // If there's already a config, use it
     let config = if validator_config_path.exists() {
-        NodeConfig::load(&validator_config_path)
-            .map_err(|err| anyhow!("Unable to load config: {}", err))?
+        let config = NodeConfig::load(&validator_config_path)
+            .map_err(|err| anyhow!("Unable to load config: {}", err))?;
+        // The indexer reads table infos from the node's storage, which only indexes them when
+        // enabled from genesis
+        if with_indexer && !config.indexer.enabled {
+            return Err(anyhow!(
+                "{:?} has no indexer, restart the testnet from genesis to add one",
+                test_dir
+            ));
+        }
+        config
     } else {
         // Build a single validator network with a generated config
         let mut template = NodeConfig::default_for_validator();
@@ -310,6 +347,24 @@ where
         if lazy {
             template.consensus.quorum_store_poll_count = u64::MAX;
         }
+        if with_indexer {
+            template.storage.enable_indexer = true;
+            template.indexer = IndexerConfig {
+                enabled: true,
+                postgres_uri: Some(format!(
+                    "sqlite://{}",
+                    test_dir.join("indexer.db").display()
+                )),
+                processor: Some(
+                    TEST_INDEXER_PROCESSORS
+                        .iter()
+                        .map(|processor| processor.to_string())
+                        .collect(),
+                ),
+                graphql_address: Some(format!("0.0.0.0:{}", TEST_INDEXER_GRAPHQL_PORT).parse()?),
+                ..template.indexer
+            };
+        }
 
         // Build genesis and validator node
         let builder = aptos_genesis::builder::Builder::new(&test_dir, framework.clone())?
@@ -337,7 +392,9 @@ where
             genesis_waypoint.to_string().as_bytes(),
         )?;
 
-        validators[0].config.clone()
+        // Load the config back, so that it's post-processed, e.g. given the indexer's defaults
+        NodeConfig::load(validators[0].config_path())
+            .map_err(|err| anyhow!("Unable to load config: {}", err))?
     };
 
     // Prepare log file since we cannot automatically route logs to stderr
@@ -361,6 +418,15 @@ where
     if lazy {
         println!("\tLazy mode is enabled");
     }
+    if config.indexer.enabled {
+        println!(
+            "\tIndexer database: {}",
+            config.indexer.postgres_uri.as_deref().unwrap_or_default()
+        );
+        if let Some(address) = config.indexer.graphql_address {
+            println!("\tIndexer GraphQL endpoint: http://{}", address);
+        }
+    }
 
     println!("\nAptos is running, press ctrl-c to exit\n");
 
@@ -581,18 +647,23 @@ pub fn setup_environment(
 
     // Open the database
     let mut instant = Instant::now();
-    let (aptos_db, db_rw) = DbReaderWriter::wrap(
-        AptosDB::open(
-            &node_config.storage.dir(),
-            false, /* readonly */
-            node_config.storage.storage_pruner_config,
-            node_config.storage.rocksdb_configs,
-            node_config.storage.enable_indexer,
-            node_config.storage.buffered_state_target_items,
-            node_config.storage.max_num_nodes_per_lru_cache_shard,
-        )
-        .map_err(|err| anyhow!("DB failed to open {}", err))?,
-    );
+    let mut aptos_db = AptosDB::open(
+        &node_config.storage.dir(),
+        false, /* readonly */
+        node_config.storage.storage_pruner_config,
+        node_config.storage.rocksdb_configs,
+        node_config.storage.enable_indexer,
+        node_config.storage.buffered_state_target_items,
+        node_config.storage.max_num_nodes_per_lru_cache_shard,
+    )
+    .map_err(|err| anyhow!("DB failed to open {}", err))?;
+    if node_config.storage.enable_event_by_type_index {
+        aptos_db.enable_event_by_type_index();
+    }
+    if node_config.storage.consistency_checker_config.enable {
+        aptos_db.start_consistency_checker(node_config.storage.consistency_checker_config);
+    }
+    let (aptos_db, db_rw) = DbReaderWriter::wrap(aptos_db);
     let backup_service = start_backup_service(
         node_config.storage.backup_service_address,
         Arc::clone(&aptos_db),
@@ -640,6 +711,13 @@ pub fn setup_environment(
     let mempool_reconfig_subscription =
         event_subscription_service.subscribe_to_reconfigurations()?;
 
+    // Create a subscription to new blocks so the API can push commits to its subscribers.
+    let api_commit_subscription = if node_config.api.enabled {
+        Some(event_subscription_service.subscribe_to_events(vec![new_block_event_key()])?)
+    } else {
+        None
+    };
+
     // Create a consensus subscription for reconfiguration events (if this node is a validator).
     let consensus_reconfig_subscription = if node_config.base.role.is_validator() {
         Some(event_subscription_service.subscribe_to_reconfigurations()?)
@@ -804,6 +882,7 @@ pub fn setup_environment(
             chain_id,
             aptos_db.clone(),
             mp_client_sender.clone(),
+            api_commit_subscription,
         )?)
     } else {
         None
@@ -38,6 +38,13 @@ pub struct ApiConfig {
     pub max_events_page_size: u16,
     pub max_account_resources_page_size: u16,
     pub max_account_modules_page_size: u16,
+    /// Maximum gas units a view function can consume before it is aborted
+    pub max_gas_view_function: u64,
+    /// Maximum number of concurrent `/subscribe` streams
+    pub max_subscriptions: usize,
+    /// Number of notifications buffered for a slow subscriber before the stream stops
+    /// reading new commits for it
+    pub subscription_buffer_size: usize,
 }
 
 pub const DEFAULT_ADDRESS: &str = "127.0.0.1";
@@ -47,6 +54,9 @@ pub const DEFAULT_MAX_SUBMIT_TRANSACTION_BATCH_SIZE: usize = 10;
 pub const DEFAULT_MAX_PAGE_SIZE: u16 = 100;
 pub const DEFAULT_MAX_ACCOUNT_RESOURCES_PAGE_SIZE: u16 = 9999;
 pub const DEFAULT_MAX_ACCOUNT_MODULES_PAGE_SIZE: u16 = 9999;
+pub const DEFAULT_MAX_GAS_VIEW_FUNCTION: u64 = 2_000_000;
+pub const DEFAULT_MAX_SUBSCRIPTIONS: usize = 100;
+pub const DEFAULT_SUBSCRIPTION_BUFFER_SIZE: usize = 100;
 
 fn default_enabled() -> bool {
     true
@@ -77,6 +87,9 @@ impl Default for ApiConfig {
             max_events_page_size: DEFAULT_MAX_PAGE_SIZE,
             max_account_resources_page_size: DEFAULT_MAX_ACCOUNT_RESOURCES_PAGE_SIZE,
             max_account_modules_page_size: DEFAULT_MAX_ACCOUNT_MODULES_PAGE_SIZE,
+            max_gas_view_function: DEFAULT_MAX_GAS_VIEW_FUNCTION,
+            max_subscriptions: DEFAULT_MAX_SUBSCRIPTIONS,
+            subscription_buffer_size: DEFAULT_SUBSCRIPTION_BUFFER_SIZE,
         }
     }
 }
@@ -2,8 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use serde::{Deserialize, Serialize};
+use std::{net::SocketAddr, path::PathBuf};
 
-#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+pub const DEFAULT_GRPC_SUBSCRIBER_BUFFER_SIZE: usize = 16;
+pub const DEFAULT_MAX_GRPC_SUBSCRIBERS: usize = 32;
+pub const DEFAULT_CONVERSION_PIPELINE_SIZE: usize = 1;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct FirehoseStreamerConfig {
     pub enabled: bool,
@@ -14,4 +19,45 @@ pub struct FirehoseStreamerConfig {
     // Alternatively can set the `STARTING_BLOCK` env var
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub starting_block: Option<u64>,
+    // If set, the height of the last block fully printed to stdout is persisted to this file, and on
+    // restart the stream resumes right after it, taking precedence over `starting_block`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor_path: Option<PathBuf>,
+    // If the block to stream has been pruned from the DB, jump forward to the first available block
+    // instead of stopping the stream
+    pub skip_pruned_blocks: bool,
+    // How many blocks are fetched and converted concurrently, ahead of the block being emitted.
+    // Blocks are still emitted strictly in order. 1 converts blocks one after the other
+    pub conversion_pipeline_size: usize,
+    // Whether to print `FIRE` lines to stdout, to be picked up by the StreamingFast Firehose console reader
+    #[serde(default = "default_enabled")]
+    pub stdout_enabled: bool,
+    // If set, serves blocks to gRPC subscribers on this address. Every subscriber picks its own starting block
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grpc_address: Option<SocketAddr>,
+    // How many converted blocks are buffered per gRPC subscriber before conversion waits on the subscriber
+    pub grpc_subscriber_buffer_size: usize,
+    // Maximum number of concurrent gRPC subscribers, further subscriptions are rejected
+    pub max_grpc_subscribers: usize,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Default for FirehoseStreamerConfig {
+    fn default() -> FirehoseStreamerConfig {
+        FirehoseStreamerConfig {
+            enabled: false,
+            starting_version: None,
+            starting_block: None,
+            cursor_path: None,
+            skip_pruned_blocks: false,
+            conversion_pipeline_size: DEFAULT_CONVERSION_PIPELINE_SIZE,
+            stdout_enabled: default_enabled(),
+            grpc_address: None,
+            grpc_subscriber_buffer_size: DEFAULT_GRPC_SUBSCRIBER_BUFFER_SIZE,
+            max_grpc_subscribers: DEFAULT_MAX_GRPC_SUBSCRIBERS,
+        }
+    }
 }
@@ -326,13 +326,12 @@ impl NodeConfig {
             must_be_set("postgres_uri", "INDEXER_DATABASE_URL"),
         );
 
-        self.indexer.processor = env_or_default(
-            "PROCESSOR_NAME",
-            self.indexer
-                .processor
-                .or_else(|| Some("default_processor".to_string())),
-            None,
-        );
+        self.indexer.processor = match std::env::var("PROCESSOR_NAME").ok() {
+            Some(processors) => Some(parse_processors(&processors)),
+            None => self.indexer.processor,
+        }
+        .filter(|processors| !processors.is_empty())
+        .or_else(|| Some(vec!["default_processor".to_string()]));
 
         self.indexer.starting_version = match std::env::var("STARTING_VERSION").ok() {
             None => self.indexer.starting_version,
@@ -365,6 +364,15 @@ impl NodeConfig {
         self.indexer.processor_tasks =
             default_if_zero_u8(self.indexer.processor_tasks, DEFAULT_PROCESSOR_TASKS);
         self.indexer.emit_every = self.indexer.emit_every.or(Some(0));
+        self.indexer.graphql_pool_size = default_if_zero(
+            self.indexer.graphql_pool_size.map(|v| v as u64),
+            DEFAULT_GRAPHQL_POOL_SIZE as u64,
+        )
+        .map(|v| v as u32);
+        self.indexer.parquet_versions_per_file = default_if_zero(
+            self.indexer.parquet_versions_per_file,
+            DEFAULT_PARQUET_VERSIONS_PER_FILE,
+        );
         self.indexer.gap_lookback_versions = env_or_default(
             "GAP_LOOKBACK_VERSIONS",
             self.indexer.gap_lookback_versions.or(Some(1_500_000)),
@@ -92,6 +92,12 @@ pub struct StorageConfig {
     /// since genesis. To recover operation after data loss, or to bootstrap a node in fast sync
     /// mode, the indexer db needs to be copied in from another node.
     pub enable_indexer: bool,
+    /// Maintain an index of events by their Move type, which backs the
+    /// `/events/by_type/:event_type` API. Like the internal indexer, only events committed while
+    /// this is on are indexed, so it should be enabled from genesis.
+    pub enable_event_by_type_index: bool,
+    /// Verifies the ledger and the state merkle trees against each other in the background.
+    pub consistency_checker_config: ConsistencyCheckerConfig,
 }
 
 pub const NO_OP_STORAGE_PRUNER_CONFIG: PrunerConfig = PrunerConfig {
@@ -168,6 +174,33 @@ impl From<EpochSnapshotPrunerConfig> for StateMerklePrunerConfig {
     }
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConsistencyCheckerConfig {
+    /// Boolean to enable/disable the consistency checker. The checker walks the transactions and
+    /// the state tree nodes as they are committed, and reports what doesn't match the
+    /// authenticated data structures.
+    pub enable: bool,
+    /// Number of transactions, or state tree nodes, to check a time.
+    pub batch_size: usize,
+    /// Pause between batches in milliseconds, which throttles the reads the checker issues.
+    pub batch_interval_ms: u64,
+    /// Pause in milliseconds after the checker catches up with the DB, before it checks newly
+    /// committed data.
+    pub idle_interval_ms: u64,
+}
+
+impl Default for ConsistencyCheckerConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            batch_size: 1_000,
+            batch_interval_ms: 100,
+            idle_interval_ms: 60_000,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, Default)]
 #[serde(default, deny_unknown_fields)]
 pub struct PrunerConfig {
@@ -239,6 +272,8 @@ impl Default for StorageConfig {
             data_dir: PathBuf::from("/opt/aptos/data"),
             rocksdb_configs: RocksdbConfigs::default(),
             enable_indexer: false,
+            enable_event_by_type_index: false,
+            consistency_checker_config: ConsistencyCheckerConfig::default(),
             buffered_state_target_items: BUFFERED_STATE_TARGET_ITEMS,
             max_num_nodes_per_lru_cache_shard: DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
         }
@@ -17,6 +17,7 @@ pub const RAW_BYTES: &str = "raw_bytes";
 /// each client.
 #[derive(Clone, Debug)]
 pub enum CompressionClient {
+    Backup,
     Consensus,
     Mempool,
     StateSync,
@@ -26,6 +27,7 @@ impl CompressionClient {
     /// Returns a summary label for the request
     pub fn get_label(&self) -> &'static str {
         match self {
+            Self::Backup => "backup",
             Self::Consensus => "consensus",
             Self::Mempool => "mempool",
             Self::StateSync => "state_sync",
@@ -165,6 +165,7 @@ mod tests {
                     changes: vec![],
                     block_height: None,
                     epoch: None,
+                    gas_breakdown: None,
                 };
                 let serializable_txn: aptos_rest_client::aptos_api_types::Transaction = (
                     txn.as_signed_user_txn().unwrap(),
@@ -20,6 +20,16 @@ impl Balance {
     }
 }
 
+/// A change to the balance of an account made by the transaction at `version`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BalanceChange {
+    pub version: u64,
+    /// Balance before the transaction, unset if the account had no coin store
+    pub before: Option<u64>,
+    /// Balance after the transaction, unset if the coin store was removed
+    pub after: Option<u64>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AptosVersion {
     pub major: U64,
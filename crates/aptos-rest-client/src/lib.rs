@@ -6,27 +6,33 @@ extern crate core;
 pub mod aptos;
 pub mod error;
 pub mod faucet;
+pub mod proof;
 
 pub use faucet::FaucetClient;
+pub use proof::verify_state_value;
 pub mod response;
 pub use response::Response;
 pub mod state;
+pub mod subscription;
 pub mod types;
 
 pub use aptos_api_types::{
     self, IndexResponseBcs, MoveModuleBytecode, PendingTransaction, Transaction,
 };
 pub use state::State;
+pub use subscription::SubscriptionFilter;
 pub use types::{deserialize_from_prefixed_hex_string, Account, Resource};
 
-use crate::aptos::{AptosVersion, Balance};
+use crate::aptos::{AptosVersion, Balance, BalanceChange};
 use crate::error::RestError;
+use crate::subscription::notification_stream;
 use anyhow::{anyhow, Result};
 use aptos_api_types::{
     deserialize_from_string,
     mime_types::{BCS, BCS_SIGNED_TRANSACTION as BCS_CONTENT_TYPE},
     AptosError, BcsBlock, Block, Bytecode, ExplainVMStatus, GasEstimation, HexEncodedBytes,
-    IndexResponse, MoveModuleId, TransactionData, TransactionOnChainData,
+    IndexResponse, MoveModuleId, ResourceChange, ResourceChangeBcs, StateValueWithProof,
+    SubscriptionNotification, TransactionData, TransactionOnChainData,
     TransactionsBatchSubmissionResult, UserTransaction, VersionedEvent,
 };
 use aptos_crypto::HashValue;
@@ -37,7 +43,7 @@ use aptos_types::{
     contract_event::EventWithVersion,
     transaction::SignedTransaction,
 };
-use futures::executor::block_on;
+use futures::{executor::block_on, stream::BoxStream};
 use move_binary_format::CompiledModule;
 use move_core_types::language_storage::{ModuleId, StructTag};
 use reqwest::header::ACCEPT;
@@ -277,6 +283,74 @@ impl Client {
         })
     }
 
+    /// Gets the changes to a resource made by the `limit` transactions from `start_version`.
+    pub async fn get_account_resource_changes(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+        start_version: Option<u64>,
+        limit: Option<u16>,
+    ) -> AptosResult<Response<Vec<ResourceChange>>> {
+        let url = self.build_path(&format!(
+            "accounts/{}/resource/{}/changes",
+            address, resource_type
+        ))?;
+        let mut request = self.inner.get(url);
+        if let Some(start_version) = start_version {
+            request = request.query(&[("start_version", start_version)])
+        }
+
+        if let Some(limit) = limit {
+            request = request.query(&[("limit", limit)])
+        }
+
+        let response = request.send().await?;
+        self.json(response).await
+    }
+
+    /// Gets the changes to the balance of a coin made by the `limit` transactions from
+    /// `start_version`.
+    pub async fn get_account_balance_changes_bcs(
+        &self,
+        address: AccountAddress,
+        coin_type: &str,
+        start_version: Option<u64>,
+        limit: Option<u16>,
+    ) -> AptosResult<Response<Vec<BalanceChange>>> {
+        let url = self.build_path(&format!(
+            "accounts/{}/resource/0x1::coin::CoinStore<{}>/changes",
+            address, coin_type
+        ))?;
+        let mut request = self.inner.get(url).header(ACCEPT, BCS);
+        if let Some(start_version) = start_version {
+            request = request.query(&[("start_version", start_version)])
+        }
+
+        if let Some(limit) = limit {
+            request = request.query(&[("limit", limit)])
+        }
+
+        let response = request.send().await?;
+        let response = self.check_and_parse_bcs_response(response).await?;
+        let balance = |bytes: Option<Vec<u8>>| {
+            bytes
+                .map(|bytes| bcs::from_bytes::<CoinStoreResource>(&bytes).map(|store| store.coin()))
+                .transpose()
+        };
+        Ok(response.and_then(|inner| {
+            bcs::from_bytes::<Vec<ResourceChangeBcs>>(&inner)?
+                .into_iter()
+                .map(|change| {
+                    Ok(BalanceChange {
+                        version: change.version,
+                        before: balance(change.before)?,
+                        after: balance(change.after)?,
+                    })
+                })
+                .collect::<Result<Vec<_>, bcs::Error>>()
+        })?)
+    }
+
     pub async fn get_index(&self) -> AptosResult<Response<IndexResponse>> {
         self.get(self.build_path("")?).await
     }
@@ -1000,6 +1074,28 @@ impl Client {
         Ok(response.map(|inner| inner.to_vec()))
     }
 
+    /// Gets a resource along with the proofs authenticating it, see [`verify_state_value`].
+    pub async fn get_account_resource_with_proof(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+        version: Option<u64>,
+    ) -> AptosResult<Response<StateValueWithProof>> {
+        let url = self.build_path(&format!("accounts/{}/resource/{}", address, resource_type))?;
+        let mut request = self
+            .inner
+            .get(url)
+            .header(ACCEPT, BCS)
+            .query(&[("with_proof", true)]);
+        if let Some(version) = version {
+            request = request.query(&[("ledger_version", version)]);
+        }
+
+        let response = request.send().await?;
+        let response = self.check_and_parse_bcs_response(response).await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
     pub async fn get_account_resource_at_version(
         &self,
         address: AccountAddress,
@@ -1098,6 +1194,29 @@ impl Client {
         Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
     }
 
+    /// Subscribes to commits matching `filter`, the stream ends when the server closes it.
+    pub async fn subscribe(
+        &self,
+        filter: &SubscriptionFilter,
+    ) -> AptosResult<BoxStream<'static, AptosResult<SubscriptionNotification>>> {
+        let url = self.build_path("subscribe")?;
+        // The request timeout of `inner` would cut the stream short, so use a client without one.
+        let client = ReqwestClient::builder()
+            .user_agent(USER_AGENT)
+            .build()
+            .map_err(anyhow::Error::from)?;
+        let response = client
+            .get(url)
+            .header(ACCEPT, "text/event-stream")
+            .query(&filter.query())
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(parse_error(response).await);
+        }
+        Ok(notification_stream(response))
+    }
+
     pub async fn get_new_block_events_bcs(
         &self,
         start: Option<u64>,
@@ -1166,6 +1285,28 @@ impl Client {
         self.json(response).await
     }
 
+    pub async fn get_table_item_at_version<K: Serialize>(
+        &self,
+        table_handle: AccountAddress,
+        key_type: &str,
+        value_type: &str,
+        key: K,
+        version: u64,
+    ) -> AptosResult<Response<Value>> {
+        let url = self.build_path(&format!(
+            "tables/{}/item?ledger_version={}",
+            table_handle, version
+        ))?;
+        let data = json!({
+            "key_type": key_type,
+            "value_type": value_type,
+            "key": json!(key),
+        });
+
+        let response = self.inner.post(url).json(&data).send().await?;
+        self.json(response).await
+    }
+
     pub async fn get_table_item_bcs<K: Serialize, T: DeserializeOwned>(
         &self,
         table_handle: AccountAddress,
@@ -1184,6 +1325,36 @@ impl Client {
         Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
     }
 
+    /// Gets a table item along with the proofs authenticating it, see [`verify_state_value`].
+    pub async fn get_table_item_with_proof<K: Serialize>(
+        &self,
+        table_handle: AccountAddress,
+        key_type: &str,
+        value_type: &str,
+        key: K,
+        version: Option<u64>,
+    ) -> AptosResult<Response<StateValueWithProof>> {
+        let url = self.build_path(&format!("tables/{}/item", table_handle))?;
+        let data = json!({
+            "key_type": key_type,
+            "value_type": value_type,
+            "key": json!(key),
+        });
+        let mut request = self
+            .inner
+            .post(url)
+            .header(ACCEPT, BCS)
+            .query(&[("with_proof", true)])
+            .json(&data);
+        if let Some(version) = version {
+            request = request.query(&[("ledger_version", version)]);
+        }
+
+        let response = request.send().await?;
+        let response = self.check_and_parse_bcs_response(response).await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
     pub async fn get_raw_table_item(
         &self,
         table_handle: AccountAddress,
@@ -0,0 +1,35 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_api_types::StateValueWithProof;
+use aptos_crypto::hash::CryptoHash;
+use aptos_types::{epoch_change::Verifier, state_store::state_key::StateKey};
+
+/// Verifies that `state_key` holds the value in `proof`, or no value if there is none in `proof`,
+/// without trusting the full node
+///
+/// The ledger info in the proof is checked with `verifier`, which is usually the
+/// [`aptos_types::epoch_state::EpochState`] of the ledger info's epoch. A
+/// [`aptos_types::waypoint::Waypoint`] only matches the ledger info it was created from, which
+/// the API returns when the state is requested at the epoch change version.
+pub fn verify_state_value(
+    proof: &StateValueWithProof,
+    state_key: &StateKey,
+    verifier: &dyn Verifier,
+) -> Result<()> {
+    verifier.verify(&proof.ledger_info_with_signatures)?;
+    proof.transaction_info_with_proof.verify(
+        proof.ledger_info_with_signatures.ledger_info(),
+        proof.version,
+    )?;
+    let state_root_hash = proof
+        .transaction_info_with_proof
+        .transaction_info()
+        .ensure_state_checkpoint_hash()?;
+    proof.sparse_merkle_proof.verify(
+        state_root_hash,
+        state_key.hash(),
+        proof.state_value.as_ref(),
+    )
+}
@@ -0,0 +1,109 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::error::RestError;
+use aptos_api_types::SubscriptionNotification;
+use aptos_types::{account_address::AccountAddress, event::EventKey};
+use futures::stream::{self, BoxStream, StreamExt};
+
+/// What to be notified about by [`crate::Client::subscribe`], at least one of `blocks`, `sender`,
+/// `event_type` or `event_key` must be set.
+#[derive(Clone, Debug, Default)]
+pub struct SubscriptionFilter {
+    /// Ledger version to start notifying from, inclusive. Only new commits are notified if unset
+    pub start_version: Option<u64>,
+    /// Notify about every new block
+    pub blocks: bool,
+    /// Notify about committed transactions sent by this account
+    pub sender: Option<AccountAddress>,
+    /// Notify about events of this Move type e.g. `0x1::coin::DepositEvent`
+    pub event_type: Option<String>,
+    /// Notify about events of this event handle
+    pub event_key: Option<EventKey>,
+}
+
+impl SubscriptionFilter {
+    pub(crate) fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query = vec![];
+        if let Some(start_version) = self.start_version {
+            query.push(("start_version", start_version.to_string()));
+        }
+        if self.blocks {
+            query.push(("blocks", true.to_string()));
+        }
+        if let Some(sender) = self.sender {
+            query.push(("sender", sender.to_hex_literal()));
+        }
+        if let Some(event_type) = &self.event_type {
+            query.push(("event_type", event_type.clone()));
+        }
+        if let Some(event_key) = &self.event_key {
+            query.push((
+                "event_address",
+                event_key.get_creator_address().to_hex_literal(),
+            ));
+            query.push((
+                "event_creation_number",
+                event_key.get_creation_number().to_string(),
+            ));
+        }
+        query
+    }
+}
+
+/// Decodes the Server-Sent Events of a subscription response into notifications
+pub(crate) fn notification_stream(
+    response: reqwest::Response,
+) -> BoxStream<'static, Result<SubscriptionNotification, RestError>> {
+    let reader = EventReader {
+        response,
+        buffer: vec![],
+        data: vec![],
+    };
+    stream::unfold(Some(reader), |reader| async move {
+        let mut reader = reader?;
+        match reader.next_event().await {
+            Ok(Some(data)) => Some((
+                serde_json::from_str(&data).map_err(RestError::from),
+                Some(reader),
+            )),
+            Ok(None) => None,
+            // The connection is unusable after an error, end the stream after reporting it.
+            Err(err) => Some((Err(err), None)),
+        }
+    })
+    .boxed()
+}
+
+struct EventReader {
+    response: reqwest::Response,
+    buffer: Vec<u8>,
+    data: Vec<String>,
+}
+
+impl EventReader {
+    /// Returns the data of the next event, or `None` once the server closed the stream
+    async fn next_event(&mut self) -> Result<Option<String>, RestError> {
+        loop {
+            while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line);
+                let line = line.trim_end_matches(&['\r', '\n'][..]);
+                if line.is_empty() {
+                    // A blank line dispatches the event
+                    if !self.data.is_empty() {
+                        return Ok(Some(std::mem::take(&mut self.data).join("\n")));
+                    }
+                } else if let Some(data) = line.strip_prefix("data:") {
+                    self.data
+                        .push(data.strip_prefix(' ').unwrap_or(data).to_string());
+                }
+                // Comments and other fields are not used by the API
+            }
+            match self.response.chunk().await? {
+                Some(chunk) => self.buffer.extend_from_slice(&chunk),
+                None => return Ok(None),
+            }
+        }
+    }
+}
@@ -1072,6 +1072,14 @@ pub struct RunLocalTestnet {
     #[clap(long)]
     do_not_delegate: bool,
 
+    /// Run an indexer alongside the node
+    ///
+    /// The indexer writes to a SQLite file in the testnet directory, so it needs no database
+    /// server, and serves its GraphQL API at `http://localhost:8090`.  Only available if the CLI
+    /// was built with the `indexer` feature
+    #[clap(long)]
+    with_indexer: bool,
+
     #[clap(flatten)]
     prompt_options: PromptOptions,
 }
@@ -1083,6 +1091,13 @@ impl CliCommand<()> for RunLocalTestnet {
     }
 
     async fn execute(mut self) -> CliTypedResult<()> {
+        if self.with_indexer && !cfg!(feature = "indexer") {
+            return Err(CliError::CommandArgumentError(
+                "--with-indexer requires the CLI to be built with the `indexer` feature"
+                    .to_string(),
+            ));
+        }
+
         let rng = self
             .seed
             .map(StdRng::from_seed)
@@ -1107,12 +1122,14 @@ impl CliCommand<()> for RunLocalTestnet {
         // Spawn the node in a separate thread
         let config_path = self.config_path.clone();
         let test_dir_copy = test_dir.clone();
+        let with_indexer = self.with_indexer;
         let _node = thread::spawn(move || {
             aptos_node::load_test_environment(
                 config_path,
                 Some(test_dir_copy),
                 false,
                 false,
+                with_indexer,
                 cached_packages::head_release_bundle(),
                 rng,
             )
@@ -1481,6 +1498,9 @@ impl CliCommand<()> for BootstrapDbFromBackup {
             replay_all: false,
             ledger_history_start_version: None,
             skip_epoch_endings: false,
+            target_time_secs: None,
+            // The node catches up with the chain by itself.
+            state_only: true,
         };
         let global_opt = GlobalRestoreOpt {
             dry_run: false,
@@ -1490,6 +1510,8 @@ impl CliCommand<()> for BootstrapDbFromBackup {
             rocksdb_opt: RocksdbOpt::default(),
             concurrent_downloads: self.concurrent_downloads,
             replay_concurrency_level: self.replay_concurrency_level,
+            replay_write_sets: false,
+            encryption_key: Default::default(),
         }
         .try_into()?;
         let storage = Arc::new(CommandAdapter::new(
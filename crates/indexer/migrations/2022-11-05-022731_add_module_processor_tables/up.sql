@@ -0,0 +1,55 @@
+-- Your SQL goes here
+-- every call to an entry function made by a user transaction
+CREATE TABLE entry_function_calls (
+  transaction_version BIGINT UNIQUE PRIMARY KEY NOT NULL,
+  sender VARCHAR(66) NOT NULL,
+  module_address VARCHAR(66) NOT NULL,
+  module_name VARCHAR(255) NOT NULL,
+  function_name VARCHAR(255) NOT NULL,
+  -- e.g. ["0x1::aptos_coin::AptosCoin"]
+  type_arguments jsonb NOT NULL,
+  is_transaction_success BOOLEAN NOT NULL,
+  gas_used BIGINT NOT NULL,
+  gas_unit_price BIGINT NOT NULL,
+  block_height BIGINT NOT NULL,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX efc_ma_mn_fn_index ON entry_function_calls (module_address, module_name, function_name);
+CREATE INDEX efc_ma_s_index ON entry_function_calls (module_address, sender);
+CREATE INDEX efc_s_index ON entry_function_calls (sender);
+CREATE INDEX efc_insat_index ON entry_function_calls (inserted_at);
+-- ABI of a module each time it's published, upgraded or deleted
+CREATE TABLE move_module_abis (
+  transaction_version BIGINT NOT NULL,
+  write_set_change_index BIGINT NOT NULL,
+  address VARCHAR(66) NOT NULL,
+  name TEXT NOT NULL,
+  exposed_functions jsonb,
+  friends jsonb,
+  structs jsonb,
+  is_deleted BOOLEAN NOT NULL,
+  block_height BIGINT NOT NULL,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  -- Constraints
+  PRIMARY KEY (transaction_version, write_set_change_index)
+);
+CREATE INDEX mma_addr_name_ver_index ON move_module_abis (address, name, transaction_version);
+CREATE INDEX mma_insat_index ON move_module_abis (inserted_at);
+-- calls per entry function, e.g. for the most called functions
+CREATE VIEW entry_function_call_stats AS
+SELECT module_address,
+  module_name,
+  function_name,
+  COUNT(*) AS num_calls,
+  COUNT(*) FILTER (
+    WHERE is_transaction_success
+  ) AS num_successful_calls,
+  COUNT(DISTINCT sender) AS num_senders,
+  SUM(gas_used) AS total_gas_used,
+  MAX(transaction_version) AS last_transaction_version
+FROM entry_function_calls
+GROUP BY module_address,
+  module_name,
+  function_name;
@@ -0,0 +1,17 @@
+-- This file should undo anything in `up.sql`
+-- SQLite can't drop indexed columns
+DROP INDEX IF EXISTS curr_to_owner_tt_am_index;
+ALTER TABLE current_token_ownerships
+DROP COLUMN collection_data_id_hash;
+ALTER TABLE current_token_ownerships
+DROP COLUMN table_type;
+ALTER TABLE current_token_datas
+DROP COLUMN collection_data_id_hash;
+ALTER TABLE token_datas
+DROP COLUMN collection_data_id_hash;
+ALTER TABLE tokens
+DROP COLUMN collection_data_id_hash;
+ALTER TABLE token_ownerships
+DROP COLUMN collection_data_id_hash;
+DROP TABLE IF EXISTS token_activities;
+DROP TABLE IF EXISTS current_token_pending_claims;
\ No newline at end of file
@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+-- SQLite can't drop indexed columns
+DROP INDEX IF EXISTS curr_cd_th_index;
+ALTER TABLE collection_datas
+DROP COLUMN table_handle;
+ALTER TABLE current_collection_datas
+DROP COLUMN table_handle;
\ No newline at end of file
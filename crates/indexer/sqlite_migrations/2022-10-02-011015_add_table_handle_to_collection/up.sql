@@ -0,0 +1,7 @@
+-- Your SQL goes here
+-- SQLite can only add NOT NULL columns with a default, the indexer always sets them
+ALTER TABLE collection_datas
+ADD COLUMN table_handle VARCHAR(66) NOT NULL DEFAULT '';
+ALTER TABLE current_collection_datas
+ADD COLUMN table_handle VARCHAR(66) NOT NULL DEFAULT '';
+CREATE INDEX curr_cd_th_index ON current_collection_datas (table_handle);
\ No newline at end of file
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS curr_to_oa_tt_am_ltv_index;
+DROP INDEX IF EXISTS curr_to_oa_tt_ltv_index;
+DROP TABLE IF EXISTS current_ans_lookup;
\ No newline at end of file
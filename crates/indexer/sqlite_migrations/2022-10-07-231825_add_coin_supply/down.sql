@@ -0,0 +1,19 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS coin_supply;
+DROP INDEX IF EXISTS cs_ct_tv_index;
+DROP INDEX IF EXISTS cs_epoch_index;
+-- SQLite can't drop indexed columns
+DROP INDEX IF EXISTS ut_epoch_index;
+DROP INDEX IF EXISTS txn_epoch_index;
+ALTER TABLE coin_infos
+DROP COLUMN supply_aggregator_table_handle;
+ALTER TABLE coin_infos
+DROP COLUMN supply_aggregator_table_key;
+ALTER TABLE token_datas
+DROP COLUMN description;
+ALTER TABLE current_token_datas
+DROP COLUMN description;
+ALTER TABLE user_transactions
+DROP COLUMN epoch;
+ALTER TABLE transactions
+DROP COLUMN epoch;
\ No newline at end of file
@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS ct_fa_ct_index;
+DROP INDEX IF EXISTS ct_ta_ct_index;
+DROP INDEX IF EXISTS ct_fa_ta_index;
+DROP INDEX IF EXISTS ct_ct_a_index;
+DROP INDEX IF EXISTS ct_insat_index;
+DROP TABLE IF EXISTS coin_transfers;
\ No newline at end of file
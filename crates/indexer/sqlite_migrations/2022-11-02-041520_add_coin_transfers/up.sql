@@ -0,0 +1,32 @@
+-- Your SQL goes here
+-- withdraw and deposit events paired into transfers (also gas fees, mints and burns)
+CREATE TABLE coin_transfers (
+  transaction_version BIGINT NOT NULL,
+  -- order of the transfer within the transaction
+  transfer_index BIGINT NOT NULL,
+  -- creator_address::name::symbol
+  coin_type VARCHAR(5000) NOT NULL,
+  amount TEXT NOT NULL,
+  -- null if the coins were deposited without being withdrawn, e.g. minted
+  from_address VARCHAR(66),
+  -- null if the coins were withdrawn without being deposited, e.g. burned or gas fees
+  to_address VARCHAR(66),
+  -- events of coin_activities making up the transfer
+  withdraw_event_creation_number BIGINT,
+  withdraw_event_sequence_number BIGINT,
+  deposit_event_creation_number BIGINT,
+  deposit_event_sequence_number BIGINT,
+  is_gas_fee BOOLEAN NOT NULL,
+  is_transaction_success BOOLEAN NOT NULL,
+  entry_function_id_str VARCHAR(100),
+  block_height BIGINT NOT NULL,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  -- Constraints
+  PRIMARY KEY (transaction_version, transfer_index)
+);
+CREATE INDEX ct_fa_ct_index ON coin_transfers (from_address, coin_type);
+CREATE INDEX ct_ta_ct_index ON coin_transfers (to_address, coin_type);
+CREATE INDEX ct_fa_ta_index ON coin_transfers (from_address, to_address);
+CREATE INDEX ct_ct_a_index ON coin_transfers (coin_type, amount);
+CREATE INDEX ct_insat_index ON coin_transfers (inserted_at);
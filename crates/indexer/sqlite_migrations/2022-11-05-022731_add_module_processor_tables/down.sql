@@ -0,0 +1,10 @@
+-- This file should undo anything in `up.sql`
+DROP VIEW IF EXISTS entry_function_call_stats;
+DROP INDEX IF EXISTS efc_ma_mn_fn_index;
+DROP INDEX IF EXISTS efc_ma_s_index;
+DROP INDEX IF EXISTS efc_s_index;
+DROP INDEX IF EXISTS efc_insat_index;
+DROP TABLE IF EXISTS entry_function_calls;
+DROP INDEX IF EXISTS mma_addr_name_ver_index;
+DROP INDEX IF EXISTS mma_insat_index;
+DROP TABLE IF EXISTS move_module_abis;
\ No newline at end of file
@@ -0,0 +1,18 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_indexer::consistency_checker::{ConsistencyChecker, ConsistencyCheckerArgs};
+use clap::Parser;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    aptos_logger::Logger::new().init();
+    let reports = ConsistencyChecker::new(ConsistencyCheckerArgs::parse())?
+        .run()
+        .await?;
+    println!("{}", serde_json::to_string_pretty(&reports)?);
+    if reports.iter().any(|report| !report.mismatches.is_empty()) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -0,0 +1,131 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Read-only GraphQL API over the tables written by the processors, so that frontends don't need
+//! to run their own GraphQL engine in front of the database
+
+pub mod query;
+pub mod types;
+
+use crate::database::ArcDbPool;
+use aptos_logger::info;
+use async_graphql::{
+    http::{playground_source, GraphQLPlaygroundConfig},
+    EmptyMutation, EmptySubscription, Schema,
+};
+use async_graphql_poem::GraphQL;
+use poem::{get, handler, listener::TcpListener, web::Html, IntoResponse, Route, Server};
+use query::QueryRoot;
+use std::net::SocketAddr;
+
+pub type IndexerSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// None of the queries nest, this only bounds what introspection queries can ask for
+const MAX_QUERY_DEPTH: usize = 16;
+
+/// Bounds how many queries a single request runs, each costing `query::QUERY_COMPLEXITY`
+const MAX_QUERY_COMPLEXITY: usize = 500;
+
+pub fn build_schema(connection_pool: ArcDbPool) -> IndexerSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(connection_pool)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .limit_complexity(MAX_QUERY_COMPLEXITY)
+        .finish()
+}
+
+#[handler]
+async fn playground() -> impl IntoResponse {
+    Html(playground_source(GraphQLPlaygroundConfig::new("/")))
+}
+
+/// Serves queries on POST at `address`, and the GraphQL playground on GET. `connection_pool`
+/// should be the API's own, so that queries can't starve the processors of connections.
+pub async fn serve(connection_pool: ArcDbPool, address: SocketAddr) -> std::io::Result<()> {
+    let app = Route::new().at(
+        "/",
+        get(playground).post(GraphQL::new(build_schema(connection_pool))),
+    );
+    info!(address = address.to_string(), "Starting GraphQL server...");
+    Server::new(TcpListener::bind(address)).run(app).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{DbConnectionManager, DbPool};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_schema_is_read_only() {
+        let sdl = Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+            .finish()
+            .sdl();
+
+        for query in [
+            "current_token_ownerships(",
+            "current_coin_balances(",
+            "coin_activities(",
+            "token_activities(",
+            "ans_lookup(",
+            "current_ans_lookups(",
+        ] {
+            assert!(sdl.contains(query), "Missing {} in:\n{}", query, sdl);
+        }
+        assert!(!sdl.contains("type Mutation"));
+    }
+
+    /// A schema whose pool never connects, for queries rejected before reaching the database
+    fn unconnected_schema() -> IndexerSchema {
+        build_schema(Arc::new(DbPool::builder().build_unchecked(
+            DbConnectionManager::new("postgresql://localhost/unused").unwrap(),
+        )))
+    }
+
+    async fn execute_error(query: &str) -> String {
+        let response = unconnected_schema().execute(query).await;
+        assert_eq!(response.errors.len(), 1, "{:?}", response.errors);
+        response.errors[0].message.clone()
+    }
+
+    #[tokio::test]
+    async fn test_too_complex_query() {
+        let query = format!(
+            "{{ {} }}",
+            (0..6)
+                .map(|i| format!("q{}: ans_lookup(domain: \"aptos\") {{ domain }}", i))
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+        assert_eq!(execute_error(&query).await, "Query is too complex.");
+    }
+
+    #[tokio::test]
+    async fn test_offset_too_large() {
+        let message = execute_error(&format!(
+            "{{ coin_activities(offset: {}) {{ amount }} }}",
+            query::MAX_OFFSET + 1
+        ))
+        .await;
+        assert!(
+            message.starts_with("offset must be between 0 and"),
+            "{}",
+            message
+        );
+    }
+
+    #[tokio::test]
+    async fn test_order_by_amount_needs_indexed_filter() {
+        assert_eq!(
+            execute_error("{ current_coin_balances(order_by: AMOUNT) { amount } }").await,
+            "order_by AMOUNT needs a coin_type filter"
+        );
+        assert_eq!(
+            execute_error(
+                "{ current_token_ownerships(where: {name: \"a\"}, order_by: AMOUNT) { amount } }"
+            )
+            .await,
+            "order_by AMOUNT needs an owner_address filter"
+        );
+    }
+}
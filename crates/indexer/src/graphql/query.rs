@@ -0,0 +1,483 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use super::types::{
+    standardize_address_arg, AnsLookup, AnsLookupFilter, CoinActivity, CoinActivityFilter,
+    CoinBalance, CoinBalanceFilter, CoinBalanceOrderBy, OrderDirection, TokenActivity,
+    TokenActivityFilter, TokenOwnership, TokenOwnershipFilter, TokenOwnershipOrderBy,
+};
+use crate::{
+    database::{ArcDbPool, DbConnection},
+    schema::{
+        coin_activities, current_ans_lookup, current_coin_balances, current_token_ownerships,
+        token_activities,
+    },
+    sql_types::NumericValue,
+    with_conn,
+};
+use async_graphql::{Context, Object, Result};
+use diesel::prelude::*;
+
+/// Most rows a single query returns, and the number of rows returned if no limit is given
+pub const MAX_LIMIT: i64 = 100;
+
+/// Largest offset accepted, as the database still reads all the rows it skips
+pub const MAX_OFFSET: i64 = 10_000;
+
+/// Complexity of each query on top of its fields, as every one of them hits the database
+pub const QUERY_COMPLEXITY: usize = 100;
+
+/// Orders a boxed query by `column` in `direction`
+macro_rules! order_by {
+    ($query:expr, $column:expr, $direction:expr) => {
+        match $direction {
+            OrderDirection::Asc => $query.order($column.asc()),
+            OrderDirection::Desc => $query.order($column.desc()),
+        }
+    };
+}
+
+/// Checks the pagination arguments, returning the limit and offset to query with
+fn page(limit: Option<i64>, offset: Option<i64>) -> Result<(i64, i64)> {
+    let limit = limit.unwrap_or(MAX_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(format!("limit must be between 1 and {}, got {}", MAX_LIMIT, limit).into());
+    }
+    let offset = offset.unwrap_or(0);
+    if !(0..=MAX_OFFSET).contains(&offset) {
+        return Err(format!(
+            "offset must be between 0 and {}, got {}",
+            MAX_OFFSET, offset
+        )
+        .into());
+    }
+    Ok((limit, offset))
+}
+
+/// Runs `query` on a connection from the pool, away from the async runtime threads as diesel
+/// blocks
+async fn load<T, F>(ctx: &Context<'_>, query: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut DbConnection) -> QueryResult<T> + Send + 'static,
+{
+    let pool = ctx.data::<ArcDbPool>()?.clone();
+    let result = tokio::task::spawn_blocking(move || -> anyhow::Result<T> {
+        let mut conn = pool.get()?;
+        Ok(query(&mut conn)?)
+    })
+    .await??;
+    Ok(result)
+}
+
+pub struct QueryRoot;
+
+#[Object(rename_fields = "snake_case", rename_args = "snake_case")]
+impl QueryRoot {
+    /// Tokens currently held by accounts, from `current_token_ownerships`. Ordered by
+    /// `last_transaction_version` descending by default.
+    #[graphql(complexity = "QUERY_COMPLEXITY + child_complexity")]
+    async fn current_token_ownerships(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "where")] filter: Option<TokenOwnershipFilter>,
+        order_by: Option<TokenOwnershipOrderBy>,
+        direction: Option<OrderDirection>,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<Vec<TokenOwnership>> {
+        use current_token_ownerships::dsl;
+
+        let filter = filter.unwrap_or_default();
+        let (limit, offset) = page(limit, offset)?;
+        let direction = direction.unwrap_or(OrderDirection::Desc);
+        let order_by = order_by.unwrap_or(TokenOwnershipOrderBy::LastTransactionVersion);
+        if order_by == TokenOwnershipOrderBy::Amount && filter.owner_address.is_none() {
+            return Err("order_by AMOUNT needs an owner_address filter".into());
+        }
+        load(ctx, move |conn| {
+            with_conn!(conn, |conn| {
+                let mut query = dsl::current_token_ownerships
+                    .select((
+                        dsl::token_data_id_hash,
+                        dsl::property_version,
+                        dsl::owner_address,
+                        dsl::creator_address,
+                        dsl::collection_name,
+                        dsl::name,
+                        dsl::amount,
+                        dsl::token_properties,
+                        dsl::last_transaction_version,
+                        dsl::collection_data_id_hash,
+                        dsl::table_type,
+                        dsl::last_transaction_timestamp,
+                    ))
+                    .into_boxed();
+                if let Some(owner_address) = filter.owner_address {
+                    query = query
+                        .filter(dsl::owner_address.eq(standardize_address_arg(&owner_address)));
+                }
+                if let Some(creator_address) = filter.creator_address {
+                    query = query
+                        .filter(dsl::creator_address.eq(standardize_address_arg(&creator_address)));
+                }
+                if let Some(collection_name) = filter.collection_name {
+                    query = query.filter(dsl::collection_name.eq(collection_name));
+                }
+                if let Some(name) = filter.name {
+                    query = query.filter(dsl::name.eq(name));
+                }
+                if let Some(token_data_id_hash) = filter.token_data_id_hash {
+                    query = query.filter(dsl::token_data_id_hash.eq(token_data_id_hash));
+                }
+                if let Some(collection_data_id_hash) = filter.collection_data_id_hash {
+                    query = query.filter(dsl::collection_data_id_hash.eq(collection_data_id_hash));
+                }
+                if let Some(amount_gt) = filter.amount_gt {
+                    query = query.filter(dsl::amount.gt(NumericValue(amount_gt)));
+                }
+                query = match order_by {
+                    TokenOwnershipOrderBy::LastTransactionVersion => {
+                        order_by!(query, dsl::last_transaction_version, direction)
+                    }
+                    TokenOwnershipOrderBy::Amount => order_by!(query, dsl::amount, direction),
+                    TokenOwnershipOrderBy::CollectionName => {
+                        order_by!(query, dsl::collection_name, direction)
+                    }
+                    TokenOwnershipOrderBy::Name => order_by!(query, dsl::name, direction),
+                };
+                // The primary key keeps pages stable when the ordered values are equal
+                query
+                    .then_order_by((
+                        dsl::token_data_id_hash,
+                        dsl::property_version,
+                        dsl::owner_address,
+                    ))
+                    .limit(limit)
+                    .offset(offset)
+                    .load(conn)
+            })
+        })
+        .await
+    }
+
+    /// Coin balances of accounts, from `current_coin_balances`. Ordered by
+    /// `last_transaction_version` descending by default.
+    #[graphql(complexity = "QUERY_COMPLEXITY + child_complexity")]
+    async fn current_coin_balances(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "where")] filter: Option<CoinBalanceFilter>,
+        order_by: Option<CoinBalanceOrderBy>,
+        direction: Option<OrderDirection>,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<Vec<CoinBalance>> {
+        use current_coin_balances::dsl;
+
+        let filter = filter.unwrap_or_default();
+        let (limit, offset) = page(limit, offset)?;
+        let direction = direction.unwrap_or(OrderDirection::Desc);
+        let order_by = order_by.unwrap_or(CoinBalanceOrderBy::LastTransactionVersion);
+        if order_by == CoinBalanceOrderBy::Amount && filter.coin_type.is_none() {
+            return Err("order_by AMOUNT needs a coin_type filter".into());
+        }
+        load(ctx, move |conn| {
+            with_conn!(conn, |conn| {
+                let mut query = dsl::current_coin_balances
+                    .select((
+                        dsl::owner_address,
+                        dsl::coin_type_hash,
+                        dsl::coin_type,
+                        dsl::amount,
+                        dsl::last_transaction_version,
+                        dsl::last_transaction_timestamp,
+                    ))
+                    .into_boxed();
+                if let Some(owner_address) = filter.owner_address {
+                    query = query
+                        .filter(dsl::owner_address.eq(standardize_address_arg(&owner_address)));
+                }
+                if let Some(coin_type) = filter.coin_type {
+                    query = query.filter(dsl::coin_type.eq(coin_type));
+                }
+                if let Some(amount_gt) = filter.amount_gt {
+                    query = query.filter(dsl::amount.gt(NumericValue(amount_gt)));
+                }
+                query = match order_by {
+                    CoinBalanceOrderBy::LastTransactionVersion => {
+                        order_by!(query, dsl::last_transaction_version, direction)
+                    }
+                    CoinBalanceOrderBy::Amount => order_by!(query, dsl::amount, direction),
+                    CoinBalanceOrderBy::CoinType => order_by!(query, dsl::coin_type, direction),
+                };
+                query
+                    .then_order_by((dsl::owner_address, dsl::coin_type_hash))
+                    .limit(limit)
+                    .offset(offset)
+                    .load(conn)
+            })
+        })
+        .await
+    }
+
+    /// Coin deposits, withdrawals and gas fees, from `coin_activities`. Ordered by transaction
+    /// version descending by default.
+    #[graphql(complexity = "QUERY_COMPLEXITY + child_complexity")]
+    async fn coin_activities(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "where")] filter: Option<CoinActivityFilter>,
+        direction: Option<OrderDirection>,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<Vec<CoinActivity>> {
+        use coin_activities::dsl;
+
+        let filter = filter.unwrap_or_default();
+        let (limit, offset) = page(limit, offset)?;
+        let direction = direction.unwrap_or(OrderDirection::Desc);
+        load(ctx, move |conn| {
+            with_conn!(conn, |conn| {
+                let mut query = dsl::coin_activities
+                    .select((
+                        dsl::transaction_version,
+                        dsl::event_account_address,
+                        dsl::event_creation_number,
+                        dsl::event_sequence_number,
+                        dsl::owner_address,
+                        dsl::coin_type,
+                        dsl::amount,
+                        dsl::activity_type,
+                        dsl::is_gas_fee,
+                        dsl::is_transaction_success,
+                        dsl::entry_function_id_str,
+                        dsl::block_height,
+                        dsl::transaction_timestamp,
+                    ))
+                    .into_boxed();
+                if let Some(owner_address) = filter.owner_address {
+                    query = query
+                        .filter(dsl::owner_address.eq(standardize_address_arg(&owner_address)));
+                }
+                if let Some(coin_type) = filter.coin_type {
+                    query = query.filter(dsl::coin_type.eq(coin_type));
+                }
+                if let Some(activity_type) = filter.activity_type {
+                    query = query.filter(dsl::activity_type.eq(activity_type));
+                }
+                if let Some(is_gas_fee) = filter.is_gas_fee {
+                    query = query.filter(dsl::is_gas_fee.eq(is_gas_fee));
+                }
+                if let Some(min_version) = filter.min_transaction_version {
+                    query = query.filter(dsl::transaction_version.ge(min_version));
+                }
+                if let Some(max_version) = filter.max_transaction_version {
+                    query = query.filter(dsl::transaction_version.le(max_version));
+                }
+                query = match direction {
+                    OrderDirection::Asc => query.order((
+                        dsl::transaction_version.asc(),
+                        dsl::event_account_address.asc(),
+                        dsl::event_creation_number.asc(),
+                        dsl::event_sequence_number.asc(),
+                    )),
+                    OrderDirection::Desc => query.order((
+                        dsl::transaction_version.desc(),
+                        dsl::event_account_address.desc(),
+                        dsl::event_creation_number.desc(),
+                        dsl::event_sequence_number.desc(),
+                    )),
+                };
+                query.limit(limit).offset(offset).load(conn)
+            })
+        })
+        .await
+    }
+
+    /// Token mints, transfers, offers and claims, from `token_activities`. Ordered by
+    /// transaction version descending by default.
+    #[graphql(complexity = "QUERY_COMPLEXITY + child_complexity")]
+    async fn token_activities(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "where")] filter: Option<TokenActivityFilter>,
+        direction: Option<OrderDirection>,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<Vec<TokenActivity>> {
+        use token_activities::dsl;
+
+        let filter = filter.unwrap_or_default();
+        let (limit, offset) = page(limit, offset)?;
+        let direction = direction.unwrap_or(OrderDirection::Desc);
+        load(ctx, move |conn| {
+            with_conn!(conn, |conn| {
+                let mut query = dsl::token_activities
+                    .select((
+                        dsl::transaction_version,
+                        dsl::event_account_address,
+                        dsl::event_creation_number,
+                        dsl::event_sequence_number,
+                        dsl::collection_data_id_hash,
+                        dsl::token_data_id_hash,
+                        dsl::property_version,
+                        dsl::creator_address,
+                        dsl::collection_name,
+                        dsl::name,
+                        dsl::transfer_type,
+                        dsl::from_address,
+                        dsl::to_address,
+                        dsl::token_amount,
+                        dsl::coin_type,
+                        dsl::coin_amount,
+                        dsl::transaction_timestamp,
+                    ))
+                    .into_boxed();
+                if let Some(account_address) = filter.account_address {
+                    let account_address = standardize_address_arg(&account_address);
+                    query = query.filter(
+                        dsl::event_account_address
+                            .eq(account_address.clone())
+                            .or(dsl::from_address.eq(account_address.clone()))
+                            .or(dsl::to_address.eq(account_address)),
+                    );
+                }
+                if let Some(creator_address) = filter.creator_address {
+                    query = query
+                        .filter(dsl::creator_address.eq(standardize_address_arg(&creator_address)));
+                }
+                if let Some(collection_name) = filter.collection_name {
+                    query = query.filter(dsl::collection_name.eq(collection_name));
+                }
+                if let Some(token_data_id_hash) = filter.token_data_id_hash {
+                    query = query.filter(dsl::token_data_id_hash.eq(token_data_id_hash));
+                }
+                if let Some(collection_data_id_hash) = filter.collection_data_id_hash {
+                    query = query.filter(dsl::collection_data_id_hash.eq(collection_data_id_hash));
+                }
+                if let Some(transfer_type) = filter.transfer_type {
+                    query = query.filter(dsl::transfer_type.eq(transfer_type));
+                }
+                if let Some(min_version) = filter.min_transaction_version {
+                    query = query.filter(dsl::transaction_version.ge(min_version));
+                }
+                if let Some(max_version) = filter.max_transaction_version {
+                    query = query.filter(dsl::transaction_version.le(max_version));
+                }
+                query = match direction {
+                    OrderDirection::Asc => query.order((
+                        dsl::transaction_version.asc(),
+                        dsl::event_account_address.asc(),
+                        dsl::event_creation_number.asc(),
+                        dsl::event_sequence_number.asc(),
+                    )),
+                    OrderDirection::Desc => query.order((
+                        dsl::transaction_version.desc(),
+                        dsl::event_account_address.desc(),
+                        dsl::event_creation_number.desc(),
+                        dsl::event_sequence_number.desc(),
+                    )),
+                };
+                query.limit(limit).offset(offset).load(conn)
+            })
+        })
+        .await
+    }
+
+    /// The address an ANS name points to, `subdomain` being empty or left out for the domain
+    /// itself. Names that expired are still returned with their `expiration_timestamp`.
+    #[graphql(complexity = "QUERY_COMPLEXITY + child_complexity")]
+    async fn ans_lookup(
+        &self,
+        ctx: &Context<'_>,
+        domain: String,
+        subdomain: Option<String>,
+    ) -> Result<Option<AnsLookup>> {
+        use current_ans_lookup::dsl;
+
+        load(ctx, move |conn| {
+            with_conn!(conn, |conn| {
+                dsl::current_ans_lookup
+                    .select((
+                        dsl::domain,
+                        dsl::subdomain,
+                        dsl::registered_address,
+                        dsl::expiration_timestamp,
+                        dsl::last_transaction_version,
+                        dsl::token_name,
+                    ))
+                    .filter(dsl::domain.eq(domain))
+                    .filter(dsl::subdomain.eq(subdomain.unwrap_or_default()))
+                    .first(conn)
+                    .optional()
+            })
+        })
+        .await
+    }
+
+    /// ANS names, from `current_ans_lookup`. Filtering on `registered_address` gives the names
+    /// pointing to an account. Ordered by domain then subdomain.
+    #[graphql(complexity = "QUERY_COMPLEXITY + child_complexity")]
+    async fn current_ans_lookups(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "where")] filter: Option<AnsLookupFilter>,
+        direction: Option<OrderDirection>,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<Vec<AnsLookup>> {
+        use current_ans_lookup::dsl;
+
+        let filter = filter.unwrap_or_default();
+        let (limit, offset) = page(limit, offset)?;
+        let direction = direction.unwrap_or(OrderDirection::Asc);
+        load(ctx, move |conn| {
+            with_conn!(conn, |conn| {
+                let mut query = dsl::current_ans_lookup
+                    .select((
+                        dsl::domain,
+                        dsl::subdomain,
+                        dsl::registered_address,
+                        dsl::expiration_timestamp,
+                        dsl::last_transaction_version,
+                        dsl::token_name,
+                    ))
+                    .into_boxed();
+                if let Some(domain) = filter.domain {
+                    query = query.filter(dsl::domain.eq(domain));
+                }
+                if let Some(subdomain) = filter.subdomain {
+                    query = query.filter(dsl::subdomain.eq(subdomain));
+                }
+                if let Some(registered_address) = filter.registered_address {
+                    query = query.filter(
+                        dsl::registered_address.eq(standardize_address_arg(&registered_address)),
+                    );
+                }
+                query = match direction {
+                    OrderDirection::Asc => query.order((dsl::domain.asc(), dsl::subdomain.asc())),
+                    OrderDirection::Desc => {
+                        query.order((dsl::domain.desc(), dsl::subdomain.desc()))
+                    }
+                };
+                query.limit(limit).offset(offset).load(conn)
+            })
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page() {
+        assert_eq!(page(None, None).unwrap(), (MAX_LIMIT, 0));
+        assert_eq!(page(Some(10), Some(20)).unwrap(), (10, 20));
+        assert!(page(Some(0), None).is_err());
+        assert!(page(Some(MAX_LIMIT + 1), None).is_err());
+        assert!(page(None, Some(-1)).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Objects returned by the GraphQL API and the filters accepted by its queries. Objects are read
+//! straight from the tables the processors write, with the same field names.
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+
+use async_graphql::{Enum, InputObject, Json, SimpleObject};
+use bigdecimal::BigDecimal;
+
+#[derive(Clone, Copy, Debug, Enum, Eq, PartialEq)]
+pub enum OrderDirection {
+    Asc,
+    Desc,
+}
+
+#[derive(Clone, Debug, Queryable, SimpleObject)]
+#[graphql(rename_fields = "snake_case")]
+pub struct TokenOwnership {
+    pub token_data_id_hash: String,
+    pub property_version: BigDecimal,
+    pub owner_address: String,
+    pub creator_address: String,
+    pub collection_name: String,
+    pub name: String,
+    pub amount: BigDecimal,
+    #[diesel(deserialize_as = serde_json::Value)]
+    pub token_properties: Json<serde_json::Value>,
+    pub last_transaction_version: i64,
+    pub collection_data_id_hash: String,
+    pub table_type: String,
+    pub last_transaction_timestamp: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Default, InputObject)]
+#[graphql(rename_fields = "snake_case")]
+pub struct TokenOwnershipFilter {
+    pub owner_address: Option<String>,
+    pub creator_address: Option<String>,
+    pub collection_name: Option<String>,
+    pub name: Option<String>,
+    pub token_data_id_hash: Option<String>,
+    pub collection_data_id_hash: Option<String>,
+    /// Only tokens of which more than this amount is held, e.g. 0 to leave out the tokens an
+    /// account used to own
+    pub amount_gt: Option<BigDecimal>,
+}
+
+#[derive(Clone, Copy, Debug, Enum, Eq, PartialEq)]
+pub enum TokenOwnershipOrderBy {
+    LastTransactionVersion,
+    /// Only with an `owner_address` filter, amounts are indexed per owner
+    Amount,
+    CollectionName,
+    Name,
+}
+
+#[derive(Clone, Debug, Queryable, SimpleObject)]
+#[graphql(rename_fields = "snake_case")]
+pub struct CoinBalance {
+    pub owner_address: String,
+    pub coin_type_hash: String,
+    pub coin_type: String,
+    pub amount: BigDecimal,
+    pub last_transaction_version: i64,
+    pub last_transaction_timestamp: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Default, InputObject)]
+#[graphql(rename_fields = "snake_case")]
+pub struct CoinBalanceFilter {
+    pub owner_address: Option<String>,
+    pub coin_type: Option<String>,
+    pub amount_gt: Option<BigDecimal>,
+}
+
+#[derive(Clone, Copy, Debug, Enum, Eq, PartialEq)]
+pub enum CoinBalanceOrderBy {
+    LastTransactionVersion,
+    /// Only with a `coin_type` filter, amounts are indexed per coin type
+    Amount,
+    CoinType,
+}
+
+#[derive(Clone, Debug, Queryable, SimpleObject)]
+#[graphql(rename_fields = "snake_case")]
+pub struct CoinActivity {
+    pub transaction_version: i64,
+    pub event_account_address: String,
+    pub event_creation_number: i64,
+    pub event_sequence_number: i64,
+    pub owner_address: String,
+    pub coin_type: String,
+    pub amount: BigDecimal,
+    pub activity_type: String,
+    pub is_gas_fee: bool,
+    pub is_transaction_success: bool,
+    pub entry_function_id_str: Option<String>,
+    pub block_height: i64,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Default, InputObject)]
+#[graphql(rename_fields = "snake_case")]
+pub struct CoinActivityFilter {
+    pub owner_address: Option<String>,
+    pub coin_type: Option<String>,
+    pub activity_type: Option<String>,
+    pub is_gas_fee: Option<bool>,
+    /// Inclusive
+    pub min_transaction_version: Option<i64>,
+    /// Inclusive
+    pub max_transaction_version: Option<i64>,
+}
+
+#[derive(Clone, Debug, Queryable, SimpleObject)]
+#[graphql(rename_fields = "snake_case")]
+pub struct TokenActivity {
+    pub transaction_version: i64,
+    pub event_account_address: String,
+    pub event_creation_number: i64,
+    pub event_sequence_number: i64,
+    pub collection_data_id_hash: String,
+    pub token_data_id_hash: String,
+    pub property_version: BigDecimal,
+    pub creator_address: String,
+    pub collection_name: String,
+    pub name: String,
+    pub transfer_type: String,
+    pub from_address: Option<String>,
+    pub to_address: Option<String>,
+    pub token_amount: BigDecimal,
+    pub coin_type: Option<String>,
+    pub coin_amount: Option<BigDecimal>,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Default, InputObject)]
+#[graphql(rename_fields = "snake_case")]
+pub struct TokenActivityFilter {
+    /// Activities of the account, whether it's the one emitting the event, the sender or the
+    /// receiver
+    pub account_address: Option<String>,
+    pub creator_address: Option<String>,
+    pub collection_name: Option<String>,
+    pub token_data_id_hash: Option<String>,
+    pub collection_data_id_hash: Option<String>,
+    pub transfer_type: Option<String>,
+    /// Inclusive
+    pub min_transaction_version: Option<i64>,
+    /// Inclusive
+    pub max_transaction_version: Option<i64>,
+}
+
+#[derive(Clone, Debug, Queryable, SimpleObject)]
+#[graphql(rename_fields = "snake_case")]
+pub struct AnsLookup {
+    pub domain: String,
+    pub subdomain: String,
+    pub registered_address: Option<String>,
+    pub expiration_timestamp: chrono::NaiveDateTime,
+    pub last_transaction_version: i64,
+    pub token_name: String,
+}
+
+#[derive(Debug, Default, InputObject)]
+#[graphql(rename_fields = "snake_case")]
+pub struct AnsLookupFilter {
+    pub domain: Option<String>,
+    /// Empty for the domain itself
+    pub subdomain: Option<String>,
+    pub registered_address: Option<String>,
+}
+
+/// Pads addresses given in queries to the 0x + 64 hex characters the processors store
+pub fn standardize_address_arg(address: &str) -> String {
+    let address = address.trim().to_lowercase();
+    let hex = address.strip_prefix("0x").unwrap_or(&address);
+    format!("0x{:0>64}", hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standardize_address_arg() {
+        assert_eq!(
+            standardize_address_arg("0x1"),
+            "0x0000000000000000000000000000000000000000000000000000000000000001"
+        );
+        assert_eq!(
+            standardize_address_arg(" 0xAB "),
+            "0x00000000000000000000000000000000000000000000000000000000000000ab"
+        );
+        assert_eq!(
+            standardize_address_arg("ab"),
+            standardize_address_arg("0xab")
+        );
+    }
+}
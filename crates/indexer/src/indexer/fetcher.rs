@@ -26,6 +26,7 @@ pub struct Fetcher {
     chain_id: u8,
     current_version: u64,
     highest_known_version: u64,
+    end_version: Option<u64>,
     transactions_sender: mpsc::Sender<Vec<Transaction>>,
 }
 
@@ -33,6 +34,7 @@ impl Fetcher {
     pub fn new(
         context: Arc<Context>,
         starting_version: u64,
+        end_version: Option<u64>,
         options: TransactionFetcherOptions,
         transactions_sender: mpsc::Sender<Vec<Transaction>>,
     ) -> Self {
@@ -42,13 +44,14 @@ impl Fetcher {
             chain_id: 0,
             current_version: starting_version,
             highest_known_version: 0,
+            end_version,
             transactions_sender,
         }
     }
 
     pub fn set_highest_known_version(&mut self) -> anyhow::Result<()> {
         let info = self.context.get_latest_ledger_info_wrapped()?;
-        self.highest_known_version = info.ledger_version.0 as u64;
+        self.highest_known_version = cap_to_end_version(info.ledger_version.0, self.end_version);
         self.chain_id = info.chain_id;
         Ok(())
     }
@@ -87,9 +90,18 @@ impl Fetcher {
     /// 2. Determine how many batches of size `options.transaction_fetch_batch_size` we need to catch up
     /// 3. Spawn tasks which fetch 'raw' `OnChainTransactions` from storage, and convert them to `Transaction`s. We spawn at most `options.max_tasks` tasks.
     /// 4. We wait for all the tasks to complete, then send the `Transaction`s to the processor, via the `transactions_sender` channel.
+    ///
+    /// Returns once every version up to the end version, if there is one, has been sent.
     pub async fn run(&mut self) {
         let transaction_fetch_batch_size = self.options.transaction_fetch_batch_size;
         loop {
+            if is_past_end_version(self.current_version, self.end_version) {
+                info!(
+                    end_version = self.end_version,
+                    "Fetched all transactions up to the end version"
+                );
+                return;
+            }
             self.ensure_highest_known_version().await;
 
             info!(
@@ -333,6 +345,19 @@ async fn fetch_nexts(
     transactions
 }
 
+/// The highest version to fetch given the ledger version, never past the end version
+pub(crate) fn cap_to_end_version(ledger_version: u64, end_version: Option<u64>) -> u64 {
+    match end_version {
+        Some(end_version) => std::cmp::min(ledger_version, end_version),
+        None => ledger_version,
+    }
+}
+
+/// Whether all the versions up to the end version, if there is one, have been fetched
+pub(crate) fn is_past_end_version(current_version: u64, end_version: Option<u64>) -> bool {
+    end_version.map_or(false, |end_version| current_version > end_version)
+}
+
 #[derive(Clone, Debug)]
 pub struct TransactionFetcherOptions {
     pub starting_retry_time_millis: u64,
@@ -398,6 +423,7 @@ impl Default for TransactionFetcherOptions {
 
 pub struct TransactionFetcher {
     starting_version: u64,
+    end_version: Option<u64>,
     options: TransactionFetcherOptions,
     pub context: Arc<Context>,
     pub resolver: Arc<StorageAdapterOwned<DbStateView>>,
@@ -418,6 +444,7 @@ impl TransactionFetcher {
 
         Self {
             starting_version,
+            end_version: None,
             options,
             context,
             resolver,
@@ -435,6 +462,8 @@ impl TransactionFetcherTrait for TransactionFetcher {
         // try_next is nonblocking unlike next. It'll try to fetch the next one and return immediately.
         match self.transaction_receiver.try_next() {
             Ok(Some(transactions)) => transactions,
+            // The fetcher only closes the channel once it has sent everything up to the end version
+            Ok(None) if self.end_version.is_some() => vec![],
             Ok(None) => {
                 // We never close the channel, so this should never happen
                 panic!("Transaction fetcher channel closed");
@@ -444,7 +473,7 @@ impl TransactionFetcherTrait for TransactionFetcher {
         }
     }
 
-    fn fetch_ledger_info(&mut self) -> LedgerInfo {
+    async fn fetch_ledger_info(&mut self) -> LedgerInfo {
         self.context
             .get_latest_ledger_info_wrapped()
             .unwrap_or_else(|err| panic!("Failed to get ledger info: {}", err))
@@ -457,6 +486,13 @@ impl TransactionFetcherTrait for TransactionFetcher {
         self.starting_version = version;
     }
 
+    async fn set_end_version(&mut self, version: u64) {
+        if self.fetcher_handle.is_some() {
+            panic!("TransactionFetcher already started!");
+        }
+        self.end_version = Some(version);
+    }
+
     async fn start(&mut self) {
         if self.fetcher_handle.is_some() {
             panic!("TransactionFetcher already started!");
@@ -464,12 +500,14 @@ impl TransactionFetcherTrait for TransactionFetcher {
         let context = self.context.clone();
         let transactions_sender = self.transactions_sender.take().unwrap();
         let starting_version = self.starting_version;
+        let end_version = self.end_version;
 
         let options2 = self.options.clone();
         let fetcher_handle = tokio::spawn(async move {
             let mut fetcher = Fetcher::new(
                 context,
                 starting_version as u64,
+                end_version,
                 options2,
                 transactions_sender,
             );
@@ -484,9 +522,33 @@ impl TransactionFetcherTrait for TransactionFetcher {
 pub trait TransactionFetcherTrait: Send + Sync {
     async fn fetch_next_batch(&mut self) -> Vec<Transaction>;
 
-    fn fetch_ledger_info(&mut self) -> LedgerInfo;
+    async fn fetch_ledger_info(&mut self) -> LedgerInfo;
 
     async fn set_version(&mut self, version: u64);
 
+    /// Stops fetching after `version`, instead of following the chain forever
+    async fn set_end_version(&mut self, version: u64);
+
     async fn start(&mut self);
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cap_to_end_version() {
+        assert_eq!(cap_to_end_version(100, None), 100);
+        assert_eq!(cap_to_end_version(100, Some(50)), 50);
+        assert_eq!(cap_to_end_version(100, Some(100)), 100);
+        assert_eq!(cap_to_end_version(100, Some(150)), 100);
+    }
+
+    #[test]
+    fn test_is_past_end_version() {
+        assert!(!is_past_end_version(100, None));
+        assert!(!is_past_end_version(49, Some(50)));
+        assert!(!is_past_end_version(50, Some(50)));
+        assert!(is_past_end_version(51, Some(50)));
+    }
+}
@@ -3,6 +3,8 @@
 
 pub mod errors;
 pub mod fetcher;
+pub mod parquet_sink;
 pub mod processing_result;
+pub mod rest_fetcher;
 pub mod tailer;
 pub mod transaction_processor;
@@ -0,0 +1,849 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Writes the models of a processor to Parquet files in a local directory, as an alternative to
+//! inserting them in the database.
+//!
+//! Every processed batch is first staged as JSON lines under `staging/<start>_<end>/`, so the rows
+//! of versions already marked as processed in `processor_status` survive restarts. Once at least
+//! `versions_per_file` consecutive versions are staged, they are rolled into a
+//! `<table>/<start>_<end>.parquet` file per table and the range is appended to `manifest.json`.
+//! Every version of a range listed in the manifest was processed, a table without a file in it
+//! had no rows.
+//!
+//! The columns of a table are those of the diesel table its rows would be inserted in, so every
+//! file of a table has the same schema whatever rows it holds.
+
+use anyhow::{bail, Context, Result};
+use aptos_config::config::{IndexerConfig, DEFAULT_PARQUET_VERSIONS_PER_FILE};
+use arrow::{
+    array::{
+        ArrayRef, BinaryArray, BooleanArray, Int32Array, Int64Array, StringArray,
+        TimestampMicrosecondArray,
+    },
+    datatypes::{DataType, Field, Schema, TimeUnit},
+    record_batch::RecordBatch,
+};
+use diesel::{sql_types, Column, Expression, Table};
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+const STAGING_DIRECTORY: &str = "staging";
+/// Written in the directory of a staged batch once all of its tables are
+const BATCH_COMPLETE_FILE: &str = "_COMPLETE";
+/// Written in the directory of a staged batch, the columns of each of its tables
+const BATCH_COLUMNS_FILE: &str = "columns.json";
+/// Fields holding the version of a row, in the order they are looked up
+const VERSION_FIELDS: [&str; 3] = ["transaction_version", "version", "last_transaction_version"];
+/// Columns filled by the database on insertion, which the models don't have
+const DATABASE_DEFAULT_COLUMNS: [&str; 1] = ["inserted_at"];
+/// Most rows buffered before they're written to a file as a row group
+const MAX_ROW_GROUP_SIZE: usize = 100_000;
+
+/// Lists the ranges of versions rolled into Parquet files
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Manifest {
+    /// Consecutive ranges, in increasing order
+    pub ranges: Vec<ManifestRange>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ManifestRange {
+    pub start_version: u64,
+    /// Inclusive
+    pub end_version: u64,
+    /// The file of every table with rows in the range, keyed by table name
+    pub files: BTreeMap<String, ManifestFile>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ManifestFile {
+    /// Relative to the directory of the manifest
+    pub path: String,
+    pub num_rows: usize,
+}
+
+impl Manifest {
+    /// The first version after the ranges rolled so far
+    pub fn next_version(&self) -> Option<u64> {
+        self.ranges.last().map(|range| range.end_version + 1)
+    }
+}
+
+/// Type of the values of a Parquet column. Decimals and json are stored as strings.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ColumnType {
+    Boolean,
+    Int32,
+    Int64,
+    Utf8,
+    Binary,
+    Timestamp,
+}
+
+impl ColumnType {
+    fn data_type(self) -> DataType {
+        match self {
+            ColumnType::Boolean => DataType::Boolean,
+            ColumnType::Int32 => DataType::Int32,
+            ColumnType::Int64 => DataType::Int64,
+            ColumnType::Utf8 => DataType::Utf8,
+            ColumnType::Binary => DataType::Binary,
+            ColumnType::Timestamp => DataType::Timestamp(TimeUnit::Microsecond, None),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ParquetColumn {
+    pub name: String,
+    pub column_type: ColumnType,
+    pub nullable: bool,
+}
+
+/// The Parquet column type a diesel SQL type is stored as
+pub trait ParquetType {
+    const COLUMN_TYPE: ColumnType;
+    const NULLABLE: bool = false;
+}
+
+macro_rules! impl_parquet_type {
+    ($($sql_type:ty => $column_type:ident),+ $(,)?) => {
+        $(
+            impl ParquetType for $sql_type {
+                const COLUMN_TYPE: ColumnType = ColumnType::$column_type;
+            }
+        )+
+    };
+}
+
+impl_parquet_type!(
+    sql_types::Bool => Boolean,
+    sql_types::Integer => Int32,
+    sql_types::BigInt => Int64,
+    sql_types::Text => Utf8,
+    crate::sql_types::Numeric => Utf8,
+    crate::sql_types::Jsonb => Utf8,
+    sql_types::Binary => Binary,
+    sql_types::Timestamp => Timestamp,
+);
+
+impl<T: ParquetType> ParquetType for sql_types::Nullable<T> {
+    const COLUMN_TYPE: ColumnType = T::COLUMN_TYPE;
+    const NULLABLE: bool = true;
+}
+
+/// The columns of a tuple of diesel columns, e.g. the `AllColumns` of a table
+pub trait ParquetColumns {
+    fn columns() -> Vec<ParquetColumn>;
+}
+
+macro_rules! impl_parquet_columns {
+    () => {};
+    ($first:ident $(, $rest:ident)*) => {
+        impl<$first $(, $rest)*> ParquetColumns for ($first, $($rest,)*)
+        where
+            $first: Column,
+            <$first as Expression>::SqlType: ParquetType,
+            $(
+                $rest: Column,
+                <$rest as Expression>::SqlType: ParquetType,
+            )*
+        {
+            fn columns() -> Vec<ParquetColumn> {
+                vec![
+                    parquet_column::<$first>(),
+                    $(parquet_column::<$rest>(),)*
+                ]
+            }
+        }
+        impl_parquet_columns!($($rest),*);
+    };
+}
+
+impl_parquet_columns!(
+    C1, C2, C3, C4, C5, C6, C7, C8, C9, C10, C11, C12, C13, C14, C15, C16, C17, C18, C19, C20, C21,
+    C22, C23, C24, C25, C26, C27, C28, C29, C30, C31, C32
+);
+
+fn parquet_column<C>() -> ParquetColumn
+where
+    C: Column,
+    <C as Expression>::SqlType: ParquetType,
+{
+    ParquetColumn {
+        name: C::NAME.to_string(),
+        column_type: <<C as Expression>::SqlType as ParquetType>::COLUMN_TYPE,
+        nullable: <<C as Expression>::SqlType as ParquetType>::NULLABLE,
+    }
+}
+
+/// The columns of the diesel table `T`, but the ones filled by the database
+pub fn table_columns<T>() -> Vec<ParquetColumn>
+where
+    T: Table,
+    T::AllColumns: ParquetColumns,
+{
+    <T::AllColumns as ParquetColumns>::columns()
+        .into_iter()
+        .filter(|column| !DATABASE_DEFAULT_COLUMNS.contains(&column.name.as_str()))
+        .collect()
+}
+
+/// The rows of every table produced by processing a batch of versions
+#[derive(Debug)]
+pub struct ParquetBatch {
+    start_version: u64,
+    end_version: u64,
+    tables: BTreeMap<&'static str, (Vec<ParquetColumn>, Vec<Value>)>,
+    /// The first rows that couldn't be serialized, returned by `ParquetSink::write`
+    error: Option<serde_json::Error>,
+}
+
+impl ParquetBatch {
+    pub fn new(start_version: u64, end_version: u64) -> Self {
+        Self {
+            start_version,
+            end_version,
+            tables: BTreeMap::new(),
+            error: None,
+        }
+    }
+
+    /// Adds rows to `table`, usually named after the diesel table `_diesel_table` they'd be
+    /// inserted in, whose columns make the schema of the Parquet files
+    pub fn add<T, R>(mut self, table: &'static str, _diesel_table: T, rows: &[R]) -> Self
+    where
+        T: Table,
+        T::AllColumns: ParquetColumns,
+        R: Serialize,
+    {
+        if self.error.is_none() {
+            match rows
+                .iter()
+                .map(serde_json::to_value)
+                .collect::<Result<Vec<_>, _>>()
+            {
+                Ok(rows) => self
+                    .tables
+                    .entry(table)
+                    .or_insert_with(|| (table_columns::<T>(), vec![]))
+                    .1
+                    .extend(rows),
+                Err(err) => self.error = Some(err),
+            }
+        }
+        self
+    }
+}
+
+#[derive(Debug)]
+struct StagedBatch {
+    start_version: u64,
+    end_version: u64,
+    path: PathBuf,
+}
+
+impl StagedBatch {
+    fn read_columns(&self) -> Result<BTreeMap<String, Vec<ParquetColumn>>> {
+        Ok(serde_json::from_slice(&fs::read(
+            self.path.join(BATCH_COLUMNS_FILE),
+        )?)?)
+    }
+
+    /// Names of the tables with rows in the batch
+    fn tables(&self) -> Result<Vec<String>> {
+        let mut tables = vec![];
+        for entry in fs::read_dir(&self.path)? {
+            let path = entry?.path();
+            if let (Some(table), Some(extension)) =
+                (path.file_stem().and_then(|s| s.to_str()), path.extension())
+            {
+                if extension == "jsonl" {
+                    tables.push(table.to_string());
+                }
+            }
+        }
+        tables.sort();
+        Ok(tables)
+    }
+
+    /// The rows of `table` from `first_version` on
+    fn read_rows(&self, table: &str, first_version: u64) -> Result<Vec<Value>> {
+        let path = self.path.join(format!("{}.jsonl", table));
+        let mut rows = vec![];
+        for line in BufReader::new(fs::File::open(&path)?).lines() {
+            let row: Value = serde_json::from_str(&line?)?;
+            // Skips the versions staged by an earlier batch too
+            if row_version(&row).map_or(true, |v| v >= first_version) {
+                rows.push(row);
+            }
+        }
+        Ok(rows)
+    }
+}
+
+#[derive(Debug)]
+pub struct ParquetSink {
+    directory: PathBuf,
+    versions_per_file: u64,
+    /// Batches are processed in parallel, but only one at a time rolls files
+    roll_lock: Mutex<()>,
+}
+
+impl ParquetSink {
+    pub fn new(directory: impl Into<PathBuf>, versions_per_file: u64) -> Result<Self> {
+        let directory = directory.into();
+        fs::create_dir_all(directory.join(STAGING_DIRECTORY))
+            .with_context(|| format!("Failed to create {}", directory.display()))?;
+        Ok(Self {
+            directory,
+            versions_per_file: versions_per_file.max(1),
+            roll_lock: Mutex::new(()),
+        })
+    }
+
+    /// The sink of `processor_name` if `config` sets a Parquet directory. Each processor writes
+    /// to its own subdirectory, with its own manifest.
+    pub fn from_config(config: &IndexerConfig, processor_name: &str) -> Option<Self> {
+        config.parquet_directory.as_ref().map(|directory| {
+            Self::new(
+                directory.join(processor_name),
+                config
+                    .parquet_versions_per_file
+                    .unwrap_or(DEFAULT_PARQUET_VERSIONS_PER_FILE),
+            )
+            .unwrap_or_else(|e| panic!("Failed to create Parquet sink: {:?}", e))
+        })
+    }
+
+    pub fn manifest(&self) -> Result<Manifest> {
+        let path = self.directory.join(MANIFEST_FILE);
+        if !path.exists() {
+            return Ok(Manifest::default());
+        }
+        Ok(serde_json::from_slice(&fs::read(&path)?)?)
+    }
+
+    /// Stages the rows of a processed batch, then rolls the staged batches into Parquet files if
+    /// enough consecutive versions are staged
+    pub fn write(&self, batch: ParquetBatch) -> Result<()> {
+        if let Some(err) = batch.error {
+            return Err(err.into());
+        }
+        let batch_directory = self
+            .directory
+            .join(STAGING_DIRECTORY)
+            .join(format!("{}_{}", batch.start_version, batch.end_version));
+        // The batch may have been staged already, e.g. when reprocessing versions after a restart
+        if batch_directory.exists() {
+            fs::remove_dir_all(&batch_directory)?;
+        }
+        fs::create_dir_all(&batch_directory)?;
+        let mut columns = BTreeMap::new();
+        for (table, (table_columns, rows)) in &batch.tables {
+            columns.insert(*table, table_columns);
+            if rows.is_empty() {
+                continue;
+            }
+            let mut contents = vec![];
+            for row in rows {
+                serde_json::to_writer(&mut contents, row)?;
+                contents.push(b'\n');
+            }
+            fs::write(batch_directory.join(format!("{}.jsonl", table)), contents)?;
+        }
+        fs::write(
+            batch_directory.join(BATCH_COLUMNS_FILE),
+            serde_json::to_vec(&columns)?,
+        )?;
+        fs::write(batch_directory.join(BATCH_COMPLETE_FILE), [])?;
+
+        let _guard = self.roll_lock.lock().unwrap();
+        while self.roll()? {}
+        Ok(())
+    }
+
+    /// Rolls the staged batches following the last range of the manifest into Parquet files, if
+    /// they cover at least `versions_per_file` consecutive versions. Returns whether it did.
+    /// The batches are read one at a time, so only the rows of a single batch are held in memory.
+    fn roll(&self) -> Result<bool> {
+        let mut manifest = self.manifest()?;
+        let mut staged = self.staged_batches()?;
+        if let Some(next_version) = manifest.next_version() {
+            for batch in staged
+                .iter()
+                .filter(|batch| batch.end_version < next_version)
+            {
+                fs::remove_dir_all(&batch.path)?;
+            }
+            staged.retain(|batch| batch.end_version >= next_version);
+        }
+        let start_version = match manifest
+            .next_version()
+            .or_else(|| staged.first().map(|batch| batch.start_version))
+        {
+            Some(start_version) => start_version,
+            None => return Ok(false),
+        };
+        let (batches, end_version) = consecutive_batches(&staged, start_version);
+        let end_version = match end_version {
+            Some(end_version) if end_version + 1 - start_version >= self.versions_per_file => {
+                end_version
+            }
+            _ => return Ok(false),
+        };
+
+        let mut writers: BTreeMap<String, TableWriter> = BTreeMap::new();
+        for (batch, first_version) in &batches {
+            let columns = batch.read_columns()?;
+            for table in batch.tables()? {
+                let rows = batch.read_rows(&table, *first_version)?;
+                if rows.is_empty() {
+                    continue;
+                }
+                if !writers.contains_key(&table) {
+                    let table_columns = columns.get(&table).with_context(|| {
+                        format!("No columns staged for {} in {:?}", table, batch.path)
+                    })?;
+                    let path = format!("{}/{}_{}.parquet", table, start_version, end_version);
+                    let writer = TableWriter::new(&self.directory, path, table_columns.clone())?;
+                    writers.insert(table.clone(), writer);
+                }
+                writers
+                    .get_mut(&table)
+                    .unwrap()
+                    .write(&rows)
+                    .with_context(|| format!("Failed to write the rows of {}", table))?;
+            }
+        }
+        let mut files = BTreeMap::new();
+        for (table, writer) in writers {
+            files.insert(table, writer.close()?);
+        }
+        manifest.ranges.push(ManifestRange {
+            start_version,
+            end_version,
+            files,
+        });
+        write_atomically(
+            &self.directory.join(MANIFEST_FILE),
+            &serde_json::to_vec_pretty(&manifest)?,
+        )?;
+        for (batch, _) in batches {
+            fs::remove_dir_all(&batch.path)?;
+        }
+        Ok(true)
+    }
+
+    /// Fully staged batches, sorted by versions
+    fn staged_batches(&self) -> Result<Vec<StagedBatch>> {
+        let mut batches = vec![];
+        for entry in fs::read_dir(self.directory.join(STAGING_DIRECTORY))? {
+            let path = entry?.path();
+            if !path.join(BATCH_COMPLETE_FILE).exists() {
+                continue;
+            }
+            let versions = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.split_once('_'))
+                .and_then(|(start, end)| Some((start.parse().ok()?, end.parse().ok()?)));
+            if let Some((start_version, end_version)) = versions {
+                batches.push(StagedBatch {
+                    start_version,
+                    end_version,
+                    path,
+                });
+            }
+        }
+        batches.sort_by_key(|batch| (batch.start_version, batch.end_version));
+        Ok(batches)
+    }
+}
+
+/// Writes the rows of a table to a Parquet file as they're read, under a temporary name until
+/// it's closed
+struct TableWriter {
+    path: String,
+    file_path: PathBuf,
+    tmp_path: PathBuf,
+    columns: Vec<ParquetColumn>,
+    schema: Arc<Schema>,
+    writer: ArrowWriter<fs::File>,
+    num_rows: usize,
+}
+
+impl TableWriter {
+    fn new(directory: &Path, path: String, columns: Vec<ParquetColumn>) -> Result<Self> {
+        let file_path = directory.join(&path);
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp_path = file_path.with_extension("tmp");
+        let schema = to_schema(&columns);
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_max_row_group_size(MAX_ROW_GROUP_SIZE)
+            .build();
+        let writer = ArrowWriter::try_new(
+            fs::File::create(&tmp_path)?,
+            schema.clone(),
+            Some(properties),
+        )?;
+        Ok(Self {
+            path,
+            file_path,
+            tmp_path,
+            columns,
+            schema,
+            writer,
+            num_rows: 0,
+        })
+    }
+
+    fn write(&mut self, rows: &[Value]) -> Result<()> {
+        let batch = to_record_batch(&self.schema, &self.columns, rows)?;
+        self.writer.write(&batch)?;
+        self.num_rows += rows.len();
+        Ok(())
+    }
+
+    fn close(self) -> Result<ManifestFile> {
+        self.writer.close()?;
+        fs::rename(&self.tmp_path, &self.file_path)?;
+        Ok(ManifestFile {
+            path: self.path,
+            num_rows: self.num_rows,
+        })
+    }
+}
+
+/// Picks the sorted `batches` covering consecutive versions from `start_version`, each with the
+/// first of its versions that no batch before it covers. Returns them with the last version covered.
+fn consecutive_batches(
+    batches: &[StagedBatch],
+    start_version: u64,
+) -> (Vec<(&StagedBatch, u64)>, Option<u64>) {
+    let mut next_version = start_version;
+    let mut picked = vec![];
+    for batch in batches {
+        if batch.start_version > next_version {
+            break;
+        }
+        if batch.end_version < next_version {
+            continue;
+        }
+        picked.push((batch, next_version));
+        next_version = batch.end_version + 1;
+    }
+    let end_version = if picked.is_empty() {
+        None
+    } else {
+        Some(next_version - 1)
+    };
+    (picked, end_version)
+}
+
+fn row_version(row: &Value) -> Option<u64> {
+    VERSION_FIELDS
+        .iter()
+        .find_map(|field| row.get(field).and_then(Value::as_u64))
+}
+
+fn write_atomically(path: &Path, contents: &[u8]) -> Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, contents)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+fn to_schema(columns: &[ParquetColumn]) -> Arc<Schema> {
+    Arc::new(Schema::new(
+        columns
+            .iter()
+            .map(|column| {
+                Field::new(
+                    &column.name,
+                    column.column_type.data_type(),
+                    column.nullable,
+                )
+            })
+            .collect(),
+    ))
+}
+
+/// Makes a record batch of `rows` with a column for each of `columns`
+fn to_record_batch(
+    schema: &Arc<Schema>,
+    columns: &[ParquetColumn],
+    rows: &[Value],
+) -> Result<RecordBatch> {
+    let arrays = columns
+        .iter()
+        .map(|column| {
+            let values = rows
+                .iter()
+                .map(|row| row_value(row, &column.name))
+                .collect::<Vec<_>>();
+            if !column.nullable && values.iter().any(|value| value.is_null()) {
+                bail!("Column {} can't be null", column.name);
+            }
+            to_array(column, &values)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(RecordBatch::try_new(schema.clone(), arrays)?)
+}
+
+/// The value of the column `name` in `row`. Diesel names the field of a column named after a Rust
+/// keyword, e.g. `type`, with a trailing underscore.
+fn row_value<'a>(row: &'a Value, name: &str) -> &'a Value {
+    row.get(name)
+        .or_else(|| row.get(format!("{}_", name)))
+        .unwrap_or(&Value::Null)
+}
+
+fn to_array(column: &ParquetColumn, values: &[&Value]) -> Result<ArrayRef> {
+    let invalid = |value: &Value| {
+        format!(
+            "Invalid value for column {} of type {:?}: {}",
+            column.name, column.column_type, value
+        )
+    };
+    let array: ArrayRef = match column.column_type {
+        ColumnType::Boolean => Arc::new(
+            values
+                .iter()
+                .map(|value| match value {
+                    Value::Null => Ok(None),
+                    value => value.as_bool().map(Some).with_context(|| invalid(value)),
+                })
+                .collect::<Result<BooleanArray>>()?,
+        ),
+        ColumnType::Int32 => Arc::new(
+            values
+                .iter()
+                .map(|value| match value {
+                    Value::Null => Ok(None),
+                    value => value
+                        .as_i64()
+                        .and_then(|v| i32::try_from(v).ok())
+                        .map(Some)
+                        .with_context(|| invalid(value)),
+                })
+                .collect::<Result<Int32Array>>()?,
+        ),
+        ColumnType::Int64 => Arc::new(
+            values
+                .iter()
+                .map(|value| match value {
+                    Value::Null => Ok(None),
+                    value => value.as_i64().map(Some).with_context(|| invalid(value)),
+                })
+                .collect::<Result<Int64Array>>()?,
+        ),
+        ColumnType::Utf8 => Arc::new(
+            values
+                .iter()
+                .map(|value| match value {
+                    Value::Null => None,
+                    Value::String(s) => Some(s.clone()),
+                    other => Some(other.to_string()),
+                })
+                .collect::<StringArray>(),
+        ),
+        ColumnType::Binary => {
+            let bytes = values
+                .iter()
+                .map(|value| match value {
+                    Value::Null => Ok(None),
+                    value => serde_json::from_value::<Vec<u8>>((*value).clone())
+                        .map(Some)
+                        .with_context(|| invalid(value)),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Arc::new(BinaryArray::from(
+                bytes
+                    .iter()
+                    .map(|bytes| bytes.as_deref())
+                    .collect::<Vec<_>>(),
+            ))
+        }
+        ColumnType::Timestamp => Arc::new(
+            values
+                .iter()
+                .map(|value| match value {
+                    Value::Null => Ok(None),
+                    value => serde_json::from_value::<chrono::NaiveDateTime>((*value).clone())
+                        .map(|timestamp| {
+                            Some(
+                                timestamp.timestamp() * 1_000_000
+                                    + timestamp.timestamp_subsec_micros() as i64,
+                            )
+                        })
+                        .with_context(|| invalid(value)),
+                })
+                .collect::<Result<TimestampMicrosecondArray>>()?,
+        ),
+    };
+    Ok(array)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_temppath::TempPath;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use serde_json::json;
+
+    diesel::table! {
+        use diesel::sql_types::*;
+        use crate::sql_types::{Jsonb, Numeric};
+
+        test_activities (transaction_version) {
+            transaction_version -> Int8,
+            amount -> Numeric,
+            is_gas_fee -> Bool,
+            entry_function_id_str -> Nullable<Varchar>,
+            #[sql_name = "type"]
+            type_ -> Varchar,
+            payload -> Nullable<Jsonb>,
+            transaction_timestamp -> Timestamp,
+            inserted_at -> Timestamp,
+        }
+    }
+
+    #[derive(Serialize)]
+    struct Row {
+        transaction_version: i64,
+        amount: String,
+        is_gas_fee: bool,
+        entry_function_id_str: Option<String>,
+        type_: String,
+        payload: Option<Value>,
+        transaction_timestamp: chrono::NaiveDateTime,
+    }
+
+    fn rows(versions: std::ops::RangeInclusive<i64>) -> Vec<Row> {
+        versions
+            .map(|transaction_version| Row {
+                transaction_version,
+                amount: "10".to_string(),
+                is_gas_fee: false,
+                entry_function_id_str: None,
+                type_: "deposit".to_string(),
+                payload: Some(json!({ "version": transaction_version })),
+                transaction_timestamp: chrono::NaiveDateTime::from_timestamp(1, 5000),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rolls_consecutive_batches() {
+        let directory = TempPath::new();
+        let sink = ParquetSink::new(directory.path(), 10).unwrap();
+        let write = |start_version: u64, end_version: u64| {
+            sink.write(
+                ParquetBatch::new(start_version, end_version)
+                    .add(
+                        "coin_activities",
+                        test_activities::table,
+                        &rows(start_version as i64..=end_version as i64),
+                    )
+                    .add::<_, Row>("coin_supply", test_activities::table, &[]),
+            )
+            .unwrap()
+        };
+
+        // Processed out of order, with a gap at 5..=9
+        write(10, 14);
+        write(0, 4);
+        assert_eq!(sink.manifest().unwrap(), Manifest::default());
+
+        // Reprocessed with a different batch size
+        write(3, 9);
+        let manifest = sink.manifest().unwrap();
+        assert_eq!(manifest.ranges.len(), 1);
+        let range = &manifest.ranges[0];
+        assert_eq!((range.start_version, range.end_version), (0, 14));
+        assert_eq!(range.files.len(), 1);
+        let file = &range.files["coin_activities"];
+        assert_eq!(file.path, "coin_activities/0_14.parquet");
+        assert_eq!(file.num_rows, 15);
+        assert!(sink.staged_batches().unwrap().is_empty());
+
+        // Each staged batch is written as it's read, the rows of all of them end up in the file
+        let reader =
+            SerializedFileReader::new(fs::File::open(directory.path().join(&file.path)).unwrap())
+                .unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 15);
+
+        // Already rolled
+        write(10, 14);
+        assert!(sink.staged_batches().unwrap().is_empty());
+        assert_eq!(sink.manifest().unwrap(), manifest);
+    }
+
+    #[test]
+    fn test_table_columns() {
+        let columns = table_columns::<test_activities::table>();
+        assert_eq!(
+            columns
+                .iter()
+                .map(|column| (column.name.as_str(), column.column_type, column.nullable))
+                .collect::<Vec<_>>(),
+            vec![
+                ("transaction_version", ColumnType::Int64, false),
+                ("amount", ColumnType::Utf8, false),
+                ("is_gas_fee", ColumnType::Boolean, false),
+                ("entry_function_id_str", ColumnType::Utf8, true),
+                ("type", ColumnType::Utf8, false),
+                ("payload", ColumnType::Utf8, true),
+                ("transaction_timestamp", ColumnType::Timestamp, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_record_batch_types() {
+        let columns = table_columns::<test_activities::table>();
+        let schema = to_schema(&columns);
+        let rows = rows(1..=2)
+            .iter()
+            .map(|row| serde_json::to_value(row).unwrap())
+            .collect::<Vec<_>>();
+        let batch = to_record_batch(&schema, &columns, &rows).unwrap();
+
+        // The schema doesn't depend on the values, even if all of them are null
+        assert_eq!(batch.schema(), schema);
+        assert_eq!(batch.num_rows(), 2);
+        let timestamps = batch
+            .column(6)
+            .as_any()
+            .downcast_ref::<TimestampMicrosecondArray>()
+            .unwrap();
+        assert_eq!(timestamps.value(0), 1_000_005);
+        let types = batch
+            .column(4)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(types.value(0), "deposit");
+
+        let mut invalid_row = rows[0].clone();
+        invalid_row["is_gas_fee"] = Value::Null;
+        assert!(to_record_batch(&schema, &columns, &[invalid_row]).is_err());
+        let mut invalid_row = rows[0].clone();
+        invalid_row["transaction_version"] = json!("1");
+        assert!(to_record_batch(&schema, &columns, &[invalid_row]).is_err());
+    }
+}
@@ -0,0 +1,906 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Fetches transactions from the REST API of any full node instead of from the local storage, so
+//! the indexer can run in its own process, see `aptos-indexer --help`.
+//!
+//! Transactions are fetched as BCS with `get_transactions_bcs` and converted to the API's
+//! `Transaction` with the node's modules, fetched once and cached. The node doesn't serve the
+//! infos of its tables, so a transaction writing to a table seen for the first time is rendered
+//! by the node instead, and the key and value types of its table items are kept for the next ones.
+
+use crate::{
+    counters::{FETCHED_TRANSACTION, UNABLE_TO_FETCH_TRANSACTION},
+    indexer::fetcher::{
+        cap_to_end_version, is_past_end_version, TransactionFetcherOptions, TransactionFetcherTrait,
+    },
+};
+use anyhow::{anyhow, bail, ensure, Result};
+use aptos_api_types::{
+    AsConverter, BcsBlock, LedgerInfo, Transaction, TransactionData, TransactionOnChainData,
+    WriteSetChange, U64,
+};
+use aptos_logger::prelude::*;
+use aptos_rest_client::{Client, Response, State};
+use aptos_state_view::StateView;
+use aptos_types::{
+    access_path::Path,
+    account_address::AccountAddress,
+    state_store::{
+        state_key::StateKey,
+        state_storage_usage::StateStorageUsage,
+        table::{TableHandle, TableInfo},
+    },
+    write_set::WriteSet,
+};
+use aptos_vm::data_cache::AsMoveResolver;
+use futures::{channel::mpsc, Future, SinkExt};
+use move_core_types::{language_storage::ModuleId, parser::parse_type_tag};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use storage_interface::DbReader;
+use tokio::{
+    runtime::Handle,
+    sync::Mutex,
+    task::JoinHandle,
+    time::{Interval, MissedTickBehavior},
+};
+
+/// How many times a request is retried before giving up, the wait between attempts doubles
+/// from `starting_retry_time` up to `max_retry_time`
+const MAX_RETRIES: u32 = 10;
+
+/// Spaces out the requests of all the fetch tasks so they don't exceed a number of requests per
+/// second. Must be created within a tokio runtime.
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    interval: Arc<Mutex<Interval>>,
+}
+
+impl RateLimiter {
+    pub fn new(max_requests_per_second: u32) -> Self {
+        let mut interval = tokio::time::interval(
+            Duration::from_secs(1) / std::cmp::max(max_requests_per_second, 1),
+        );
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        Self {
+            interval: Arc::new(Mutex::new(interval)),
+        }
+    }
+
+    /// Waits until another request can be sent
+    pub async fn wait(&self) {
+        self.interval.lock().await.tick().await;
+    }
+}
+
+/// Sends a request with retries, waiting for the rate limiter before every attempt
+async fn request_with_retries<T, E, F, Fut>(
+    rate_limiter: &Option<RateLimiter>,
+    options: &TransactionFetcherOptions,
+    description: &str,
+    request: F,
+) -> Result<T>
+where
+    E: Debug,
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<Response<T>, E>>,
+{
+    let mut retries = 0;
+    let mut retry_time = options.starting_retry_time;
+    loop {
+        if let Some(rate_limiter) = rate_limiter {
+            rate_limiter.wait().await;
+        }
+        match request().await {
+            Ok(response) => return Ok(response.into_inner()),
+            Err(err) => {
+                UNABLE_TO_FETCH_TRANSACTION.inc();
+                retries += 1;
+                if retries >= MAX_RETRIES {
+                    error!(
+                        request = description,
+                        error = format!("{:?}", err),
+                        "Request to node failed: retries exhausted",
+                    );
+                    return Err(anyhow!(
+                        "Request to node for {} failed after {} retries: {:?}",
+                        description,
+                        retries,
+                        err
+                    ));
+                }
+                error!(
+                    request = description,
+                    retry_millis = retry_time.as_millis() as u64,
+                    error = format!("{:?}", err),
+                    "Request to node failed: will retry",
+                );
+                tokio::time::sleep(retry_time).await;
+                retry_time = std::cmp::min(retry_time * 2, options.max_retry_time);
+            }
+        }
+    }
+}
+
+/// Fetches the node's ledger info, retrying for as long as the node can't be reached
+async fn fetch_state(
+    client: &Client,
+    rate_limiter: &Option<RateLimiter>,
+    options: &TransactionFetcherOptions,
+) -> State {
+    loop {
+        match request_with_retries(rate_limiter, options, "ledger info", || {
+            client.get_ledger_information()
+        })
+        .await
+        {
+            Ok(state) => return state,
+            Err(_) => tokio::time::sleep(options.max_retry_time).await,
+        }
+    }
+}
+
+fn to_ledger_info(state: &State) -> LedgerInfo {
+    LedgerInfo {
+        chain_id: state.chain_id,
+        epoch: state.epoch.into(),
+        ledger_version: state.version.into(),
+        oldest_ledger_version: state.oldest_ledger_version.into(),
+        block_height: state.block_height.into(),
+        oldest_block_height: state.oldest_block_height.into(),
+        ledger_timestamp: state.timestamp_usecs.into(),
+    }
+}
+
+/// What converting BCS transactions needs from the node's state, shared by all the fetch tasks:
+/// the modules, fetched from the node when first needed, and the infos of the tables seen so far.
+///
+/// Modules are fetched at the latest version and fetched again once a transaction publishes them.
+/// Upgrades are meant to keep the layouts of existing structs, so this usually converts older
+/// transactions too, but nothing enforces it: a transaction that fails to convert is converted
+/// once more with all the modules fetched again, in case the cached ones were stale.
+#[derive(Clone, Debug)]
+pub struct RemoteState {
+    client: Client,
+    rate_limiter: Option<RateLimiter>,
+    options: TransactionFetcherOptions,
+    modules: Arc<RwLock<HashMap<ModuleId, Vec<u8>>>>,
+    table_infos: Arc<RwLock<HashMap<TableHandle, TableInfo>>>,
+}
+
+impl RemoteState {
+    pub fn new(
+        client: Client,
+        rate_limiter: Option<RateLimiter>,
+        options: TransactionFetcherOptions,
+    ) -> Self {
+        Self {
+            client,
+            rate_limiter,
+            options,
+            modules: Arc::new(RwLock::new(HashMap::new())),
+            table_infos: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Whether the write set writes to or deletes from a table whose info isn't known yet
+    fn has_unknown_tables(&self, write_set: &WriteSet) -> bool {
+        let table_infos = self.table_infos.read().unwrap();
+        write_set.iter().any(|(state_key, _)| match state_key {
+            StateKey::TableItem { handle, .. } => !table_infos.contains_key(handle),
+            _ => false,
+        })
+    }
+
+    /// Keeps the infos of the tables the node decoded the items of in a transaction it rendered
+    fn learn_table_infos(&self, transaction: &Transaction) -> Result<()> {
+        let changes = match transaction {
+            Transaction::UserTransaction(ut) => &ut.info.changes,
+            Transaction::GenesisTransaction(gt) => &gt.info.changes,
+            Transaction::BlockMetadataTransaction(bmt) => &bmt.info.changes,
+            Transaction::StateCheckpointTransaction(sct) => &sct.info.changes,
+            Transaction::PendingTransaction(_) => {
+                bail!("Indexer should never see pending transactions")
+            }
+        };
+        let mut table_infos = self.table_infos.write().unwrap();
+        for change in changes {
+            if let WriteSetChange::WriteTableItem(item) = change {
+                if let Some(data) = &item.data {
+                    table_infos.insert(
+                        TableHandle(AccountAddress::from_bytes(item.handle.inner())?),
+                        TableInfo {
+                            key_type: parse_type_tag(&data.key_type)?,
+                            value_type: parse_type_tag(&data.value_type)?,
+                        },
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Drops the cached modules the write set publishes, so their upgrades are fetched
+    fn evict_written_modules(&self, write_set: &WriteSet) {
+        let mut modules = self.modules.write().unwrap();
+        for (state_key, _) in write_set.iter() {
+            if let StateKey::AccessPath(access_path) = state_key {
+                if let Path::Code(module_id) = access_path.get_path() {
+                    modules.remove(&module_id);
+                }
+            }
+        }
+    }
+
+    fn evict_all_modules(&self) {
+        self.modules.write().unwrap().clear();
+    }
+
+    /// Must be called outside of the async context, e.g. from a `spawn_blocking` task, as it
+    /// blocks on the requests to the node
+    fn get_module(&self, module_id: &ModuleId) -> Result<Vec<u8>> {
+        if let Some(module) = self.modules.read().unwrap().get(module_id) {
+            return Ok(module.clone());
+        }
+        let client = &self.client;
+        let module = Handle::current().block_on(request_with_retries(
+            &self.rate_limiter,
+            &self.options,
+            &format!("module {}", module_id),
+            || client.get_account_module_bcs(*module_id.address(), module_id.name().as_str()),
+        ))?;
+        self.modules
+            .write()
+            .unwrap()
+            .insert(module_id.clone(), module.to_vec());
+        Ok(module.to_vec())
+    }
+}
+
+impl StateView for RemoteState {
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<Vec<u8>>> {
+        match state_key {
+            StateKey::AccessPath(access_path) => match access_path.get_path() {
+                Path::Code(module_id) => self.get_module(&module_id).map(Some),
+                Path::Resource(_) => bail!("Only modules are fetched from the node"),
+            },
+            _ => bail!("Only modules are fetched from the node"),
+        }
+    }
+
+    fn is_genesis(&self) -> bool {
+        false
+    }
+
+    fn get_usage(&self) -> Result<StateStorageUsage> {
+        bail!("State storage usage isn't fetched from the node")
+    }
+}
+
+impl DbReader for RemoteState {
+    fn get_table_info(&self, handle: TableHandle) -> Result<TableInfo> {
+        self.table_infos
+            .read()
+            .unwrap()
+            .get(&handle)
+            .cloned()
+            .ok_or_else(|| anyhow!("Unknown table {:?}", handle))
+    }
+
+    fn indexer_enabled(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug)]
+pub struct RestFetcher {
+    client: Client,
+    rate_limiter: Option<RateLimiter>,
+    options: TransactionFetcherOptions,
+    remote_state: RemoteState,
+    current_version: u64,
+    highest_known_version: u64,
+    end_version: Option<u64>,
+    transactions_sender: mpsc::Sender<Vec<Transaction>>,
+}
+
+impl RestFetcher {
+    pub fn new(
+        client: Client,
+        rate_limiter: Option<RateLimiter>,
+        remote_state: RemoteState,
+        starting_version: u64,
+        end_version: Option<u64>,
+        options: TransactionFetcherOptions,
+        transactions_sender: mpsc::Sender<Vec<Transaction>>,
+    ) -> Self {
+        Self {
+            client,
+            rate_limiter,
+            options,
+            remote_state,
+            current_version: starting_version,
+            highest_known_version: 0,
+            end_version,
+            transactions_sender,
+        }
+    }
+
+    /// Will keep polling the node's ledger info until there are new transactions
+    async fn ensure_highest_known_version(&mut self) {
+        let mut empty_loops = 0;
+        while self.highest_known_version == 0 || self.current_version > self.highest_known_version {
+            if empty_loops > 0 {
+                tokio::time::sleep(self.options.starting_retry_time).await;
+            }
+            empty_loops += 1;
+            let state = fetch_state(&self.client, &self.rate_limiter, &self.options).await;
+            self.highest_known_version = cap_to_end_version(state.version, self.end_version);
+            sample!(
+                SampleRate::Frequency(10),
+                aptos_logger::info!(
+                    highest_known_version = self.highest_known_version,
+                    "Found new highest known version",
+                )
+            );
+        }
+    }
+
+    /// Main loop for fetching transactions, the same as [`crate::indexer::fetcher::Fetcher::run`]
+    /// except that every task fetches its range of versions from the node
+    pub async fn run(&mut self) {
+        let transaction_fetch_batch_size = self.options.transaction_fetch_batch_size;
+        loop {
+            if is_past_end_version(self.current_version, self.end_version) {
+                info!(
+                    end_version = self.end_version,
+                    "Fetched all transactions up to the end version"
+                );
+                return;
+            }
+            self.ensure_highest_known_version().await;
+
+            info!(
+                current_version = self.current_version,
+                highest_known_version = self.highest_known_version,
+                max_batch_size = transaction_fetch_batch_size,
+                "Preparing to fetch transactions"
+            );
+
+            let fetch_start = chrono::Utc::now().naive_utc();
+            let mut tasks = vec![];
+            let mut starting_version = self.current_version;
+            let mut num_fetches = 0;
+
+            while num_fetches < self.options.max_tasks
+                && starting_version <= self.highest_known_version
+            {
+                let num_transactions_to_fetch = std::cmp::min(
+                    transaction_fetch_batch_size as u64,
+                    self.highest_known_version - starting_version + 1,
+                ) as u16;
+
+                let client = self.client.clone();
+                let rate_limiter = self.rate_limiter.clone();
+                let options = self.options.clone();
+                let remote_state = self.remote_state.clone();
+                let task = tokio::spawn(async move {
+                    fetch_nexts(
+                        client,
+                        rate_limiter,
+                        options,
+                        remote_state,
+                        starting_version,
+                        num_transactions_to_fetch,
+                    )
+                    .await
+                });
+                tasks.push(task);
+                starting_version += num_transactions_to_fetch as u64;
+                num_fetches += 1;
+            }
+
+            // Keeps the batches up to the first failed one, the next loop fetches again from there
+            let mut batches = vec![];
+            let mut failed = false;
+            for result in futures::future::join_all(tasks).await {
+                match result.map_err(anyhow::Error::from).and_then(|batch| batch) {
+                    Ok(batch) => batches.push(batch),
+                    Err(err) => {
+                        error!(
+                            error = format!("{:?}", err),
+                            "Could not fetch transaction batch: will retry",
+                        );
+                        failed = true;
+                        break;
+                    }
+                }
+            }
+
+            let versions_fetched = batches.iter().fold(0, |acc, v| acc + v.len());
+            let fetch_millis = (chrono::Utc::now().naive_utc() - fetch_start).num_milliseconds();
+            info!(
+                versions_fetched = versions_fetched,
+                fetch_millis = fetch_millis,
+                num_batches = batches.len(),
+                "Finished fetching transaction batches"
+            );
+            self.send_transaction_batches(batches).await;
+            if failed {
+                tokio::time::sleep(self.options.max_retry_time).await;
+            }
+        }
+    }
+
+    /// Sends the transaction batches to the processor via the `transactions_sender` channel
+    async fn send_transaction_batches(&mut self, transaction_batches: Vec<Vec<Transaction>>) {
+        for batch in transaction_batches {
+            self.current_version = std::cmp::max(
+                batch.last().unwrap().version().unwrap() + 1,
+                self.current_version,
+            );
+            self.transactions_sender
+                .send(batch)
+                .await
+                .expect("Should be able to send transaction on channel");
+        }
+    }
+}
+
+fn set_block_info(transaction: &mut Transaction, block_height: U64, epoch: U64) {
+    let info = match transaction {
+        Transaction::PendingTransaction(_) => {
+            unreachable!("Indexer should never see pending transactions")
+        }
+        Transaction::UserTransaction(ut) => &mut ut.info,
+        Transaction::GenesisTransaction(gt) => &mut gt.info,
+        Transaction::BlockMetadataTransaction(bmt) => &mut bmt.info,
+        Transaction::StateCheckpointTransaction(sct) => &mut sct.info,
+    };
+    info.block_height = Some(block_height);
+    info.epoch = Some(epoch);
+}
+
+/// Fetches `num_transactions_to_fetch` transactions starting at `starting_version`, over as many
+/// requests as the node's page size requires, converts them and fills in their block height and
+/// epoch
+async fn fetch_nexts(
+    client: Client,
+    rate_limiter: Option<RateLimiter>,
+    options: TransactionFetcherOptions,
+    remote_state: RemoteState,
+    starting_version: u64,
+    num_transactions_to_fetch: u16,
+) -> Result<Vec<Transaction>> {
+    let start_millis = chrono::Utc::now().naive_utc();
+    let client = &client;
+
+    // The node only returns block heights through the block API, and epochs in block metadata
+    let block: BcsBlock = request_with_retries(
+        &rate_limiter,
+        &options,
+        &format!("block of version {}", starting_version),
+        || client.get_block_by_version_bcs(starting_version, false),
+    )
+    .await?;
+    let first_version = block.first_version;
+    let first_transaction = request_with_retries(
+        &rate_limiter,
+        &options,
+        &format!("transaction {}", first_version),
+        || client.get_transaction_by_version_bcs(first_version),
+    )
+    .await?;
+    let mut epoch = match first_transaction {
+        TransactionData::OnChain(data) => match data.transaction {
+            aptos_types::transaction::Transaction::BlockMetadata(block_metadata) => {
+                block_metadata.epoch()
+            }
+            aptos_types::transaction::Transaction::GenesisTransaction(_) => 0,
+            _ => bail!(
+                "Block {} doesn't start with block metadata",
+                block.block_height
+            ),
+        },
+        TransactionData::Pending(_) => bail!("Transaction {} is pending", first_version),
+    };
+    let mut block_height = block.block_height;
+    let mut timestamp = block.block_timestamp;
+
+    let mut raw_transactions: Vec<TransactionOnChainData> =
+        Vec::with_capacity(num_transactions_to_fetch as usize);
+    while raw_transactions.len() < num_transactions_to_fetch as usize {
+        let start = starting_version + raw_transactions.len() as u64;
+        let limit = num_transactions_to_fetch - raw_transactions.len() as u16;
+        let page = request_with_retries(
+            &rate_limiter,
+            &options,
+            &format!("{} transactions from {}", limit, start),
+            || client.get_transactions_bcs(Some(start), Some(limit)),
+        )
+        .await?;
+        ensure!(
+            !page.is_empty(),
+            "No transactions returned from version {}",
+            start
+        );
+        raw_transactions.extend(page);
+    }
+
+    let mut rendered_transactions = HashMap::new();
+    for raw_transaction in &raw_transactions {
+        remote_state.evict_written_modules(&raw_transaction.changes);
+        if remote_state.has_unknown_tables(&raw_transaction.changes) {
+            let version = raw_transaction.version;
+            let transaction = request_with_retries(
+                &rate_limiter,
+                &options,
+                &format!("transaction {}", version),
+                || client.get_transaction_by_version(version),
+            )
+            .await?;
+            remote_state.learn_table_infos(&transaction)?;
+            rendered_transactions.insert(version, transaction);
+        }
+    }
+
+    // Fetching the modules blocks on requests to the node
+    let transactions = tokio::task::spawn_blocking(move || -> Result<Vec<Transaction>> {
+        let resolver = remote_state.as_move_resolver();
+        let converter = resolver.as_converter(Arc::new(remote_state.clone()));
+        raw_transactions
+            .into_iter()
+            .enumerate()
+            .map(|(ind, raw_transaction)| {
+                if let aptos_types::transaction::Transaction::BlockMetadata(ref block_metadata) =
+                    raw_transaction.transaction
+                {
+                    timestamp = block_metadata.timestamp_usecs();
+                    // Do not update block_height if first block is block metadata
+                    if ind > 0 {
+                        epoch = block_metadata.epoch();
+                        block_height += 1;
+                    }
+                }
+                let mut transaction = match rendered_transactions.remove(&raw_transaction.version) {
+                    Some(transaction) => transaction,
+                    None => converter
+                        .try_into_onchain_transaction(timestamp, raw_transaction.clone())
+                        .or_else(|err| {
+                            warn!(
+                                version = raw_transaction.version,
+                                error = format!("{:?}", err),
+                                "Failed to convert transaction: fetching the modules again",
+                            );
+                            // The converter keeps the modules it loaded, so a new one is needed
+                            remote_state.evict_all_modules();
+                            remote_state
+                                .as_move_resolver()
+                                .as_converter(Arc::new(remote_state.clone()))
+                                .try_into_onchain_transaction(timestamp, raw_transaction)
+                        })?,
+                };
+                set_block_info(&mut transaction, U64::from(block_height), U64::from(epoch));
+                Ok(transaction)
+            })
+            .collect()
+    })
+    .await??;
+
+    let fetch_millis = (chrono::Utc::now().naive_utc() - start_millis).num_milliseconds();
+
+    info!(
+        starting_version = starting_version,
+        num_transactions = transactions.len(),
+        time_millis = fetch_millis,
+        actual_last_version = transactions
+            .last()
+            .map(|txn| txn.version().unwrap())
+            .unwrap_or(0),
+        "Fetched transactions",
+    );
+
+    FETCHED_TRANSACTION.inc();
+
+    Ok(transactions)
+}
+
+/// A [`TransactionFetcherTrait`] pulling transactions from a full node over REST
+pub struct RestTransactionFetcher {
+    starting_version: u64,
+    end_version: Option<u64>,
+    options: TransactionFetcherOptions,
+    client: Client,
+    rate_limiter: Option<RateLimiter>,
+    remote_state: RemoteState,
+    fetcher_handle: Option<JoinHandle<()>>,
+    transactions_sender: Option<mpsc::Sender<Vec<Transaction>>>,
+    transaction_receiver: mpsc::Receiver<Vec<Transaction>>,
+}
+
+impl RestTransactionFetcher {
+    /// `rate_limiter` is shared by all the requests to the node, unset to not limit them
+    pub fn new(
+        client: Client,
+        rate_limiter: Option<RateLimiter>,
+        starting_version: u64,
+        options: TransactionFetcherOptions,
+    ) -> Self {
+        let (transactions_sender, transaction_receiver) =
+            mpsc::channel::<Vec<Transaction>>(options.max_pending_batches);
+
+        Self {
+            starting_version,
+            end_version: None,
+            remote_state: RemoteState::new(client.clone(), rate_limiter.clone(), options.clone()),
+            options,
+            client,
+            rate_limiter,
+            fetcher_handle: None,
+            transactions_sender: Some(transactions_sender),
+            transaction_receiver,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TransactionFetcherTrait for RestTransactionFetcher {
+    async fn fetch_next_batch(&mut self) -> Vec<Transaction> {
+        match self.transaction_receiver.try_next() {
+            Ok(Some(transactions)) => transactions,
+            // The fetcher only closes the channel once it has sent everything up to the end version
+            Ok(None) if self.end_version.is_some() => vec![],
+            Ok(None) => {
+                // We never close the channel, so this should never happen
+                panic!("Transaction fetcher channel closed");
+            }
+            // The error here is when the channel is empty which we definitely expect.
+            Err(_) => vec![],
+        }
+    }
+
+    async fn fetch_ledger_info(&mut self) -> LedgerInfo {
+        let state = fetch_state(&self.client, &self.rate_limiter, &self.options).await;
+        to_ledger_info(&state)
+    }
+
+    async fn set_version(&mut self, version: u64) {
+        if self.fetcher_handle.is_some() {
+            panic!("TransactionFetcher already started!");
+        }
+        self.starting_version = version;
+    }
+
+    async fn set_end_version(&mut self, version: u64) {
+        if self.fetcher_handle.is_some() {
+            panic!("TransactionFetcher already started!");
+        }
+        self.end_version = Some(version);
+    }
+
+    async fn start(&mut self) {
+        if self.fetcher_handle.is_some() {
+            panic!("TransactionFetcher already started!");
+        }
+        let mut fetcher = RestFetcher::new(
+            self.client.clone(),
+            self.rate_limiter.clone(),
+            self.remote_state.clone(),
+            self.starting_version,
+            self.end_version,
+            self.options.clone(),
+            self.transactions_sender.take().unwrap(),
+        );
+        self.fetcher_handle = Some(tokio::spawn(async move {
+            fetcher.run().await;
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_api_test_context::{new_test_context, ApiSpecificConfig, TestContext};
+    use aptos_types::{
+        access_path::AccessPath,
+        write_set::{WriteOp, WriteSetMut},
+    };
+    use move_core_types::ident_str;
+    use serde_json::json;
+    use url::Url;
+
+    fn test_options() -> TransactionFetcherOptions {
+        TransactionFetcherOptions::new(Some(10), Some(100), Some(5), None, 1)
+    }
+
+    /// Commits blocks creating two collections of the same creator, so the first writes to the
+    /// creator's new tables and the second to tables already seen
+    async fn new_test_node(test_name: &str) -> (TestContext, Client) {
+        let mut context = new_test_context(test_name.to_string(), true);
+        let mut creator = context.gen_account();
+        let txn = context.create_user_account(&creator);
+        context.commit_block(&[txn]).await;
+        for name in ["first collection", "second collection"] {
+            context
+                .api_execute_txn(
+                    &mut creator,
+                    json!({
+                        "type": "entry_function_payload",
+                        "function": "0x3::token::create_collection_script",
+                        "type_arguments": [],
+                        "arguments": [name, "description", "uri", "20000000", [false, false, false]]
+                    }),
+                )
+                .await;
+        }
+        let ApiSpecificConfig::V1(address) = &context.api_specific_config;
+        let client = Client::new(Url::parse(&format!("http://{}", address)).unwrap());
+        (context, client)
+    }
+
+    /// The transactions as rendered by the node, with the block height and epoch of their block
+    async fn expected_transactions(
+        context: &TestContext,
+        client: &Client,
+        start: u64,
+        limit: u16,
+    ) -> Vec<Transaction> {
+        let mut transactions = client
+            .get_transactions(Some(start), Some(limit))
+            .await
+            .unwrap()
+            .into_inner();
+        for txn in transactions.iter_mut() {
+            let (_, _, block_event) = context
+                .db
+                .get_block_info_by_version(txn.version().unwrap())
+                .unwrap();
+            set_block_info(
+                txn,
+                U64::from(block_event.height()),
+                U64::from(block_event.epoch()),
+            );
+        }
+        transactions
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_fetch_nexts() {
+        let (context, client) = new_test_node("test_fetch_nexts").await;
+        let options = test_options();
+        let remote_state = RemoteState::new(client.clone(), None, options.clone());
+        let num_transactions = context.db.get_latest_version().unwrap() as u16 + 1;
+
+        let transactions = fetch_nexts(
+            client.clone(),
+            None,
+            options,
+            remote_state.clone(),
+            0,
+            num_transactions,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            transactions,
+            expected_transactions(&context, &client, 0, num_transactions).await
+        );
+        assert!(!remote_state.table_infos.read().unwrap().is_empty());
+        assert!(!remote_state.modules.read().unwrap().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_fetch_nexts_block_heights() {
+        let (context, client) = new_test_node("test_fetch_nexts_block_heights").await;
+        let options = test_options();
+        let remote_state = RemoteState::new(client.clone(), None, options.clone());
+        let latest_version = context.db.get_latest_version().unwrap();
+        let (last_block_start, _, last_block_event) = context
+            .db
+            .get_block_info_by_version(latest_version)
+            .unwrap();
+
+        // Starts in the middle of the second to last block and ends in the middle of the last one
+        let start = last_block_start - 2;
+        let transactions = fetch_nexts(client.clone(), None, options, remote_state, start, 4)
+            .await
+            .unwrap();
+        let heights: Vec<_> = transactions
+            .iter()
+            .map(|txn| txn.transaction_info().unwrap().block_height.unwrap().0)
+            .collect();
+        let last_block = last_block_event.height();
+        assert_eq!(
+            heights,
+            vec![last_block - 1, last_block - 1, last_block, last_block]
+        );
+        assert_eq!(
+            transactions,
+            expected_transactions(&context, &client, start, 4).await
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_fetch_nexts_stale_modules() {
+        let (context, client) = new_test_node("test_fetch_nexts_stale_modules").await;
+        let options = test_options();
+        let remote_state = RemoteState::new(client.clone(), None, options.clone());
+        let num_transactions = context.db.get_latest_version().unwrap() as u16;
+
+        // Can't be deserialized, like a module whose structs changed can't convert older values
+        let token_module = ModuleId::new(
+            AccountAddress::from_hex_literal("0x3").unwrap(),
+            ident_str!("token").to_owned(),
+        );
+        let stale_module = vec![0xde, 0xad];
+        remote_state
+            .modules
+            .write()
+            .unwrap()
+            .insert(token_module.clone(), stale_module.clone());
+
+        let transactions = fetch_nexts(
+            client.clone(),
+            None,
+            options,
+            remote_state.clone(),
+            1,
+            num_transactions,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            transactions,
+            expected_transactions(&context, &client, 1, num_transactions).await
+        );
+        assert_ne!(
+            remote_state.modules.read().unwrap().get(&token_module),
+            Some(&stale_module)
+        );
+    }
+
+    #[test]
+    fn test_evict_written_modules() {
+        let client = Client::new(Url::parse("http://127.0.0.1:1").unwrap());
+        let remote_state = RemoteState::new(client, None, test_options());
+        let coin_module = ModuleId::new(AccountAddress::ONE, ident_str!("coin").to_owned());
+        let token_module = ModuleId::new(
+            AccountAddress::from_hex_literal("0x3").unwrap(),
+            ident_str!("token").to_owned(),
+        );
+        for module_id in [&coin_module, &token_module] {
+            remote_state
+                .modules
+                .write()
+                .unwrap()
+                .insert(module_id.clone(), vec![]);
+        }
+
+        let write_set = WriteSetMut::new(vec![(
+            StateKey::AccessPath(AccessPath::code_access_path(coin_module.clone())),
+            WriteOp::Modification(vec![]),
+        )])
+        .freeze()
+        .unwrap();
+        remote_state.evict_written_modules(&write_set);
+        let modules = remote_state.modules.read().unwrap();
+        assert!(!modules.contains_key(&coin_module));
+        assert!(modules.contains_key(&token_module));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_fetch_nexts_unreachable_node() {
+        let client = Client::new(Url::parse("http://127.0.0.1:1").unwrap());
+        let options = TransactionFetcherOptions::new(Some(1), Some(1), Some(5), None, 1);
+        let remote_state = RemoteState::new(client.clone(), None, options.clone());
+
+        assert!(fetch_nexts(client, None, options, remote_state, 0, 5)
+            .await
+            .is_err());
+    }
+}
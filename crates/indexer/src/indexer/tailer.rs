@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 use crate::{
-    database::{execute_with_better_error, PgDbPool},
+    database::{execute_with_better_error, ArcDbPool, DbBackend},
     indexer::{
         errors::TransactionProcessingError,
         fetcher::{TransactionFetcher, TransactionFetcherOptions, TransactionFetcherTrait},
@@ -13,15 +13,16 @@ use crate::{
         processor_status::{ProcessorStatusV2, ProcessorStatusV2Query},
     },
     schema::{ledger_infos, processor_status},
+    with_conn,
 };
 use anyhow::{ensure, Context, Result};
 use aptos_api::context::Context as ApiContext;
 use aptos_logger::{debug, info};
 use chrono::ParseError;
 use diesel::{
-    pg::upsert::excluded,
     sql_query,
     sql_types::{BigInt, Text},
+    upsert::excluded,
     ExpressionMethods, RunQueryDsl,
 };
 use diesel_migrations::{EmbeddedMigrations, MigrationHarness};
@@ -29,44 +30,98 @@ use std::{fmt::Debug, sync::Arc};
 use tokio::{sync::Mutex, task::JoinHandle};
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+/// The same tables as [`MIGRATIONS`], for indexers writing to SQLite
+pub const SQLITE_MIGRATIONS: EmbeddedMigrations = embed_migrations!("sqlite_migrations");
 
 #[derive(Clone)]
 pub struct Tailer {
     pub transaction_fetcher: Arc<Mutex<dyn TransactionFetcherTrait>>,
-    processor: Arc<dyn TransactionProcessor>,
-    connection_pool: PgDbPool,
+    processors: Vec<Arc<dyn TransactionProcessor>>,
+    connection_pool: ArcDbPool,
 }
 
 impl Tailer {
     pub fn new(
         context: Arc<ApiContext>,
-        connection_pool: PgDbPool,
-        processor: Arc<dyn TransactionProcessor>,
+        connection_pool: ArcDbPool,
+        processors: Vec<Arc<dyn TransactionProcessor>>,
         options: TransactionFetcherOptions,
     ) -> Result<Tailer, ParseError> {
         let resolver = Arc::new(context.move_resolver().unwrap());
         let transaction_fetcher = TransactionFetcher::new(context, resolver, 0, options);
 
-        Ok(Self {
-            transaction_fetcher: Arc::new(Mutex::new(transaction_fetcher)),
+        Ok(Self::new_with_fetcher(
             connection_pool,
-            processor,
-        })
+            processors,
+            Arc::new(Mutex::new(transaction_fetcher)),
+        ))
+    }
+
+    /// Creates a tailer getting its transactions from `transaction_fetcher`, e.g. a
+    /// [`crate::indexer::rest_fetcher::RestTransactionFetcher`] when not running within a node
+    pub fn new_with_fetcher(
+        connection_pool: ArcDbPool,
+        processors: Vec<Arc<dyn TransactionProcessor>>,
+        transaction_fetcher: Arc<Mutex<dyn TransactionFetcherTrait>>,
+    ) -> Tailer {
+        Self {
+            transaction_fetcher,
+            connection_pool,
+            processors,
+        }
+    }
+
+    /// The processors each batch is fanned out to, in the order their results are returned
+    pub fn processors(&self) -> &[Arc<dyn TransactionProcessor>] {
+        &self.processors
+    }
+
+    pub fn connection_pool(&self) -> &ArcDbPool {
+        &self.connection_pool
+    }
+
+    /// Names of all the processors, comma separated for logging
+    pub fn processor_names(&self) -> String {
+        self.processors
+            .iter()
+            .map(|processor| processor.name())
+            .collect::<Vec<_>>()
+            .join(",")
     }
 
     pub fn run_migrations(&self) {
-        let _ = &self
+        match self.backend() {
+            DbBackend::Postgres => self.run_migrations_from(MIGRATIONS),
+            DbBackend::Sqlite => self.run_migrations_from(SQLITE_MIGRATIONS),
+        }
+    }
+
+    /// Runs migrations embedded in another crate, e.g. the ones creating the tables of its own
+    /// processors. They must be written for the database the tailer writes to, see
+    /// [`Tailer::backend`].
+    pub fn run_migrations_from(&self, migrations: EmbeddedMigrations) {
+        let mut conn = self
             .connection_pool
             .get()
-            .expect("Could not get connection for migrations")
-            .run_pending_migrations(MIGRATIONS)
-            .expect("migrations failed!");
+            .expect("Could not get connection for migrations");
+        with_conn!(&mut conn, |conn| conn
+            .run_pending_migrations(migrations)
+            .map(|_| ()))
+        .expect("migrations failed!");
+    }
+
+    /// The database the tailer writes to
+    pub fn backend(&self) -> DbBackend {
+        self.connection_pool
+            .get()
+            .expect("Could not get connection to the database")
+            .backend()
     }
 
     /// If chain id doesn't exist, save it. Otherwise, make sure that we're indexing the same chain
     pub async fn check_or_update_chain_id(&self) -> Result<u64> {
         info!(
-            processor_name = self.processor.name(),
+            processor_name = self.processor_names(),
             "Checking if chain id is correct"
         );
         let mut conn = self.connection_pool.get()?;
@@ -78,13 +133,14 @@ impl Tailer {
             .lock()
             .await
             .fetch_ledger_info()
+            .await
             .chain_id as i64;
 
         match maybe_existing_chain_id {
             Some(chain_id) => {
                 ensure!(chain_id == new_chain_id, "Wrong chain detected! Trying to index chain {} now but existing data is for chain {}", new_chain_id, chain_id);
                 info!(
-                    processor_name = self.processor.name(),
+                    processor_name = self.processor_names(),
                     chain_id = chain_id,
                     "Chain id matches! Continue to index...",
                 );
@@ -92,17 +148,17 @@ impl Tailer {
             }
             None => {
                 info!(
-                    processor_name = self.processor.name(),
+                    processor_name = self.processor_names(),
                     chain_id = new_chain_id,
                     "Adding chain id to db, continue to index.."
                 );
-                execute_with_better_error(
-                    &mut conn,
+                with_conn!(&mut conn, |conn| execute_with_better_error(
+                    conn,
                     diesel::insert_into(ledger_infos::table).values(LedgerInfo {
                         chain_id: new_chain_id,
                     }),
                     None,
-                )
+                ))
                 .context(r#"Error updating chain_id!"#)
                 .map(|_| new_chain_id as u64)
             }
@@ -118,11 +174,44 @@ impl Tailer {
         info!(version = version, "Will start fetching from version");
     }
 
+    pub async fn set_fetcher_end_version(&self, version: u64) {
+        self.transaction_fetcher
+            .lock()
+            .await
+            .set_end_version(version)
+            .await;
+        info!(version = version, "Will stop fetching after version");
+    }
+
+    /// Fetches the next batch and has every processor process it, returning the number of
+    /// transactions in the batch and the result of each processor
     pub async fn process_next_batch(
         &self,
     ) -> (
         u64,
-        Option<Result<ProcessingResult, TransactionProcessingError>>,
+        Option<Vec<Result<ProcessingResult, TransactionProcessingError>>>,
+    ) {
+        self.fetch_and_process_next_batch(true).await
+    }
+
+    /// Same as [`Tailer::process_next_batch`] without tracking the processed versions in
+    /// `processor_statuses`, so that a backfill doesn't mark versions as started behind the
+    /// processors following the chain
+    pub async fn backfill_next_batch(
+        &self,
+    ) -> (
+        u64,
+        Option<Vec<Result<ProcessingResult, TransactionProcessingError>>>,
+    ) {
+        self.fetch_and_process_next_batch(false).await
+    }
+
+    async fn fetch_and_process_next_batch(
+        &self,
+        track_status: bool,
+    ) -> (
+        u64,
+        Option<Vec<Result<ProcessingResult, TransactionProcessingError>>>,
     ) {
         let transactions = self
             .transaction_fetcher
@@ -148,10 +237,21 @@ impl Tailer {
 
         let batch_start = chrono::Utc::now().naive_utc();
 
-        let results = self
-            .processor
-            .process_transactions_with_status(transactions)
-            .await;
+        let results = futures::future::join_all(self.processors.iter().map(|processor| {
+            let transactions = transactions.clone();
+            async move {
+                if track_status {
+                    processor
+                        .process_transactions_with_status(transactions)
+                        .await
+                } else {
+                    processor
+                        .process_transactions_without_status(transactions)
+                        .await
+                }
+            }
+        }))
+        .await;
 
         let batch_millis = (chrono::Utc::now().naive_utc() - batch_start).num_milliseconds();
 
@@ -175,8 +275,8 @@ impl Tailer {
             processor: processor_name.to_owned(),
             last_success_version: version as i64,
         };
-        execute_with_better_error(
-            &mut conn,
+        with_conn!(&mut conn, |conn| execute_with_better_error(
+            conn,
             diesel::insert_into(processor_status::table)
                 .values(&status)
                 .on_conflict(processor_status::processor)
@@ -187,7 +287,7 @@ impl Tailer {
                     processor_status::last_updated.eq(excluded(processor_status::last_updated)),
                 )),
             Some(" WHERE processor_status.last_success_version <= EXCLUDED.last_success_version "),
-        )?;
+        ))?;
         Ok(())
     }
 
@@ -215,7 +315,8 @@ impl Tailer {
 
         // This query gets the first version that isn't equal to the next version (versions would be sorted of course).
         // There's also special handling if the gap happens in the beginning.
-        let sql = "
+        let sql = format!(
+            "
         WITH raw_boundaries AS
         (
             SELECT
@@ -237,7 +338,7 @@ impl Tailer {
             WHERE
                 name = $1
                 AND success = true
-                and version >= GREATEST(MAX_V - $2, 0)
+                and version >= {greatest}(MAX_V - $2, 0)
         ),
         gap AS
         (
@@ -256,7 +357,7 @@ impl Tailer {
                     WHERE
                         name = $1
                         AND success = TRUE
-                        AND version >= GREATEST(MAX_V - $2, 0)
+                        AND version >= {greatest}(MAX_V - $2, 0)
                 ) a
             WHERE
                 version + 1 <> next_version
@@ -264,27 +365,33 @@ impl Tailer {
         SELECT
             CASE
                 WHEN
-                    MIN_V <> GREATEST(MAX_V - $2, 0)
+                    MIN_V <> {greatest}(MAX_V - $2, 0)
                 THEN
-                    GREATEST(MAX_V - $2, 0)
+                    {greatest}(MAX_V - $2, 0)
                 ELSE
                     COALESCE(maybe_gap, MAX_V + 1)
             END
             AS version
         FROM
             gap, boundaries
-        ";
+        ",
+            // SQLite has no `GREATEST`, but its `MAX` is one when given several arguments
+            greatest = match conn.backend() {
+                DbBackend::Postgres => "GREATEST",
+                DbBackend::Sqlite => "MAX",
+            }
+        );
         #[derive(Debug, QueryableByName)]
         pub struct Gap {
             #[diesel(sql_type = BigInt)]
             pub version: i64,
         }
-        let mut res: Vec<Option<Gap>> = sql_query(sql)
+        let mut res: Vec<Option<Gap>> = with_conn!(&mut conn, |conn| sql_query(&sql)
             .bind::<Text, _>(processor_name)
             // This is the number used to determine how far we look back for gaps. Increasing it may result in slower startup
             .bind::<BigInt, _>(lookback_versions)
-            .get_results(&mut conn)
-            .unwrap();
+            .get_results(conn))
+        .unwrap();
         res.pop().unwrap().map(|g| g.version)
     }
 }
@@ -304,16 +411,17 @@ pub async fn await_tasks<T: Debug>(tasks: Vec<JoinHandle<T>>) -> Vec<T> {
 }
 
 #[cfg(test)]
-mod test {
+pub(crate) mod test {
     use super::*;
     use crate::{
-        database::{new_db_pool, PgPoolConnection},
+        database::{new_db_pool, DbConnection},
         models::transactions::TransactionQuery,
         processors::default_processor::DefaultTransactionProcessor,
     };
     use aptos_api_test_context::new_test_context;
     use aptos_api_types::{LedgerInfo as APILedgerInfo, Transaction, U64};
-    use diesel::RunQueryDsl;
+    use aptos_temppath::TempPath;
+    use diesel::{QueryableByName, RunQueryDsl};
     use serde_json::json;
 
     struct FakeFetcher {
@@ -336,7 +444,7 @@ mod test {
             unimplemented!();
         }
 
-        fn fetch_ledger_info(&mut self) -> APILedgerInfo {
+        async fn fetch_ledger_info(&mut self) -> APILedgerInfo {
             APILedgerInfo {
                 chain_id: self.chain_id,
                 epoch: U64::from(0),
@@ -354,35 +462,68 @@ mod test {
             self.chain_id = version as u8;
         }
 
+        async fn set_end_version(&mut self, _version: u64) {
+            unimplemented!();
+        }
+
         async fn start(&mut self) {
             // do nothing
         }
     }
 
-    pub fn wipe_database(conn: &mut PgPoolConnection) {
-        for command in [
-            "DROP SCHEMA public CASCADE",
-            "CREATE SCHEMA public",
-            "GRANT ALL ON SCHEMA public TO postgres",
-            "GRANT ALL ON SCHEMA public TO public",
-        ] {
-            diesel::sql_query(command).execute(conn).unwrap();
+    /// The tests share the database, so they take turns wiping and using it
+    pub fn lock_database() -> std::sync::MutexGuard<'static, ()> {
+        static DATABASE: std::sync::Mutex<()> = std::sync::Mutex::new(());
+        DATABASE.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    pub fn wipe_database(conn: &mut DbConnection) {
+        match conn {
+            DbConnection::Postgres(conn) => {
+                for command in [
+                    "DROP SCHEMA public CASCADE",
+                    "CREATE SCHEMA public",
+                    "GRANT ALL ON SCHEMA public TO postgres",
+                    "GRANT ALL ON SCHEMA public TO public",
+                ] {
+                    diesel::sql_query(command).execute(conn).unwrap();
+                }
+            }
+            DbConnection::Sqlite(conn) => {
+                #[derive(QueryableByName)]
+                struct SchemaObject {
+                    #[diesel(sql_type = Text)]
+                    type_: String,
+                    #[diesel(sql_type = Text)]
+                    name: String,
+                }
+                let objects = diesel::sql_query(
+                    "SELECT type AS type_, name FROM sqlite_master \
+                     WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%'",
+                )
+                .load::<SchemaObject>(conn)
+                .unwrap();
+                for object in objects {
+                    diesel::sql_query(format!("DROP {} \"{}\"", object.type_, object.name))
+                        .execute(conn)
+                        .unwrap();
+                }
+            }
         }
     }
 
-    pub fn setup_indexer() -> Result<(PgDbPool, Tailer)> {
-        let database_url = std::env::var("INDEXER_DATABASE_URL")
-            .expect("must set 'INDEXER_DATABASE_URL' to run tests!");
-        let conn_pool = new_db_pool(database_url.as_str())?;
+    pub fn setup_indexer(database_url: &str) -> Result<(ArcDbPool, Tailer)> {
+        let conn_pool = new_db_pool(database_url)?;
         wipe_database(&mut conn_pool.get()?);
 
         let test_context = new_test_context("doesnt_matter".to_string(), true);
         let context: Arc<ApiContext> = Arc::new(test_context.context);
-        let pg_transaction_processor = DefaultTransactionProcessor::new(conn_pool.clone());
+        let pg_transaction_processor =
+            DefaultTransactionProcessor::new(conn_pool.clone(), None, false);
         let mut tailer = Tailer::new(
             context,
             conn_pool.clone(),
-            Arc::new(pg_transaction_processor),
+            vec![Arc::new(pg_transaction_processor)],
             TransactionFetcherOptions::default(),
         )?;
         tailer.transaction_fetcher = Arc::new(Mutex::new(FakeFetcher::new(None)));
@@ -396,7 +537,26 @@ mod test {
         if crate::should_skip_pg_tests() {
             return;
         }
-        let (conn_pool, tailer) = setup_indexer().unwrap();
+        let _database = lock_database();
+        let database_url = std::env::var("INDEXER_DATABASE_URL")
+            .expect("must set 'INDEXER_DATABASE_URL' to run tests!");
+        parsing_and_writing(&database_url).await;
+    }
+
+    /// Same as `test_parsing_and_writing`, in a SQLite file needing no database server
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_parsing_and_writing_sqlite() {
+        let directory = TempPath::new();
+        directory.create_as_dir().unwrap();
+        parsing_and_writing(&format!(
+            "sqlite://{}",
+            directory.path().join("indexer.db").display()
+        ))
+        .await;
+    }
+
+    async fn parsing_and_writing(database_url: &str) {
+        let (conn_pool, tailer) = setup_indexer(database_url).unwrap();
         // An abridged genesis transaction
         let genesis_txn: Transaction = serde_json::from_value(json!(
             {
@@ -600,8 +760,7 @@ mod test {
             }
         )).unwrap();
 
-        tailer
-            .processor
+        tailer.processors[0]
             .process_transactions_with_status(vec![genesis_txn.clone()])
             .await
             .unwrap();
@@ -688,8 +847,7 @@ mod test {
             bmt.info.epoch = Some(aptos_api_types::U64::from(1));
         }
 
-        tailer
-            .processor
+        tailer.processors[0]
             .process_transactions_with_status(vec![block_metadata_transaction.clone()])
             .await
             .unwrap();
@@ -812,13 +970,11 @@ mod test {
         )).unwrap();
 
         // We run it twice to ensure we don't explode. Idempotency!
-        tailer
-            .processor
+        tailer.processors[0]
             .process_transactions_with_status(vec![user_txn.clone()])
             .await
             .unwrap();
-        tailer
-            .processor
+        tailer.processors[0]
             .process_transactions_with_status(vec![user_txn.clone()])
             .await
             .unwrap();
@@ -901,13 +1057,12 @@ mod test {
         )).unwrap();
 
         let txns = vec![message_txn];
-        tailer
-            .processor
+        tailer.processors[0]
             .process_transactions_with_status(txns)
             .await
             .unwrap();
 
-        let (_conn_pool, tailer) = setup_indexer().unwrap();
+        let (_conn_pool, tailer) = setup_indexer(database_url).unwrap();
         tailer.set_fetcher_version(4).await;
         assert!(tailer.check_or_update_chain_id().await.is_ok());
         assert!(tailer.check_or_update_chain_id().await.is_ok());
@@ -2,21 +2,20 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::counters::LATEST_PROCESSED_VERSION;
-use crate::database::get_chunks;
 use crate::{
     counters::{
         GOT_CONNECTION, PROCESSOR_ERRORS, PROCESSOR_INVOCATIONS, PROCESSOR_SUCCESSES,
         UNABLE_TO_GET_CONNECTION,
     },
-    database::{execute_with_better_error, PgDbPool, PgPoolConnection},
+    database::{execute_with_better_error, ArcDbPool, DbPoolConnection},
+    execute_in_chunks,
     indexer::{errors::TransactionProcessingError, processing_result::ProcessingResult},
     models::processor_statuses::ProcessorStatusModel,
     schema,
 };
 use aptos_api_types::Transaction;
 use async_trait::async_trait;
-use diesel::{pg::upsert::excluded, prelude::*};
-use field_count::FieldCount;
+use diesel::{prelude::*, upsert::excluded};
 use schema::processor_statuses::{self, dsl};
 use std::fmt::Debug;
 
@@ -38,13 +37,13 @@ pub trait TransactionProcessor: Send + Sync + Debug {
 
     /// Gets a reference to the connection pool
     /// This is used by the `get_conn()` helper below
-    fn connection_pool(&self) -> &PgDbPool;
+    fn connection_pool(&self) -> &ArcDbPool;
 
     //* Below are helper methods that don't need to be implemented *//
 
     /// Gets the connection.
     /// If it was unable to do so (default timeout: 30s), it will keep retrying until it can.
-    fn get_conn(&self) -> PgPoolConnection {
+    fn get_conn(&self) -> DbPoolConnection {
         let pool = self.connection_pool();
         loop {
             match pool.get() {
@@ -92,6 +91,26 @@ pub trait TransactionProcessor: Send + Sync + Debug {
         res
     }
 
+    /// Same as `process_transactions_with_status` without writing to `processor_statuses`, for
+    /// backfills reprocessing versions the live processor may already be past
+    async fn process_transactions_without_status(
+        &self,
+        txns: Vec<Transaction>,
+    ) -> Result<ProcessingResult, TransactionProcessingError> {
+        assert!(
+            !txns.is_empty(),
+            "Must provide at least one transaction to this function"
+        );
+        PROCESSOR_INVOCATIONS
+            .with_label_values(&[self.name()])
+            .inc();
+
+        let start_version = txns.first().unwrap().version().unwrap();
+        let end_version = txns.last().unwrap().version().unwrap();
+        self.process_transactions(txns, start_version, end_version)
+            .await
+    }
+
     /// Writes that a version has been started for this `TransactionProcessor` to the DB
     fn mark_versions_started(&self, start_version: u64, end_version: u64) {
         aptos_logger::debug!(
@@ -147,12 +166,11 @@ pub trait TransactionProcessor: Send + Sync + Debug {
     /// Actually performs the write for a `ProcessorStatusModel` changeset
     fn apply_processor_status(&self, psms: &[ProcessorStatusModel]) {
         let mut conn = self.get_conn();
-        let chunks = get_chunks(psms.len(), ProcessorStatusModel::field_count());
-        for (start_ind, end_ind) in chunks {
+        execute_in_chunks!(&mut conn, psms, |conn, items| {
             execute_with_better_error(
-                &mut conn,
+                conn,
                 diesel::insert_into(processor_statuses::table)
-                    .values(&psms[start_ind..end_ind])
+                    .values(items)
                     .on_conflict((dsl::name, dsl::version))
                     .do_update()
                     .set((
@@ -163,6 +181,6 @@ pub trait TransactionProcessor: Send + Sync + Debug {
                 None,
             )
             .expect("Error updating Processor Status!");
-        }
+        });
     }
 }
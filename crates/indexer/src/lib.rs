@@ -11,13 +11,17 @@ extern crate diesel_migrations;
 #[macro_use]
 extern crate diesel;
 
+pub mod consistency_checker;
 pub mod counters;
 pub mod database;
+pub mod graphql;
 pub mod indexer;
 pub mod models;
 pub mod processors;
 pub mod runtime;
 pub mod schema;
+pub mod sql_types;
+pub mod standalone;
 mod util;
 
 /// By default, skips test unless `INDEXER_DATABASE_URL` is set.
@@ -0,0 +1,11 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_indexer::standalone::IndexerArgs;
+use clap::Parser;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    aptos_logger::Logger::new().init();
+    IndexerArgs::parse().run().await
+}
@@ -8,6 +8,7 @@
 use super::transactions::{Transaction, TransactionQuery};
 use crate::{
     schema::block_metadata_transactions,
+    sql_types::JsonbValue,
     util::{parse_timestamp, standardize_address},
 };
 use aptos_api_types::BlockMetadataTransaction as APIBlockMetadataTransaction;
@@ -26,8 +27,10 @@ pub struct BlockMetadataTransaction {
     pub id: String,
     pub round: i64,
     pub epoch: i64,
+    #[diesel(serialize_as = JsonbValue)]
     pub previous_block_votes_bitvec: serde_json::Value,
     pub proposer: String,
+    #[diesel(serialize_as = JsonbValue)]
     pub failed_proposer_indices: serde_json::Value,
     pub timestamp: chrono::NaiveDateTime,
 }
@@ -13,6 +13,7 @@ use super::{
 };
 use crate::{
     schema::coin_activities,
+    sql_types::NumericValue,
     util::{parse_timestamp, standardize_address, truncate_str},
 };
 use aptos_api_types::{
@@ -36,7 +37,7 @@ type CoinType = String;
 pub type CurrentCoinBalancePK = (OwnerAddress, CoinType);
 pub type EventToCoinType = HashMap<EventGuidResource, CoinType>;
 
-#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
 #[diesel(primary_key(
     transaction_version,
     event_account_address,
@@ -51,6 +52,7 @@ pub struct CoinActivity {
     pub event_sequence_number: i64,
     pub owner_address: String,
     pub coin_type: String,
+    #[diesel(serialize_as = NumericValue)]
     pub amount: BigDecimal,
     pub activity_type: String,
     pub is_gas_fee: bool,
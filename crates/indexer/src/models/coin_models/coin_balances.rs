@@ -9,14 +9,17 @@ use super::{
     coin_activities::EventToCoinType,
     coin_utils::{CoinInfoType, CoinResource},
 };
-use crate::{schema::coin_balances, schema::current_coin_balances, util::standardize_address};
+use crate::{
+    schema::coin_balances, schema::current_coin_balances, sql_types::NumericValue,
+    util::standardize_address,
+};
 use aptos_api_types::WriteResource as APIWriteResource;
 use bigdecimal::BigDecimal;
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
 #[diesel(primary_key(transaction_version, owner_address, coin_type))]
 #[diesel(table_name = coin_balances)]
 pub struct CoinBalance {
@@ -24,17 +27,19 @@ pub struct CoinBalance {
     pub owner_address: String,
     pub coin_type_hash: String,
     pub coin_type: String,
+    #[diesel(serialize_as = NumericValue)]
     pub amount: BigDecimal,
     pub transaction_timestamp: chrono::NaiveDateTime,
 }
 
-#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
 #[diesel(primary_key(owner_address, coin_type))]
 #[diesel(table_name = current_coin_balances)]
 pub struct CurrentCoinBalance {
     pub owner_address: String,
     pub coin_type_hash: String,
     pub coin_type: String,
+    #[diesel(serialize_as = NumericValue)]
     pub amount: BigDecimal,
     pub last_transaction_version: i64,
     pub last_transaction_timestamp: chrono::NaiveDateTime,
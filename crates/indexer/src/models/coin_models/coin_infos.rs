@@ -6,13 +6,13 @@
 #![allow(clippy::unused_unit)]
 
 use super::coin_utils::{CoinInfoType, CoinResource};
-use crate::{database::PgPoolConnection, schema::coin_infos};
+use crate::{database::DbConnection, schema::coin_infos, with_conn};
 use aptos_api_types::WriteResource as APIWriteResource;
 use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
 #[diesel(primary_key(coin_type_hash))]
 #[diesel(table_name = coin_infos)]
 pub struct CoinInfo {
@@ -84,11 +84,11 @@ impl CoinInfo {
 impl CoinInfoQuery {
     pub fn get_by_coin_type(
         coin_type: String,
-        conn: &mut PgPoolConnection,
+        conn: &mut DbConnection,
     ) -> diesel::QueryResult<Option<Self>> {
-        coin_infos::table
+        with_conn!(conn, |conn| coin_infos::table
             .filter(coin_infos::coin_type.eq(coin_type))
             .first::<Self>(conn)
-            .optional()
+            .optional())
     }
 }
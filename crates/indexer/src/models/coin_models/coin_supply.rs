@@ -6,20 +6,21 @@
 #![allow(clippy::unused_unit)]
 
 use super::coin_infos::CoinInfoQuery;
-use crate::schema::coin_supply;
+use crate::{schema::coin_supply, sql_types::NumericValue};
 use anyhow::Context;
 use aptos_api_types::WriteTableItem as APIWriteTableItem;
 use bigdecimal::BigDecimal;
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
 #[diesel(primary_key(transaction_version, coin_type_hash))]
 #[diesel(table_name = coin_supply)]
 pub struct CoinSupply {
     pub transaction_version: i64,
     pub coin_type_hash: String,
     pub coin_type: String,
+    #[diesel(serialize_as = NumericValue)]
     pub supply: BigDecimal,
     pub transaction_timestamp: chrono::NaiveDateTime,
     pub transaction_epoch: i64,
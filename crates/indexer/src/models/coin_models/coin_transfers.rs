@@ -0,0 +1,251 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use super::coin_activities::CoinActivity;
+use crate::{schema::coin_transfers, sql_types::NumericValue};
+use bigdecimal::{BigDecimal, Zero};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+const WITHDRAW_EVENT: &str = "0x1::coin::WithdrawEvent";
+const DEPOSIT_EVENT: &str = "0x1::coin::DepositEvent";
+
+/// Coins moved from one account to another within a transaction, pairing a `WithdrawEvent` with
+/// a `DepositEvent` of the same coin type.
+/// Coins deposited without being withdrawn (e.g. minted) have no sender, and coins withdrawn
+/// without being deposited (e.g. burned, or gas fees) have no receiver.
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version, transfer_index))]
+#[diesel(table_name = coin_transfers)]
+pub struct CoinTransfer {
+    pub transaction_version: i64,
+    pub transfer_index: i64,
+    pub coin_type: String,
+    #[diesel(serialize_as = NumericValue)]
+    pub amount: BigDecimal,
+    pub from_address: Option<String>,
+    pub to_address: Option<String>,
+    pub withdraw_event_creation_number: Option<i64>,
+    pub withdraw_event_sequence_number: Option<i64>,
+    pub deposit_event_creation_number: Option<i64>,
+    pub deposit_event_sequence_number: Option<i64>,
+    pub is_gas_fee: bool,
+    pub is_transaction_success: bool,
+    pub entry_function_id_str: Option<String>,
+    pub block_height: i64,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+/// A withdrawal whose coins haven't all been deposited yet
+struct PendingWithdrawal<'a> {
+    activity: &'a CoinActivity,
+    remaining: BigDecimal,
+}
+
+impl CoinTransfer {
+    /// Pairs the coin activities of a single transaction, in event order, into transfers.
+    /// A deposit is matched with a pending withdrawal of the same amount if there is one, which
+    /// keeps the transfers of a multi-recipient script apart, otherwise it takes coins from the
+    /// earliest withdrawals. Coins can be split across several deposits or merged from several
+    /// withdrawals, so one event may be part of several transfers.
+    pub fn from_coin_activities(coin_activities: &[CoinActivity]) -> Vec<Self> {
+        let mut transfers = vec![];
+        let mut pending: Vec<PendingWithdrawal> = vec![];
+
+        for activity in coin_activities {
+            if activity.is_gas_fee {
+                transfers.push(Self::new(
+                    transfers.len(),
+                    activity.amount.clone(),
+                    Some(activity),
+                    None,
+                ));
+                continue;
+            }
+            match activity.activity_type.as_str() {
+                WITHDRAW_EVENT => pending.push(PendingWithdrawal {
+                    activity,
+                    remaining: activity.amount.clone(),
+                }),
+                DEPOSIT_EVENT => {
+                    let mut remaining = activity.amount.clone();
+                    if let Some(index) = pending.iter().position(|withdrawal| {
+                        withdrawal.activity.coin_type == activity.coin_type
+                            && withdrawal.remaining == remaining
+                    }) {
+                        let withdrawal = pending.remove(index);
+                        transfers.push(Self::new(
+                            transfers.len(),
+                            remaining,
+                            Some(withdrawal.activity),
+                            Some(activity),
+                        ));
+                        continue;
+                    }
+                    while !remaining.is_zero() {
+                        let index = match pending.iter().position(|withdrawal| {
+                            withdrawal.activity.coin_type == activity.coin_type
+                        }) {
+                            Some(index) => index,
+                            None => break,
+                        };
+                        let withdrawal = &mut pending[index];
+                        let amount = std::cmp::min(&withdrawal.remaining, &remaining).clone();
+                        withdrawal.remaining = &withdrawal.remaining - &amount;
+                        remaining = &remaining - &amount;
+                        transfers.push(Self::new(
+                            transfers.len(),
+                            amount,
+                            Some(withdrawal.activity),
+                            Some(activity),
+                        ));
+                        if withdrawal.remaining.is_zero() {
+                            pending.remove(index);
+                        }
+                    }
+                    if !remaining.is_zero() || activity.amount.is_zero() {
+                        transfers.push(Self::new(transfers.len(), remaining, None, Some(activity)));
+                    }
+                }
+                _ => {}
+            }
+        }
+        for withdrawal in pending {
+            transfers.push(Self::new(
+                transfers.len(),
+                withdrawal.remaining,
+                Some(withdrawal.activity),
+                None,
+            ));
+        }
+        transfers
+    }
+
+    fn new(
+        transfer_index: usize,
+        amount: BigDecimal,
+        withdrawal: Option<&CoinActivity>,
+        deposit: Option<&CoinActivity>,
+    ) -> Self {
+        let activity = withdrawal
+            .or(deposit)
+            .expect("A transfer has a withdrawal or a deposit");
+        Self {
+            transaction_version: activity.transaction_version,
+            transfer_index: transfer_index as i64,
+            coin_type: activity.coin_type.clone(),
+            amount,
+            from_address: withdrawal.map(|w| w.owner_address.clone()),
+            to_address: deposit.map(|d| d.owner_address.clone()),
+            withdraw_event_creation_number: withdrawal.map(|w| w.event_creation_number),
+            withdraw_event_sequence_number: withdrawal.map(|w| w.event_sequence_number),
+            deposit_event_creation_number: deposit.map(|d| d.event_creation_number),
+            deposit_event_sequence_number: deposit.map(|d| d.event_sequence_number),
+            is_gas_fee: activity.is_gas_fee,
+            is_transaction_success: activity.is_transaction_success,
+            entry_function_id_str: activity.entry_function_id_str.clone(),
+            block_height: activity.block_height,
+            transaction_timestamp: activity.transaction_timestamp,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const APT: &str = "0x1::aptos_coin::AptosCoin";
+
+    fn activity(
+        activity_type: &str,
+        owner_address: &str,
+        sequence_number: i64,
+        amount: u64,
+    ) -> CoinActivity {
+        CoinActivity {
+            transaction_version: 1,
+            event_account_address: owner_address.to_string(),
+            event_creation_number: if activity_type == WITHDRAW_EVENT {
+                3
+            } else {
+                2
+            },
+            event_sequence_number: sequence_number,
+            owner_address: owner_address.to_string(),
+            coin_type: APT.to_string(),
+            amount: BigDecimal::from(amount),
+            activity_type: activity_type.to_string(),
+            is_gas_fee: false,
+            is_transaction_success: true,
+            entry_function_id_str: None,
+            block_height: 1,
+            transaction_timestamp: chrono::NaiveDateTime::from_timestamp(0, 0),
+        }
+    }
+
+    fn flows(transfers: &[CoinTransfer]) -> Vec<(Option<&str>, Option<&str>, BigDecimal)> {
+        transfers
+            .iter()
+            .map(|transfer| {
+                (
+                    transfer.from_address.as_deref(),
+                    transfer.to_address.as_deref(),
+                    transfer.amount.clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_multi_recipient_transfers() {
+        let mut gas = activity("0x1::aptos_coin::GasFeeEvent", "0xa", 0, 5);
+        gas.is_gas_fee = true;
+        let activities = vec![
+            gas,
+            activity(WITHDRAW_EVENT, "0xa", 0, 10),
+            activity(WITHDRAW_EVENT, "0xa", 1, 20),
+            activity(DEPOSIT_EVENT, "0xc", 0, 20),
+            activity(DEPOSIT_EVENT, "0xb", 0, 10),
+        ];
+        let transfers = CoinTransfer::from_coin_activities(&activities);
+
+        assert_eq!(
+            flows(&transfers),
+            vec![
+                (Some("0xa"), None, BigDecimal::from(5)),
+                (Some("0xa"), Some("0xc"), BigDecimal::from(20)),
+                (Some("0xa"), Some("0xb"), BigDecimal::from(10)),
+            ]
+        );
+        assert!(transfers[0].is_gas_fee);
+        assert_eq!(transfers[1].withdraw_event_sequence_number, Some(1));
+        assert_eq!(transfers[2].transfer_index, 2);
+    }
+
+    #[test]
+    fn test_split_merged_and_unmatched_transfers() {
+        let activities = vec![
+            activity(WITHDRAW_EVENT, "0xa", 0, 30),
+            activity(DEPOSIT_EVENT, "0xb", 0, 10),
+            activity(DEPOSIT_EVENT, "0xc", 0, 15),
+            activity(DEPOSIT_EVENT, "0xd", 0, 8),
+            activity(WITHDRAW_EVENT, "0xe", 0, 4),
+        ];
+        let transfers = CoinTransfer::from_coin_activities(&activities);
+
+        assert_eq!(
+            flows(&transfers),
+            vec![
+                (Some("0xa"), Some("0xb"), BigDecimal::from(10)),
+                (Some("0xa"), Some("0xc"), BigDecimal::from(15)),
+                (Some("0xa"), Some("0xd"), BigDecimal::from(5)),
+                (None, Some("0xd"), BigDecimal::from(3)),
+                (Some("0xe"), None, BigDecimal::from(4)),
+            ]
+        );
+    }
+}
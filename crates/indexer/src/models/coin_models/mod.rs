@@ -5,4 +5,5 @@ pub mod coin_activities;
 pub mod coin_balances;
 pub mod coin_infos;
 pub mod coin_supply;
+pub mod coin_transfers;
 mod coin_utils;
@@ -1,14 +1,19 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 #![allow(clippy::extra_unused_lifetimes)]
-use crate::{models::transactions::Transaction, schema::events, util::standardize_address};
+use crate::{
+    models::transactions::Transaction, schema::events, sql_types::JsonbValue,
+    util::standardize_address,
+};
 use aptos_api_types::Event as APIEvent;
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
 
 use super::transactions::TransactionQuery;
 
-#[derive(Associations, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[derive(
+    Associations, Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize,
+)]
 #[diesel(belongs_to(Transaction, foreign_key = transaction_version))]
 #[diesel(primary_key(account_address, creation_number, sequence_number))]
 #[diesel(table_name = events)]
@@ -19,6 +24,7 @@ pub struct Event {
     pub transaction_version: i64,
     pub transaction_block_height: i64,
     pub type_: String,
+    #[diesel(serialize_as = JsonbValue)]
     pub data: serde_json::Value,
 }
 
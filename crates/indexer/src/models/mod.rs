@@ -5,6 +5,7 @@ pub mod block_metadata_transactions;
 pub mod coin_models;
 pub mod events;
 pub mod ledger_info;
+pub mod module_models;
 pub mod move_modules;
 pub mod move_resources;
 pub mod move_tables;
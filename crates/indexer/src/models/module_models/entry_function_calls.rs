@@ -0,0 +1,164 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use crate::{
+    schema::entry_function_calls,
+    sql_types::JsonbValue,
+    util::{parse_timestamp, standardize_address},
+};
+use anyhow::Context;
+use aptos_api_types::{Transaction as APITransaction, TransactionPayload};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+/// A call to an entry function by a user transaction, whether or not the transaction succeeded
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version))]
+#[diesel(table_name = entry_function_calls)]
+pub struct EntryFunctionCall {
+    pub transaction_version: i64,
+    pub sender: String,
+    pub module_address: String,
+    pub module_name: String,
+    pub function_name: String,
+    #[diesel(serialize_as = JsonbValue)]
+    pub type_arguments: serde_json::Value,
+    pub is_transaction_success: bool,
+    pub gas_used: i64,
+    pub gas_unit_price: i64,
+    pub block_height: i64,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl EntryFunctionCall {
+    pub fn from_transaction(transaction: &APITransaction) -> anyhow::Result<Option<Self>> {
+        let user_txn = match transaction {
+            APITransaction::UserTransaction(inner) => inner,
+            _ => return Ok(None),
+        };
+        let payload = match &user_txn.request.payload {
+            TransactionPayload::EntryFunctionPayload(payload) => payload,
+            _ => return Ok(None),
+        };
+        let version = user_txn.info.version.0 as i64;
+        let type_arguments = serde_json::to_value(&payload.type_arguments).context(format!(
+            "version {} failed! failed to serialize type arguments {:?}",
+            version, payload.type_arguments
+        ))?;
+        let block_height = user_txn
+            .info
+            .block_height
+            .context(format!(
+                "version {} failed! block height is missing",
+                version
+            ))?
+            .0 as i64;
+        Ok(Some(Self {
+            transaction_version: version,
+            sender: standardize_address(&user_txn.request.sender.inner().to_hex_literal()),
+            module_address: standardize_address(
+                &payload.function.module.address.inner().to_hex_literal(),
+            ),
+            module_name: payload.function.module.name.to_string(),
+            function_name: payload.function.name.to_string(),
+            type_arguments,
+            is_transaction_success: user_txn.info.success,
+            gas_used: user_txn.info.gas_used.0 as i64,
+            gas_unit_price: user_txn.request.gas_unit_price.0 as i64,
+            block_height,
+            transaction_timestamp: parse_timestamp(user_txn.timestamp.0, version),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_transaction(payload: serde_json::Value, block_height: Option<&str>) -> APITransaction {
+        let mut transaction = serde_json::json!({
+            "type": "user_transaction",
+            "version": "100",
+            "hash": format!("0x{}", "1".repeat(64)),
+            "state_change_hash": format!("0x{}", "2".repeat(64)),
+            "event_root_hash": format!("0x{}", "3".repeat(64)),
+            "state_checkpoint_hash": null,
+            "gas_used": "25",
+            "success": false,
+            "vm_status": "Move abort",
+            "accumulator_root_hash": format!("0x{}", "4".repeat(64)),
+            "changes": [],
+            "sender": "0xa",
+            "sequence_number": "3",
+            "max_gas_amount": "2000",
+            "gas_unit_price": "150",
+            "expiration_timestamp_secs": "1000",
+            "payload": payload,
+            "events": [],
+            "timestamp": "1000000",
+        });
+        if let Some(block_height) = block_height {
+            transaction["block_height"] = serde_json::json!(block_height);
+        }
+        serde_json::from_value(transaction).unwrap()
+    }
+
+    fn entry_function_payload() -> serde_json::Value {
+        serde_json::json!({
+            "type": "entry_function_payload",
+            "function": "0x1::coin::transfer",
+            "type_arguments": ["0x1::aptos_coin::AptosCoin"],
+            "arguments": ["0xb", "100"],
+        })
+    }
+
+    #[test]
+    fn test_entry_function_call() {
+        let transaction = user_transaction(entry_function_payload(), Some("7"));
+        let call = EntryFunctionCall::from_transaction(&transaction)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(call.transaction_version, 100);
+        assert_eq!(call.sender, standardize_address("0xa"));
+        assert_eq!(call.module_address, standardize_address("0x1"));
+        assert_eq!(call.module_name, "coin");
+        assert_eq!(call.function_name, "transfer");
+        assert_eq!(
+            call.type_arguments,
+            serde_json::json!(["0x1::aptos_coin::AptosCoin"])
+        );
+        assert!(!call.is_transaction_success);
+        assert_eq!(call.gas_used, 25);
+        assert_eq!(call.gas_unit_price, 150);
+        assert_eq!(call.block_height, 7);
+        assert_eq!(
+            call.transaction_timestamp,
+            chrono::NaiveDateTime::from_timestamp(1, 0)
+        );
+    }
+
+    #[test]
+    fn test_no_entry_function_call() {
+        let script_payload = serde_json::json!({
+            "type": "script_payload",
+            "code": { "bytecode": "0x01" },
+            "type_arguments": [],
+            "arguments": [],
+        });
+        let transaction = user_transaction(script_payload, Some("7"));
+        assert!(EntryFunctionCall::from_transaction(&transaction)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_missing_block_height() {
+        let transaction = user_transaction(entry_function_payload(), None);
+        assert!(EntryFunctionCall::from_transaction(&transaction).is_err());
+    }
+}
@@ -0,0 +1,5 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod entry_function_calls;
+pub mod move_module_abis;
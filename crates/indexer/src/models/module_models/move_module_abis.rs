@@ -0,0 +1,205 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use crate::{
+    models::move_modules::MoveModule, schema::move_module_abis, sql_types::NullableJsonbValue,
+    util::parse_timestamp,
+};
+use anyhow::Context;
+use aptos_api_types::{Transaction as APITransaction, WriteSetChange as APIWriteSetChange};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+/// The ABI of a module each time it's published, upgraded or deleted, without its bytecode.
+/// The ABI is unset if the bytecode couldn't be parsed.
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version, write_set_change_index))]
+#[diesel(table_name = move_module_abis)]
+pub struct MoveModuleAbi {
+    pub transaction_version: i64,
+    pub write_set_change_index: i64,
+    pub address: String,
+    pub name: String,
+    #[diesel(serialize_as = NullableJsonbValue)]
+    pub exposed_functions: Option<serde_json::Value>,
+    #[diesel(serialize_as = NullableJsonbValue)]
+    pub friends: Option<serde_json::Value>,
+    #[diesel(serialize_as = NullableJsonbValue)]
+    pub structs: Option<serde_json::Value>,
+    pub is_deleted: bool,
+    pub block_height: i64,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl MoveModuleAbi {
+    pub fn from_transaction(transaction: &APITransaction) -> anyhow::Result<Vec<Self>> {
+        let (txn_info, txn_timestamp) = match transaction {
+            APITransaction::GenesisTransaction(inner) => {
+                (&inner.info, chrono::NaiveDateTime::from_timestamp(0, 0))
+            }
+            APITransaction::UserTransaction(inner) => (
+                &inner.info,
+                parse_timestamp(inner.timestamp.0, inner.info.version.0 as i64),
+            ),
+            _ => return Ok(vec![]),
+        };
+        let txn_version = txn_info.version.0 as i64;
+        let block_height = txn_info
+            .block_height
+            .context(format!(
+                "version {} failed! block height is missing",
+                txn_version
+            ))?
+            .0 as i64;
+
+        Ok(txn_info
+            .changes
+            .iter()
+            .enumerate()
+            .filter_map(|(index, wsc)| {
+                let module = match wsc {
+                    APIWriteSetChange::WriteModule(write_module) => MoveModule::from_write_module(
+                        write_module,
+                        index as i64,
+                        txn_version,
+                        block_height,
+                    ),
+                    APIWriteSetChange::DeleteModule(delete_module) => {
+                        MoveModule::from_delete_module(
+                            delete_module,
+                            index as i64,
+                            txn_version,
+                            block_height,
+                        )
+                    }
+                    _ => return None,
+                };
+                Some(Self::from_move_module(module, txn_timestamp))
+            })
+            .collect())
+    }
+
+    fn from_move_module(module: MoveModule, transaction_timestamp: chrono::NaiveDateTime) -> Self {
+        Self {
+            transaction_version: module.transaction_version,
+            write_set_change_index: module.write_set_change_index,
+            address: module.address,
+            name: module.name,
+            exposed_functions: module.exposed_functions,
+            friends: module.friends,
+            structs: module.structs,
+            is_deleted: module.is_deleted,
+            block_height: module.transaction_block_height,
+            transaction_timestamp,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::standardize_address;
+    use move_binary_format::file_format::empty_module;
+    use move_core_types::{account_address::AccountAddress, identifier::Identifier};
+
+    fn genesis_transaction(changes: serde_json::Value) -> APITransaction {
+        serde_json::from_value(serde_json::json!({
+            "type": "genesis_transaction",
+            "version": "0",
+            "hash": format!("0x{}", "1".repeat(64)),
+            "state_change_hash": format!("0x{}", "2".repeat(64)),
+            "event_root_hash": format!("0x{}", "3".repeat(64)),
+            "state_checkpoint_hash": null,
+            "gas_used": "0",
+            "success": true,
+            "vm_status": "Executed successfully",
+            "accumulator_root_hash": format!("0x{}", "4".repeat(64)),
+            "changes": changes,
+            "block_height": "0",
+            "payload": {
+                "type": "write_set_payload",
+                "write_set": {
+                    "type": "direct_write_set",
+                    "changes": [],
+                    "events": [],
+                },
+            },
+            "events": [],
+        }))
+        .unwrap()
+    }
+
+    /// The bytecode of an empty module named `0x1::hello`
+    fn hello_module_bytecode() -> String {
+        let mut module = empty_module();
+        module.identifiers[0] = Identifier::new("hello").unwrap();
+        module.address_identifiers[0] = AccountAddress::ONE;
+        let mut bytecode = vec![];
+        module.serialize(&mut bytecode).unwrap();
+        format!("0x{}", hex::encode(bytecode))
+    }
+
+    #[test]
+    fn test_module_abis() {
+        let transaction = genesis_transaction(serde_json::json!([
+            {
+                "type": "write_module",
+                "address": "0x1",
+                "state_key_hash": format!("0x{}", "5".repeat(64)),
+                "data": { "bytecode": hello_module_bytecode() },
+            },
+            {
+                "type": "write_resource",
+                "address": "0x1",
+                "state_key_hash": format!("0x{}", "6".repeat(64)),
+                "data": { "type": "0x1::hello::Greeting", "data": {} },
+            },
+            {
+                "type": "write_module",
+                "address": "0x1",
+                "state_key_hash": format!("0x{}", "7".repeat(64)),
+                "data": { "bytecode": "0x0102" },
+            },
+            {
+                "type": "delete_module",
+                "address": "0x1",
+                "state_key_hash": format!("0x{}", "8".repeat(64)),
+                "module": "0x1::goodbye",
+            },
+        ]));
+        let abis = MoveModuleAbi::from_transaction(&transaction).unwrap();
+
+        assert_eq!(
+            abis.iter()
+                .map(|abi| (
+                    abi.write_set_change_index,
+                    abi.name.as_str(),
+                    abi.is_deleted
+                ))
+                .collect::<Vec<_>>(),
+            vec![(0, "hello", false), (2, "", false), (3, "goodbye", true)]
+        );
+        assert!(abis
+            .iter()
+            .all(|abi| abi.address == standardize_address("0x1") && abi.block_height == 0));
+        assert_eq!(abis[0].exposed_functions, Some(serde_json::json!([])));
+        assert_eq!(abis[0].friends, Some(serde_json::json!([])));
+        assert_eq!(abis[0].structs, Some(serde_json::json!([])));
+        // Neither the bytecode that can't be parsed nor the deleted module have an ABI
+        assert!(abis[1].exposed_functions.is_none());
+        assert!(abis[2].exposed_functions.is_none());
+    }
+
+    #[test]
+    fn test_missing_block_height() {
+        let mut transaction = genesis_transaction(serde_json::json!([]));
+        if let APITransaction::GenesisTransaction(inner) = &mut transaction {
+            inner.info.block_height = None;
+        }
+        assert!(MoveModuleAbi::from_transaction(&transaction).is_err());
+    }
+}
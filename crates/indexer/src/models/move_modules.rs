@@ -1,7 +1,10 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 #![allow(clippy::extra_unused_lifetimes)]
-use crate::{models::transactions::Transaction, schema::move_modules, util::standardize_address};
+use crate::{
+    models::transactions::Transaction, schema::move_modules, sql_types::NullableJsonbValue,
+    util::standardize_address,
+};
 use aptos_api_types::{DeleteModule, MoveModule as APIMoveModule, MoveModuleBytecode, WriteModule};
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
@@ -19,8 +22,11 @@ pub struct MoveModule {
     pub name: String,
     pub address: String,
     pub bytecode: Option<Vec<u8>>,
+    #[diesel(serialize_as = NullableJsonbValue)]
     pub exposed_functions: Option<serde_json::Value>,
+    #[diesel(serialize_as = NullableJsonbValue)]
     pub friends: Option<serde_json::Value>,
+    #[diesel(serialize_as = NullableJsonbValue)]
     pub structs: Option<serde_json::Value>,
     pub is_deleted: bool,
 }
@@ -1,7 +1,10 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 #![allow(clippy::extra_unused_lifetimes)]
-use crate::{models::transactions::Transaction, schema::move_resources, util::standardize_address};
+use crate::{
+    models::transactions::Transaction, schema::move_resources, sql_types::NullableJsonbValue,
+    util::standardize_address,
+};
 use anyhow::{Context, Result};
 use aptos_api_types::{DeleteResource, MoveStructTag as APIMoveStructTag, WriteResource};
 use field_count::FieldCount;
@@ -20,7 +23,9 @@ pub struct MoveResource {
     pub type_: String,
     pub address: String,
     pub module: String,
+    #[diesel(serialize_as = NullableJsonbValue)]
     pub generic_type_params: Option<serde_json::Value>,
+    #[diesel(serialize_as = NullableJsonbValue)]
     pub data: Option<serde_json::Value>,
     pub is_deleted: bool,
 }
@@ -4,6 +4,7 @@
 use crate::{
     models::transactions::Transaction,
     schema::{table_items, table_metadatas},
+    sql_types::{JsonbValue, NullableJsonbValue},
     util::standardize_address,
 };
 use aptos_api_types::{DeleteTableItem, WriteTableItem};
@@ -22,7 +23,9 @@ pub struct TableItem {
     pub transaction_block_height: i64,
     pub key: String,
     pub table_handle: String,
+    #[diesel(serialize_as = JsonbValue)]
     pub decoded_key: serde_json::Value,
+    #[diesel(serialize_as = NullableJsonbValue)]
     pub decoded_value: Option<serde_json::Value>,
     pub is_deleted: bool,
 }
@@ -4,7 +4,7 @@
 use crate::{indexer::errors::TransactionProcessingError, schema::processor_statuses};
 use field_count::FieldCount;
 
-#[derive(AsChangeset, Debug, FieldCount, Insertable, Queryable)]
+#[derive(AsChangeset, Clone, Debug, FieldCount, Insertable, Queryable)]
 #[diesel(treat_none_as_null = true)]
 #[diesel(table_name = processor_statuses)]
 /// We are deprecating this in favor of ProcessorStatusV2
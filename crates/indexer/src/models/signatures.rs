@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 #![allow(clippy::extra_unused_lifetimes)]
 
-use crate::{models::transactions::Transaction, schema::signatures, util::standardize_address};
+use crate::{
+    models::transactions::Transaction, schema::signatures, sql_types::JsonbValue,
+    util::standardize_address,
+};
 use anyhow::{Context, Result};
 use aptos_api_types::{
     AccountSignature as APIAccountSignature, Ed25519Signature as APIEd25519Signature,
@@ -36,6 +39,7 @@ pub struct Signature {
     pub public_key: String,
     pub signature: String,
     pub threshold: i64,
+    #[diesel(serialize_as = JsonbValue)]
     pub public_key_indices: serde_json::Value,
 }
 
@@ -7,6 +7,7 @@
 use super::stake_utils::StakeEvent;
 use crate::{
     schema::proposal_votes,
+    sql_types::NumericValue,
     util::{parse_timestamp, standardize_address},
 };
 use aptos_api_types::Transaction as APITransaction;
@@ -14,7 +15,7 @@ use bigdecimal::BigDecimal;
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
 #[diesel(primary_key(transaction_version, proposal_id, voter_address))]
 #[diesel(table_name = proposal_votes)]
 pub struct ProposalVote {
@@ -22,6 +23,7 @@ pub struct ProposalVote {
     pub proposal_id: i64,
     pub voter_address: String,
     pub staking_pool_address: String,
+    #[diesel(serialize_as = NumericValue)]
     pub num_votes: BigDecimal,
     pub should_pass: bool,
     pub transaction_timestamp: chrono::NaiveDateTime,
@@ -10,9 +10,11 @@ use super::{
     tokens::TableHandleToOwner,
 };
 use crate::{
-    database::PgPoolConnection,
+    database::DbConnection,
     schema::{collection_datas, current_collection_datas},
+    sql_types::NumericValue,
     util::standardize_address,
+    with_conn,
 };
 use anyhow::Context;
 use aptos_api_types::WriteTableItem as APIWriteTableItem;
@@ -23,7 +25,7 @@ use serde::{Deserialize, Serialize};
 
 const QUERY_RETRIES: u32 = 5;
 const QUERY_RETRY_DELAY_MS: u64 = 500;
-#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
 #[diesel(primary_key(collection_data_id_hash, transaction_version))]
 #[diesel(table_name = collection_datas)]
 pub struct CollectionData {
@@ -33,7 +35,9 @@ pub struct CollectionData {
     pub collection_name: String,
     pub description: String,
     pub metadata_uri: String,
+    #[diesel(serialize_as = NumericValue)]
     pub supply: BigDecimal,
+    #[diesel(serialize_as = NumericValue)]
     pub maximum: BigDecimal,
     pub maximum_mutable: bool,
     pub uri_mutable: bool,
@@ -42,7 +46,7 @@ pub struct CollectionData {
     pub transaction_timestamp: chrono::NaiveDateTime,
 }
 
-#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
 #[diesel(primary_key(collection_data_id_hash))]
 #[diesel(table_name = current_collection_datas)]
 pub struct CurrentCollectionData {
@@ -51,7 +55,9 @@ pub struct CurrentCollectionData {
     pub collection_name: String,
     pub description: String,
     pub metadata_uri: String,
+    #[diesel(serialize_as = NumericValue)]
     pub supply: BigDecimal,
+    #[diesel(serialize_as = NumericValue)]
     pub maximum: BigDecimal,
     pub maximum_mutable: bool,
     pub uri_mutable: bool,
@@ -88,7 +94,7 @@ impl CollectionData {
         txn_version: i64,
         txn_timestamp: chrono::NaiveDateTime,
         table_handle_to_owner: &TableHandleToOwner,
-        conn: &mut PgPoolConnection,
+        conn: &mut DbConnection,
     ) -> anyhow::Result<Option<(Self, CurrentCollectionData)>> {
         let table_item_data = table_item.data.as_ref().unwrap();
 
@@ -160,7 +166,7 @@ impl CollectionData {
     /// cannot change, we can just look in the current_collection_datas table.
     /// Retrying a few times since this collection could've been written in a separate thread.
     pub fn get_collection_creator(
-        conn: &mut PgPoolConnection,
+        conn: &mut DbConnection,
         table_handle: &str,
     ) -> anyhow::Result<String> {
         let mut retried = 0;
@@ -179,11 +185,11 @@ impl CollectionData {
 
 impl CurrentCollectionDataQuery {
     pub fn get_by_table_handle(
-        conn: &mut PgPoolConnection,
+        conn: &mut DbConnection,
         table_handle: &str,
     ) -> diesel::QueryResult<Self> {
-        current_collection_datas::table
+        with_conn!(conn, |conn| current_collection_datas::table
             .filter(current_collection_datas::table_handle.eq(table_handle))
-            .first::<Self>(conn)
+            .first::<Self>(conn))
     }
 }
@@ -8,6 +8,7 @@
 use super::token_utils::{TokenDataIdType, TokenEvent};
 use crate::{
     schema::token_activities,
+    sql_types::{NullableNumericValue, NumericValue},
     util::{parse_timestamp, standardize_address},
 };
 use aptos_api_types::{Event as APIEvent, Transaction as APITransaction};
@@ -15,7 +16,7 @@ use bigdecimal::{BigDecimal, Zero};
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
 #[diesel(primary_key(
     transaction_version,
     event_account_address,
@@ -29,6 +30,7 @@ pub struct TokenActivity {
     pub event_creation_number: i64,
     pub event_sequence_number: i64,
     pub token_data_id_hash: String,
+    #[diesel(serialize_as = NumericValue)]
     pub property_version: BigDecimal,
     pub creator_address: String,
     pub collection_name: String,
@@ -36,8 +38,10 @@ pub struct TokenActivity {
     pub transfer_type: String,
     pub from_address: Option<String>,
     pub to_address: Option<String>,
+    #[diesel(serialize_as = NumericValue)]
     pub token_amount: BigDecimal,
     pub coin_type: Option<String>,
+    #[diesel(serialize_as = NullableNumericValue)]
     pub coin_amount: Option<BigDecimal>,
     pub collection_data_id_hash: String,
     pub transaction_timestamp: chrono::NaiveDateTime,
@@ -6,17 +6,20 @@
 #![allow(clippy::unused_unit)]
 
 use super::{token_utils::TokenWriteSet, tokens::TableHandleToOwner};
-use crate::{schema::current_token_pending_claims, util::standardize_address};
+use crate::{
+    schema::current_token_pending_claims, sql_types::NumericValue, util::standardize_address,
+};
 use aptos_api_types::{DeleteTableItem as APIDeleteTableItem, WriteTableItem as APIWriteTableItem};
 use bigdecimal::{BigDecimal, Zero};
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
 #[diesel(primary_key(token_data_id_hash, property_version, from_address, to_address))]
 #[diesel(table_name = current_token_pending_claims)]
 pub struct CurrentTokenPendingClaim {
     pub token_data_id_hash: String,
+    #[diesel(serialize_as = NumericValue)]
     pub property_version: BigDecimal,
     pub from_address: String,
     pub to_address: String,
@@ -24,6 +27,7 @@ pub struct CurrentTokenPendingClaim {
     pub creator_address: String,
     pub collection_name: String,
     pub name: String,
+    #[diesel(serialize_as = NumericValue)]
     pub amount: BigDecimal,
     pub table_handle: String,
     pub last_transaction_version: i64,
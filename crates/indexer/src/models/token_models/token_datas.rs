@@ -8,6 +8,7 @@
 use super::token_utils::TokenWriteSet;
 use crate::{
     schema::{current_token_datas, token_datas},
+    sql_types::{JsonbValue, NumericValue},
     util::standardize_address,
 };
 use aptos_api_types::WriteTableItem as APIWriteTableItem;
@@ -15,7 +16,7 @@ use bigdecimal::BigDecimal;
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
 #[diesel(primary_key(token_data_id_hash, transaction_version))]
 #[diesel(table_name = token_datas)]
 pub struct TokenData {
@@ -24,25 +25,31 @@ pub struct TokenData {
     pub creator_address: String,
     pub collection_name: String,
     pub name: String,
+    #[diesel(serialize_as = NumericValue)]
     pub maximum: BigDecimal,
+    #[diesel(serialize_as = NumericValue)]
     pub supply: BigDecimal,
+    #[diesel(serialize_as = NumericValue)]
     pub largest_property_version: BigDecimal,
     pub metadata_uri: String,
     pub payee_address: String,
+    #[diesel(serialize_as = NumericValue)]
     pub royalty_points_numerator: BigDecimal,
+    #[diesel(serialize_as = NumericValue)]
     pub royalty_points_denominator: BigDecimal,
     pub maximum_mutable: bool,
     pub uri_mutable: bool,
     pub description_mutable: bool,
     pub properties_mutable: bool,
     pub royalty_mutable: bool,
+    #[diesel(serialize_as = JsonbValue)]
     pub default_properties: serde_json::Value,
     pub collection_data_id_hash: String,
     pub transaction_timestamp: chrono::NaiveDateTime,
     pub description: String,
 }
 
-#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
 #[diesel(primary_key(token_data_id_hash))]
 #[diesel(table_name = current_token_datas)]
 pub struct CurrentTokenData {
@@ -62,6 +69,7 @@ pub struct CurrentTokenData {
     pub description_mutable: bool,
     pub properties_mutable: bool,
     pub royalty_mutable: bool,
+    #[diesel(serialize_as = JsonbValue)]
     pub default_properties: serde_json::Value,
     pub last_transaction_version: i64,
     pub collection_data_id_hash: String,
@@ -11,6 +11,7 @@ use super::{
 };
 use crate::{
     schema::{current_token_ownerships, token_ownerships},
+    sql_types::{JsonbValue, NumericValue},
     util::standardize_address,
 };
 use bigdecimal::BigDecimal;
@@ -18,7 +19,7 @@ use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
 #[diesel(primary_key(
     token_data_id_hash,
     property_version,
@@ -28,6 +29,7 @@ use serde_json::Value;
 #[diesel(table_name = token_ownerships)]
 pub struct TokenOwnership {
     pub token_data_id_hash: String,
+    #[diesel(serialize_as = NumericValue)]
     pub property_version: BigDecimal,
     pub transaction_version: i64,
     pub table_handle: String,
@@ -35,23 +37,27 @@ pub struct TokenOwnership {
     pub collection_name: String,
     pub name: String,
     pub owner_address: Option<String>,
+    #[diesel(serialize_as = NumericValue)]
     pub amount: BigDecimal,
     pub table_type: Option<String>,
     pub collection_data_id_hash: String,
     pub transaction_timestamp: chrono::NaiveDateTime,
 }
 
-#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
 #[diesel(primary_key(token_data_id_hash, property_version, owner_address))]
 #[diesel(table_name = current_token_ownerships)]
 pub struct CurrentTokenOwnership {
     pub token_data_id_hash: String,
+    #[diesel(serialize_as = NumericValue)]
     pub property_version: BigDecimal,
     pub owner_address: String,
     pub creator_address: String,
     pub collection_name: String,
     pub name: String,
+    #[diesel(serialize_as = NumericValue)]
     pub amount: BigDecimal,
+    #[diesel(serialize_as = JsonbValue)]
     pub token_properties: serde_json::Value,
     pub last_transaction_version: i64,
     pub collection_data_id_hash: String,
@@ -13,9 +13,10 @@ use super::{
     token_utils::{TokenResource, TokenWriteSet},
 };
 use crate::{
-    database::PgPoolConnection,
+    database::DbConnection,
     models::move_resources::MoveResource,
     schema::tokens,
+    sql_types::{JsonbValue, NumericValue},
     util::{ensure_not_negative, parse_timestamp, standardize_address},
 };
 use aptos_api_types::{
@@ -40,16 +41,18 @@ pub type CurrentTokenPendingClaimPK = (TokenDataIdHash, BigDecimal, Address, Add
 // PK of tokens table, used to dedupe tokens
 pub type TokenPK = (TokenDataIdHash, BigDecimal);
 
-#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
 #[diesel(primary_key(token_data_id_hash, property_version, transaction_version))]
 #[diesel(table_name = tokens)]
 pub struct Token {
     pub token_data_id_hash: String,
+    #[diesel(serialize_as = NumericValue)]
     pub property_version: BigDecimal,
     pub transaction_version: i64,
     pub creator_address: String,
     pub collection_name: String,
     pub name: String,
+    #[diesel(serialize_as = JsonbValue)]
     pub token_properties: serde_json::Value,
     pub collection_data_id_hash: String,
     pub transaction_timestamp: chrono::NaiveDateTime,
@@ -70,7 +73,7 @@ impl Token {
     pub fn from_transaction(
         transaction: &APITransaction,
         table_handle_to_owner: &TableHandleToOwner,
-        conn: &mut PgPoolConnection,
+        conn: &mut DbConnection,
     ) -> (
         Vec<Self>,
         Vec<TokenOwnership>,
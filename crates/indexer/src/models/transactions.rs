@@ -7,12 +7,13 @@
 
 use crate::{
     schema::{block_metadata_transactions, transactions, user_transactions},
+    sql_types::{NullableJsonbValue, NumericValue},
     util::u64_to_bigdecimal,
 };
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
 
-use crate::database::PgPoolConnection;
+use crate::{database::DbConnection, with_conn};
 use aptos_api_types::{Transaction as APITransaction, TransactionInfo};
 use bigdecimal::BigDecimal;
 use diesel::{
@@ -27,7 +28,7 @@ use super::{
     write_set_changes::{WriteSetChangeDetail, WriteSetChangeModel, WriteSetChangeQuery},
 };
 
-#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
 #[diesel(primary_key(version))]
 #[diesel(table_name = transactions)]
 pub struct Transaction {
@@ -35,10 +36,12 @@ pub struct Transaction {
     pub block_height: i64,
     pub hash: String,
     pub type_: String,
+    #[diesel(serialize_as = NullableJsonbValue)]
     pub payload: Option<serde_json::Value>,
     pub state_change_hash: String,
     pub event_root_hash: String,
     pub state_checkpoint_hash: Option<String>,
+    #[diesel(serialize_as = NumericValue)]
     pub gas_used: BigDecimal,
     pub success: bool,
     pub vm_status: String,
@@ -258,7 +261,7 @@ impl TransactionQuery {
     pub fn get_many_by_version(
         start_version: u64,
         number_to_get: i64,
-        conn: &mut PgPoolConnection,
+        conn: &mut DbConnection,
     ) -> diesel::QueryResult<
         Vec<(
             Self,
@@ -268,30 +271,33 @@ impl TransactionQuery {
             Vec<WriteSetChangeQuery>,
         )>,
     > {
-        let mut txs = transactions::table
+        let mut txs = with_conn!(conn, |conn| transactions::table
             .filter(transactions::version.ge(start_version as i64))
             .order(transactions::version.asc())
             .limit(number_to_get as i64)
-            .load::<Self>(conn)?;
+            .load::<Self>(conn))?;
 
         let mut user_transactions: Vec<Vec<UserTransactionQuery>> =
-            UserTransactionQuery::belonging_to(&txs)
-                .load::<UserTransactionQuery>(conn)?
-                .grouped_by(&txs);
+            with_conn!(conn, |conn| UserTransactionQuery::belonging_to(&txs)
+                .load::<UserTransactionQuery>(conn))?
+            .grouped_by(&txs);
 
-        let mut block_metadata_transactions: Vec<Vec<BlockMetadataTransactionQuery>> =
-            BlockMetadataTransactionQuery::belonging_to(&txs)
-                .load::<BlockMetadataTransactionQuery>(conn)?
-                .grouped_by(&txs);
+        let mut block_metadata_transactions: Vec<Vec<BlockMetadataTransactionQuery>> = with_conn!(
+            conn,
+            |conn| BlockMetadataTransactionQuery::belonging_to(&txs)
+                .load::<BlockMetadataTransactionQuery>(conn)
+        )?
+        .grouped_by(&txs);
 
-        let mut events: Vec<Vec<EventQuery>> = EventQuery::belonging_to(&txs)
-            .load::<EventQuery>(conn)?
+        let mut events: Vec<Vec<EventQuery>> =
+            with_conn!(conn, |conn| EventQuery::belonging_to(&txs)
+                .load::<EventQuery>(conn))?
             .grouped_by(&txs);
 
         let mut write_set_changes: Vec<Vec<WriteSetChangeQuery>> =
-            WriteSetChangeQuery::belonging_to(&txs)
-                .load::<WriteSetChangeQuery>(conn)?
-                .grouped_by(&txs);
+            with_conn!(conn, |conn| WriteSetChangeQuery::belonging_to(&txs)
+                .load::<WriteSetChangeQuery>(conn))?
+            .grouped_by(&txs);
 
         // Convert to the nice result tuple
         let mut result = vec![];
@@ -310,7 +316,7 @@ impl TransactionQuery {
 
     pub fn get_by_version(
         version: u64,
-        conn: &mut PgPoolConnection,
+        conn: &mut DbConnection,
     ) -> diesel::QueryResult<(
         Self,
         Option<UserTransactionQuery>,
@@ -318,9 +324,9 @@ impl TransactionQuery {
         Vec<EventQuery>,
         Vec<WriteSetChangeQuery>,
     )> {
-        let transaction = transactions::table
+        let transaction = with_conn!(conn, |conn| transactions::table
             .filter(transactions::version.eq(version as i64))
-            .first::<Self>(conn)?;
+            .first::<Self>(conn))?;
 
         let (user_transaction, block_metadata_transaction, events, write_set_changes) =
             transaction.get_details_for_transaction(conn)?;
@@ -336,7 +342,7 @@ impl TransactionQuery {
 
     pub fn get_by_hash(
         transaction_hash: &str,
-        conn: &mut PgPoolConnection,
+        conn: &mut DbConnection,
     ) -> diesel::QueryResult<(
         Self,
         Option<UserTransactionQuery>,
@@ -344,9 +350,9 @@ impl TransactionQuery {
         Vec<EventQuery>,
         Vec<WriteSetChangeQuery>,
     )> {
-        let transaction = transactions::table
+        let transaction = with_conn!(conn, |conn| transactions::table
             .filter(transactions::hash.eq(&transaction_hash))
-            .first::<Self>(conn)?;
+            .first::<Self>(conn))?;
 
         let (user_transaction, block_metadata_transaction, events, write_set_changes) =
             transaction.get_details_for_transaction(conn)?;
@@ -362,7 +368,7 @@ impl TransactionQuery {
 
     fn get_details_for_transaction(
         &self,
-        conn: &mut PgPoolConnection,
+        conn: &mut DbConnection,
     ) -> diesel::QueryResult<(
         Option<UserTransactionQuery>,
         Option<BlockMetadataTransactionQuery>,
@@ -372,26 +378,27 @@ impl TransactionQuery {
         let mut user_transaction: Option<UserTransactionQuery> = None;
         let mut block_metadata_transaction: Option<BlockMetadataTransactionQuery> = None;
 
-        let events = crate::schema::events::table
+        let events = with_conn!(conn, |conn| crate::schema::events::table
             .filter(crate::schema::events::transaction_version.eq(&self.version))
-            .load::<EventQuery>(conn)?;
+            .load::<EventQuery>(conn))?;
 
-        let write_set_changes = crate::schema::write_set_changes::table
+        let write_set_changes = with_conn!(conn, |conn| crate::schema::write_set_changes::table
             .filter(crate::schema::write_set_changes::transaction_version.eq(&self.version))
-            .load::<WriteSetChangeQuery>(conn)?;
+            .load::<WriteSetChangeQuery>(conn))?;
 
         match self.type_.as_str() {
             "user_transaction" => {
-                user_transaction = user_transactions::table
+                user_transaction = with_conn!(conn, |conn| user_transactions::table
                     .filter(user_transactions::version.eq(&self.version))
                     .first::<UserTransactionQuery>(conn)
-                    .optional()?;
+                    .optional())?;
             }
             "block_metadata_transaction" => {
-                block_metadata_transaction = block_metadata_transactions::table
-                    .filter(block_metadata_transactions::version.eq(&self.version))
-                    .first::<BlockMetadataTransactionQuery>(conn)
-                    .optional()?;
+                block_metadata_transaction =
+                    with_conn!(conn, |conn| block_metadata_transactions::table
+                        .filter(block_metadata_transactions::version.eq(&self.version))
+                        .first::<BlockMetadataTransactionQuery>(conn)
+                        .optional())?;
             }
             "genesis_transaction" => {}
             "state_checkpoint_transaction" => {}
@@ -11,6 +11,7 @@ use super::{
 };
 use crate::{
     schema::user_transactions,
+    sql_types::NumericValue,
     util::{parse_timestamp, parse_timestamp_secs, standardize_address, u64_to_bigdecimal},
 };
 use aptos_api_types::{TransactionPayload, UserTransaction as APIUserTransaction};
@@ -30,8 +31,10 @@ pub struct UserTransaction {
     pub parent_signature_type: String,
     pub sender: String,
     pub sequence_number: i64,
+    #[diesel(serialize_as = NumericValue)]
     pub max_gas_amount: BigDecimal,
     pub expiration_timestamp_secs: chrono::NaiveDateTime,
+    #[diesel(serialize_as = NumericValue)]
     pub gas_unit_price: BigDecimal,
     pub timestamp: chrono::NaiveDateTime,
     pub entry_function_id_str: String,
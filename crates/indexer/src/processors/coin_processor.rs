@@ -3,10 +3,13 @@
 
 use crate::{
     database::{
-        clean_data_for_db, execute_with_better_error, get_chunks, PgDbPool, PgPoolConnection,
+        clean_data_for_db, execute_with_better_error, ArcDbPool, DbConnection, DbPoolConnection,
     },
+    execute_in_chunks,
     indexer::{
-        errors::TransactionProcessingError, processing_result::ProcessingResult,
+        errors::TransactionProcessingError,
+        parquet_sink::{ParquetBatch, ParquetSink},
+        processing_result::ProcessingResult,
         transaction_processor::TransactionProcessor,
     },
     models::coin_models::{
@@ -14,24 +17,34 @@ use crate::{
         coin_balances::{CoinBalance, CurrentCoinBalance},
         coin_infos::{CoinInfo, CoinInfoQuery},
         coin_supply::CoinSupply,
+        coin_transfers::CoinTransfer,
     },
     schema,
 };
 use aptos_api_types::Transaction as APITransaction;
 use aptos_types::APTOS_COIN_TYPE;
 use async_trait::async_trait;
-use diesel::{pg::upsert::excluded, result::Error, ExpressionMethods, PgConnection};
-use field_count::FieldCount;
+use diesel::{result::Error, upsert::excluded, ExpressionMethods};
 use std::{collections::HashMap, fmt::Debug};
 
 pub const NAME: &str = "coin_processor";
 pub struct CoinTransactionProcessor {
-    connection_pool: PgDbPool,
+    connection_pool: ArcDbPool,
+    parquet_sink: Option<ParquetSink>,
+    overwrite_existing_rows: bool,
 }
 
 impl CoinTransactionProcessor {
-    pub fn new(connection_pool: PgDbPool) -> Self {
-        Self { connection_pool }
+    pub fn new(
+        connection_pool: ArcDbPool,
+        parquet_sink: Option<ParquetSink>,
+        overwrite_existing_rows: bool,
+    ) -> Self {
+        Self {
+            connection_pool,
+            parquet_sink,
+            overwrite_existing_rows,
+        }
     }
 }
 
@@ -47,23 +60,26 @@ impl Debug for CoinTransactionProcessor {
 }
 
 fn insert_to_db_impl(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     coin_activities: &[CoinActivity],
     coin_infos: &[CoinInfo],
     coin_balances: &[CoinBalance],
     current_coin_balances: &[CurrentCoinBalance],
     coin_supply: &[CoinSupply],
+    coin_transfers: &[CoinTransfer],
+    overwrite_existing_rows: bool,
 ) -> Result<(), diesel::result::Error> {
-    insert_coin_activities(conn, coin_activities)?;
+    insert_coin_activities(conn, coin_activities, overwrite_existing_rows)?;
     insert_coin_infos(conn, coin_infos)?;
-    insert_coin_balances(conn, coin_balances)?;
+    insert_coin_balances(conn, coin_balances, overwrite_existing_rows)?;
     insert_current_coin_balances(conn, current_coin_balances)?;
-    insert_coin_supply(conn, coin_supply)?;
+    insert_coin_supply(conn, coin_supply, overwrite_existing_rows)?;
+    insert_coin_transfers(conn, coin_transfers, overwrite_existing_rows)?;
     Ok(())
 }
 
 fn insert_to_db(
-    conn: &mut PgPoolConnection,
+    conn: &mut DbPoolConnection,
     name: &'static str,
     start_version: u64,
     end_version: u64,
@@ -72,6 +88,8 @@ fn insert_to_db(
     coin_balances: Vec<CoinBalance>,
     current_coin_balances: Vec<CurrentCoinBalance>,
     coin_supply: Vec<CoinSupply>,
+    coin_transfers: Vec<CoinTransfer>,
+    overwrite_existing_rows: bool,
 ) -> Result<(), diesel::result::Error> {
     aptos_logger::trace!(
         name = name,
@@ -79,78 +97,90 @@ fn insert_to_db(
         end_version = end_version,
         "Inserting to db",
     );
-    match conn
-        .build_transaction()
-        .read_write()
-        .run::<_, Error, _>(|pg_conn| {
+    match conn.transaction::<_, Error, _>(|conn| {
+        insert_to_db_impl(
+            conn,
+            &coin_activities,
+            &coin_infos,
+            &coin_balances,
+            &current_coin_balances,
+            &coin_supply,
+            &coin_transfers,
+            overwrite_existing_rows,
+        )
+    }) {
+        Ok(_) => Ok(()),
+        Err(_) => conn.transaction::<_, Error, _>(|conn| {
+            let coin_activities = clean_data_for_db(coin_activities, true);
+            let coin_infos = clean_data_for_db(coin_infos, true);
+            let coin_balances = clean_data_for_db(coin_balances, true);
+            let current_coin_balances = clean_data_for_db(current_coin_balances, true);
+            let coin_transfers = clean_data_for_db(coin_transfers, true);
+
             insert_to_db_impl(
-                pg_conn,
+                conn,
                 &coin_activities,
                 &coin_infos,
                 &coin_balances,
                 &current_coin_balances,
                 &coin_supply,
+                &coin_transfers,
+                overwrite_existing_rows,
             )
-        }) {
-        Ok(_) => Ok(()),
-        Err(_) => conn
-            .build_transaction()
-            .read_write()
-            .run::<_, Error, _>(|pg_conn| {
-                let coin_activities = clean_data_for_db(coin_activities, true);
-                let coin_infos = clean_data_for_db(coin_infos, true);
-                let coin_balances = clean_data_for_db(coin_balances, true);
-                let current_coin_balances = clean_data_for_db(current_coin_balances, true);
-
-                insert_to_db_impl(
-                    pg_conn,
-                    &coin_activities,
-                    &coin_infos,
-                    &coin_balances,
-                    &current_coin_balances,
-                    &coin_supply,
-                )
-            }),
+        }),
     }
 }
 
 fn insert_coin_activities(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     item_to_insert: &[CoinActivity],
+    overwrite_existing_rows: bool,
 ) -> Result<(), diesel::result::Error> {
     use schema::coin_activities::dsl::*;
 
-    let chunks = get_chunks(item_to_insert.len(), CoinActivity::field_count());
-    for (start_ind, end_ind) in chunks {
-        execute_with_better_error(
-            conn,
-            diesel::insert_into(schema::coin_activities::table)
-                .values(&item_to_insert[start_ind..end_ind])
-                .on_conflict((
-                    transaction_version,
-                    event_account_address,
-                    event_creation_number,
-                    event_sequence_number,
-                ))
-                .do_nothing(),
-            None,
-        )?;
-    }
+    execute_in_chunks!(conn, item_to_insert, |conn, items| {
+        let query = diesel::insert_into(schema::coin_activities::table)
+            .values(items)
+            .on_conflict((
+                transaction_version,
+                event_account_address,
+                event_creation_number,
+                event_sequence_number,
+            ));
+        if overwrite_existing_rows {
+            execute_with_better_error(
+                conn,
+                query.do_update().set((
+                    owner_address.eq(excluded(owner_address)),
+                    coin_type.eq(excluded(coin_type)),
+                    amount.eq(excluded(amount)),
+                    activity_type.eq(excluded(activity_type)),
+                    is_gas_fee.eq(excluded(is_gas_fee)),
+                    is_transaction_success.eq(excluded(is_transaction_success)),
+                    entry_function_id_str.eq(excluded(entry_function_id_str)),
+                    block_height.eq(excluded(block_height)),
+                    transaction_timestamp.eq(excluded(transaction_timestamp)),
+                )),
+                None,
+            )?;
+        } else {
+            execute_with_better_error(conn, query.do_nothing(), None)?;
+        }
+    });
     Ok(())
 }
 
 fn insert_coin_infos(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     item_to_insert: &[CoinInfo],
 ) -> Result<(), diesel::result::Error> {
     use schema::coin_infos::dsl::*;
 
-    let chunks = get_chunks(item_to_insert.len(), CoinInfo::field_count());
-    for (start_ind, end_ind) in chunks {
+    execute_in_chunks!(conn, item_to_insert, |conn, items| {
         execute_with_better_error(
             conn,
             diesel::insert_into(schema::coin_infos::table)
-                .values(&item_to_insert[start_ind..end_ind])
+                .values(items)
                 .on_conflict(coin_type_hash)
                 .do_update()
                 .set((
@@ -166,42 +196,49 @@ fn insert_coin_infos(
                 )),
             Some(" WHERE coin_infos.transaction_version_created >= EXCLUDED.transaction_version_created "),
         )?;
-    }
+    });
     Ok(())
 }
 
 fn insert_coin_balances(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     item_to_insert: &[CoinBalance],
+    overwrite_existing_rows: bool,
 ) -> Result<(), diesel::result::Error> {
     use schema::coin_balances::dsl::*;
 
-    let chunks = get_chunks(item_to_insert.len(), CoinBalance::field_count());
-    for (start_ind, end_ind) in chunks {
-        execute_with_better_error(
-            conn,
-            diesel::insert_into(schema::coin_balances::table)
-                .values(&item_to_insert[start_ind..end_ind])
-                .on_conflict((transaction_version, owner_address, coin_type_hash))
-                .do_nothing(),
-            None,
-        )?;
-    }
+    execute_in_chunks!(conn, item_to_insert, |conn, items| {
+        let query = diesel::insert_into(schema::coin_balances::table)
+            .values(items)
+            .on_conflict((transaction_version, owner_address, coin_type_hash));
+        if overwrite_existing_rows {
+            execute_with_better_error(
+                conn,
+                query.do_update().set((
+                    coin_type.eq(excluded(coin_type)),
+                    amount.eq(excluded(amount)),
+                    transaction_timestamp.eq(excluded(transaction_timestamp)),
+                )),
+                None,
+            )?;
+        } else {
+            execute_with_better_error(conn, query.do_nothing(), None)?;
+        }
+    });
     Ok(())
 }
 
 fn insert_current_coin_balances(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     item_to_insert: &[CurrentCoinBalance],
 ) -> Result<(), diesel::result::Error> {
     use schema::current_coin_balances::dsl::*;
 
-    let chunks = get_chunks(item_to_insert.len(), CurrentCoinBalance::field_count());
-    for (start_ind, end_ind) in chunks {
+    execute_in_chunks!(conn, item_to_insert, |conn, items| {
         execute_with_better_error(
             conn,
             diesel::insert_into(schema::current_coin_balances::table)
-                .values(&item_to_insert[start_ind..end_ind])
+                .values(items)
                 .on_conflict((owner_address, coin_type_hash))
                 .do_update()
                 .set((
@@ -212,27 +249,74 @@ fn insert_current_coin_balances(
                 )),
                 Some(" WHERE current_coin_balances.last_transaction_version <= excluded.last_transaction_version "),
             )?;
-    }
+    });
     Ok(())
 }
 
 fn insert_coin_supply(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     item_to_insert: &[CoinSupply],
+    overwrite_existing_rows: bool,
 ) -> Result<(), diesel::result::Error> {
     use schema::coin_supply::dsl::*;
 
-    let chunks = get_chunks(item_to_insert.len(), CoinSupply::field_count());
-    for (start_ind, end_ind) in chunks {
-        execute_with_better_error(
-            conn,
-            diesel::insert_into(schema::coin_supply::table)
-                .values(&item_to_insert[start_ind..end_ind])
-                .on_conflict((transaction_version, coin_type_hash))
-                .do_nothing(),
-            None,
-        )?;
-    }
+    execute_in_chunks!(conn, item_to_insert, |conn, items| {
+        let query = diesel::insert_into(schema::coin_supply::table)
+            .values(items)
+            .on_conflict((transaction_version, coin_type_hash));
+        if overwrite_existing_rows {
+            execute_with_better_error(
+                conn,
+                query.do_update().set((
+                    coin_type.eq(excluded(coin_type)),
+                    supply.eq(excluded(supply)),
+                    transaction_timestamp.eq(excluded(transaction_timestamp)),
+                    transaction_epoch.eq(excluded(transaction_epoch)),
+                )),
+                None,
+            )?;
+        } else {
+            execute_with_better_error(conn, query.do_nothing(), None)?;
+        }
+    });
+    Ok(())
+}
+
+fn insert_coin_transfers(
+    conn: &mut DbConnection,
+    item_to_insert: &[CoinTransfer],
+    overwrite_existing_rows: bool,
+) -> Result<(), diesel::result::Error> {
+    use schema::coin_transfers::dsl::*;
+
+    execute_in_chunks!(conn, item_to_insert, |conn, items| {
+        let query = diesel::insert_into(schema::coin_transfers::table)
+            .values(items)
+            .on_conflict((transaction_version, transfer_index));
+        if overwrite_existing_rows {
+            execute_with_better_error(
+                conn,
+                query.do_update().set((
+                    coin_type.eq(excluded(coin_type)),
+                    amount.eq(excluded(amount)),
+                    from_address.eq(excluded(from_address)),
+                    to_address.eq(excluded(to_address)),
+                    withdraw_event_creation_number.eq(excluded(withdraw_event_creation_number)),
+                    withdraw_event_sequence_number.eq(excluded(withdraw_event_sequence_number)),
+                    deposit_event_creation_number.eq(excluded(deposit_event_creation_number)),
+                    deposit_event_sequence_number.eq(excluded(deposit_event_sequence_number)),
+                    is_gas_fee.eq(excluded(is_gas_fee)),
+                    is_transaction_success.eq(excluded(is_transaction_success)),
+                    entry_function_id_str.eq(excluded(entry_function_id_str)),
+                    block_height.eq(excluded(block_height)),
+                    transaction_timestamp.eq(excluded(transaction_timestamp)),
+                )),
+                None,
+            )?;
+        } else {
+            execute_with_better_error(conn, query.do_nothing(), None)?;
+        }
+    });
     Ok(())
 }
 
@@ -260,6 +344,7 @@ impl TransactionProcessor for CoinTransactionProcessor {
         let mut all_current_coin_balances: HashMap<CurrentCoinBalancePK, CurrentCoinBalance> =
             HashMap::new();
         let mut all_coin_supply = vec![];
+        let mut all_coin_transfers = vec![];
 
         for txn in &transactions {
             let (
@@ -269,6 +354,7 @@ impl TransactionProcessor for CoinTransactionProcessor {
                 current_coin_balances,
                 mut coin_supply,
             ) = CoinActivity::from_transaction(txn, maybe_aptos_coin_info);
+            all_coin_transfers.append(&mut CoinTransfer::from_coin_activities(&coin_activities));
             all_coin_activities.append(&mut coin_activities);
             all_coin_balances.append(&mut coin_balances);
             all_coin_supply.append(&mut coin_supply);
@@ -289,17 +375,55 @@ impl TransactionProcessor for CoinTransactionProcessor {
             (&a.owner_address, &a.coin_type).cmp(&(&b.owner_address, &b.coin_type))
         });
 
-        let tx_result = insert_to_db(
-            &mut conn,
-            self.name(),
-            start_version,
-            end_version,
-            all_coin_activities,
-            all_coin_infos,
-            all_coin_balances,
-            all_current_coin_balances,
-            all_coin_supply,
-        );
+        let tx_result = match &self.parquet_sink {
+            Some(parquet_sink) => {
+                // Later batches look up the Aptos coin info in the database, so coin infos are kept
+                // there too
+                insert_coin_infos(&mut conn, &all_coin_infos)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|_| {
+                        parquet_sink.write(
+                            ParquetBatch::new(start_version, end_version)
+                                .add(
+                                    "coin_activities",
+                                    schema::coin_activities::table,
+                                    &all_coin_activities,
+                                )
+                                .add("coin_infos", schema::coin_infos::table, &all_coin_infos)
+                                .add(
+                                    "coin_balances",
+                                    schema::coin_balances::table,
+                                    &all_coin_balances,
+                                )
+                                .add(
+                                    "current_coin_balances",
+                                    schema::current_coin_balances::table,
+                                    &all_current_coin_balances,
+                                )
+                                .add("coin_supply", schema::coin_supply::table, &all_coin_supply)
+                                .add(
+                                    "coin_transfers",
+                                    schema::coin_transfers::table,
+                                    &all_coin_transfers,
+                                ),
+                        )
+                    })
+            }
+            None => insert_to_db(
+                &mut conn,
+                self.name(),
+                start_version,
+                end_version,
+                all_coin_activities,
+                all_coin_infos,
+                all_coin_balances,
+                all_current_coin_balances,
+                all_coin_supply,
+                all_coin_transfers,
+                self.overwrite_existing_rows,
+            )
+            .map_err(anyhow::Error::from),
+        };
         match tx_result {
             Ok(_) => Ok(ProcessingResult::new(
                 self.name(),
@@ -307,7 +431,7 @@ impl TransactionProcessor for CoinTransactionProcessor {
                 end_version,
             )),
             Err(err) => Err(TransactionProcessingError::TransactionCommitError((
-                anyhow::Error::from(err),
+                err,
                 start_version,
                 end_version,
                 self.name(),
@@ -315,7 +439,7 @@ impl TransactionProcessor for CoinTransactionProcessor {
         }
     }
 
-    fn connection_pool(&self) -> &PgDbPool {
+    fn connection_pool(&self) -> &ArcDbPool {
         &self.connection_pool
     }
 }
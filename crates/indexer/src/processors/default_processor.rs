@@ -3,10 +3,13 @@
 
 use crate::{
     database::{
-        clean_data_for_db, execute_with_better_error, get_chunks, PgDbPool, PgPoolConnection,
+        clean_data_for_db, execute_with_better_error, ArcDbPool, DbConnection, DbPoolConnection,
     },
+    execute_in_chunks,
     indexer::{
-        errors::TransactionProcessingError, processing_result::ProcessingResult,
+        errors::TransactionProcessingError,
+        parquet_sink::{ParquetBatch, ParquetSink},
+        processing_result::ProcessingResult,
         transaction_processor::TransactionProcessor,
     },
     models::{
@@ -24,18 +27,27 @@ use crate::{
 };
 use aptos_api_types::Transaction;
 use async_trait::async_trait;
-use diesel::{result::Error, PgConnection};
-use field_count::FieldCount;
+use diesel::{result::Error, upsert::excluded, ExpressionMethods};
 use std::fmt::Debug;
 
 pub const NAME: &str = "default_processor";
 pub struct DefaultTransactionProcessor {
-    connection_pool: PgDbPool,
+    connection_pool: ArcDbPool,
+    parquet_sink: Option<ParquetSink>,
+    overwrite_existing_rows: bool,
 }
 
 impl DefaultTransactionProcessor {
-    pub fn new(connection_pool: PgDbPool) -> Self {
-        Self { connection_pool }
+    pub fn new(
+        connection_pool: ArcDbPool,
+        parquet_sink: Option<ParquetSink>,
+        overwrite_existing_rows: bool,
+    ) -> Self {
+        Self {
+            connection_pool,
+            parquet_sink,
+            overwrite_existing_rows,
+        }
     }
 }
 
@@ -51,7 +63,7 @@ impl Debug for DefaultTransactionProcessor {
 }
 
 fn insert_to_db(
-    conn: &mut PgPoolConnection,
+    conn: &mut DbPoolConnection,
     name: &'static str,
     start_version: u64,
     end_version: u64,
@@ -60,6 +72,7 @@ fn insert_to_db(
     events: Vec<EventModel>,
     wscs: Vec<WriteSetChangeModel>,
     wsc_details: Vec<WriteSetChangeDetail>,
+    overwrite_existing_rows: bool,
 ) -> Result<(), diesel::result::Error> {
     aptos_logger::trace!(
         name = name,
@@ -67,66 +80,143 @@ fn insert_to_db(
         end_version = end_version,
         "Inserting to db",
     );
-    match conn
-        .build_transaction()
-        .read_write()
-        .run::<_, Error, _>(|pg_conn| {
-            insert_transactions(pg_conn, &txns)?;
-            insert_user_transactions_w_sigs(pg_conn, &txn_details)?;
-            insert_block_metadata_transactions(pg_conn, &txn_details)?;
-            insert_events(pg_conn, &events)?;
-            insert_write_set_changes(pg_conn, &wscs)?;
-            insert_move_modules(pg_conn, &wsc_details)?;
-            insert_move_resources(pg_conn, &wsc_details)?;
-            insert_table_data(pg_conn, &wsc_details)?;
-            Ok(())
-        }) {
+    match conn.transaction::<_, Error, _>(|conn| {
+        insert_transactions(conn, &txns, overwrite_existing_rows)?;
+        insert_user_transactions_w_sigs(conn, &txn_details, overwrite_existing_rows)?;
+        insert_block_metadata_transactions(conn, &txn_details, overwrite_existing_rows)?;
+        insert_events(conn, &events, overwrite_existing_rows)?;
+        insert_write_set_changes(conn, &wscs, overwrite_existing_rows)?;
+        insert_move_modules(conn, &wsc_details, overwrite_existing_rows)?;
+        insert_move_resources(conn, &wsc_details, overwrite_existing_rows)?;
+        insert_table_data(conn, &wsc_details, overwrite_existing_rows)?;
+        Ok(())
+    }) {
         Ok(_) => Ok(()),
-        Err(_) => conn
-            .build_transaction()
-            .read_write()
-            .run::<_, Error, _>(|pg_conn| {
-                let txns = clean_data_for_db(txns, true);
-                let txn_details = clean_data_for_db(txn_details, true);
-                let events = clean_data_for_db(events, true);
-                let wscs = clean_data_for_db(wscs, true);
-                let wsc_details = clean_data_for_db(wsc_details, true);
+        Err(_) => conn.transaction::<_, Error, _>(|conn| {
+            let txns = clean_data_for_db(txns, true);
+            let txn_details = clean_data_for_db(txn_details, true);
+            let events = clean_data_for_db(events, true);
+            let wscs = clean_data_for_db(wscs, true);
+            let wsc_details = clean_data_for_db(wsc_details, true);
+
+            insert_transactions(conn, &txns, overwrite_existing_rows)?;
+            insert_user_transactions_w_sigs(conn, &txn_details, overwrite_existing_rows)?;
+            insert_block_metadata_transactions(conn, &txn_details, overwrite_existing_rows)?;
+            insert_events(conn, &events, overwrite_existing_rows)?;
+            insert_write_set_changes(conn, &wscs, overwrite_existing_rows)?;
+            insert_move_modules(conn, &wsc_details, overwrite_existing_rows)?;
+            insert_move_resources(conn, &wsc_details, overwrite_existing_rows)?;
+            insert_table_data(conn, &wsc_details, overwrite_existing_rows)?;
+            Ok(())
+        }),
+    }
+}
 
-                insert_transactions(pg_conn, &txns)?;
-                insert_user_transactions_w_sigs(pg_conn, &txn_details)?;
-                insert_block_metadata_transactions(pg_conn, &txn_details)?;
-                insert_events(pg_conn, &events)?;
-                insert_write_set_changes(pg_conn, &wscs)?;
-                insert_move_modules(pg_conn, &wsc_details)?;
-                insert_move_resources(pg_conn, &wsc_details)?;
-                insert_table_data(pg_conn, &wsc_details)?;
-                Ok(())
-            }),
+fn write_to_parquet(
+    parquet_sink: &ParquetSink,
+    start_version: u64,
+    end_version: u64,
+    txns: &[TransactionModel],
+    txn_details: &[TransactionDetail],
+    events: &[EventModel],
+    wscs: &[WriteSetChangeModel],
+    wsc_details: &[WriteSetChangeDetail],
+) -> anyhow::Result<()> {
+    let mut user_transactions = vec![];
+    let mut signatures = vec![];
+    let mut block_metadata_transactions = vec![];
+    for detail in txn_details {
+        match detail {
+            TransactionDetail::User(user_txn, sigs) => {
+                user_transactions.push(user_txn);
+                signatures.extend(sigs);
+            }
+            TransactionDetail::BlockMetadata(bmt) => block_metadata_transactions.push(bmt),
+        }
+    }
+    let mut modules = vec![];
+    let mut resources = vec![];
+    let mut table_items = vec![];
+    let mut table_metadatas = vec![];
+    for detail in wsc_details {
+        match detail {
+            WriteSetChangeDetail::Module(module) => modules.push(module),
+            WriteSetChangeDetail::Resource(resource) => resources.push(resource),
+            WriteSetChangeDetail::Table(table_item, table_metadata) => {
+                table_items.push(table_item);
+                table_metadatas.extend(table_metadata);
+            }
+        }
     }
+    parquet_sink.write(
+        ParquetBatch::new(start_version, end_version)
+            .add("transactions", schema::transactions::table, txns)
+            .add(
+                "user_transactions",
+                schema::user_transactions::table,
+                &user_transactions,
+            )
+            .add("signatures", schema::signatures::table, &signatures)
+            .add(
+                "block_metadata_transactions",
+                schema::block_metadata_transactions::table,
+                &block_metadata_transactions,
+            )
+            .add("events", schema::events::table, events)
+            .add("write_set_changes", schema::write_set_changes::table, wscs)
+            .add("move_modules", schema::move_modules::table, &modules)
+            .add("move_resources", schema::move_resources::table, &resources)
+            .add("table_items", schema::table_items::table, &table_items)
+            .add(
+                "table_metadatas",
+                schema::table_metadatas::table,
+                &table_metadatas,
+            ),
+    )
 }
 
 fn insert_transactions(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     txns: &[TransactionModel],
+    overwrite_existing_rows: bool,
 ) -> Result<(), diesel::result::Error> {
     use schema::transactions::dsl::*;
-    let chunks = get_chunks(txns.len(), TransactionModel::field_count());
-    for (start_ind, end_ind) in chunks {
-        execute_with_better_error(
-            conn,
-            diesel::insert_into(schema::transactions::table)
-                .values(&txns[start_ind..end_ind])
-                .on_conflict(version)
-                .do_nothing(),
-            None,
-        )?;
-    }
+    execute_in_chunks!(conn, txns, |conn, items| {
+        let query = diesel::insert_into(schema::transactions::table)
+            .values(items)
+            .on_conflict(version);
+        if overwrite_existing_rows {
+            execute_with_better_error(
+                conn,
+                query.do_update().set((
+                    block_height.eq(excluded(block_height)),
+                    hash.eq(excluded(hash)),
+                    type_.eq(excluded(type_)),
+                    payload.eq(excluded(payload)),
+                    state_change_hash.eq(excluded(state_change_hash)),
+                    event_root_hash.eq(excluded(event_root_hash)),
+                    state_checkpoint_hash.eq(excluded(state_checkpoint_hash)),
+                    gas_used.eq(excluded(gas_used)),
+                    success.eq(excluded(success)),
+                    vm_status.eq(excluded(vm_status)),
+                    accumulator_root_hash.eq(excluded(accumulator_root_hash)),
+                    num_events.eq(excluded(num_events)),
+                    num_write_set_changes.eq(excluded(num_write_set_changes)),
+                    epoch.eq(excluded(epoch)),
+                )),
+                None,
+            )?;
+        } else {
+            execute_with_better_error(conn, query.do_nothing(), None)?;
+        }
+    });
     Ok(())
 }
 
 fn insert_user_transactions_w_sigs(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     txn_details: &[TransactionDetail],
+    overwrite_existing_rows: bool,
 ) -> Result<(), diesel::result::Error> {
     use schema::{signatures::dsl as sig_schema, user_transactions::dsl as ut_schema};
     let mut all_signatures = vec![];
@@ -137,42 +227,67 @@ fn insert_user_transactions_w_sigs(
             all_user_transactions.push(user_txn.clone());
         }
     }
-    let chunks = get_chunks(
-        all_user_transactions.len(),
-        UserTransactionModel::field_count(),
-    );
-    for (start_ind, end_ind) in chunks {
-        execute_with_better_error(
-            conn,
-            diesel::insert_into(schema::user_transactions::table)
-                .values(&all_user_transactions[start_ind..end_ind])
-                .on_conflict(ut_schema::version)
-                .do_nothing(),
-            None,
-        )?;
-    }
-    let chunks = get_chunks(all_signatures.len(), Signature::field_count());
-    for (start_ind, end_ind) in chunks {
-        execute_with_better_error(
-            conn,
-            diesel::insert_into(schema::signatures::table)
-                .values(&all_signatures[start_ind..end_ind])
-                .on_conflict((
-                    sig_schema::transaction_version,
-                    sig_schema::multi_agent_index,
-                    sig_schema::multi_sig_index,
-                    sig_schema::is_sender_primary,
-                ))
-                .do_nothing(),
-            None,
-        )?;
-    }
+    execute_in_chunks!(conn, all_user_transactions, |conn, items| {
+        let query = diesel::insert_into(schema::user_transactions::table)
+            .values(items)
+            .on_conflict(ut_schema::version);
+        if overwrite_existing_rows {
+            execute_with_better_error(
+                conn,
+                query.do_update().set((
+                    ut_schema::block_height.eq(excluded(ut_schema::block_height)),
+                    ut_schema::parent_signature_type.eq(excluded(ut_schema::parent_signature_type)),
+                    ut_schema::sender.eq(excluded(ut_schema::sender)),
+                    ut_schema::sequence_number.eq(excluded(ut_schema::sequence_number)),
+                    ut_schema::max_gas_amount.eq(excluded(ut_schema::max_gas_amount)),
+                    ut_schema::expiration_timestamp_secs
+                        .eq(excluded(ut_schema::expiration_timestamp_secs)),
+                    ut_schema::gas_unit_price.eq(excluded(ut_schema::gas_unit_price)),
+                    ut_schema::timestamp.eq(excluded(ut_schema::timestamp)),
+                    ut_schema::entry_function_id_str.eq(excluded(ut_schema::entry_function_id_str)),
+                    ut_schema::epoch.eq(excluded(ut_schema::epoch)),
+                )),
+                None,
+            )?;
+        } else {
+            execute_with_better_error(conn, query.do_nothing(), None)?;
+        }
+    });
+    execute_in_chunks!(conn, all_signatures, |conn, items| {
+        let query = diesel::insert_into(schema::signatures::table)
+            .values(items)
+            .on_conflict((
+                sig_schema::transaction_version,
+                sig_schema::multi_agent_index,
+                sig_schema::multi_sig_index,
+                sig_schema::is_sender_primary,
+            ));
+        if overwrite_existing_rows {
+            execute_with_better_error(
+                conn,
+                query.do_update().set((
+                    sig_schema::transaction_block_height
+                        .eq(excluded(sig_schema::transaction_block_height)),
+                    sig_schema::signer.eq(excluded(sig_schema::signer)),
+                    sig_schema::type_.eq(excluded(sig_schema::type_)),
+                    sig_schema::public_key.eq(excluded(sig_schema::public_key)),
+                    sig_schema::signature.eq(excluded(sig_schema::signature)),
+                    sig_schema::threshold.eq(excluded(sig_schema::threshold)),
+                    sig_schema::public_key_indices.eq(excluded(sig_schema::public_key_indices)),
+                )),
+                None,
+            )?;
+        } else {
+            execute_with_better_error(conn, query.do_nothing(), None)?;
+        }
+    });
     Ok(())
 }
 
 fn insert_block_metadata_transactions(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     txn_details: &[TransactionDetail],
+    overwrite_existing_rows: bool,
 ) -> Result<(), diesel::result::Error> {
     use schema::block_metadata_transactions::dsl::*;
 
@@ -184,62 +299,93 @@ fn insert_block_metadata_transactions(
         })
         .collect::<Vec<BlockMetadataTransactionModel>>();
 
-    let chunks = get_chunks(bmt.len(), BlockMetadataTransactionModel::field_count());
-    for (start_ind, end_ind) in chunks {
-        execute_with_better_error(
-            conn,
-            diesel::insert_into(schema::block_metadata_transactions::table)
-                .values(&bmt[start_ind..end_ind])
-                .on_conflict(version)
-                .do_nothing(),
-            None,
-        )?;
-    }
+    execute_in_chunks!(conn, bmt, |conn, items| {
+        let query = diesel::insert_into(schema::block_metadata_transactions::table)
+            .values(items)
+            .on_conflict(version);
+        if overwrite_existing_rows {
+            execute_with_better_error(
+                conn,
+                query.do_update().set((
+                    block_height.eq(excluded(block_height)),
+                    id.eq(excluded(id)),
+                    round.eq(excluded(round)),
+                    epoch.eq(excluded(epoch)),
+                    previous_block_votes_bitvec.eq(excluded(previous_block_votes_bitvec)),
+                    proposer.eq(excluded(proposer)),
+                    failed_proposer_indices.eq(excluded(failed_proposer_indices)),
+                    timestamp.eq(excluded(timestamp)),
+                )),
+                None,
+            )?;
+        } else {
+            execute_with_better_error(conn, query.do_nothing(), None)?;
+        }
+    });
     Ok(())
 }
 
-fn insert_events(conn: &mut PgConnection, ev: &[EventModel]) -> Result<(), diesel::result::Error> {
+fn insert_events(
+    conn: &mut DbConnection,
+    ev: &[EventModel],
+    overwrite_existing_rows: bool,
+) -> Result<(), diesel::result::Error> {
     use schema::events::dsl::*;
 
-    let chunks = get_chunks(ev.len(), EventModel::field_count());
-
-    for (start_ind, end_ind) in chunks {
-        execute_with_better_error(
-            conn,
-            diesel::insert_into(schema::events::table)
-                .values(&ev[start_ind..end_ind])
-                .on_conflict((account_address, creation_number, sequence_number))
-                .do_nothing(),
-            None,
-        )?;
-    }
+    execute_in_chunks!(conn, ev, |conn, items| {
+        let query = diesel::insert_into(schema::events::table)
+            .values(items)
+            .on_conflict((account_address, creation_number, sequence_number));
+        if overwrite_existing_rows {
+            execute_with_better_error(
+                conn,
+                query.do_update().set((
+                    transaction_version.eq(excluded(transaction_version)),
+                    transaction_block_height.eq(excluded(transaction_block_height)),
+                    type_.eq(excluded(type_)),
+                    data.eq(excluded(data)),
+                )),
+                None,
+            )?;
+        } else {
+            execute_with_better_error(conn, query.do_nothing(), None)?;
+        }
+    });
     Ok(())
 }
 
 fn insert_write_set_changes(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     wscs: &[WriteSetChangeModel],
+    overwrite_existing_rows: bool,
 ) -> Result<(), diesel::result::Error> {
     use schema::write_set_changes::dsl::*;
 
-    let chunks = get_chunks(wscs.len(), WriteSetChangeModel::field_count());
-
-    for (start_ind, end_ind) in chunks {
-        execute_with_better_error(
-            conn,
-            diesel::insert_into(schema::write_set_changes::table)
-                .values(&wscs[start_ind..end_ind])
-                .on_conflict((transaction_version, index))
-                .do_nothing(),
-            None,
-        )?;
-    }
+    execute_in_chunks!(conn, wscs, |conn, items| {
+        let query = diesel::insert_into(schema::write_set_changes::table)
+            .values(items)
+            .on_conflict((transaction_version, index));
+        if overwrite_existing_rows {
+            execute_with_better_error(
+                conn,
+                query.do_update().set((
+                    hash.eq(excluded(hash)),
+                    type_.eq(excluded(type_)),
+                    address.eq(excluded(address)),
+                )),
+                None,
+            )?;
+        } else {
+            execute_with_better_error(conn, query.do_nothing(), None)?;
+        }
+    });
     Ok(())
 }
 
 fn insert_move_modules(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     wsc_details: &[WriteSetChangeDetail],
+    overwrite_existing_rows: bool,
 ) -> Result<(), diesel::result::Error> {
     use schema::move_modules::dsl::*;
 
@@ -251,23 +397,36 @@ fn insert_move_modules(
         })
         .collect::<Vec<MoveModule>>();
 
-    let chunks = get_chunks(modules.len(), MoveModule::field_count());
-    for (start_ind, end_ind) in chunks {
-        execute_with_better_error(
-            conn,
-            diesel::insert_into(schema::move_modules::table)
-                .values(&modules[start_ind..end_ind])
-                .on_conflict((transaction_version, write_set_change_index))
-                .do_nothing(),
-            None,
-        )?;
-    }
+    execute_in_chunks!(conn, modules, |conn, items| {
+        let query = diesel::insert_into(schema::move_modules::table)
+            .values(items)
+            .on_conflict((transaction_version, write_set_change_index));
+        if overwrite_existing_rows {
+            execute_with_better_error(
+                conn,
+                query.do_update().set((
+                    transaction_block_height.eq(excluded(transaction_block_height)),
+                    name.eq(excluded(name)),
+                    address.eq(excluded(address)),
+                    bytecode.eq(excluded(bytecode)),
+                    exposed_functions.eq(excluded(exposed_functions)),
+                    friends.eq(excluded(friends)),
+                    structs.eq(excluded(structs)),
+                    is_deleted.eq(excluded(is_deleted)),
+                )),
+                None,
+            )?;
+        } else {
+            execute_with_better_error(conn, query.do_nothing(), None)?;
+        }
+    });
     Ok(())
 }
 
 fn insert_move_resources(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     wsc_details: &[WriteSetChangeDetail],
+    overwrite_existing_rows: bool,
 ) -> Result<(), diesel::result::Error> {
     use schema::move_resources::dsl::*;
 
@@ -279,24 +438,37 @@ fn insert_move_resources(
         })
         .collect::<Vec<MoveResource>>();
 
-    let chunks = get_chunks(resources.len(), MoveResource::field_count());
-    for (start_ind, end_ind) in chunks {
-        execute_with_better_error(
-            conn,
-            diesel::insert_into(schema::move_resources::table)
-                .values(&resources[start_ind..end_ind])
-                .on_conflict((transaction_version, write_set_change_index))
-                .do_nothing(),
-            None,
-        )?;
-    }
+    execute_in_chunks!(conn, resources, |conn, items| {
+        let query = diesel::insert_into(schema::move_resources::table)
+            .values(items)
+            .on_conflict((transaction_version, write_set_change_index));
+        if overwrite_existing_rows {
+            execute_with_better_error(
+                conn,
+                query.do_update().set((
+                    transaction_block_height.eq(excluded(transaction_block_height)),
+                    name.eq(excluded(name)),
+                    type_.eq(excluded(type_)),
+                    address.eq(excluded(address)),
+                    module.eq(excluded(module)),
+                    generic_type_params.eq(excluded(generic_type_params)),
+                    data.eq(excluded(data)),
+                    is_deleted.eq(excluded(is_deleted)),
+                )),
+                None,
+            )?;
+        } else {
+            execute_with_better_error(conn, query.do_nothing(), None)?;
+        }
+    });
     Ok(())
 }
 
 /// This will insert all table data within each transaction within a block
 fn insert_table_data(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     wsc_details: &[WriteSetChangeDetail],
+    overwrite_existing_rows: bool,
 ) -> Result<(), diesel::result::Error> {
     use schema::{table_items::dsl as ti, table_metadatas::dsl as tm};
 
@@ -318,28 +490,44 @@ fn insert_table_data(
     metadata_nonnull.dedup_by(|a, b| a.handle == b.handle);
     metadata_nonnull.sort_by(|a, b| a.handle.cmp(&b.handle));
 
-    let chunks = get_chunks(items.len(), TableItem::field_count());
-    for (start_ind, end_ind) in chunks {
-        execute_with_better_error(
-            conn,
-            diesel::insert_into(schema::table_items::table)
-                .values(&items[start_ind..end_ind])
-                .on_conflict((ti::transaction_version, ti::write_set_change_index))
-                .do_nothing(),
-            None,
-        )?;
-    }
-    let chunks = get_chunks(metadata_nonnull.len(), TableMetadata::field_count());
-    for (start_ind, end_ind) in chunks {
-        execute_with_better_error(
-            conn,
-            diesel::insert_into(schema::table_metadatas::table)
-                .values(&metadata_nonnull[start_ind..end_ind])
-                .on_conflict(tm::handle)
-                .do_nothing(),
-            None,
-        )?;
-    }
+    execute_in_chunks!(conn, items, |conn, items| {
+        let query = diesel::insert_into(schema::table_items::table)
+            .values(items)
+            .on_conflict((ti::transaction_version, ti::write_set_change_index));
+        if overwrite_existing_rows {
+            execute_with_better_error(
+                conn,
+                query.do_update().set((
+                    ti::transaction_block_height.eq(excluded(ti::transaction_block_height)),
+                    ti::key.eq(excluded(ti::key)),
+                    ti::table_handle.eq(excluded(ti::table_handle)),
+                    ti::decoded_key.eq(excluded(ti::decoded_key)),
+                    ti::decoded_value.eq(excluded(ti::decoded_value)),
+                    ti::is_deleted.eq(excluded(ti::is_deleted)),
+                )),
+                None,
+            )?;
+        } else {
+            execute_with_better_error(conn, query.do_nothing(), None)?;
+        }
+    });
+    execute_in_chunks!(conn, metadata_nonnull, |conn, items| {
+        let query = diesel::insert_into(schema::table_metadatas::table)
+            .values(items)
+            .on_conflict(tm::handle);
+        if overwrite_existing_rows {
+            execute_with_better_error(
+                conn,
+                query.do_update().set((
+                    tm::key_type.eq(excluded(tm::key_type)),
+                    tm::value_type.eq(excluded(tm::value_type)),
+                )),
+                None,
+            )?;
+        } else {
+            execute_with_better_error(conn, query.do_nothing(), None)?;
+        }
+    });
     Ok(())
 }
 
@@ -358,18 +546,31 @@ impl TransactionProcessor for DefaultTransactionProcessor {
         let (txns, user_txns, bm_txns, events, write_set_changes) =
             TransactionModel::from_transactions(&transactions);
 
-        let mut conn = self.get_conn();
-        let tx_result = insert_to_db(
-            &mut conn,
-            self.name(),
-            start_version,
-            end_version,
-            txns,
-            user_txns,
-            bm_txns,
-            events,
-            write_set_changes,
-        );
+        let tx_result = match &self.parquet_sink {
+            Some(parquet_sink) => write_to_parquet(
+                parquet_sink,
+                start_version,
+                end_version,
+                &txns,
+                &user_txns,
+                &bm_txns,
+                &events,
+                &write_set_changes,
+            ),
+            None => insert_to_db(
+                &mut self.get_conn(),
+                self.name(),
+                start_version,
+                end_version,
+                txns,
+                user_txns,
+                bm_txns,
+                events,
+                write_set_changes,
+                self.overwrite_existing_rows,
+            )
+            .map_err(anyhow::Error::from),
+        };
         match tx_result {
             Ok(_) => Ok(ProcessingResult::new(
                 self.name(),
@@ -377,7 +578,7 @@ impl TransactionProcessor for DefaultTransactionProcessor {
                 end_version,
             )),
             Err(err) => Err(TransactionProcessingError::TransactionCommitError((
-                anyhow::Error::from(err),
+                err,
                 start_version,
                 end_version,
                 self.name(),
@@ -385,7 +586,7 @@ impl TransactionProcessor for DefaultTransactionProcessor {
         }
     }
 
-    fn connection_pool(&self) -> &PgDbPool {
+    fn connection_pool(&self) -> &ArcDbPool {
         &self.connection_pool
     }
 }
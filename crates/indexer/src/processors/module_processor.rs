@@ -0,0 +1,353 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    database::{
+        clean_data_for_db, execute_with_better_error, ArcDbPool, DbConnection, DbPoolConnection,
+    },
+    execute_in_chunks,
+    indexer::{
+        errors::TransactionProcessingError,
+        parquet_sink::{ParquetBatch, ParquetSink},
+        processing_result::ProcessingResult,
+        transaction_processor::TransactionProcessor,
+    },
+    models::module_models::{
+        entry_function_calls::EntryFunctionCall, move_module_abis::MoveModuleAbi,
+    },
+    schema,
+};
+use aptos_api_types::Transaction as APITransaction;
+use async_trait::async_trait;
+use diesel::{result::Error, upsert::excluded, ExpressionMethods};
+use std::fmt::Debug;
+
+pub const NAME: &str = "module_processor";
+pub struct ModuleTransactionProcessor {
+    connection_pool: ArcDbPool,
+    parquet_sink: Option<ParquetSink>,
+    overwrite_existing_rows: bool,
+}
+
+impl ModuleTransactionProcessor {
+    pub fn new(
+        connection_pool: ArcDbPool,
+        parquet_sink: Option<ParquetSink>,
+        overwrite_existing_rows: bool,
+    ) -> Self {
+        Self {
+            connection_pool,
+            parquet_sink,
+            overwrite_existing_rows,
+        }
+    }
+}
+
+impl Debug for ModuleTransactionProcessor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = &self.connection_pool.state();
+        write!(
+            f,
+            "ModuleTransactionProcessor {{ connections: {:?}  idle_connections: {:?} }}",
+            state.connections, state.idle_connections
+        )
+    }
+}
+
+fn insert_to_db_impl(
+    conn: &mut DbConnection,
+    entry_function_calls: &[EntryFunctionCall],
+    move_module_abis: &[MoveModuleAbi],
+    overwrite_existing_rows: bool,
+) -> Result<(), diesel::result::Error> {
+    insert_entry_function_calls(conn, entry_function_calls, overwrite_existing_rows)?;
+    insert_move_module_abis(conn, move_module_abis, overwrite_existing_rows)?;
+    Ok(())
+}
+
+fn insert_to_db(
+    conn: &mut DbPoolConnection,
+    name: &'static str,
+    start_version: u64,
+    end_version: u64,
+    entry_function_calls: Vec<EntryFunctionCall>,
+    move_module_abis: Vec<MoveModuleAbi>,
+    overwrite_existing_rows: bool,
+) -> Result<(), diesel::result::Error> {
+    aptos_logger::trace!(
+        name = name,
+        start_version = start_version,
+        end_version = end_version,
+        "Inserting to db",
+    );
+    match conn.transaction::<_, Error, _>(|conn| {
+        insert_to_db_impl(
+            conn,
+            &entry_function_calls,
+            &move_module_abis,
+            overwrite_existing_rows,
+        )
+    }) {
+        Ok(_) => Ok(()),
+        Err(_) => conn.transaction::<_, Error, _>(|conn| {
+            let entry_function_calls = clean_data_for_db(entry_function_calls, true);
+            let move_module_abis = clean_data_for_db(move_module_abis, true);
+
+            insert_to_db_impl(
+                conn,
+                &entry_function_calls,
+                &move_module_abis,
+                overwrite_existing_rows,
+            )
+        }),
+    }
+}
+
+fn insert_entry_function_calls(
+    conn: &mut DbConnection,
+    item_to_insert: &[EntryFunctionCall],
+    overwrite_existing_rows: bool,
+) -> Result<(), diesel::result::Error> {
+    use schema::entry_function_calls::dsl::*;
+
+    execute_in_chunks!(conn, item_to_insert, |conn, items| {
+        let query = diesel::insert_into(schema::entry_function_calls::table)
+            .values(items)
+            .on_conflict(transaction_version);
+        if overwrite_existing_rows {
+            execute_with_better_error(
+                conn,
+                query.do_update().set((
+                    sender.eq(excluded(sender)),
+                    module_address.eq(excluded(module_address)),
+                    module_name.eq(excluded(module_name)),
+                    function_name.eq(excluded(function_name)),
+                    type_arguments.eq(excluded(type_arguments)),
+                    is_transaction_success.eq(excluded(is_transaction_success)),
+                    gas_used.eq(excluded(gas_used)),
+                    gas_unit_price.eq(excluded(gas_unit_price)),
+                    block_height.eq(excluded(block_height)),
+                    transaction_timestamp.eq(excluded(transaction_timestamp)),
+                )),
+                None,
+            )?;
+        } else {
+            execute_with_better_error(conn, query.do_nothing(), None)?;
+        }
+    });
+    Ok(())
+}
+
+fn insert_move_module_abis(
+    conn: &mut DbConnection,
+    item_to_insert: &[MoveModuleAbi],
+    overwrite_existing_rows: bool,
+) -> Result<(), diesel::result::Error> {
+    use schema::move_module_abis::dsl::*;
+
+    execute_in_chunks!(conn, item_to_insert, |conn, items| {
+        let query = diesel::insert_into(schema::move_module_abis::table)
+            .values(items)
+            .on_conflict((transaction_version, write_set_change_index));
+        if overwrite_existing_rows {
+            execute_with_better_error(
+                conn,
+                query.do_update().set((
+                    address.eq(excluded(address)),
+                    name.eq(excluded(name)),
+                    exposed_functions.eq(excluded(exposed_functions)),
+                    friends.eq(excluded(friends)),
+                    structs.eq(excluded(structs)),
+                    is_deleted.eq(excluded(is_deleted)),
+                    block_height.eq(excluded(block_height)),
+                    transaction_timestamp.eq(excluded(transaction_timestamp)),
+                )),
+                None,
+            )?;
+        } else {
+            execute_with_better_error(conn, query.do_nothing(), None)?;
+        }
+    });
+    Ok(())
+}
+
+fn parse_transactions(
+    transactions: &[APITransaction],
+) -> anyhow::Result<(Vec<EntryFunctionCall>, Vec<MoveModuleAbi>)> {
+    let mut all_entry_function_calls = vec![];
+    let mut all_move_module_abis = vec![];
+
+    for txn in transactions {
+        if let Some(entry_function_call) = EntryFunctionCall::from_transaction(txn)? {
+            all_entry_function_calls.push(entry_function_call);
+        }
+        all_move_module_abis.append(&mut MoveModuleAbi::from_transaction(txn)?);
+    }
+    Ok((all_entry_function_calls, all_move_module_abis))
+}
+
+#[async_trait]
+impl TransactionProcessor for ModuleTransactionProcessor {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    async fn process_transactions(
+        &self,
+        transactions: Vec<APITransaction>,
+        start_version: u64,
+        end_version: u64,
+    ) -> Result<ProcessingResult, TransactionProcessingError> {
+        let (all_entry_function_calls, all_move_module_abis) =
+            match parse_transactions(&transactions) {
+                Ok(parsed) => parsed,
+                Err(err) => {
+                    return Err(TransactionProcessingError::TransactionCommitError((
+                        err,
+                        start_version,
+                        end_version,
+                        self.name(),
+                    )))
+                }
+            };
+
+        let tx_result = match &self.parquet_sink {
+            Some(parquet_sink) => parquet_sink.write(
+                ParquetBatch::new(start_version, end_version)
+                    .add(
+                        "entry_function_calls",
+                        schema::entry_function_calls::table,
+                        &all_entry_function_calls,
+                    )
+                    .add(
+                        "move_module_abis",
+                        schema::move_module_abis::table,
+                        &all_move_module_abis,
+                    ),
+            ),
+            None => insert_to_db(
+                &mut self.get_conn(),
+                self.name(),
+                start_version,
+                end_version,
+                all_entry_function_calls,
+                all_move_module_abis,
+                self.overwrite_existing_rows,
+            )
+            .map_err(anyhow::Error::from),
+        };
+        match tx_result {
+            Ok(_) => Ok(ProcessingResult::new(
+                self.name(),
+                start_version,
+                end_version,
+            )),
+            Err(err) => Err(TransactionProcessingError::TransactionCommitError((
+                err,
+                start_version,
+                end_version,
+                self.name(),
+            ))),
+        }
+    }
+
+    fn connection_pool(&self) -> &ArcDbPool {
+        &self.connection_pool
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        database::{DbConnectionManager, DbPool},
+        indexer::parquet_sink::MANIFEST_FILE,
+    };
+    use aptos_temppath::TempPath;
+    use field_count::FieldCount;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use std::sync::Arc;
+
+    fn entry_function_transaction(version: u64) -> APITransaction {
+        serde_json::from_value(serde_json::json!({
+            "type": "user_transaction",
+            "version": version.to_string(),
+            "hash": format!("0x{}", "1".repeat(64)),
+            "state_change_hash": format!("0x{}", "2".repeat(64)),
+            "event_root_hash": format!("0x{}", "3".repeat(64)),
+            "state_checkpoint_hash": null,
+            "gas_used": "25",
+            "success": true,
+            "vm_status": "Executed successfully",
+            "accumulator_root_hash": format!("0x{}", "4".repeat(64)),
+            "changes": [],
+            "block_height": (version / 2).to_string(),
+            "sender": "0xa",
+            "sequence_number": version.to_string(),
+            "max_gas_amount": "2000",
+            "gas_unit_price": "100",
+            "expiration_timestamp_secs": "1000",
+            "payload": {
+                "type": "entry_function_payload",
+                "function": "0x1::coin::transfer",
+                "type_arguments": ["0x1::aptos_coin::AptosCoin"],
+                "arguments": ["0xb", "100"],
+            },
+            "events": [],
+            "timestamp": "1000000",
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_parquet_sink_batches() {
+        let directory = TempPath::new();
+        // The pool never connects, the rows are only written to Parquet files
+        let conn_pool =
+            Arc::new(DbPool::builder().build_unchecked(
+                DbConnectionManager::new("postgresql://localhost/unused").unwrap(),
+            ));
+        let processor = ModuleTransactionProcessor::new(
+            conn_pool,
+            Some(ParquetSink::new(directory.path(), 4).unwrap()),
+            false,
+        );
+        let process = |start_version: u64, end_version: u64| {
+            processor.process_transactions(
+                (start_version..=end_version)
+                    .map(entry_function_transaction)
+                    .collect(),
+                start_version,
+                end_version,
+            )
+        };
+
+        process(0, 1).await.unwrap();
+        assert!(!directory.path().join(MANIFEST_FILE).exists());
+
+        process(2, 3).await.unwrap();
+        let manifest = processor.parquet_sink.as_ref().unwrap().manifest().unwrap();
+        assert_eq!(manifest.ranges.len(), 1);
+        let range = &manifest.ranges[0];
+        assert_eq!((range.start_version, range.end_version), (0, 3));
+        // No module was published
+        assert_eq!(
+            range.files.keys().collect::<Vec<_>>(),
+            vec!["entry_function_calls"]
+        );
+        let file = &range.files["entry_function_calls"];
+        assert_eq!(file.num_rows, 4);
+
+        let reader = SerializedFileReader::new(
+            std::fs::File::open(directory.path().join(&file.path)).unwrap(),
+        )
+        .unwrap();
+        let metadata = reader.metadata().file_metadata();
+        assert_eq!(metadata.num_rows(), 4);
+        // Every column of the table but `inserted_at`
+        assert_eq!(
+            metadata.schema_descr().num_columns(),
+            EntryFunctionCall::field_count()
+        );
+    }
+}
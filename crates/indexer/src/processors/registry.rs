@@ -0,0 +1,277 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    database::{ArcDbPool, DbBackend},
+    indexer::{parquet_sink::ParquetSink, transaction_processor::TransactionProcessor},
+    processors::{
+        coin_processor::{CoinTransactionProcessor, NAME as COIN_PROCESSOR_NAME},
+        default_processor::{DefaultTransactionProcessor, NAME as DEFAULT_PROCESSOR_NAME},
+        module_processor::{ModuleTransactionProcessor, NAME as MODULE_PROCESSOR_NAME},
+        stake_processor::{StakeTransactionProcessor, NAME as STAKE_PROCESSOR_NAME},
+        token_processor::{TokenTransactionProcessor, NAME as TOKEN_PROCESSOR_NAME},
+    },
+};
+use anyhow::{format_err, Result};
+use aptos_config::config::IndexerConfig;
+use diesel_migrations::EmbeddedMigrations;
+use std::collections::BTreeMap;
+
+/// Creates a processor writing to the given connection pool
+pub type ProcessorFactory =
+    Box<dyn Fn(ArcDbPool, &IndexerConfig) -> Box<dyn TransactionProcessor> + Send + Sync>;
+
+/// The processors an indexer can run, keyed by the name they are given in
+/// `IndexerConfig::processor`, along with the migrations creating their tables.
+///
+/// Downstream crates can register their own processors and run them with
+/// [`crate::runtime::run_forever_with_registry`] instead of forking this crate:
+/// ```ignore
+/// let mut registry = ProcessorRegistry::default();
+/// registry
+///     .register("my_processor", |conn_pool, _config| {
+///         Box::new(MyTransactionProcessor::new(conn_pool))
+///     })
+///     .add_migrations(MY_MIGRATIONS)
+///     .add_sqlite_migrations(MY_SQLITE_MIGRATIONS);
+/// ```
+/// The migrations of all crates are tracked in the same diesel migrations table, so their
+/// versions must not collide with the ones in this crate.
+pub struct ProcessorRegistry {
+    factories: BTreeMap<String, ProcessorFactory>,
+    migrations: Vec<EmbeddedMigrations>,
+    sqlite_migrations: Vec<EmbeddedMigrations>,
+}
+
+impl ProcessorRegistry {
+    /// A registry without any processors, not even the ones of this crate
+    pub fn empty() -> Self {
+        Self {
+            factories: BTreeMap::new(),
+            migrations: vec![],
+            sqlite_migrations: vec![],
+        }
+    }
+
+    /// Registers a processor under `name`, replacing any processor already registered under it
+    pub fn register<F>(&mut self, name: impl Into<String>, factory: F) -> &mut Self
+    where
+        F: Fn(ArcDbPool, &IndexerConfig) -> Box<dyn TransactionProcessor> + Send + Sync + 'static,
+    {
+        self.factories.insert(name.into(), Box::new(factory));
+        self
+    }
+
+    /// Adds migrations to run on startup after the ones of this crate, unless
+    /// `IndexerConfig::skip_migrations` is set
+    pub fn add_migrations(&mut self, migrations: EmbeddedMigrations) -> &mut Self {
+        self.migrations.push(migrations);
+        self
+    }
+
+    /// Same as [`Self::add_migrations`], for indexers writing to SQLite
+    pub fn add_sqlite_migrations(&mut self, migrations: EmbeddedMigrations) -> &mut Self {
+        self.sqlite_migrations.push(migrations);
+        self
+    }
+
+    /// Names of the registered processors, in alphabetical order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.factories.keys().map(String::as_str)
+    }
+
+    /// Creates the processor registered under `name`
+    pub fn create(
+        &self,
+        name: &str,
+        conn_pool: ArcDbPool,
+        config: &IndexerConfig,
+    ) -> Result<Box<dyn TransactionProcessor>> {
+        let factory = self.factories.get(name).ok_or_else(|| {
+            format_err!(
+                "Processor unsupported {}, expected one of: {}",
+                name,
+                self.names().collect::<Vec<_>>().join(", ")
+            )
+        })?;
+        Ok(factory(conn_pool, config))
+    }
+
+    /// Takes the migrations added for `backend`, in the order they were added
+    pub fn into_migrations(self, backend: DbBackend) -> Vec<EmbeddedMigrations> {
+        match backend {
+            DbBackend::Postgres => self.migrations,
+            DbBackend::Sqlite => self.sqlite_migrations,
+        }
+    }
+}
+
+impl Default for ProcessorRegistry {
+    /// A registry with the processors of this crate
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry
+            .register(DEFAULT_PROCESSOR_NAME, |conn_pool, config| {
+                Box::new(DefaultTransactionProcessor::new(
+                    conn_pool,
+                    ParquetSink::from_config(config, DEFAULT_PROCESSOR_NAME),
+                    config.overwrite_existing_rows.unwrap_or_default(),
+                ))
+            })
+            .register(TOKEN_PROCESSOR_NAME, |conn_pool, config| {
+                Box::new(TokenTransactionProcessor::new(
+                    conn_pool,
+                    config.ans_contract_address.clone(),
+                    ParquetSink::from_config(config, TOKEN_PROCESSOR_NAME),
+                    config.overwrite_existing_rows.unwrap_or_default(),
+                ))
+            })
+            .register(COIN_PROCESSOR_NAME, |conn_pool, config| {
+                Box::new(CoinTransactionProcessor::new(
+                    conn_pool,
+                    ParquetSink::from_config(config, COIN_PROCESSOR_NAME),
+                    config.overwrite_existing_rows.unwrap_or_default(),
+                ))
+            })
+            .register(STAKE_PROCESSOR_NAME, |conn_pool, config| {
+                Box::new(StakeTransactionProcessor::new(
+                    conn_pool,
+                    ParquetSink::from_config(config, STAKE_PROCESSOR_NAME),
+                    config.overwrite_existing_rows.unwrap_or_default(),
+                ))
+            })
+            .register(MODULE_PROCESSOR_NAME, |conn_pool, config| {
+                Box::new(ModuleTransactionProcessor::new(
+                    conn_pool,
+                    ParquetSink::from_config(config, MODULE_PROCESSOR_NAME),
+                    config.overwrite_existing_rows.unwrap_or_default(),
+                ))
+            });
+        registry
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::{
+        database::{DbConnectionManager, DbPool},
+        indexer::{errors::TransactionProcessingError, processing_result::ProcessingResult},
+    };
+    use aptos_api_types::Transaction;
+    use std::sync::Arc;
+
+    /// Does nothing with the transactions but report them processed, under any name
+    #[derive(Debug)]
+    pub struct NamedProcessor {
+        pub name: &'static str,
+        pub connection_pool: ArcDbPool,
+    }
+
+    #[async_trait::async_trait]
+    impl TransactionProcessor for NamedProcessor {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        async fn process_transactions(
+            &self,
+            _transactions: Vec<Transaction>,
+            start_version: u64,
+            end_version: u64,
+        ) -> Result<ProcessingResult, TransactionProcessingError> {
+            Ok(ProcessingResult::new(self.name, start_version, end_version))
+        }
+
+        fn connection_pool(&self) -> &ArcDbPool {
+            &self.connection_pool
+        }
+    }
+
+    /// A pool that never connects, the processors are only created
+    fn conn_pool() -> ArcDbPool {
+        Arc::new(
+            DbPool::builder().build_unchecked(
+                DbConnectionManager::new("postgresql://localhost/unused").unwrap(),
+            ),
+        )
+    }
+
+    #[test]
+    fn test_default_processors() {
+        let registry = ProcessorRegistry::default();
+        assert_eq!(
+            registry.names().collect::<Vec<_>>(),
+            vec![
+                COIN_PROCESSOR_NAME,
+                DEFAULT_PROCESSOR_NAME,
+                MODULE_PROCESSOR_NAME,
+                STAKE_PROCESSOR_NAME,
+                TOKEN_PROCESSOR_NAME,
+            ]
+        );
+        for name in registry.names() {
+            let processor = registry
+                .create(name, conn_pool(), &IndexerConfig::default())
+                .unwrap();
+            assert_eq!(processor.name(), name);
+        }
+    }
+
+    #[test]
+    fn test_register_and_create() {
+        let mut registry = ProcessorRegistry::empty();
+        assert_eq!(registry.names().count(), 0);
+        assert!(registry
+            .create(
+                DEFAULT_PROCESSOR_NAME,
+                conn_pool(),
+                &IndexerConfig::default()
+            )
+            .is_err());
+
+        registry
+            .register("my_processor", |connection_pool, _config| {
+                Box::new(NamedProcessor {
+                    name: "my_processor",
+                    connection_pool,
+                })
+            })
+            .add_migrations(crate::indexer::tailer::MIGRATIONS)
+            .add_sqlite_migrations(crate::indexer::tailer::SQLITE_MIGRATIONS);
+        let processor = registry
+            .create("my_processor", conn_pool(), &IndexerConfig::default())
+            .unwrap();
+        assert_eq!(processor.name(), "my_processor");
+
+        let err = registry
+            .create("other_processor", conn_pool(), &IndexerConfig::default())
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Processor unsupported other_processor, expected one of: my_processor"
+        );
+        assert_eq!(registry.into_migrations(DbBackend::Sqlite).len(), 1);
+    }
+
+    #[test]
+    fn test_register_replaces() {
+        let mut registry = ProcessorRegistry::default();
+        registry.register(DEFAULT_PROCESSOR_NAME, |connection_pool, _config| {
+            Box::new(NamedProcessor {
+                name: "replaced_processor",
+                connection_pool,
+            })
+        });
+        assert_eq!(registry.names().count(), 5);
+        let processor = registry
+            .create(
+                DEFAULT_PROCESSOR_NAME,
+                conn_pool(),
+                &IndexerConfig::default(),
+            )
+            .unwrap();
+        assert_eq!(processor.name(), "replaced_processor");
+    }
+}
@@ -3,10 +3,13 @@
 
 use crate::{
     database::{
-        clean_data_for_db, execute_with_better_error, get_chunks, PgDbPool, PgPoolConnection,
+        clean_data_for_db, execute_with_better_error, ArcDbPool, DbConnection, DbPoolConnection,
     },
+    execute_in_chunks,
     indexer::{
-        errors::TransactionProcessingError, processing_result::ProcessingResult,
+        errors::TransactionProcessingError,
+        parquet_sink::{ParquetBatch, ParquetSink},
+        processing_result::ProcessingResult,
         transaction_processor::TransactionProcessor,
     },
     models::stake_models::{
@@ -17,18 +20,27 @@ use crate::{
 };
 use aptos_api_types::Transaction as APITransaction;
 use async_trait::async_trait;
-use diesel::{pg::upsert::excluded, result::Error, ExpressionMethods, PgConnection};
-use field_count::FieldCount;
+use diesel::{result::Error, upsert::excluded, ExpressionMethods};
 use std::{collections::HashMap, fmt::Debug};
 
 pub const NAME: &str = "stake_processor";
 pub struct StakeTransactionProcessor {
-    connection_pool: PgDbPool,
+    connection_pool: ArcDbPool,
+    parquet_sink: Option<ParquetSink>,
+    overwrite_existing_rows: bool,
 }
 
 impl StakeTransactionProcessor {
-    pub fn new(connection_pool: PgDbPool) -> Self {
-        Self { connection_pool }
+    pub fn new(
+        connection_pool: ArcDbPool,
+        parquet_sink: Option<ParquetSink>,
+        overwrite_existing_rows: bool,
+    ) -> Self {
+        Self {
+            connection_pool,
+            parquet_sink,
+            overwrite_existing_rows,
+        }
     }
 }
 
@@ -44,22 +56,24 @@ impl Debug for StakeTransactionProcessor {
 }
 
 fn insert_to_db_impl(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     current_stake_pool_voters: &[CurrentStakingPoolVoter],
     proposal_votes: &[ProposalVote],
+    overwrite_existing_rows: bool,
 ) -> Result<(), diesel::result::Error> {
     insert_current_stake_pool_voter(conn, current_stake_pool_voters)?;
-    insert_proposal_votes(conn, proposal_votes)?;
+    insert_proposal_votes(conn, proposal_votes, overwrite_existing_rows)?;
     Ok(())
 }
 
 fn insert_to_db(
-    conn: &mut PgPoolConnection,
+    conn: &mut DbPoolConnection,
     name: &'static str,
     start_version: u64,
     end_version: u64,
     current_stake_pool_voters: Vec<CurrentStakingPoolVoter>,
     proposal_votes: Vec<ProposalVote>,
+    overwrite_existing_rows: bool,
 ) -> Result<(), diesel::result::Error> {
     aptos_logger::trace!(
         name = name,
@@ -67,37 +81,40 @@ fn insert_to_db(
         end_version = end_version,
         "Inserting to db",
     );
-    match conn
-        .build_transaction()
-        .read_write()
-        .run::<_, Error, _>(|pg_conn| {
-            insert_to_db_impl(pg_conn, &current_stake_pool_voters, &proposal_votes)
-        }) {
+    match conn.transaction::<_, Error, _>(|conn| {
+        insert_to_db_impl(
+            conn,
+            &current_stake_pool_voters,
+            &proposal_votes,
+            overwrite_existing_rows,
+        )
+    }) {
         Ok(_) => Ok(()),
-        Err(_) => conn
-            .build_transaction()
-            .read_write()
-            .run::<_, Error, _>(|pg_conn| {
-                let current_stake_pool_voters = clean_data_for_db(current_stake_pool_voters, true);
-                let proposal_votes = clean_data_for_db(proposal_votes, true);
-
-                insert_to_db_impl(pg_conn, &current_stake_pool_voters, &proposal_votes)
-            }),
+        Err(_) => conn.transaction::<_, Error, _>(|conn| {
+            let current_stake_pool_voters = clean_data_for_db(current_stake_pool_voters, true);
+            let proposal_votes = clean_data_for_db(proposal_votes, true);
+
+            insert_to_db_impl(
+                conn,
+                &current_stake_pool_voters,
+                &proposal_votes,
+                overwrite_existing_rows,
+            )
+        }),
     }
 }
 
 fn insert_current_stake_pool_voter(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     item_to_insert: &[CurrentStakingPoolVoter],
 ) -> Result<(), diesel::result::Error> {
     use schema::current_staking_pool_voter::dsl::*;
 
-    let chunks = get_chunks(item_to_insert.len(), CurrentStakingPoolVoter::field_count());
-    for (start_ind, end_ind) in chunks {
+    execute_in_chunks!(conn, item_to_insert, |conn, items| {
         execute_with_better_error(
             conn,
             diesel::insert_into(schema::current_staking_pool_voter::table)
-                .values(&item_to_insert[start_ind..end_ind])
+                .values(items)
                 .on_conflict(staking_pool_address)
                 .do_update()
                 .set((
@@ -110,27 +127,36 @@ fn insert_current_stake_pool_voter(
                 " WHERE current_staking_pool_voter.last_transaction_version <= EXCLUDED.last_transaction_version ",
             ),
         )?;
-    }
+    });
     Ok(())
 }
 
 fn insert_proposal_votes(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     item_to_insert: &[ProposalVote],
+    overwrite_existing_rows: bool,
 ) -> Result<(), diesel::result::Error> {
     use schema::proposal_votes::dsl::*;
 
-    let chunks = get_chunks(item_to_insert.len(), ProposalVote::field_count());
-    for (start_ind, end_ind) in chunks {
-        execute_with_better_error(
-            conn,
-            diesel::insert_into(schema::proposal_votes::table)
-                .values(&item_to_insert[start_ind..end_ind])
-                .on_conflict((transaction_version, proposal_id, voter_address))
-                .do_nothing(),
-            None,
-        )?;
-    }
+    execute_in_chunks!(conn, item_to_insert, |conn, items| {
+        let query = diesel::insert_into(schema::proposal_votes::table)
+            .values(items)
+            .on_conflict((transaction_version, proposal_id, voter_address));
+        if overwrite_existing_rows {
+            execute_with_better_error(
+                conn,
+                query.do_update().set((
+                    staking_pool_address.eq(excluded(staking_pool_address)),
+                    num_votes.eq(excluded(num_votes)),
+                    should_pass.eq(excluded(should_pass)),
+                    transaction_timestamp.eq(excluded(transaction_timestamp)),
+                )),
+                None,
+            )?;
+        } else {
+            execute_with_better_error(conn, query.do_nothing(), None)?;
+        }
+    });
     Ok(())
 }
 
@@ -163,15 +189,31 @@ impl TransactionProcessor for StakeTransactionProcessor {
         all_current_stake_pool_voters
             .sort_by(|a, b| a.staking_pool_address.cmp(&b.staking_pool_address));
 
-        let mut conn = self.get_conn();
-        let tx_result = insert_to_db(
-            &mut conn,
-            self.name(),
-            start_version,
-            end_version,
-            all_current_stake_pool_voters,
-            all_proposal_votes,
-        );
+        let tx_result = match &self.parquet_sink {
+            Some(parquet_sink) => parquet_sink.write(
+                ParquetBatch::new(start_version, end_version)
+                    .add(
+                        "current_staking_pool_voter",
+                        schema::current_staking_pool_voter::table,
+                        &all_current_stake_pool_voters,
+                    )
+                    .add(
+                        "proposal_votes",
+                        schema::proposal_votes::table,
+                        &all_proposal_votes,
+                    ),
+            ),
+            None => insert_to_db(
+                &mut self.get_conn(),
+                self.name(),
+                start_version,
+                end_version,
+                all_current_stake_pool_voters,
+                all_proposal_votes,
+                self.overwrite_existing_rows,
+            )
+            .map_err(anyhow::Error::from),
+        };
         match tx_result {
             Ok(_) => Ok(ProcessingResult::new(
                 self.name(),
@@ -179,7 +221,7 @@ impl TransactionProcessor for StakeTransactionProcessor {
                 end_version,
             )),
             Err(err) => Err(TransactionProcessingError::TransactionCommitError((
-                anyhow::Error::from(err),
+                err,
                 start_version,
                 end_version,
                 self.name(),
@@ -187,7 +229,7 @@ impl TransactionProcessor for StakeTransactionProcessor {
         }
     }
 
-    fn connection_pool(&self) -> &PgDbPool {
+    fn connection_pool(&self) -> &ArcDbPool {
         &self.connection_pool
     }
 }
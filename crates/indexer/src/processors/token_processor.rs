@@ -3,10 +3,13 @@
 
 use crate::{
     database::{
-        clean_data_for_db, execute_with_better_error, get_chunks, PgDbPool, PgPoolConnection,
+        clean_data_for_db, execute_with_better_error, ArcDbPool, DbConnection, DbPoolConnection,
     },
+    execute_in_chunks,
     indexer::{
-        errors::TransactionProcessingError, processing_result::ProcessingResult,
+        errors::TransactionProcessingError,
+        parquet_sink::{ParquetBatch, ParquetSink},
+        processing_result::ProcessingResult,
         transaction_processor::TransactionProcessor,
     },
     models::token_models::{
@@ -25,18 +28,24 @@ use crate::{
 };
 use aptos_api_types::Transaction;
 use async_trait::async_trait;
-use diesel::{pg::upsert::excluded, result::Error, ExpressionMethods, PgConnection};
-use field_count::FieldCount;
+use diesel::{result::Error, upsert::excluded, ExpressionMethods};
 use std::{collections::HashMap, fmt::Debug};
 
 pub const NAME: &str = "token_processor";
 pub struct TokenTransactionProcessor {
-    connection_pool: PgDbPool,
+    connection_pool: ArcDbPool,
     ans_contract_address: Option<String>,
+    parquet_sink: Option<ParquetSink>,
+    overwrite_existing_rows: bool,
 }
 
 impl TokenTransactionProcessor {
-    pub fn new(connection_pool: PgDbPool, ans_contract_address: Option<String>) -> Self {
+    pub fn new(
+        connection_pool: ArcDbPool,
+        ans_contract_address: Option<String>,
+        parquet_sink: Option<ParquetSink>,
+        overwrite_existing_rows: bool,
+    ) -> Self {
         aptos_logger::info!(
             ans_contract_address = ans_contract_address,
             "init TokenTransactionProcessor"
@@ -44,6 +53,8 @@ impl TokenTransactionProcessor {
         Self {
             connection_pool,
             ans_contract_address,
+            parquet_sink,
+            overwrite_existing_rows,
         }
     }
 }
@@ -60,7 +71,7 @@ impl Debug for TokenTransactionProcessor {
 }
 
 fn insert_to_db_impl(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     basic_token_transaction_lists: (&[Token], &[TokenOwnership], &[TokenData], &[CollectionData]),
     basic_token_current_lists: (
         &[CurrentTokenOwnership],
@@ -70,25 +81,26 @@ fn insert_to_db_impl(
     token_activities: &[TokenActivity],
     current_token_claims: &[CurrentTokenPendingClaim],
     current_ans_lookups: &[CurrentAnsLookup],
+    overwrite_existing_rows: bool,
 ) -> Result<(), diesel::result::Error> {
     let (tokens, token_ownerships, token_datas, collection_datas) = basic_token_transaction_lists;
     let (current_token_ownerships, current_token_datas, current_collection_datas) =
         basic_token_current_lists;
     insert_tokens(conn, tokens)?;
     insert_token_datas(conn, token_datas)?;
-    insert_token_ownerships(conn, token_ownerships)?;
-    insert_collection_datas(conn, collection_datas)?;
+    insert_token_ownerships(conn, token_ownerships, overwrite_existing_rows)?;
+    insert_collection_datas(conn, collection_datas, overwrite_existing_rows)?;
     insert_current_token_ownerships(conn, current_token_ownerships)?;
     insert_current_token_datas(conn, current_token_datas)?;
     insert_current_collection_datas(conn, current_collection_datas)?;
-    insert_token_activities(conn, token_activities)?;
+    insert_token_activities(conn, token_activities, overwrite_existing_rows)?;
     insert_current_token_claims(conn, current_token_claims)?;
     insert_current_ans_lookups(conn, current_ans_lookups)?;
     Ok(())
 }
 
 fn insert_to_db(
-    conn: &mut PgPoolConnection,
+    conn: &mut DbPoolConnection,
     name: &'static str,
     start_version: u64,
     end_version: u64,
@@ -106,6 +118,7 @@ fn insert_to_db(
     token_activities: Vec<TokenActivity>,
     current_token_claims: Vec<CurrentTokenPendingClaim>,
     current_ans_lookups: Vec<CurrentAnsLookup>,
+    overwrite_existing_rows: bool,
 ) -> Result<(), diesel::result::Error> {
     aptos_logger::trace!(
         name = name,
@@ -116,12 +129,36 @@ fn insert_to_db(
     let (tokens, token_ownerships, token_datas, collection_datas) = basic_token_transaction_lists;
     let (current_token_ownerships, current_token_datas, current_collection_datas) =
         basic_token_current_lists;
-    match conn
-        .build_transaction()
-        .read_write()
-        .run::<_, Error, _>(|pg_conn| {
+    match conn.transaction::<_, Error, _>(|conn| {
+        insert_to_db_impl(
+            conn,
+            (&tokens, &token_ownerships, &token_datas, &collection_datas),
+            (
+                &current_token_ownerships,
+                &current_token_datas,
+                &current_collection_datas,
+            ),
+            &token_activities,
+            &current_token_claims,
+            &current_ans_lookups,
+            overwrite_existing_rows,
+        )
+    }) {
+        Ok(_) => Ok(()),
+        Err(_) => conn.transaction::<_, Error, _>(|conn| {
+            let tokens = clean_data_for_db(tokens, true);
+            let token_datas = clean_data_for_db(token_datas, true);
+            let token_ownerships = clean_data_for_db(token_ownerships, true);
+            let collection_datas = clean_data_for_db(collection_datas, true);
+            let current_token_ownerships = clean_data_for_db(current_token_ownerships, true);
+            let current_token_datas = clean_data_for_db(current_token_datas, true);
+            let current_collection_datas = clean_data_for_db(current_collection_datas, true);
+            let token_activities = clean_data_for_db(token_activities, true);
+            let current_token_claims = clean_data_for_db(current_token_claims, true);
+            let current_ans_lookups = clean_data_for_db(current_ans_lookups, true);
+
             insert_to_db_impl(
-                pg_conn,
+                conn,
                 (&tokens, &token_ownerships, &token_datas, &collection_datas),
                 (
                     &current_token_ownerships,
@@ -131,52 +168,23 @@ fn insert_to_db(
                 &token_activities,
                 &current_token_claims,
                 &current_ans_lookups,
+                overwrite_existing_rows,
             )
-        }) {
-        Ok(_) => Ok(()),
-        Err(_) => conn
-            .build_transaction()
-            .read_write()
-            .run::<_, Error, _>(|pg_conn| {
-                let tokens = clean_data_for_db(tokens, true);
-                let token_datas = clean_data_for_db(token_datas, true);
-                let token_ownerships = clean_data_for_db(token_ownerships, true);
-                let collection_datas = clean_data_for_db(collection_datas, true);
-                let current_token_ownerships = clean_data_for_db(current_token_ownerships, true);
-                let current_token_datas = clean_data_for_db(current_token_datas, true);
-                let current_collection_datas = clean_data_for_db(current_collection_datas, true);
-                let token_activities = clean_data_for_db(token_activities, true);
-                let current_token_claims = clean_data_for_db(current_token_claims, true);
-                let current_ans_lookups = clean_data_for_db(current_ans_lookups, true);
-
-                insert_to_db_impl(
-                    pg_conn,
-                    (&tokens, &token_ownerships, &token_datas, &collection_datas),
-                    (
-                        &current_token_ownerships,
-                        &current_token_datas,
-                        &current_collection_datas,
-                    ),
-                    &token_activities,
-                    &current_token_claims,
-                    &current_ans_lookups,
-                )
-            }),
+        }),
     }
 }
 
 fn insert_tokens(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     tokens_to_insert: &[Token],
 ) -> Result<(), diesel::result::Error> {
     use schema::tokens::dsl::*;
 
-    let chunks = get_chunks(tokens_to_insert.len(), Token::field_count());
-    for (start_ind, end_ind) in chunks {
+    execute_in_chunks!(conn, tokens_to_insert, |conn, items| {
         execute_with_better_error(
             conn,
             diesel::insert_into(schema::tokens::table)
-                .values(&tokens_to_insert[start_ind..end_ind])
+                .values(items)
                 .on_conflict((token_data_id_hash, property_version, transaction_version))
                 .do_update()
                 .set((
@@ -185,50 +193,59 @@ fn insert_tokens(
                 )),
             None,
         )?;
-    }
+    });
     Ok(())
 }
 
 fn insert_token_ownerships(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     token_ownerships_to_insert: &[TokenOwnership],
+    overwrite_existing_rows: bool,
 ) -> Result<(), diesel::result::Error> {
     use schema::token_ownerships::dsl::*;
 
-    let chunks = get_chunks(
-        token_ownerships_to_insert.len(),
-        TokenOwnership::field_count(),
-    );
-    for (start_ind, end_ind) in chunks {
-        execute_with_better_error(
-            conn,
-            diesel::insert_into(schema::token_ownerships::table)
-                .values(&token_ownerships_to_insert[start_ind..end_ind])
-                .on_conflict((
-                    token_data_id_hash,
-                    property_version,
-                    transaction_version,
-                    table_handle,
-                ))
-                .do_nothing(),
-            None,
-        )?;
-    }
+    execute_in_chunks!(conn, token_ownerships_to_insert, |conn, items| {
+        let query = diesel::insert_into(schema::token_ownerships::table)
+            .values(items)
+            .on_conflict((
+                token_data_id_hash,
+                property_version,
+                transaction_version,
+                table_handle,
+            ));
+        if overwrite_existing_rows {
+            execute_with_better_error(
+                conn,
+                query.do_update().set((
+                    creator_address.eq(excluded(creator_address)),
+                    collection_name.eq(excluded(collection_name)),
+                    name.eq(excluded(name)),
+                    owner_address.eq(excluded(owner_address)),
+                    amount.eq(excluded(amount)),
+                    table_type.eq(excluded(table_type)),
+                    collection_data_id_hash.eq(excluded(collection_data_id_hash)),
+                    transaction_timestamp.eq(excluded(transaction_timestamp)),
+                )),
+                None,
+            )?;
+        } else {
+            execute_with_better_error(conn, query.do_nothing(), None)?;
+        }
+    });
     Ok(())
 }
 
 fn insert_token_datas(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     token_datas_to_insert: &[TokenData],
 ) -> Result<(), diesel::result::Error> {
     use schema::token_datas::dsl::*;
 
-    let chunks = get_chunks(token_datas_to_insert.len(), TokenData::field_count());
-    for (start_ind, end_ind) in chunks {
+    execute_in_chunks!(conn, token_datas_to_insert, |conn, items| {
         execute_with_better_error(
             conn,
             diesel::insert_into(schema::token_datas::table)
-                .values(&token_datas_to_insert[start_ind..end_ind])
+                .values(items)
                 .on_conflict((token_data_id_hash, transaction_version))
                 .do_update()
                 .set((
@@ -237,46 +254,57 @@ fn insert_token_datas(
                 )),
             None,
         )?;
-    }
+    });
     Ok(())
 }
 
 fn insert_collection_datas(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     collection_datas_to_insert: &[CollectionData],
+    overwrite_existing_rows: bool,
 ) -> Result<(), diesel::result::Error> {
     use schema::collection_datas::dsl::*;
 
-    let chunks = get_chunks(
-        collection_datas_to_insert.len(),
-        CollectionData::field_count(),
-    );
-    for (start_ind, end_ind) in chunks {
-        execute_with_better_error(
-            conn,
-            diesel::insert_into(schema::collection_datas::table)
-                .values(&collection_datas_to_insert[start_ind..end_ind])
-                .on_conflict((collection_data_id_hash, transaction_version))
-                .do_nothing(),
-            None,
-        )?;
-    }
+    execute_in_chunks!(conn, collection_datas_to_insert, |conn, items| {
+        let query = diesel::insert_into(schema::collection_datas::table)
+            .values(items)
+            .on_conflict((collection_data_id_hash, transaction_version));
+        if overwrite_existing_rows {
+            execute_with_better_error(
+                conn,
+                query.do_update().set((
+                    creator_address.eq(excluded(creator_address)),
+                    collection_name.eq(excluded(collection_name)),
+                    description.eq(excluded(description)),
+                    metadata_uri.eq(excluded(metadata_uri)),
+                    supply.eq(excluded(supply)),
+                    maximum.eq(excluded(maximum)),
+                    maximum_mutable.eq(excluded(maximum_mutable)),
+                    uri_mutable.eq(excluded(uri_mutable)),
+                    description_mutable.eq(excluded(description_mutable)),
+                    table_handle.eq(excluded(table_handle)),
+                    transaction_timestamp.eq(excluded(transaction_timestamp)),
+                )),
+                None,
+            )?;
+        } else {
+            execute_with_better_error(conn, query.do_nothing(), None)?;
+        }
+    });
     Ok(())
 }
 
 fn insert_current_token_ownerships(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     items_to_insert: &[CurrentTokenOwnership],
 ) -> Result<(), diesel::result::Error> {
     use schema::current_token_ownerships::dsl::*;
 
-    let chunks = get_chunks(items_to_insert.len(), CurrentTokenOwnership::field_count());
-
-    for (start_ind, end_ind) in chunks {
+    execute_in_chunks!(conn, items_to_insert, |conn, items| {
         execute_with_better_error(
             conn,
             diesel::insert_into(schema::current_token_ownerships::table)
-                .values(&items_to_insert[start_ind..end_ind])
+                .values(items)
                 .on_conflict((token_data_id_hash, property_version, owner_address))
                 .do_update()
                 .set((
@@ -292,23 +320,21 @@ fn insert_current_token_ownerships(
                 )),
             Some(" WHERE current_token_ownerships.last_transaction_version <= excluded.last_transaction_version "),
         )?;
-    }
+    });
     Ok(())
 }
 
 fn insert_current_token_datas(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     items_to_insert: &[CurrentTokenData],
 ) -> Result<(), diesel::result::Error> {
     use schema::current_token_datas::dsl::*;
 
-    let chunks = get_chunks(items_to_insert.len(), CurrentTokenData::field_count());
-
-    for (start_ind, end_ind) in chunks {
+    execute_in_chunks!(conn, items_to_insert, |conn, items| {
         execute_with_better_error(
             conn,
             diesel::insert_into(schema::current_token_datas::table)
-                .values(&items_to_insert[start_ind..end_ind])
+                .values(items)
                 .on_conflict(token_data_id_hash)
                 .do_update()
                 .set((
@@ -335,23 +361,21 @@ fn insert_current_token_datas(
                 )),
             Some(" WHERE current_token_datas.last_transaction_version <= excluded.last_transaction_version "),
         )?;
-    }
+    });
     Ok(())
 }
 
 fn insert_current_collection_datas(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     items_to_insert: &[CurrentCollectionData],
 ) -> Result<(), diesel::result::Error> {
     use schema::current_collection_datas::dsl::*;
 
-    let chunks = get_chunks(items_to_insert.len(), CurrentCollectionData::field_count());
-
-    for (start_ind, end_ind) in chunks {
+    execute_in_chunks!(conn, items_to_insert, |conn, items| {
         execute_with_better_error(
             conn,
             diesel::insert_into(schema::current_collection_datas::table)
-                .values(&items_to_insert[start_ind..end_ind])
+                .values(items)
                 .on_conflict(collection_data_id_hash)
                 .do_update()
                 .set((
@@ -370,51 +394,63 @@ fn insert_current_collection_datas(
                 )),
             Some(" WHERE current_collection_datas.last_transaction_version <= excluded.last_transaction_version "),
         )?;
-    }
+    });
     Ok(())
 }
 
 fn insert_token_activities(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     items_to_insert: &[TokenActivity],
+    overwrite_existing_rows: bool,
 ) -> Result<(), diesel::result::Error> {
     use schema::token_activities::dsl::*;
 
-    let chunks = get_chunks(items_to_insert.len(), TokenActivity::field_count());
-
-    for (start_ind, end_ind) in chunks {
-        execute_with_better_error(
-            conn,
-            diesel::insert_into(schema::token_activities::table)
-                .values(&items_to_insert[start_ind..end_ind])
-                .on_conflict((
-                    transaction_version,
-                    event_account_address,
-                    event_creation_number,
-                    event_sequence_number,
-                ))
-                .do_nothing(),
-            None,
-        )?;
-    }
+    execute_in_chunks!(conn, items_to_insert, |conn, items| {
+        let query = diesel::insert_into(schema::token_activities::table)
+            .values(items)
+            .on_conflict((
+                transaction_version,
+                event_account_address,
+                event_creation_number,
+                event_sequence_number,
+            ));
+        if overwrite_existing_rows {
+            execute_with_better_error(
+                conn,
+                query.do_update().set((
+                    token_data_id_hash.eq(excluded(token_data_id_hash)),
+                    property_version.eq(excluded(property_version)),
+                    creator_address.eq(excluded(creator_address)),
+                    collection_name.eq(excluded(collection_name)),
+                    name.eq(excluded(name)),
+                    transfer_type.eq(excluded(transfer_type)),
+                    from_address.eq(excluded(from_address)),
+                    to_address.eq(excluded(to_address)),
+                    token_amount.eq(excluded(token_amount)),
+                    coin_type.eq(excluded(coin_type)),
+                    coin_amount.eq(excluded(coin_amount)),
+                    collection_data_id_hash.eq(excluded(collection_data_id_hash)),
+                    transaction_timestamp.eq(excluded(transaction_timestamp)),
+                )),
+                None,
+            )?;
+        } else {
+            execute_with_better_error(conn, query.do_nothing(), None)?;
+        }
+    });
     Ok(())
 }
 fn insert_current_token_claims(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     items_to_insert: &[CurrentTokenPendingClaim],
 ) -> Result<(), diesel::result::Error> {
     use schema::current_token_pending_claims::dsl::*;
 
-    let chunks = get_chunks(
-        items_to_insert.len(),
-        CurrentTokenPendingClaim::field_count(),
-    );
-
-    for (start_ind, end_ind) in chunks {
+    execute_in_chunks!(conn, items_to_insert, |conn, items| {
         execute_with_better_error(
             conn,
             diesel::insert_into(schema::current_token_pending_claims::table)
-                .values(&items_to_insert[start_ind..end_ind])
+                .values(items)
                 .on_conflict((
                     token_data_id_hash, property_version, from_address, to_address
                 ))
@@ -431,23 +467,21 @@ fn insert_current_token_claims(
                 )),
             Some(" WHERE current_token_pending_claims.last_transaction_version <= excluded.last_transaction_version "),
         )?;
-    }
+    });
     Ok(())
 }
 
 fn insert_current_ans_lookups(
-    conn: &mut PgConnection,
+    conn: &mut DbConnection,
     items_to_insert: &[CurrentAnsLookup],
 ) -> Result<(), diesel::result::Error> {
     use schema::current_ans_lookup::dsl::*;
 
-    let chunks = get_chunks(items_to_insert.len(), CurrentAnsLookup::field_count());
-
-    for (start_ind, end_ind) in chunks {
+    execute_in_chunks!(conn, items_to_insert, |conn, items| {
         execute_with_better_error(
             conn,
             diesel::insert_into(schema::current_ans_lookup::table)
-                .values(&items_to_insert[start_ind..end_ind])
+                .values(items)
                 .on_conflict((domain, subdomain))
                 .do_update()
                 .set((
@@ -459,7 +493,7 @@ fn insert_current_ans_lookups(
                 )),
                 Some(" WHERE current_ans_lookup.last_transaction_version <= excluded.last_transaction_version "),
             )?;
-    }
+    });
     Ok(())
 }
 
@@ -583,26 +617,83 @@ impl TransactionProcessor for TokenTransactionProcessor {
         all_current_ans_lookups
             .sort_by(|a, b| a.domain.cmp(&b.domain).then(a.subdomain.cmp(&b.subdomain)));
 
-        let tx_result = insert_to_db(
-            &mut conn,
-            self.name(),
-            start_version,
-            end_version,
-            (
-                all_tokens,
-                all_token_ownerships,
-                all_token_datas,
-                all_collection_datas,
-            ),
-            (
-                all_current_token_ownerships,
-                all_current_token_datas,
-                all_current_collection_datas,
-            ),
-            all_token_activities,
-            all_current_token_claims,
-            all_current_ans_lookups,
-        );
+        let tx_result = match &self.parquet_sink {
+            Some(parquet_sink) => {
+                // Later batches look up the creators of collections in the database, so their current data is
+                // kept there too
+                insert_current_collection_datas(&mut conn, &all_current_collection_datas)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|_| {
+                        parquet_sink.write(
+                            ParquetBatch::new(start_version, end_version)
+                                .add("tokens", schema::tokens::table, &all_tokens)
+                                .add(
+                                    "token_ownerships",
+                                    schema::token_ownerships::table,
+                                    &all_token_ownerships,
+                                )
+                                .add("token_datas", schema::token_datas::table, &all_token_datas)
+                                .add(
+                                    "collection_datas",
+                                    schema::collection_datas::table,
+                                    &all_collection_datas,
+                                )
+                                .add(
+                                    "current_token_ownerships",
+                                    schema::current_token_ownerships::table,
+                                    &all_current_token_ownerships,
+                                )
+                                .add(
+                                    "current_token_datas",
+                                    schema::current_token_datas::table,
+                                    &all_current_token_datas,
+                                )
+                                .add(
+                                    "current_collection_datas",
+                                    schema::current_collection_datas::table,
+                                    &all_current_collection_datas,
+                                )
+                                .add(
+                                    "token_activities",
+                                    schema::token_activities::table,
+                                    &all_token_activities,
+                                )
+                                .add(
+                                    "current_token_pending_claims",
+                                    schema::current_token_pending_claims::table,
+                                    &all_current_token_claims,
+                                )
+                                .add(
+                                    "current_ans_lookup",
+                                    schema::current_ans_lookup::table,
+                                    &all_current_ans_lookups,
+                                ),
+                        )
+                    })
+            }
+            None => insert_to_db(
+                &mut conn,
+                self.name(),
+                start_version,
+                end_version,
+                (
+                    all_tokens,
+                    all_token_ownerships,
+                    all_token_datas,
+                    all_collection_datas,
+                ),
+                (
+                    all_current_token_ownerships,
+                    all_current_token_datas,
+                    all_current_collection_datas,
+                ),
+                all_token_activities,
+                all_current_token_claims,
+                all_current_ans_lookups,
+                self.overwrite_existing_rows,
+            )
+            .map_err(anyhow::Error::from),
+        };
         match tx_result {
             Ok(_) => Ok(ProcessingResult::new(
                 self.name(),
@@ -610,7 +701,7 @@ impl TransactionProcessor for TokenTransactionProcessor {
                 end_version,
             )),
             Err(err) => Err(TransactionProcessingError::TransactionCommitError((
-                anyhow::Error::from(err),
+                err,
                 start_version,
                 end_version,
                 self.name(),
@@ -618,7 +709,7 @@ impl TransactionProcessor for TokenTransactionProcessor {
         }
     }
 
-    fn connection_pool(&self) -> &PgDbPool {
+    fn connection_pool(&self) -> &ArcDbPool {
         &self.connection_pool
     }
 }
@@ -2,16 +2,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    database::new_db_pool,
+    database::{new_db_pool, new_db_pool_with_max_size, DbBackend},
+    graphql,
     indexer::{
-        fetcher::TransactionFetcherOptions, processing_result::ProcessingResult, tailer::Tailer,
+        errors::TransactionProcessingError,
+        fetcher::{TransactionFetcher, TransactionFetcherOptions, TransactionFetcherTrait},
+        processing_result::ProcessingResult,
+        tailer::Tailer,
         transaction_processor::TransactionProcessor,
     },
-    processors::{
-        coin_processor::CoinTransactionProcessor, default_processor::DefaultTransactionProcessor,
-        stake_processor::StakeTransactionProcessor, token_processor::TokenTransactionProcessor,
-        Processor,
-    },
+    processors::ProcessorRegistry,
 };
 
 use aptos_api::context::Context;
@@ -22,7 +22,10 @@ use aptos_types::chain_id::ChainId;
 use std::collections::VecDeque;
 use std::sync::Arc;
 use storage_interface::DbReader;
-use tokio::runtime::{Builder, Runtime};
+use tokio::{
+    runtime::{Builder, Runtime},
+    sync::Mutex,
+};
 
 pub struct MovingAverage {
     window_millis: u64,
@@ -81,10 +84,32 @@ pub fn bootstrap(
     chain_id: ChainId,
     db: Arc<dyn DbReader>,
     mp_sender: MempoolClientSender,
+) -> Option<anyhow::Result<Runtime>> {
+    bootstrap_with_registry(
+        config,
+        chain_id,
+        db,
+        mp_sender,
+        ProcessorRegistry::default(),
+    )
+}
+
+/// Same as [`bootstrap`], with the processors to choose from given by `registry`
+pub fn bootstrap_with_registry(
+    config: &NodeConfig,
+    chain_id: ChainId,
+    db: Arc<dyn DbReader>,
+    mp_sender: MempoolClientSender,
+    registry: ProcessorRegistry,
 ) -> Option<anyhow::Result<Runtime>> {
     if !config.indexer.enabled {
         return None;
     }
+    if let Some(postgres_uri) = &config.indexer.postgres_uri {
+        if let Err(err) = DbBackend::from_url(postgres_uri) {
+            return Some(Err(err));
+        }
+    }
 
     let runtime = Builder::new_multi_thread()
         .thread_name("indexer")
@@ -98,26 +123,72 @@ pub fn bootstrap(
 
     runtime.spawn(async move {
         let context = Arc::new(Context::new(chain_id, db, mp_sender, node_config));
-        run_forever(indexer_config, context).await;
+        run_forever_with_registry(indexer_config, context, registry).await;
     });
 
     Some(Ok(runtime))
 }
 
 pub async fn run_forever(config: IndexerConfig, context: Arc<Context>) {
-    // All of these options should be filled already with defaults
-    let processor_name = config.processor.clone().unwrap();
-    let check_chain_id = config.check_chain_id.unwrap();
-    let skip_migrations = config.skip_migrations.unwrap();
+    run_forever_with_registry(config, context, ProcessorRegistry::default()).await
+}
+
+/// Runs the processors of `config` found in `registry`, which lets binaries embedding the indexer
+/// run their own processors and migrations
+pub async fn run_forever_with_registry(
+    config: IndexerConfig,
+    context: Arc<Context>,
+    registry: ProcessorRegistry,
+) {
+    // Filled already with defaults
     let fetch_tasks = config.fetch_tasks.unwrap();
-    let processor_tasks = config.processor_tasks.unwrap();
-    let emit_every = config.emit_every.unwrap();
     let batch_size = config.batch_size.unwrap();
-    let lookback_versions = config.gap_lookback_versions.unwrap() as i64;
 
+    let options =
+        TransactionFetcherOptions::new(None, None, Some(batch_size), None, fetch_tasks as usize);
+    let resolver = Arc::new(context.move_resolver().unwrap());
+    let transaction_fetcher = TransactionFetcher::new(context, resolver, 0, options);
+
+    run_forever_with_fetcher(config, Arc::new(Mutex::new(transaction_fetcher)), registry).await
+}
+
+fn processing_result_or_panic(
+    processor_name: &str,
+    res: Result<ProcessingResult, TransactionProcessingError>,
+) -> ProcessingResult {
+    match res {
+        Ok(res) => res,
+        Err(tpe) => {
+            let (err, start_version, end_version, _) = tpe.inner();
+            error!(
+                processor_name = processor_name,
+                start_version = start_version,
+                end_version = end_version,
+                error =? err,
+                "Error processing batch!"
+            );
+            panic!(
+                "Error in '{}' while processing batch: {:?}",
+                processor_name, err
+            );
+        }
+    }
+}
+
+/// Connects to the database, creates the processors of `config` and runs the migrations
+fn create_tailer(
+    config: &IndexerConfig,
+    transaction_fetcher: Arc<Mutex<dyn TransactionFetcherTrait>>,
+    registry: ProcessorRegistry,
+) -> Tailer {
+    // All of these options should be filled already with defaults
+    let processor_names = config.processor.clone().unwrap();
+    let skip_migrations = config.skip_migrations.unwrap();
+
+    let processor_name = processor_names.join(",");
     info!(processor_name = processor_name, "Starting indexer...");
 
-    let db_uri = &config.postgres_uri.unwrap();
+    let db_uri = &config.postgres_uri.clone().unwrap();
     info!(
         processor_name = processor_name,
         "Creating connection pool..."
@@ -130,28 +201,55 @@ pub async fn run_forever(config: IndexerConfig, context: Arc<Context>) {
 
     info!(processor_name = processor_name, "Instantiating tailer... ");
 
-    let processor_enum = Processor::from_string(&processor_name);
-    let processor: Arc<dyn TransactionProcessor> = match processor_enum {
-        Processor::DefaultProcessor => {
-            Arc::new(DefaultTransactionProcessor::new(conn_pool.clone()))
-        }
-        Processor::TokenProcessor => Arc::new(TokenTransactionProcessor::new(
-            conn_pool.clone(),
-            config.ans_contract_address,
-        )),
-        Processor::CoinProcessor => Arc::new(CoinTransactionProcessor::new(conn_pool.clone())),
-        Processor::StakeProcessor => Arc::new(StakeTransactionProcessor::new(conn_pool.clone())),
-    };
-
-    let options =
-        TransactionFetcherOptions::new(None, None, Some(batch_size), None, fetch_tasks as usize);
+    let processors: Vec<Arc<dyn TransactionProcessor>> = processor_names
+        .iter()
+        .map(|processor_name| {
+            registry
+                .create(processor_name, conn_pool.clone(), config)
+                .map(Arc::from)
+                .unwrap_or_else(|e| panic!("Failed to create processor: {:?}", e))
+        })
+        .collect();
 
-    let tailer = Tailer::new(context, conn_pool.clone(), processor, options)
-        .expect("Failed to instantiate tailer");
+    let tailer = Tailer::new_with_fetcher(conn_pool, processors, transaction_fetcher);
 
     if !skip_migrations {
         info!(processor_name = processor_name, "Running migrations...");
         tailer.run_migrations();
+        for migrations in registry.into_migrations(tailer.backend()) {
+            tailer.run_migrations_from(migrations);
+        }
+    }
+    tailer
+}
+
+/// Runs the processors of `config` found in `registry` on the transactions of
+/// `transaction_fetcher`, which doesn't have to read them from a local node
+pub async fn run_forever_with_fetcher(
+    config: IndexerConfig,
+    transaction_fetcher: Arc<Mutex<dyn TransactionFetcherTrait>>,
+    registry: ProcessorRegistry,
+) {
+    // All of these options should be filled already with defaults
+    let check_chain_id = config.check_chain_id.unwrap();
+    let processor_tasks = config.processor_tasks.unwrap();
+    let emit_every = config.emit_every.unwrap();
+    let lookback_versions = config.gap_lookback_versions.unwrap() as i64;
+
+    let tailer = create_tailer(&config, transaction_fetcher, registry);
+    let processor_name = tailer.processor_names();
+
+    if let Some(address) = config.graphql_address {
+        let connection_pool = new_db_pool_with_max_size(
+            config.postgres_uri.as_ref().unwrap(),
+            config.graphql_pool_size.unwrap(),
+        )
+        .expect("Failed to create the GraphQL connection pool");
+        tokio::spawn(async move {
+            if let Err(err) = graphql::serve(connection_pool, address).await {
+                error!(error =? err, "GraphQL server stopped");
+            }
+        });
     }
 
     info!(
@@ -159,17 +257,7 @@ pub async fn run_forever(config: IndexerConfig, context: Arc<Context>) {
         lookback_versions = lookback_versions,
         "Fetching starting version from db..."
     );
-    // For now this is not being used but we'd want to track it anyway
-    let starting_version_from_db_short = tailer
-        .get_start_version(&processor_name)
-        .unwrap_or_else(|e| panic!("Failed to get starting version: {:?}", e))
-        .unwrap_or_else(|| {
-            info!(
-                processor_name = processor_name,
-                "No starting version from db so starting from version 0"
-            );
-            0
-        }) as u64;
+    let starting_version_from_db_short = get_start_version(&tailer);
     let start_version = match config.starting_version {
         None => starting_version_from_db_short,
         Some(version) => version,
@@ -207,61 +295,204 @@ pub async fn run_forever(config: IndexerConfig, context: Arc<Context>) {
     let mut ma = MovingAverage::new(10_000);
 
     loop {
-        let mut tasks = vec![];
-        for _ in 0..processor_tasks {
-            let other_tailer = tailer.clone();
-            let task = tokio::spawn(async move { other_tailer.process_next_batch().await });
-            tasks.push(task);
+        let (num_res, batch_start_version, batch_end_version) =
+            process_next_batches(&tailer, processor_tasks).await;
+
+        ma.tick_now(num_res);
+
+        versions_processed += num_res;
+        if emit_every != 0 {
+            let new_base: u64 = versions_processed / (emit_every as u64);
+            if base != new_base {
+                base = new_base;
+                info!(
+                    processor_name = processor_name,
+                    batch_start_version = batch_start_version,
+                    batch_end_version = batch_end_version,
+                    versions_processed = versions_processed,
+                    tps = (ma.avg() * 1000.0) as u64,
+                    "Processed batch version"
+                );
+            }
         }
-        let batches = match futures::future::try_join_all(tasks).await {
-            Ok(res) => res,
-            Err(err) => panic!("Error processing transaction batches: {:?}", err),
-        };
+    }
+}
 
-        let mut batch_start_version = u64::MAX;
-        let mut batch_end_version = 0;
-        let mut num_res = 0;
+/// Returns the version the fetcher has to start from, which is the one of the processor furthest
+/// behind, so the processors ahead of it will reprocess some versions
+fn get_start_version(tailer: &Tailer) -> u64 {
+    let mut start_version = u64::MAX;
+    for processor in tailer.processors() {
+        let processor_start_version = tailer
+            .get_start_version(&processor.name().to_string())
+            .unwrap_or_else(|e| panic!("Failed to get starting version: {:?}", e))
+            .unwrap_or_else(|| {
+                info!(
+                    processor_name = processor.name(),
+                    "No starting version from db so starting from version 0"
+                );
+                0
+            }) as u64;
+        start_version = std::cmp::min(start_version, processor_start_version);
+    }
+    start_version
+}
 
-        for (num_txn, res) in batches {
-            let processed_result: ProcessingResult = match res {
-                // When the batch is empty b/c we're caught up, continue to next batch
-                None => continue,
-                Some(Ok(res)) => res,
-                Some(Err(tpe)) => {
-                    let (err, start_version, end_version, _) = tpe.inner();
-                    error!(
-                        processor_name = processor_name,
-                        start_version = start_version,
-                        end_version = end_version,
-                        error =? err,
-                        "Error processing batch!"
-                    );
-                    panic!(
-                        "Error in '{}' while processing batch: {:?}",
-                        processor_name, err
-                    );
-                }
-            };
+/// Has every processor process the next `processor_tasks` batches and records how far each of
+/// them got. Returns the number of transactions in the batches and the versions they span.
+async fn process_next_batches(tailer: &Tailer, processor_tasks: u8) -> (u64, u64, u64) {
+    let mut tasks = vec![];
+    for _ in 0..processor_tasks {
+        let other_tailer = tailer.clone();
+        let task = tokio::spawn(async move { other_tailer.process_next_batch().await });
+        tasks.push(task);
+    }
+    let batches = match futures::future::try_join_all(tasks).await {
+        Ok(res) => res,
+        Err(err) => panic!("Error processing transaction batches: {:?}", err),
+    };
+
+    let mut batch_start_version = u64::MAX;
+    let mut batch_end_version = 0;
+    let mut num_res = 0;
+    // The last version processed by each processor, in the order of `tailer.processors()`
+    let mut processor_end_versions: Vec<Option<u64>> = vec![None; tailer.processors().len()];
+
+    for (num_txn, results) in batches {
+        let results = match results {
+            // When the batch is empty b/c we're caught up, continue to next batch
+            None => continue,
+            Some(results) => results,
+        };
+        for ((processor, processor_end_version), res) in tailer
+            .processors()
+            .iter()
+            .zip(processor_end_versions.iter_mut())
+            .zip(results)
+        {
+            let processed_result = processing_result_or_panic(processor.name(), res);
             batch_start_version =
                 std::cmp::min(batch_start_version, processed_result.start_version);
             batch_end_version = std::cmp::max(batch_end_version, processed_result.end_version);
-            num_res += num_txn;
+            *processor_end_version = Some(std::cmp::max(
+                processor_end_version.unwrap_or(0),
+                processed_result.end_version,
+            ));
         }
+        num_res += num_txn;
+    }
 
+    for (processor, processor_end_version) in tailer.processors().iter().zip(processor_end_versions)
+    {
+        let end_version = match processor_end_version {
+            Some(end_version) => end_version,
+            None => continue,
+        };
         tailer
-            .update_last_processed_version(&processor_name, batch_end_version)
+            .update_last_processed_version(processor.name(), end_version)
             .unwrap_or_else(|e| {
                 error!(
-                    processor_name = processor_name,
-                    end_version = batch_end_version,
+                    processor_name = processor.name(),
+                    end_version = end_version,
                     error = format!("{:?}", e),
                     "Failed to update last processed version!"
                 );
                 panic!("Failed to update last processed version: {:?}", e);
             });
+    }
+
+    (num_res, batch_start_version, batch_end_version)
+}
+
+/// Reprocesses the versions from `start_version` to `end_version` inclusive with the single
+/// processor of `config`, e.g. after fixing a bug in it, then returns
+///
+/// It can run while the same processor keeps indexing the chain elsewhere: the processors
+/// overwrite the rows they already inserted for the reprocessed versions, and the `current_*`
+/// tables are only updated from a later version, so reprocessing older versions never overwrites
+/// newer state. Neither `processor_status` nor `processor_statuses` are written to, the live
+/// tailer stays the only one tracking the processor's progress.
+pub async fn run_backfill(
+    mut config: IndexerConfig,
+    transaction_fetcher: Arc<Mutex<dyn TransactionFetcherTrait>>,
+    registry: ProcessorRegistry,
+    start_version: u64,
+    end_version: u64,
+) {
+    assert!(
+        start_version <= end_version,
+        "Backfill start version {} is after its end version {}",
+        start_version,
+        end_version
+    );
+    // All of these options should be filled already with defaults
+    let processor_names = config.processor.clone().unwrap();
+    let check_chain_id = config.check_chain_id.unwrap();
+    let processor_tasks = config.processor_tasks.unwrap();
+    let emit_every = config.emit_every.unwrap();
+    assert_eq!(
+        processor_names.len(),
+        1,
+        "Backfill runs a single processor, got {:?}",
+        processor_names
+    );
+    config.overwrite_existing_rows = Some(true);
+
+    let tailer = create_tailer(&config, transaction_fetcher, registry);
+    let processor_name = tailer.processor_names();
+
+    if check_chain_id {
+        tailer
+            .check_or_update_chain_id()
+            .await
+            .expect("Failed to get chain ID");
+    }
+
+    tailer.set_fetcher_version(start_version).await;
+    tailer.set_fetcher_end_version(end_version).await;
+    tailer.transaction_fetcher.lock().await.start().await;
+
+    info!(
+        processor_name = processor_name,
+        start_version = start_version,
+        end_version = end_version,
+        "Backfill started!"
+    );
+
+    let mut versions_remaining = end_version - start_version + 1;
+    let mut versions_processed: u64 = 0;
+    let mut base: u64 = 0;
+    let mut ma = MovingAverage::new(10_000);
+
+    while versions_remaining > 0 {
+        let mut tasks = vec![];
+        for _ in 0..processor_tasks {
+            let other_tailer = tailer.clone();
+            let task = tokio::spawn(async move { other_tailer.backfill_next_batch().await });
+            tasks.push(task);
+        }
+        let batches = match futures::future::try_join_all(tasks).await {
+            Ok(res) => res,
+            Err(err) => panic!("Error processing transaction batches: {:?}", err),
+        };
+
+        let mut batch_end_version = 0;
+        let mut num_res = 0;
+        for (num_txn, results) in batches {
+            let results = match results {
+                None => continue,
+                Some(results) => results,
+            };
+            for res in results {
+                let processed_result = processing_result_or_panic(&processor_name, res);
+                batch_end_version = std::cmp::max(batch_end_version, processed_result.end_version);
+            }
+            num_res += num_txn;
+        }
 
         ma.tick_now(num_res);
 
+        versions_remaining -= num_res;
         versions_processed += num_res;
         if emit_every != 0 {
             let new_base: u64 = versions_processed / (emit_every as u64);
@@ -269,13 +500,398 @@ pub async fn run_forever(config: IndexerConfig, context: Arc<Context>) {
                 base = new_base;
                 info!(
                     processor_name = processor_name,
-                    batch_start_version = batch_start_version,
                     batch_end_version = batch_end_version,
                     versions_processed = versions_processed,
+                    versions_remaining = versions_remaining,
                     tps = (ma.avg() * 1000.0) as u64,
-                    "Processed batch version"
+                    "Backfilled batch version"
                 );
             }
         }
     }
+
+    info!(
+        processor_name = processor_name,
+        start_version = start_version,
+        end_version = end_version,
+        "Backfill finished!"
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        database::{new_db_pool, ArcDbPool},
+        indexer::tailer::test::{lock_database, wipe_database},
+        models::processor_status::ProcessorStatusV2Query,
+        processors::registry::test::NamedProcessor,
+        with_conn,
+    };
+    use aptos_api_test_context::new_test_context;
+    use aptos_api_types::{
+        HashValue, LedgerInfo as APILedgerInfo, StateCheckpointTransaction, Transaction,
+        TransactionInfo, U64,
+    };
+    use aptos_temppath::TempPath;
+    use diesel::{sql_types::BigInt, QueryResult, RunQueryDsl};
+    use diesel_migrations::EmbeddedMigrations;
+    use std::time::Duration;
+
+    /// Creates the table of `VersionRecordingProcessor`, in Postgres or SQLite
+    const TEST_MIGRATIONS: EmbeddedMigrations = embed_migrations!("test_migrations");
+
+    /// Serves state checkpoint transactions, from version 0 to `num_versions - 1`, in batches
+    struct FakeFetcher {
+        num_versions: u64,
+        batch_size: u64,
+        version: u64,
+        end_version: Option<u64>,
+    }
+
+    #[async_trait::async_trait]
+    impl TransactionFetcherTrait for FakeFetcher {
+        async fn fetch_next_batch(&mut self) -> Vec<Transaction> {
+            let mut end = std::cmp::min(self.version + self.batch_size, self.num_versions);
+            if let Some(end_version) = self.end_version {
+                end = std::cmp::min(end, end_version + 1);
+            }
+            let batch = (self.version..end)
+                .map(state_checkpoint_transaction)
+                .collect();
+            self.version = std::cmp::max(self.version, end);
+            batch
+        }
+
+        async fn fetch_ledger_info(&mut self) -> APILedgerInfo {
+            unimplemented!();
+        }
+
+        async fn set_version(&mut self, version: u64) {
+            self.version = version;
+        }
+
+        async fn set_end_version(&mut self, version: u64) {
+            self.end_version = Some(version);
+        }
+
+        async fn start(&mut self) {
+            // do nothing
+        }
+    }
+
+    fn state_checkpoint_transaction(version: u64) -> Transaction {
+        let hash: HashValue = "0".repeat(64).parse().unwrap();
+        Transaction::StateCheckpointTransaction(StateCheckpointTransaction {
+            info: TransactionInfo {
+                version: U64::from(version),
+                hash,
+                state_change_hash: hash,
+                event_root_hash: hash,
+                state_checkpoint_hash: None,
+                gas_used: U64::from(0),
+                success: true,
+                vm_status: "Executed successfully".to_string(),
+                accumulator_root_hash: hash,
+                changes: vec![],
+                block_height: Some(U64::from(version)),
+                epoch: Some(U64::from(0)),
+                gas_breakdown: None,
+            },
+            timestamp: U64::from(0),
+        })
+    }
+
+    /// Records the versions it processes in the table created by `TEST_MIGRATIONS`, like a
+    /// processor from another crate would, flagging the rows it overwrites
+    #[derive(Debug)]
+    struct VersionRecordingProcessor {
+        connection_pool: ArcDbPool,
+        overwrite_existing_rows: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl TransactionProcessor for VersionRecordingProcessor {
+        fn name(&self) -> &'static str {
+            "version_recording_processor"
+        }
+
+        async fn process_transactions(
+            &self,
+            _transactions: Vec<Transaction>,
+            start_version: u64,
+            end_version: u64,
+        ) -> Result<ProcessingResult, TransactionProcessingError> {
+            let mut conn = self.get_conn();
+            let on_conflict = if self.overwrite_existing_rows {
+                "DO UPDATE SET overwritten = TRUE"
+            } else {
+                "DO NOTHING"
+            };
+            for version in start_version..=end_version {
+                let query = diesel::sql_query(format!(
+                    "INSERT INTO test_processor_versions (version) VALUES ($1) ON CONFLICT (version) {}",
+                    on_conflict
+                ))
+                .bind::<BigInt, _>(version as i64);
+                with_conn!(&mut conn, |conn| query.execute(conn)).unwrap();
+            }
+            Ok(ProcessingResult::new(
+                self.name(),
+                start_version,
+                end_version,
+            ))
+        }
+
+        fn connection_pool(&self) -> &ArcDbPool {
+            &self.connection_pool
+        }
+    }
+
+    fn version_recording_registry() -> ProcessorRegistry {
+        let mut registry = ProcessorRegistry::empty();
+        registry
+            .register("version_recording_processor", |connection_pool, config| {
+                Box::new(VersionRecordingProcessor {
+                    connection_pool,
+                    overwrite_existing_rows: config.overwrite_existing_rows.unwrap_or_default(),
+                })
+            })
+            .add_migrations(TEST_MIGRATIONS)
+            .add_sqlite_migrations(TEST_MIGRATIONS);
+        registry
+    }
+
+    #[derive(Debug, QueryableByName)]
+    struct RecordedVersions {
+        #[diesel(sql_type = BigInt)]
+        count: i64,
+    }
+
+    fn count_rows(conn_pool: &ArcDbPool, query: &str) -> QueryResult<i64> {
+        let query = diesel::sql_query(query);
+        with_conn!(&mut conn_pool.get().unwrap(), |conn| query
+            .get_result::<RecordedVersions>(conn))
+        .map(|recorded_versions| recorded_versions.count)
+    }
+
+    fn fake_fetcher(num_versions: u64) -> FakeFetcher {
+        FakeFetcher {
+            num_versions,
+            batch_size: 5,
+            version: 0,
+            end_version: None,
+        }
+    }
+
+    fn setup_tailer(processor_names: &[&'static str], num_versions: u64) -> Tailer {
+        let database_url = std::env::var("INDEXER_DATABASE_URL")
+            .expect("must set 'INDEXER_DATABASE_URL' to run tests!");
+        let conn_pool = new_db_pool(database_url.as_str()).unwrap();
+        wipe_database(&mut conn_pool.get().unwrap());
+
+        let processors: Vec<Arc<dyn TransactionProcessor>> = processor_names
+            .iter()
+            .map(|name| -> Arc<dyn TransactionProcessor> {
+                Arc::new(NamedProcessor {
+                    name: *name,
+                    connection_pool: conn_pool.clone(),
+                })
+            })
+            .collect();
+        let tailer = Tailer::new_with_fetcher(
+            conn_pool,
+            processors,
+            Arc::new(Mutex::new(fake_fetcher(num_versions))),
+        );
+        tailer.run_migrations();
+        tailer
+    }
+
+    fn last_success_version(tailer: &Tailer, processor_name: &str) -> i64 {
+        ProcessorStatusV2Query::get_by_processor(
+            &processor_name.to_string(),
+            &mut tailer.connection_pool().get().unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .last_success_version
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_processors_with_different_progress() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let _database = lock_database();
+        let tailer = setup_tailer(&["ahead_processor", "behind_processor"], 20);
+        tailer
+            .update_last_processed_version("ahead_processor", 12)
+            .unwrap();
+        tailer
+            .update_last_processed_version("behind_processor", 4)
+            .unwrap();
+
+        // The fetcher starts from the processor furthest behind
+        let start_version = get_start_version(&tailer);
+        assert_eq!(start_version, 5);
+        tailer.set_fetcher_version(start_version).await;
+
+        // The processor ahead reprocesses versions it went through already, without its progress
+        // going backwards
+        assert_eq!(process_next_batches(&tailer, 1).await, (5, 5, 9));
+        assert_eq!(last_success_version(&tailer, "ahead_processor"), 12);
+        assert_eq!(last_success_version(&tailer, "behind_processor"), 9);
+
+        assert_eq!(process_next_batches(&tailer, 3).await, (10, 10, 19));
+        assert_eq!(last_success_version(&tailer, "ahead_processor"), 19);
+        assert_eq!(last_success_version(&tailer, "behind_processor"), 19);
+
+        // Caught up
+        assert_eq!(process_next_batches(&tailer, 1).await, (0, u64::MAX, 0));
+        assert_eq!(last_success_version(&tailer, "ahead_processor"), 19);
+        assert_eq!(last_success_version(&tailer, "behind_processor"), 19);
+        assert_eq!(get_start_version(&tailer), 20);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_bootstrap_with_registry() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let _database = lock_database();
+        let database_url = std::env::var("INDEXER_DATABASE_URL")
+            .expect("must set 'INDEXER_DATABASE_URL' to run tests!");
+        bootstrap_with_registry_into(database_url).await;
+    }
+
+    /// Same as `test_bootstrap_with_registry`, indexing into a SQLite file as a local testnet
+    /// does, without any database server
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_bootstrap_with_registry_sqlite() {
+        let directory = TempPath::new();
+        directory.create_as_dir().unwrap();
+        bootstrap_with_registry_into(format!(
+            "sqlite://{}",
+            directory.path().join("indexer.db").display()
+        ))
+        .await;
+    }
+
+    async fn bootstrap_with_registry_into(database_url: String) {
+        let conn_pool = new_db_pool(database_url.as_str()).unwrap();
+        wipe_database(&mut conn_pool.get().unwrap());
+
+        let test_context = new_test_context("test_bootstrap_with_registry".to_string(), true);
+        let latest_version = test_context.get_latest_ledger_info().ledger_version.0;
+        let mut node_config = NodeConfig::default();
+        node_config.indexer = IndexerConfig {
+            enabled: true,
+            postgres_uri: Some(database_url),
+            processor: Some(vec!["version_recording_processor".to_string()]),
+            skip_migrations: Some(false),
+            check_chain_id: Some(true),
+            batch_size: Some(10),
+            fetch_tasks: Some(1),
+            processor_tasks: Some(1),
+            emit_every: Some(0),
+            gap_lookback_versions: Some(1_500_000),
+            ..IndexerConfig::default()
+        };
+        let runtime = bootstrap_with_registry(
+            &node_config,
+            ChainId::test(),
+            test_context.context.db.clone(),
+            test_context.mempool.ac_client.clone(),
+            version_recording_registry(),
+        )
+        .unwrap()
+        .unwrap();
+
+        // The processor of the registry runs on every version, in the table of its migrations
+        let mut num_recorded_versions = 0;
+        for _ in 0..100 {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            num_recorded_versions = count_rows(
+                &conn_pool,
+                "SELECT COUNT(*) AS count FROM test_processor_versions",
+            )
+            .unwrap_or(0);
+            if num_recorded_versions == latest_version as i64 + 1 {
+                break;
+            }
+        }
+        runtime.shutdown_background();
+        assert_eq!(num_recorded_versions, latest_version as i64 + 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_run_backfill() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let _database = lock_database();
+        let tailer = setup_tailer(&[], 30);
+        tailer.run_migrations_from(TEST_MIGRATIONS);
+        // The processor following the chain indexed the first 30 versions
+        let query = diesel::sql_query(
+            "INSERT INTO test_processor_versions (version) SELECT generate_series(0, 29)",
+        );
+        with_conn!(&mut tailer.connection_pool().get().unwrap(), |conn| query
+            .execute(conn))
+        .unwrap();
+        tailer
+            .update_last_processed_version("version_recording_processor", 29)
+            .unwrap();
+
+        let config = IndexerConfig {
+            enabled: true,
+            postgres_uri: Some(std::env::var("INDEXER_DATABASE_URL").unwrap()),
+            processor: Some(vec!["version_recording_processor".to_string()]),
+            skip_migrations: Some(false),
+            check_chain_id: Some(false),
+            processor_tasks: Some(2),
+            emit_every: Some(0),
+            ..IndexerConfig::default()
+        };
+        run_backfill(
+            config,
+            Arc::new(Mutex::new(fake_fetcher(30))),
+            version_recording_registry(),
+            5,
+            14,
+        )
+        .await;
+
+        // Only the rows of the range are rewritten, and the processor's progress isn't touched
+        assert_eq!(
+            count_rows(
+                tailer.connection_pool(),
+                "SELECT COUNT(*) AS count FROM test_processor_versions WHERE overwritten"
+            )
+            .unwrap(),
+            10
+        );
+        assert_eq!(
+            count_rows(
+                tailer.connection_pool(),
+                "SELECT COUNT(*) AS count FROM test_processor_versions \
+                WHERE overwritten AND version BETWEEN 5 AND 14"
+            )
+            .unwrap(),
+            10
+        );
+        assert_eq!(
+            count_rows(
+                tailer.connection_pool(),
+                "SELECT COUNT(*) AS count FROM processor_statuses"
+            )
+            .unwrap(),
+            0
+        );
+        assert_eq!(
+            last_success_version(&tailer, "version_recording_processor"),
+            29
+        );
+    }
 }
@@ -4,6 +4,9 @@
 // @generated automatically by Diesel CLI.
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     block_metadata_transactions (version) {
         version -> Int8,
         block_height -> Int8,
@@ -19,6 +22,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     coin_activities (transaction_version, event_account_address, event_creation_number, event_sequence_number) {
         transaction_version -> Int8,
         event_account_address -> Varchar,
@@ -38,6 +44,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     coin_balances (transaction_version, owner_address, coin_type_hash) {
         transaction_version -> Int8,
         owner_address -> Varchar,
@@ -50,6 +59,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     coin_infos (coin_type_hash) {
         coin_type_hash -> Varchar,
         coin_type -> Varchar,
@@ -66,6 +78,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     coin_supply (transaction_version, coin_type_hash) {
         transaction_version -> Int8,
         coin_type_hash -> Varchar,
@@ -78,6 +93,33 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
+    coin_transfers (transaction_version, transfer_index) {
+        transaction_version -> Int8,
+        transfer_index -> Int8,
+        coin_type -> Varchar,
+        amount -> Numeric,
+        from_address -> Nullable<Varchar>,
+        to_address -> Nullable<Varchar>,
+        withdraw_event_creation_number -> Nullable<Int8>,
+        withdraw_event_sequence_number -> Nullable<Int8>,
+        deposit_event_creation_number -> Nullable<Int8>,
+        deposit_event_sequence_number -> Nullable<Int8>,
+        is_gas_fee -> Bool,
+        is_transaction_success -> Bool,
+        entry_function_id_str -> Nullable<Varchar>,
+        block_height -> Int8,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     collection_datas (collection_data_id_hash, transaction_version) {
         collection_data_id_hash -> Varchar,
         transaction_version -> Int8,
@@ -97,6 +139,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     current_ans_lookup (domain, subdomain) {
         domain -> Varchar,
         subdomain -> Varchar,
@@ -109,6 +154,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     current_coin_balances (owner_address, coin_type_hash) {
         owner_address -> Varchar,
         coin_type_hash -> Varchar,
@@ -121,6 +169,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     current_collection_datas (collection_data_id_hash) {
         collection_data_id_hash -> Varchar,
         creator_address -> Varchar,
@@ -140,6 +191,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     current_staking_pool_voter (staking_pool_address) {
         staking_pool_address -> Varchar,
         voter_address -> Varchar,
@@ -149,6 +203,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     current_token_datas (token_data_id_hash) {
         token_data_id_hash -> Varchar,
         creator_address -> Varchar,
@@ -176,6 +233,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     current_token_ownerships (token_data_id_hash, property_version, owner_address) {
         token_data_id_hash -> Varchar,
         property_version -> Numeric,
@@ -194,6 +254,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     current_token_pending_claims (token_data_id_hash, property_version, from_address, to_address) {
         token_data_id_hash -> Varchar,
         property_version -> Numeric,
@@ -212,6 +275,29 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
+    entry_function_calls (transaction_version) {
+        transaction_version -> Int8,
+        sender -> Varchar,
+        module_address -> Varchar,
+        module_name -> Varchar,
+        function_name -> Varchar,
+        type_arguments -> Jsonb,
+        is_transaction_success -> Bool,
+        gas_used -> Int8,
+        gas_unit_price -> Int8,
+        block_height -> Int8,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     events (account_address, creation_number, sequence_number) {
         sequence_number -> Int8,
         creation_number -> Int8,
@@ -226,6 +312,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     indexer_status (db) {
         db -> Varchar,
         is_indexer_up -> Bool,
@@ -234,12 +323,37 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     ledger_infos (chain_id) {
         chain_id -> Int8,
     }
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
+    move_module_abis (transaction_version, write_set_change_index) {
+        transaction_version -> Int8,
+        write_set_change_index -> Int8,
+        address -> Varchar,
+        name -> Text,
+        exposed_functions -> Nullable<Jsonb>,
+        friends -> Nullable<Jsonb>,
+        structs -> Nullable<Jsonb>,
+        is_deleted -> Bool,
+        block_height -> Int8,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     move_modules (transaction_version, write_set_change_index) {
         transaction_version -> Int8,
         write_set_change_index -> Int8,
@@ -256,6 +370,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     move_resources (transaction_version, write_set_change_index) {
         transaction_version -> Int8,
         write_set_change_index -> Int8,
@@ -273,6 +390,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     processor_status (processor) {
         processor -> Varchar,
         last_success_version -> Int8,
@@ -281,6 +401,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     processor_statuses (name, version) {
         name -> Varchar,
         version -> Int8,
@@ -291,6 +414,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     proposal_votes (transaction_version, proposal_id, voter_address) {
         transaction_version -> Int8,
         proposal_id -> Int8,
@@ -304,6 +430,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     signatures (transaction_version, multi_agent_index, multi_sig_index, is_sender_primary) {
         transaction_version -> Int8,
         multi_agent_index -> Int8,
@@ -322,6 +451,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     table_items (transaction_version, write_set_change_index) {
         key -> Text,
         transaction_version -> Int8,
@@ -336,6 +468,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     table_metadatas (handle) {
         handle -> Varchar,
         key_type -> Text,
@@ -345,6 +480,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     token_activities (transaction_version, event_account_address, event_creation_number, event_sequence_number) {
         transaction_version -> Int8,
         event_account_address -> Varchar,
@@ -368,6 +506,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     token_datas (token_data_id_hash, transaction_version) {
         token_data_id_hash -> Varchar,
         transaction_version -> Int8,
@@ -395,6 +536,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     token_ownerships (token_data_id_hash, property_version, transaction_version, table_handle) {
         token_data_id_hash -> Varchar,
         property_version -> Numeric,
@@ -413,6 +557,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     tokens (token_data_id_hash, property_version, transaction_version) {
         token_data_id_hash -> Varchar,
         property_version -> Numeric,
@@ -428,6 +575,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     transactions (version) {
         version -> Int8,
         block_height -> Int8,
@@ -450,6 +600,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     user_transactions (version) {
         version -> Int8,
         block_height -> Int8,
@@ -467,6 +620,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::{Jsonb, Numeric};
+
     write_set_changes (transaction_version, index) {
         transaction_version -> Int8,
         index -> Int8,
@@ -485,6 +641,7 @@ diesel::allow_tables_to_appear_in_same_query!(
     coin_balances,
     coin_infos,
     coin_supply,
+    coin_transfers,
     collection_datas,
     current_ans_lookup,
     current_coin_balances,
@@ -493,9 +650,11 @@ diesel::allow_tables_to_appear_in_same_query!(
     current_token_datas,
     current_token_ownerships,
     current_token_pending_claims,
+    entry_function_calls,
     events,
     indexer_status,
     ledger_infos,
+    move_module_abis,
     move_modules,
     move_resources,
     processor_status,
@@ -0,0 +1,187 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! SQL types of the columns whose representation differs between the backends. Postgres stores
+//! them in its own `jsonb` and `numeric` types, SQLite, which has neither, as text.
+#![allow(clippy::extra_unused_lifetimes)]
+
+use bigdecimal::BigDecimal;
+use diesel::{
+    deserialize::{self, FromSql},
+    pg::{Pg, PgValue},
+    serialize::{self, Output, ToSql},
+    sql_types::{self, ops, Nullable},
+    sqlite::{Sqlite, SqliteValue},
+};
+use std::str::FromStr;
+
+/// `jsonb` in Postgres, JSON text in SQLite
+#[derive(Clone, Copy, Debug, Default, QueryId, SqlType)]
+#[diesel(postgres_type(oid = 3802, array_oid = 3807))]
+#[diesel(sqlite_type(name = "Text"))]
+pub struct Jsonb;
+
+/// `numeric` in Postgres, the decimal text of the number in SQLite, as SQLite only keeps 15
+/// significant digits of the numbers it stores itself. Comparing and ordering such columns in
+/// SQLite compares the text rather than the numbers.
+#[derive(Clone, Copy, Debug, Default, QueryId, SqlType)]
+#[diesel(postgres_type(oid = 1700, array_oid = 1231))]
+#[diesel(sqlite_type(name = "Text"))]
+pub struct Numeric;
+
+// `diesel::table!` gives the columns named after the numeric types arithmetic operators
+impl ops::Add for Numeric {
+    type Rhs = Numeric;
+    type Output = Numeric;
+}
+
+impl ops::Sub for Numeric {
+    type Rhs = Numeric;
+    type Output = Numeric;
+}
+
+impl ops::Mul for Numeric {
+    type Rhs = Numeric;
+    type Output = Numeric;
+}
+
+impl ops::Div for Numeric {
+    type Rhs = Numeric;
+    type Output = Numeric;
+}
+
+/// A json value to insert in a [`Jsonb`] column. The coherence rules keep this crate from making
+/// `serde_json::Value` an expression of [`Jsonb`], so the models insert their values through this
+/// wrapper, with `#[diesel(serialize_as = JsonbValue)]`.
+#[derive(AsExpression, Debug)]
+#[diesel(sql_type = Jsonb)]
+pub struct JsonbValue(pub serde_json::Value);
+
+#[derive(AsExpression, Debug)]
+#[diesel(sql_type = Nullable<Jsonb>)]
+pub struct NullableJsonbValue(pub Option<serde_json::Value>);
+
+/// A number to insert in, or compare with, a [`Numeric`] column, see [`JsonbValue`]
+#[derive(AsExpression, Debug)]
+#[diesel(sql_type = Numeric)]
+pub struct NumericValue(pub BigDecimal);
+
+#[derive(AsExpression, Debug)]
+#[diesel(sql_type = Nullable<Numeric>)]
+pub struct NullableNumericValue(pub Option<BigDecimal>);
+
+impl ToSql<Jsonb, Pg> for serde_json::Value {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        ToSql::<sql_types::Jsonb, Pg>::to_sql(self, out)
+    }
+}
+
+impl FromSql<Jsonb, Pg> for serde_json::Value {
+    fn from_sql(value: PgValue<'_>) -> deserialize::Result<Self> {
+        FromSql::<sql_types::Jsonb, Pg>::from_sql(value)
+    }
+}
+
+impl ToSql<Jsonb, Sqlite> for serde_json::Value {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> serialize::Result {
+        out.set_value(serde_json::to_string(self)?);
+        Ok(serialize::IsNull::No)
+    }
+}
+
+impl FromSql<Jsonb, Sqlite> for serde_json::Value {
+    fn from_sql(value: SqliteValue<'_, '_, '_>) -> deserialize::Result<Self> {
+        let text = <String as FromSql<sql_types::Text, Sqlite>>::from_sql(value)?;
+        Ok(serde_json::from_str(&text)?)
+    }
+}
+
+impl ToSql<Numeric, Pg> for BigDecimal {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        ToSql::<sql_types::Numeric, Pg>::to_sql(self, out)
+    }
+}
+
+impl FromSql<Numeric, Pg> for BigDecimal {
+    fn from_sql(value: PgValue<'_>) -> deserialize::Result<Self> {
+        FromSql::<sql_types::Numeric, Pg>::from_sql(value)
+    }
+}
+
+impl ToSql<Numeric, Sqlite> for BigDecimal {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> serialize::Result {
+        out.set_value(self.to_string());
+        Ok(serialize::IsNull::No)
+    }
+}
+
+impl FromSql<Numeric, Sqlite> for BigDecimal {
+    fn from_sql(value: SqliteValue<'_, '_, '_>) -> deserialize::Result<Self> {
+        let text = <String as FromSql<sql_types::Text, Sqlite>>::from_sql(value)?;
+        Ok(BigDecimal::from_str(&text)?)
+    }
+}
+
+impl From<serde_json::Value> for JsonbValue {
+    fn from(value: serde_json::Value) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Option<serde_json::Value>> for NullableJsonbValue {
+    fn from(value: Option<serde_json::Value>) -> Self {
+        Self(value)
+    }
+}
+
+impl From<BigDecimal> for NumericValue {
+    fn from(value: BigDecimal) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Option<BigDecimal>> for NullableNumericValue {
+    fn from(value: Option<BigDecimal>) -> Self {
+        Self(value)
+    }
+}
+
+impl<DB> ToSql<Jsonb, DB> for JsonbValue
+where
+    DB: diesel::backend::Backend,
+    serde_json::Value: ToSql<Jsonb, DB>,
+{
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, DB>) -> serialize::Result {
+        self.0.to_sql(out)
+    }
+}
+
+impl<DB> ToSql<Nullable<Jsonb>, DB> for NullableJsonbValue
+where
+    DB: diesel::backend::Backend,
+    serde_json::Value: ToSql<Jsonb, DB>,
+{
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, DB>) -> serialize::Result {
+        ToSql::<Nullable<Jsonb>, DB>::to_sql(&self.0, out)
+    }
+}
+
+impl<DB> ToSql<Numeric, DB> for NumericValue
+where
+    DB: diesel::backend::Backend,
+    BigDecimal: ToSql<Numeric, DB>,
+{
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, DB>) -> serialize::Result {
+        self.0.to_sql(out)
+    }
+}
+
+impl<DB> ToSql<Nullable<Numeric>, DB> for NullableNumericValue
+where
+    DB: diesel::backend::Backend,
+    BigDecimal: ToSql<Numeric, DB>,
+{
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, DB>) -> serialize::Result {
+        ToSql::<Nullable<Numeric>, DB>::to_sql(&self.0, out)
+    }
+}
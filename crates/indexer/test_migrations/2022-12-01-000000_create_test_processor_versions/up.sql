@@ -0,0 +1,6 @@
+-- Your SQL goes here
+-- Table of the processor registered by the runtime tests
+CREATE TABLE test_processor_versions (
+  version BIGINT PRIMARY KEY NOT NULL,
+  overwritten BOOLEAN NOT NULL DEFAULT FALSE
+);
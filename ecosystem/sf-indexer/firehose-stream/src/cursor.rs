@@ -0,0 +1,59 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result};
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// Persists the height of the last block that was fully emitted, so the streamer can resume right
+/// after it on restart.
+///
+/// The cursor is only moved once a block has been completely emitted. A crash in the middle of a
+/// block therefore leaves the cursor on the previous block and the whole block is emitted again on
+/// restart, which downstream consumers already de-duplicate by block height.
+#[derive(Clone, Debug)]
+pub struct BlockCursor {
+    path: PathBuf,
+}
+
+impl BlockCursor {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the last fully emitted block height, or None if nothing was persisted yet
+    pub fn load(&self) -> Result<Option<u64>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read cursor file {:?}", self.path))?;
+        let block_height = content
+            .trim()
+            .parse::<u64>()
+            .with_context(|| format!("Malformed cursor file {:?}: {:?}", self.path, content))?;
+        Ok(Some(block_height))
+    }
+
+    /// Records `block_height` as fully emitted. The file is replaced atomically so a crash while
+    /// saving never leaves a truncated cursor behind.
+    pub fn save(&self, block_height: u64) -> Result<()> {
+        let tmp_path = PathBuf::from(format!("{}.tmp", self.path.display()));
+        let mut file = File::create(&tmp_path)
+            .with_context(|| format!("Failed to create cursor file {:?}", tmp_path))?;
+        file.write_all(block_height.to_string().as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, &self.path)
+            .with_context(|| format!("Failed to move cursor file to {:?}", self.path))?;
+        Ok(())
+    }
+}
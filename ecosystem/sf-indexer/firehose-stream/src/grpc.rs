@@ -0,0 +1,140 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    metrics,
+    proto::{firehose_stream_server::FirehoseStream, StreamBlocksRequest},
+    runtime::{FirehoseStreamError, FirehoseStreamer},
+};
+use aptos_api::context::Context;
+use aptos_logger::{debug, warn};
+use aptos_mempool::MempoolClientSender;
+use aptos_protos::extractor::v1::{Block, Transaction as TransactionPB};
+use futures::{
+    channel::mpsc::{channel, Receiver},
+    SinkExt, StreamExt,
+};
+use std::{sync::Arc, time::Instant};
+use tokio::sync::Semaphore;
+use tonic::{Request, Response, Status};
+
+/// Serves converted blocks to gRPC subscribers. Every subscriber gets its own `FirehoseStreamer`
+/// starting at the requested block, which writes into a bounded channel: once the subscriber stops
+/// reading, the channel fills up and conversion for that subscriber waits (backpressure).
+pub struct FirehoseGrpcService {
+    context: Arc<Context>,
+    mp_sender: MempoolClientSender,
+    subscriber_buffer_size: usize,
+    subscriber_permits: Arc<Semaphore>,
+}
+
+impl FirehoseGrpcService {
+    pub fn new(
+        context: Arc<Context>,
+        mp_sender: MempoolClientSender,
+        subscriber_buffer_size: usize,
+        max_subscribers: usize,
+    ) -> Self {
+        Self {
+            context,
+            mp_sender,
+            subscriber_buffer_size,
+            subscriber_permits: Arc::new(Semaphore::new(max_subscribers)),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl FirehoseStream for FirehoseGrpcService {
+    type StreamBlocksStream = Receiver<Result<Block, Status>>;
+
+    async fn stream_blocks(
+        &self,
+        request: Request<StreamBlocksRequest>,
+    ) -> Result<Response<Self::StreamBlocksStream>, Status> {
+        let request = request.into_inner();
+        let starting_block = request.starting_block;
+        let end_block = request.end_block;
+        if let Some(end_block) = end_block {
+            if end_block < starting_block {
+                return Err(Status::invalid_argument(format!(
+                    "end_block {} is lower than starting_block {}",
+                    end_block, starting_block
+                )));
+            }
+        }
+
+        let permit = self
+            .subscriber_permits
+            .clone()
+            .try_acquire_owned()
+            .map_err(|_| Status::resource_exhausted("Too many gRPC subscribers"))?;
+        let streamer = FirehoseStreamer::try_new(
+            self.context.clone(),
+            starting_block,
+            Some(self.mp_sender.clone()),
+        )
+        .map_err(|err| {
+            if err.is::<FirehoseStreamError>() {
+                Status::out_of_range(err.to_string())
+            } else {
+                Status::not_found(format!("{:#}", err))
+            }
+        })?;
+        let chain_id = self.context.chain_id().id() as u32;
+
+        let (mut sender, receiver) = channel(self.subscriber_buffer_size);
+        tokio::spawn(async move {
+            let _permit = permit;
+            metrics::GRPC_SUBSCRIBERS.inc();
+            debug!(
+                "[fh-stream] gRPC subscriber starting from block {}",
+                starting_block
+            );
+            let mut blocks = streamer.block_stream();
+            while let Some(transactions) = blocks.next().await {
+                let transactions = match transactions {
+                    Ok(transactions) => transactions,
+                    Err(err) => {
+                        // Subscriber might be gone already, nothing else to do in that case
+                        let _ = sender
+                            .send(Err(Status::out_of_range(err.to_string())))
+                            .await;
+                        break;
+                    }
+                };
+                let block = to_block(transactions, chain_id);
+                let block_height = block.height;
+                let emit_start = Instant::now();
+                if sender.send(Ok(block)).await.is_err() {
+                    warn!(
+                        "[fh-stream] gRPC subscriber went away at block {}",
+                        block_height
+                    );
+                    break;
+                }
+                metrics::STAGE_LATENCY
+                    .with_label_values(&["emit"])
+                    .observe(emit_start.elapsed().as_secs_f64());
+                metrics::GRPC_BLOCKS_SENT.inc();
+                if Some(block_height) == end_block {
+                    break;
+                }
+            }
+            metrics::GRPC_SUBSCRIBERS.dec();
+        });
+
+        Ok(Response::new(receiver))
+    }
+}
+
+/// Wraps the transactions of a block, as returned by `FirehoseStreamer::convert_next_block`, into a `Block`
+pub fn to_block(transactions: Vec<TransactionPB>, chain_id: u32) -> Block {
+    let first_txn = &transactions[0];
+    Block {
+        timestamp: first_txn.timestamp.clone(),
+        height: first_txn.block_height,
+        transactions,
+        chain_id,
+    }
+}
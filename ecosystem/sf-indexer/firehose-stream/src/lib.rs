@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod convert;
+pub mod cursor;
+pub mod grpc;
 pub mod metrics;
+pub mod proto;
 pub mod runtime;
 
 #[cfg(test)]
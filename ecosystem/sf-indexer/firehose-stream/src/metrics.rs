@@ -1,7 +1,10 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_metrics_core::{register_int_counter, IntCounter};
+use aptos_metrics_core::{
+    register_histogram_vec, register_int_counter, register_int_gauge, HistogramVec, IntCounter,
+    IntGauge,
+};
 use once_cell::sync::Lazy;
 
 pub static TRANSACTIONS_SENT: Lazy<IntCounter> = Lazy::new(|| {
@@ -19,3 +22,44 @@ pub static BLOCKS_SENT: Lazy<IntCounter> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static GRPC_BLOCKS_SENT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_fh_stream_grpc_blocks_sent_count",
+        "Blocks converted and sent out to gRPC subscribers",
+    )
+    .unwrap()
+});
+
+pub static GRPC_SUBSCRIBERS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_fh_stream_grpc_subscribers",
+        "Number of gRPC subscribers currently being streamed blocks",
+    )
+    .unwrap()
+});
+
+pub static PRUNED_BLOCK_ERRORS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_fh_stream_pruned_block_errors_count",
+        "Times a block to stream was found to be pruned from the DB",
+    )
+    .unwrap()
+});
+
+pub static PRUNE_WINDOW_HEADROOM_VERSIONS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_fh_stream_prune_window_headroom_versions",
+        "How many versions the last streamed block is ahead of the ledger pruner window, negative means it is about to be pruned",
+    )
+    .unwrap()
+});
+
+pub static STAGE_LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_fh_stream_stage_latency_secs",
+        "Time spent per block in each stage: fetching from the DB, converting to protobuf and emitting to a sink",
+        &["stage"]
+    )
+    .unwrap()
+});
@@ -0,0 +1,15 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+// @generated
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StreamBlocksRequest {
+    /// Height of the first block to send.
+    #[prost(uint64, tag = "1")]
+    pub starting_block: u64,
+    /// If set, the stream is closed once this block (inclusive) has been sent.
+    #[prost(uint64, optional, tag = "2")]
+    pub end_block: ::core::option::Option<u64>,
+}
+include!("aptos.firehose_stream.v1.tonic.rs");
+// @@protoc_insertion_point(module)
@@ -0,0 +1,7 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+#[path = "aptos.firehose_stream.v1.rs"]
+mod pbfirehose_stream;
+
+pub use pbfirehose_stream::*;
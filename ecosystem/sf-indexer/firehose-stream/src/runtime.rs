@@ -1,28 +1,43 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::metrics;
+use crate::{
+    cursor::BlockCursor, grpc::FirehoseGrpcService, metrics,
+    proto::firehose_stream_server::FirehoseStreamServer,
+};
 use aptos_protos::extractor::v1 as extractor;
 
 use crate::convert::convert_transaction;
+use anyhow::{ensure, Context as AnyhowContext};
 use aptos_api::context::Context;
 use aptos_api_types::{AsConverter, Transaction};
 use aptos_config::config::NodeConfig;
-use aptos_logger::{debug, error, warn};
+use aptos_logger::{debug, error, info, warn};
 use aptos_mempool::MempoolClientSender;
 use aptos_types::chain_id::ChainId;
 use aptos_vm::data_cache::StorageAdapterOwned;
 use extractor::Transaction as TransactionPB;
-use futures::channel::mpsc::channel;
+use futures::{
+    channel::mpsc::channel,
+    future,
+    stream::{self, BoxStream},
+    FutureExt, StreamExt,
+};
 use prost::Message;
-use std::{convert::TryInto, sync::Arc, time::Duration};
+use std::{
+    convert::TryInto,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use storage_interface::{state_view::DbStateView, DbReader};
+use thiserror::Error;
 use tokio::{
     runtime::{Builder, Runtime},
     time::sleep,
 };
 
 /// Creates a runtime which creates a thread pool which pushes firehose of block protobuf to SF endpoint
+/// (stdout) and/or serves it to gRPC subscribers, depending on the config.
 /// Returns corresponding Tokio runtime
 pub fn bootstrap(
     config: &NodeConfig,
@@ -46,20 +61,124 @@ pub fn bootstrap(
     runtime.spawn(async move {
         let context = Context::new(chain_id, db, mp_sender.clone(), node_config.clone());
         let context_arc = Arc::new(context);
-        // Let the env variable take precedence over the config file, (if env is not set it just default to 0)
-        let config_starting_block = node_config.firehose_stream.starting_block.unwrap_or(0);
-        let mut starting_block = std::env::var("STARTING_BLOCK")
-            .map(|v| v.parse::<u64>().unwrap_or(0))
-            .unwrap_or(0);
-        if starting_block == 0 {
-            starting_block = config_starting_block;
+        let firehose_config = &node_config.firehose_stream;
+
+        if let Some(grpc_address) = firehose_config.grpc_address {
+            let service = FirehoseGrpcService::new(
+                context_arc.clone(),
+                mp_sender.clone(),
+                firehose_config.grpc_subscriber_buffer_size,
+                firehose_config.max_grpc_subscribers,
+            );
+            tokio::spawn(async move {
+                info!(
+                    "[fh-stream] serving gRPC firehose stream on {}",
+                    grpc_address
+                );
+                if let Err(err) = tonic::transport::Server::builder()
+                    .add_service(FirehoseStreamServer::new(service))
+                    .serve(grpc_address)
+                    .await
+                {
+                    error!("[fh-stream] gRPC server stopped with error: {}", err);
+                }
+            });
+        }
+
+        if firehose_config.stdout_enabled {
+            let cursor = firehose_config.cursor_path.as_ref().map(BlockCursor::new);
+            let resume_block = cursor.as_ref().and_then(|cursor| {
+                cursor
+                    .load()
+                    .unwrap_or_else(|err| panic!("Could not load firehose cursor: {:?}", err))
+                    .map(|last_emitted_block| last_emitted_block + 1)
+            });
+            let starting_block = match resume_block {
+                Some(resume_block) => {
+                    info!(
+                        "[fh-stream] resuming from block {} based on cursor",
+                        resume_block
+                    );
+                    resume_block
+                }
+                None => {
+                    // Let the env variable take precedence over the config file, (if env is not set it just default to 0)
+                    let config_starting_block = firehose_config.starting_block.unwrap_or(0);
+                    let mut starting_block = std::env::var("STARTING_BLOCK")
+                        .map(|v| v.parse::<u64>().unwrap_or(0))
+                        .unwrap_or(0);
+                    if starting_block == 0 {
+                        starting_block = config_starting_block;
+                    }
+                    starting_block
+                }
+            };
+            // When resuming, the block right after the cursor might not have been committed yet
+            let streamer = loop {
+                match FirehoseStreamer::try_new(
+                    context_arc.clone(),
+                    starting_block,
+                    Some(mp_sender.clone()),
+                ) {
+                    Ok(streamer) => break streamer,
+                    Err(err) if err.is::<FirehoseStreamError>() => {
+                        error!(
+                            "[fh-stream] failed to start streaming to stdout. Error: {}",
+                            err
+                        );
+                        return;
+                    }
+                    Err(err) => {
+                        warn!(
+                            "[fh-stream] failed to start streaming from block {}. Error: {:?}",
+                            starting_block, err
+                        );
+                        sleep(Duration::from_secs(1)).await;
+                    }
+                }
+            };
+            let mut streamer = streamer.with_cursor(cursor);
+            if let Err(err) = streamer.start().await {
+                error!("[fh-stream] stopped streaming to stdout. Error: {}", err);
+            }
         }
-        let mut streamer = FirehoseStreamer::new(context_arc, starting_block, Some(mp_sender));
-        streamer.start().await;
     });
     Some(Ok(runtime))
 }
 
+#[derive(Debug, Error)]
+pub enum FirehoseStreamError {
+    #[error(
+        "Block {block_height} has been pruned, first available block is {first_available_block}"
+    )]
+    BlockPruned {
+        block_height: u64,
+        first_available_block: u64,
+    },
+}
+
+/// Returns the first block that is still fully available in the DB if `block_height` has been pruned
+pub fn check_block_pruned(
+    db: &Arc<dyn DbReader>,
+    block_height: u64,
+) -> anyhow::Result<Option<u64>> {
+    let first_viable_version = db.get_first_viable_txn_version()?;
+    // The block holding the first viable version might have been partially pruned, in which case
+    // its NewBlockEvent is gone with its first versions. The first fully available block is the
+    // first one starting at or after the first viable version.
+    let (_, block_event) = db.get_next_block_event(first_viable_version)?;
+    let first_available_block = block_event.height();
+    let (block_start_version, _, _) = db.get_block_info_by_height(first_available_block)?;
+    ensure!(
+        block_start_version >= first_viable_version,
+        "Block {} starting at version {} is not fully available, first viable version is {}",
+        first_available_block,
+        block_start_version,
+        first_viable_version,
+    );
+    Ok((block_height < first_available_block).then(|| first_available_block))
+}
+
 pub struct FirehoseStreamer {
     pub context: Arc<Context>,
     pub resolver: Arc<StorageAdapterOwned<DbStateView>>,
@@ -67,6 +186,8 @@ pub struct FirehoseStreamer {
     pub current_epoch: u64,
     // This is only ever used for testing
     pub mp_sender: MempoolClientSender,
+    // If set, progress of the stdout sink is persisted here after every block
+    pub cursor: Option<BlockCursor>,
 }
 
 impl FirehoseStreamer {
@@ -75,16 +196,42 @@ impl FirehoseStreamer {
         starting_block: u64,
         mp_client_sender: Option<MempoolClientSender>,
     ) -> Self {
-        let resolver = Arc::new(context.move_resolver().unwrap());
+        Self::try_new(context, starting_block, mp_client_sender)
+            .unwrap_or_else(|err| panic!("{:?}", err))
+    }
+
+    /// Same as `new`, but returns an error instead of panicking if the starting block is not available
+    pub fn try_new(
+        context: Arc<Context>,
+        starting_block: u64,
+        mp_client_sender: Option<MempoolClientSender>,
+    ) -> anyhow::Result<Self> {
+        let resolver = Arc::new(context.move_resolver()?);
+        let mut starting_block = starting_block;
+        if let Some(first_available_block) = check_block_pruned(&context.db, starting_block)? {
+            if !context.node_config.firehose_stream.skip_pruned_blocks {
+                metrics::PRUNED_BLOCK_ERRORS.inc();
+                return Err(FirehoseStreamError::BlockPruned {
+                    block_height: starting_block,
+                    first_available_block,
+                }
+                .into());
+            }
+            warn!(
+                "[fh-stream] starting block {} has been pruned, skipping to block {}",
+                starting_block, first_available_block
+            );
+            starting_block = first_available_block;
+        }
         let (_block_start_version, _block_last_version, block_event) = context
             .db
             .get_block_info_by_height(starting_block)
-            .unwrap_or_else(|_| {
-                panic!(
+            .with_context(|| {
+                format!(
                     "Could not get block_info for starting block {}",
                     starting_block,
                 )
-            });
+            })?;
 
         // fake mempool client/sender, if we need to, so we can use the same code for both api and fh-streamer
         let mp_client_sender = mp_client_sender.unwrap_or_else(|| {
@@ -92,46 +239,130 @@ impl FirehoseStreamer {
             mp_client_sender
         });
 
-        Self {
+        Ok(Self {
             context,
             resolver,
             current_block_height: block_event.height(),
             current_epoch: block_event.epoch(),
             mp_sender: mp_client_sender,
-        }
+            cursor: None,
+        })
+    }
+
+    pub fn with_cursor(mut self, cursor: Option<BlockCursor>) -> Self {
+        self.cursor = cursor;
+        self
     }
 
-    pub async fn start(&mut self) {
+    /// Streams blocks to stdout. Only returns if the stream can't continue, e.g. blocks got pruned
+    pub async fn start(&mut self) -> Result<(), FirehoseStreamError> {
         // Format is FIRE INIT aptos-node <PACKAGE_VERSION> <MAJOR_VERSION> <MINOR_VERSION> <CHAIN_ID>
         println!(
             "\nFIRE INIT aptos-node {} aptos 0 0 {}",
             env!("CARGO_PKG_VERSION"),
             self.context.chain_id().id(),
         );
-        loop {
-            self.convert_next_block().await;
+        let mut blocks = self.block_stream();
+        while let Some(block) = blocks.next().await {
+            let block = block?;
+            let block_height = block[0].block_height;
+            let emit_start = Instant::now();
+            self.print_block(&block);
+            self.save_cursor(block_height);
+            metrics::STAGE_LATENCY
+                .with_label_values(&["emit"])
+                .observe(emit_start.elapsed().as_secs_f64());
+            self.current_block_height = block_height + 1;
+        }
+        Ok(())
+    }
+
+    /// Streams converted blocks strictly in order, starting at `current_block_height`. Up to
+    /// `conversion_pipeline_size` blocks are fetched and converted concurrently on the runtime's
+    /// worker threads, ahead of the block being consumed. Blocks are only fetched and converted as
+    /// the stream is polled, so a slow consumer pauses conversion.
+    pub fn block_stream(
+        &self,
+    ) -> BoxStream<'static, Result<Vec<TransactionPB>, FirehoseStreamError>> {
+        let pipeline_size = self
+            .context
+            .node_config
+            .firehose_stream
+            .conversion_pipeline_size
+            .max(1);
+        let streamer = self.fork(self.current_block_height);
+        let mut next_block_height = self.current_block_height;
+        stream::iter(self.current_block_height..)
+            .map(move |block_height| {
+                let mut streamer = streamer.fork(block_height);
+                // Dropping the remote handle (e.g. subscriber went away) cancels the conversion
+                let (conversion, handle) = async move {
+                    loop {
+                        let block = streamer.convert_next_block().await?;
+                        if !block.is_empty() {
+                            return Ok(block);
+                        }
+                    }
+                }
+                .remote_handle();
+                tokio::spawn(conversion);
+                handle
+            })
+            .buffered(pipeline_size)
+            // When skipping pruned blocks, several conversions can land on the same first available
+            // block, only the first one of those is kept
+            .filter(move |block| {
+                future::ready(match block {
+                    Ok(block) => {
+                        let block_height = block[0].block_height;
+                        if block_height < next_block_height {
+                            false
+                        } else {
+                            next_block_height = block_height + 1;
+                            true
+                        }
+                    }
+                    Err(_) => true,
+                })
+            })
+            .boxed()
+    }
+
+    /// Creates a streamer sharing the same context and resolver, positioned at `block_height`
+    fn fork(&self, block_height: u64) -> Self {
+        Self {
+            context: self.context.clone(),
+            resolver: self.resolver.clone(),
+            current_block_height: block_height,
+            current_epoch: self.current_epoch,
+            mp_sender: self.mp_sender.clone(),
+            cursor: None,
         }
     }
 
-    pub async fn convert_next_block(&mut self) -> Vec<TransactionPB> {
+    /// Converts the block at `current_block_height`. An empty result means the block is not
+    /// available yet (or failed validation) and should simply be retried. Errors are not retryable.
+    pub async fn convert_next_block(&mut self) -> Result<Vec<TransactionPB>, FirehoseStreamError> {
         let mut result: Vec<TransactionPB> = vec![];
 
-        let (block_start_version, block_last_version, _) = match self
+        let fetch_start = Instant::now();
+        let (block_start_version, block_last_version, block_event) = match self
             .context
             .db
             .get_block_info_by_height(self.current_block_height)
         {
             Ok(block_info) => block_info,
             Err(err) => {
-                // TODO: If block has been pruned, panic
+                self.handle_pruned_block()?;
                 warn!(
                     "[fh-stream] failed to get block info for block_height={}. Error: {}",
                     self.current_block_height, err
                 );
                 sleep(Duration::from_millis(300)).await;
-                return vec![];
+                return Ok(vec![]);
             }
         };
+        self.current_epoch = block_event.epoch();
 
         let ledger_info = self.context.get_latest_ledger_info_wrapped().unwrap();
         let block_timestamp = self
@@ -144,10 +375,10 @@ impl FirehoseStreamer {
                     block_start_version
                 )
             });
-        // We are validating the block as we convert and print each transactions. The rules are as follows:
+        // We are validating the block as we convert each transactions. The rules are as follows:
         // 1. first (and only first) transaction is a block metadata or genesis 2. versions are monotonically increasing 3. start and end versions match block boundaries
         // Retry if the block is not valid. Panic if there's anything wrong with encoding a transaction.
-        println!("\nFIRE BLOCK_START {}", self.current_block_height);
+        // Only fully converted and validated blocks are returned, so that every sink sees whole blocks.
 
         let transactions = match self.context.get_transactions(
             block_start_version,
@@ -160,14 +391,14 @@ impl FirehoseStreamer {
             Err(err) => {
                 error!("[fh-stream] failed to get transactions: {}", err);
                 sleep(Duration::from_millis(100)).await;
-                return vec![];
+                return Ok(vec![]);
             }
         };
 
         if transactions.is_empty() {
             debug!("[fh-stream] no transactions to send");
             sleep(Duration::from_millis(100)).await;
-            return vec![];
+            return Ok(vec![]);
         }
         debug!(
             "[fh-stream] got {} transactions from {} to {} [version on last actual transaction {}]",
@@ -176,6 +407,11 @@ impl FirehoseStreamer {
             block_last_version,
             transactions.last().map(|txn| txn.version).unwrap_or(0)
         );
+        metrics::STAGE_LATENCY
+            .with_label_values(&["fetch"])
+            .observe(fetch_start.elapsed().as_secs_f64());
+
+        let convert_start = Instant::now();
 
         let mut curr_version = block_start_version;
         for onchain_txn in transactions {
@@ -211,7 +447,7 @@ impl FirehoseStreamer {
                             self.current_block_height
                         );
                 sleep(Duration::from_millis(500)).await;
-                return vec![];
+                return Ok(vec![]);
             }
             if curr_version != txn_version {
                 error!(
@@ -219,11 +455,10 @@ impl FirehoseStreamer {
                     self.current_block_height, curr_version,
                 );
                 sleep(Duration::from_millis(500)).await;
-                return vec![];
+                return Ok(vec![]);
             }
             let txn_proto =
                 convert_transaction(&txn, self.current_block_height, self.current_epoch);
-            self.print_transaction(&txn_proto);
             result.push(txn_proto);
             curr_version += 1;
         }
@@ -236,13 +471,65 @@ impl FirehoseStreamer {
                 curr_version - 1,
             );
             sleep(Duration::from_millis(500)).await;
-            return vec![];
+            return Ok(vec![]);
         }
 
-        println!("\nFIRE BLOCK_END {}", self.current_block_height);
-        metrics::BLOCKS_SENT.inc();
+        metrics::STAGE_LATENCY
+            .with_label_values(&["convert"])
+            .observe(convert_start.elapsed().as_secs_f64());
+        self.update_prune_window_headroom(block_start_version, ledger_info.version());
         self.current_block_height += 1;
-        result
+        Ok(result)
+    }
+
+    /// If the current block has been pruned, either skips forward to the first available block
+    /// (when configured to) or fails, since waiting won't ever make the block available again.
+    fn handle_pruned_block(&mut self) -> Result<(), FirehoseStreamError> {
+        let first_available_block =
+            match check_block_pruned(&self.context.db, self.current_block_height) {
+                Ok(Some(first_available_block)) => first_available_block,
+                Ok(None) => return Ok(()),
+                Err(err) => {
+                    warn!(
+                        "[fh-stream] failed to check if block_height={} has been pruned. Error: {}",
+                        self.current_block_height, err
+                    );
+                    return Ok(());
+                }
+            };
+        metrics::PRUNED_BLOCK_ERRORS.inc();
+        if !self.context.node_config.firehose_stream.skip_pruned_blocks {
+            error!(
+                "[fh-stream] block_height={} has been pruned, first available block is {}",
+                self.current_block_height, first_available_block
+            );
+            return Err(FirehoseStreamError::BlockPruned {
+                block_height: self.current_block_height,
+                first_available_block,
+            });
+        }
+        warn!(
+            "[fh-stream] block_height={} has been pruned, skipping to block {}",
+            self.current_block_height, first_available_block
+        );
+        self.current_block_height = first_available_block;
+        Ok(())
+    }
+
+    /// Reports how many versions the stream is ahead of the ledger pruner, so operators can tell
+    /// whether a lagging stream is about to have its next blocks pruned from under it.
+    fn update_prune_window_headroom(&self, block_start_version: u64, latest_version: u64) {
+        let ledger_pruner_config = &self
+            .context
+            .node_config
+            .storage
+            .storage_pruner_config
+            .ledger_pruner_config;
+        if ledger_pruner_config.enable {
+            let headroom = block_start_version as i64 + ledger_pruner_config.prune_window as i64
+                - latest_version as i64;
+            metrics::PRUNE_WINDOW_HEADROOM_VERSIONS.set(headroom);
+        }
     }
 
     /// First, and only first, transaction in a block has to be bmt or genesis
@@ -254,6 +541,31 @@ impl FirehoseStreamer {
         is_first_txn == is_bm_or_genesis
     }
 
+    /// Prints a converted block in the format expected by the StreamingFast Firehose console reader
+    fn print_block(&self, block: &[TransactionPB]) {
+        let block_height = block[0].block_height;
+        println!("\nFIRE BLOCK_START {}", block_height);
+        for transaction in block {
+            self.print_transaction(transaction);
+        }
+        println!("\nFIRE BLOCK_END {}", block_height);
+        metrics::BLOCKS_SENT.inc();
+    }
+
+    /// Block is only recorded after it has been fully printed, so a partially printed block gets re-emitted in full
+    fn save_cursor(&self, block_height: u64) {
+        if let Some(cursor) = &self.cursor {
+            if let Err(err) = cursor.save(block_height) {
+                error!(
+                    "[fh-stream] failed to persist cursor at block {} to {:?}. Error: {:?}",
+                    block_height,
+                    cursor.path(),
+                    err
+                );
+            }
+        }
+    }
+
     fn print_transaction(&self, transaction: &TransactionPB) {
         let mut buf = vec![];
         transaction.encode(&mut buf).unwrap_or_else(|_| {
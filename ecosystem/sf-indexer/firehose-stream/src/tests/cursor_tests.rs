@@ -0,0 +1,36 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::cursor::BlockCursor;
+use aptos_temppath::TempPath;
+
+#[test]
+fn test_cursor_roundtrip() {
+    let path = TempPath::new();
+    let cursor = BlockCursor::new(path.path());
+    assert_eq!(cursor.load().unwrap(), None);
+
+    cursor.save(10).unwrap();
+    assert_eq!(cursor.load().unwrap(), Some(10));
+
+    // Saving again overwrites the previous cursor
+    cursor.save(11).unwrap();
+    assert_eq!(cursor.load().unwrap(), Some(11));
+    assert_eq!(BlockCursor::new(path.path()).load().unwrap(), Some(11));
+}
+
+#[test]
+fn test_cursor_with_tmp_extension() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let cursor = BlockCursor::new(dir.path().join("cursor.tmp"));
+    cursor.save(10).unwrap();
+    assert_eq!(cursor.load().unwrap(), Some(10));
+}
+
+#[test]
+fn test_malformed_cursor() {
+    let path = TempPath::new();
+    std::fs::write(path.path(), "not a block").unwrap();
+    assert!(BlockCursor::new(path.path()).load().is_err());
+}
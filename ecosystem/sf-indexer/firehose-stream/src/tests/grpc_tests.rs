@@ -0,0 +1,94 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    grpc::FirehoseGrpcService,
+    proto::{firehose_stream_server::FirehoseStream, StreamBlocksRequest},
+    tests::new_test_context,
+};
+
+use aptos_api_test_context::current_function_name;
+use aptos_protos::extractor::v1::transaction::TransactionType;
+use futures::{channel::mpsc::channel, StreamExt};
+use std::sync::Arc;
+use tonic::{Code, Request};
+
+fn new_service(
+    context: aptos_api::context::Context,
+    max_subscribers: usize,
+) -> FirehoseGrpcService {
+    let (mp_sender, _mp_events) = channel(1);
+    FirehoseGrpcService::new(Arc::new(context), mp_sender, 2, max_subscribers)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_blocks_in_order() {
+    let mut test_context = new_test_context(current_function_name!());
+    let account = test_context.gen_account();
+    let txn = test_context.create_user_account(&account);
+    test_context.commit_block(&vec![txn.clone()]).await;
+
+    let service = new_service(test_context.context.clone(), 1);
+    let stream = service
+        .stream_blocks(Request::new(StreamBlocksRequest {
+            starting_block: 0,
+            end_block: Some(1),
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    let blocks: Vec<_> = stream.map(|block| block.unwrap()).collect().await;
+
+    assert_eq!(blocks.len(), 2);
+    assert_eq!(blocks[0].height, 0);
+    assert_eq!(blocks[0].transactions[0].r#type(), TransactionType::Genesis);
+    assert_eq!(blocks[1].height, 1);
+    assert_eq!(
+        blocks[1].transactions[0].r#type(),
+        TransactionType::BlockMetadata
+    );
+    assert_eq!(blocks[1].transactions[1].r#type(), TransactionType::User);
+    assert_eq!(
+        blocks[1].chain_id,
+        test_context.context.chain_id().id() as u32
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_blocks_rejects_unavailable_starting_block() {
+    let test_context = new_test_context(current_function_name!());
+
+    let service = new_service(test_context.context.clone(), 1);
+    let status = service
+        .stream_blocks(Request::new(StreamBlocksRequest {
+            starting_block: 1000,
+            end_block: None,
+        }))
+        .await
+        .unwrap_err();
+
+    assert_eq!(status.code(), Code::NotFound);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_blocks_limits_subscribers() {
+    let test_context = new_test_context(current_function_name!());
+
+    let service = new_service(test_context.context.clone(), 1);
+    let _first = service
+        .stream_blocks(Request::new(StreamBlocksRequest {
+            starting_block: 0,
+            end_block: None,
+        }))
+        .await
+        .unwrap();
+    let status = service
+        .stream_blocks(Request::new(StreamBlocksRequest {
+            starting_block: 0,
+            end_block: None,
+        }))
+        .await
+        .unwrap_err();
+
+    assert_eq!(status.code(), Code::ResourceExhausted);
+}
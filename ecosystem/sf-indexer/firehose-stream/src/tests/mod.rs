@@ -1,7 +1,10 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+mod cursor_tests;
+mod grpc_tests;
 mod proto_converter_tests;
+mod pruning_tests;
 
 pub use aptos_api_test_context::{new_test_context as super_new_test_context, TestContext};
 
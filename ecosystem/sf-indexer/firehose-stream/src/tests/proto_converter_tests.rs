@@ -16,6 +16,8 @@ use aptos_protos::extractor::v1::{
 
 use aptos_sdk::types::{account_config::aptos_test_root_address, LocalAccount};
 
+use futures::StreamExt;
+
 use move_core_types::{account_address::AccountAddress, value::MoveValue};
 use move_package::BuildConfig;
 use serde_json::{json, Value};
@@ -27,7 +29,7 @@ async fn test_genesis_works() {
 
     let context = Arc::new(test_context.context);
     let mut streamer = FirehoseStreamer::new(context, 0, None);
-    let converted = streamer.convert_next_block().await;
+    let converted = streamer.convert_next_block().await.unwrap();
 
     // position 0 should be genesis
     let txn = converted.first().unwrap().clone();
@@ -55,13 +57,13 @@ async fn test_block_transactions_work() {
     let mut streamer = FirehoseStreamer::new(context, 0, None);
 
     // emulating real stream, getting first block
-    let block_0 = streamer.convert_next_block().await;
+    let block_0 = streamer.convert_next_block().await.unwrap();
     let txn = block_0.first().unwrap().clone();
     assert_eq!(txn.version, 0);
     assert_eq!(txn.r#type(), TransactionType::Genesis);
 
     // getting second block
-    let block_1 = streamer.convert_next_block().await;
+    let block_1 = streamer.convert_next_block().await.unwrap();
     // block metadata expected
     let txn = block_1[0].clone();
     assert_eq!(txn.version, 1);
@@ -110,6 +112,37 @@ async fn test_block_transactions_work() {
     assert_eq!(txn.r#type(), TransactionType::StateCheckpoint);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_pipelined_blocks_are_in_order() {
+    let mut test_context = new_test_context(current_function_name!());
+
+    let mut root_account = test_context.root_account();
+    for _ in 0..3 {
+        let account = test_context.gen_account();
+        let txn = test_context.create_user_account_by(&mut root_account, &account);
+        test_context.commit_block(&vec![txn.clone()]).await;
+    }
+
+    let mut context = test_context.context.clone();
+    context.node_config.firehose_stream.conversion_pipeline_size = 3;
+    let streamer = FirehoseStreamer::new(Arc::new(context), 0, None);
+    let blocks: Vec<Vec<TransactionPB>> = streamer
+        .block_stream()
+        .take(4)
+        .map(|block| block.unwrap())
+        .collect()
+        .await;
+
+    let mut expected_version = 0;
+    for (block_height, block) in blocks.iter().enumerate() {
+        for txn in block {
+            assert_eq!(txn.block_height, block_height as u64);
+            assert_eq!(txn.version, expected_version);
+            expected_version += 1;
+        }
+    }
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_block_height_and_ts_work() {
     let start_ts_usecs = 1000 * 1000000;
@@ -270,9 +303,9 @@ async fn build_test_module(account: AccountAddress) -> Vec<u8> {
 
 async fn fetch_all_stream(mut streamer: FirehoseStreamer) -> Vec<TransactionPB> {
     // Overfetching should work
-    let mut res = streamer.convert_next_block().await;
+    let mut res = streamer.convert_next_block().await.unwrap();
     for _ in 0..20 {
-        res.append(&mut streamer.convert_next_block().await);
+        res.append(&mut streamer.convert_next_block().await.unwrap());
     }
     res
 }
@@ -0,0 +1,100 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::runtime::{check_block_pruned, FirehoseStreamError, FirehoseStreamer};
+use aptos_api_test_context::{current_function_name, new_test_context_with_pruner, TestContext};
+use aptos_config::config::{LedgerPrunerConfig, PrunerConfig, NO_OP_STORAGE_PRUNER_CONFIG};
+use aptos_types::transaction::Version;
+use std::{sync::Arc, time::Duration};
+use storage_interface::DbReader;
+
+/// Commits 3 blocks of 3 versions each (block metadata, user transaction and state checkpoint) to a
+/// DB keeping the last `prune_window` versions, and waits until the ledger is pruned up to
+/// `latest_version - prune_window`. Returns the context and the height of the last block.
+async fn new_pruned_test_context(test_name: String, prune_window: u64) -> (TestContext, u64) {
+    let mut context = new_test_context_with_pruner(
+        test_name,
+        PrunerConfig {
+            ledger_pruner_config: LedgerPrunerConfig {
+                enable: true,
+                prune_window,
+                batch_size: 1,
+                user_pruning_window_offset: 0,
+            },
+            ..NO_OP_STORAGE_PRUNER_CONFIG
+        },
+    );
+    for _ in 0..3 {
+        let account = context.gen_account();
+        let txn = context.create_user_account(&account);
+        context.commit_block(&[txn]).await;
+    }
+
+    let db = context.context.db.clone();
+    let latest_version = db.get_latest_version().unwrap();
+    let (last_block_start_version, _, last_block_event) =
+        db.get_block_info_by_version(latest_version).unwrap();
+    assert_eq!(last_block_start_version, latest_version - 2);
+
+    let pruned_until = latest_version - prune_window;
+    wait_for_pruner(&db, pruned_until).await;
+    assert_eq!(db.get_first_viable_txn_version().unwrap(), pruned_until);
+    (context, last_block_event.height())
+}
+
+async fn wait_for_pruner(db: &Arc<dyn DbReader>, min_readable_version: Version) {
+    for _ in 0..500 {
+        if db.get_first_write_set_version().unwrap() == Some(min_readable_version) {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!(
+        "Ledger wasn't pruned up to version {}",
+        min_readable_version
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_block_boundary_pruned() {
+    // Prunes the second to last block as a whole
+    let (context, last_block) = new_pruned_test_context(current_function_name!(), 2).await;
+    let db = context.context.db.clone();
+
+    assert_eq!(
+        check_block_pruned(&db, last_block - 1).unwrap(),
+        Some(last_block)
+    );
+    assert_eq!(check_block_pruned(&db, last_block).unwrap(), None);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_partially_pruned_block_is_unavailable() {
+    // Prunes the first version of the second to last block, which holds its NewBlockEvent
+    let (context, last_block) = new_pruned_test_context(current_function_name!(), 4).await;
+    let db = context.context.db.clone();
+    assert!(db.get_block_info_by_height(last_block - 1).is_err());
+
+    assert_eq!(
+        check_block_pruned(&db, last_block - 2).unwrap(),
+        Some(last_block)
+    );
+    assert_eq!(
+        check_block_pruned(&db, last_block - 1).unwrap(),
+        Some(last_block)
+    );
+    assert_eq!(check_block_pruned(&db, last_block).unwrap(), None);
+
+    let err = FirehoseStreamer::try_new(Arc::new(context.context), last_block - 1, None)
+        .err()
+        .unwrap();
+    match err.downcast::<FirehoseStreamError>().unwrap() {
+        FirehoseStreamError::BlockPruned {
+            block_height,
+            first_available_block,
+        } => {
+            assert_eq!(block_height, last_block - 1);
+            assert_eq!(first_available_block, last_block);
+        }
+    }
+}
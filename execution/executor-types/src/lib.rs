@@ -124,6 +124,16 @@ pub trait TransactionReplayer: Send {
         transaction_infos: Vec<TransactionInfo>,
     ) -> Result<()>;
 
+    /// Like `replay()`, but applies the write sets and events the transactions produced instead
+    /// of executing them. The results are still checked against `transaction_infos`.
+    fn replay_write_sets(
+        &self,
+        transactions: Vec<Transaction>,
+        transaction_infos: Vec<TransactionInfo>,
+        write_sets: Vec<WriteSet>,
+        event_vecs: Vec<Vec<ContractEvent>>,
+    ) -> Result<()>;
+
     fn commit(&self) -> Result<Arc<ExecutedChunk>>;
 }
 
@@ -15,19 +15,23 @@ use crate::{
         APTOS_EXECUTOR_EXECUTE_CHUNK_SECONDS, APTOS_EXECUTOR_VM_EXECUTE_CHUNK_SECONDS,
     },
 };
-use anyhow::Result;
+use anyhow::{ensure, Result};
 use aptos_infallible::{Mutex, RwLock};
 use aptos_logger::prelude::*;
 use aptos_state_view::StateViewId;
 use aptos_types::{
+    contract_event::ContractEvent,
     ledger_info::LedgerInfoWithSignatures,
     transaction::{
-        Transaction, TransactionInfo, TransactionListWithProof, TransactionOutputListWithProof,
+        Transaction, TransactionInfo, TransactionListWithProof, TransactionOutput,
+        TransactionOutputListWithProof, TransactionStatus,
     },
+    write_set::WriteSet,
 };
 use aptos_vm::VMExecutor;
 use executor_types::{
-    ChunkCommitNotification, ChunkExecutorTrait, ExecutedChunk, TransactionReplayer,
+    in_memory_state_calculator::NEW_EPOCH_EVENT_KEY, ChunkCommitNotification, ChunkExecutorTrait,
+    ExecutedChunk, TransactionReplayer,
 };
 use fail::fail_point;
 use std::{marker::PhantomData, sync::Arc};
@@ -306,6 +310,21 @@ impl<V: VMExecutor> TransactionReplayer for ChunkExecutor<V> {
             .replay(transactions, transaction_infos)
     }
 
+    fn replay_write_sets(
+        &self,
+        transactions: Vec<Transaction>,
+        transaction_infos: Vec<TransactionInfo>,
+        write_sets: Vec<WriteSet>,
+        event_vecs: Vec<Vec<ContractEvent>>,
+    ) -> Result<()> {
+        self.maybe_initialize()?;
+        self.inner
+            .read()
+            .as_ref()
+            .expect("not reset")
+            .replay_write_sets(transactions, transaction_infos, write_sets, event_vecs)
+    }
+
     fn commit(&self) -> Result<Arc<ExecutedChunk>> {
         self.inner.read().as_ref().expect("not reset").commit()
     }
@@ -347,6 +366,70 @@ impl<V: VMExecutor> TransactionReplayer for ChunkExecutorInner<V> {
         Ok(())
     }
 
+    fn replay_write_sets(
+        &self,
+        transactions: Vec<Transaction>,
+        mut transaction_infos: Vec<TransactionInfo>,
+        write_sets: Vec<WriteSet>,
+        event_vecs: Vec<Vec<ContractEvent>>,
+    ) -> Result<()> {
+        ensure!(
+            transactions.len() == transaction_infos.len()
+                && transactions.len() == write_sets.len()
+                && transactions.len() == event_vecs.len(),
+            "Lengths of transactions, transaction infos, write sets and events don't match."
+        );
+        let (_persisted_view, mut latest_view) =
+            self.commit_queue.lock().persisted_and_latest_view();
+
+        let mut to_apply: Vec<_> =
+            itertools::multizip((transactions, &transaction_infos, write_sets, event_vecs))
+                .map(|(txn, txn_info, write_set, events)| {
+                    let output = TransactionOutput::new(
+                        write_set,
+                        events,
+                        txn_info.gas_used(),
+                        TransactionStatus::Keep(txn_info.status().clone()),
+                    );
+                    (txn, output)
+                })
+                .collect();
+
+        let mut executed_chunk = ExecutedChunk::default();
+        while !to_apply.is_empty() {
+            // Transactions after a reconfiguration are applied on top of the new epoch, like
+            // `replay()` retries them.
+            let num_to_apply = to_apply
+                .iter()
+                .position(|(_txn, output)| {
+                    output
+                        .events()
+                        .iter()
+                        .any(|e| *e.key() == *NEW_EPOCH_EVENT_KEY)
+                })
+                .map_or(to_apply.len(), |idx| idx + 1);
+            let rest = to_apply.split_off(num_to_apply);
+
+            let state_view = self.state_view(&latest_view)?;
+            let (executed, to_discard, to_retry) =
+                ChunkOutput::by_transaction_output(to_apply, state_view)?
+                    .apply_to_ledger(&latest_view)?;
+            ensure_no_discard(to_discard)?;
+            ensure_no_retry(to_retry)?;
+            executed.ensure_transaction_infos_match(&transaction_infos[..num_to_apply])?;
+            transaction_infos.drain(..num_to_apply);
+
+            to_apply = rest;
+            executed_chunk = executed_chunk.combine(executed)?;
+            latest_view = executed_chunk.result_view.clone();
+        }
+
+        // Add result to commit queue.
+        self.commit_queue.lock().enqueue(executed_chunk);
+
+        Ok(())
+    }
+
     fn commit(&self) -> Result<Arc<ExecutedChunk>> {
         self.commit_chunk_impl()
     }
@@ -661,7 +661,20 @@ proptest! {
             // replay txns in one batch across epoch boundary,
             // and the replayer should deal with `Retry`s automatically
             let replayer = chunk_executor_tests::TestExecutor::new();
-            replayer.executor.replay(block.txns, txn_infos).unwrap();
+            replayer.executor.replay(block.txns, txn_infos.clone()).unwrap();
+            replayer.executor.commit().unwrap();
+            let replayed_db = replayer.db.reader.clone();
+            prop_assert_eq!(
+                replayed_db.get_accumulator_root_hash(num_txns).unwrap(),
+                db.get_accumulator_root_hash(num_txns).unwrap()
+            );
+
+            // apply the write sets instead of executing, across the epoch boundary as well
+            let txn_output_list = db.get_transaction_outputs(1 /* start version */, num_txns as u64, num_txns as Version /* ledger version */).unwrap();
+            let (txns, outputs): (Vec<_>, Vec<_>) = txn_output_list.transactions_and_outputs.into_iter().unzip();
+            let (write_sets, event_vecs): (Vec<_>, Vec<_>) = outputs.into_iter().map(TransactionOutput::into).unzip();
+            let replayer = chunk_executor_tests::TestExecutor::new();
+            replayer.executor.replay_write_sets(txns, txn_infos, write_sets, event_vecs).unwrap();
             replayer.executor.commit().unwrap();
             let replayed_db = replayer.db.reader.clone();
             prop_assert_eq!(
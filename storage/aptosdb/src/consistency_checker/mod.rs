@@ -0,0 +1,692 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides `ConsistencyChecker`, which verifies the data in an `AptosDB` against the
+//! authenticated data structures that the DB itself holds: transactions, write sets and events
+//! against their `TransactionInfo`s, `TransactionInfo`s against the transaction accumulator, and
+//! the state merkle trees against the state checkpoint hashes and against themselves.
+//!
+//! It reads the DB through plain iterators and point lookups, so it can run alongside a node that
+//! keeps committing to the same DB, either in the background (see
+//! [`AptosDB::start_consistency_checker`](crate::AptosDB::start_consistency_checker)) or through
+//! the `db-check` tool.
+
+use crate::{
+    event_store::EventStore,
+    ledger_store::LedgerStore,
+    metrics::{CONSISTENCY_CHECKER_INCONSISTENCIES, CONSISTENCY_CHECKER_PROGRESS},
+    pruner::{pruner_manager::PrunerManager, state_store::generics::StaleNodeIndexSchemaTrait},
+    schema::{
+        db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
+        jellyfish_merkle_node::JellyfishMerkleNodeSchema,
+        stale_node_index::StaleNodeIndexSchema,
+        stale_node_index_cross_epoch::StaleNodeIndexCrossEpochSchema,
+        transaction_info::TransactionInfoSchema,
+    },
+    state_merkle_db::Node,
+    state_store::StateStore,
+    transaction_store::TransactionStore,
+    OTHER_TIMERS_SECONDS,
+};
+use anyhow::{ensure, Result};
+use aptos_config::config::ConsistencyCheckerConfig;
+use aptos_crypto::{
+    hash::{CryptoHash, EventAccumulatorHasher, TransactionAccumulatorHasher},
+    HashValue,
+};
+use aptos_infallible::Mutex;
+use aptos_jellyfish_merkle::{node_type::NodeKey, StaleNodeIndex};
+use aptos_logger::prelude::*;
+use aptos_types::{proof::accumulator::InMemoryAccumulator, transaction::Version};
+use itertools::izip;
+use schemadb::{schema::KeyCodec, ReadOptions, DB};
+use std::{
+    sync::{mpsc, Arc},
+    thread,
+    thread::JoinHandle,
+    time::Duration,
+};
+
+#[cfg(test)]
+mod test;
+
+/// A piece of data in the DB that doesn't match what authenticates it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Inconsistency {
+    /// The transaction doesn't hash to the `transaction_hash` in its `TransactionInfo`.
+    TransactionHashMismatch {
+        version: Version,
+        expected: HashValue,
+        actual: HashValue,
+    },
+    /// The write set doesn't hash to the `state_change_hash` in its `TransactionInfo`.
+    WriteSetHashMismatch {
+        version: Version,
+        expected: HashValue,
+        actual: HashValue,
+    },
+    /// The accumulator of the events doesn't match the `event_root_hash` in its `TransactionInfo`.
+    EventRootHashMismatch {
+        version: Version,
+        expected: HashValue,
+        actual: HashValue,
+    },
+    /// The persisted event accumulator doesn't match the `event_root_hash` in the
+    /// `TransactionInfo`.
+    EventAccumulatorMismatch {
+        version: Version,
+        expected: HashValue,
+        actual: HashValue,
+    },
+    /// The transaction accumulator at `version` doesn't match, either the persisted one against
+    /// the one recalculated from the `TransactionInfo`s, or the latest ledger info against the
+    /// persisted one.
+    TransactionAccumulatorMismatch {
+        version: Version,
+        expected: HashValue,
+        actual: HashValue,
+    },
+    /// The root of the state tree at `version` doesn't match the `state_checkpoint_hash` in the
+    /// `TransactionInfo`.
+    StateCheckpointHashMismatch {
+        version: Version,
+        expected: HashValue,
+        actual: HashValue,
+    },
+    /// The node doesn't hash to what its parent holds for it.
+    NodeHashMismatch {
+        node_key: NodeKey,
+        expected: HashValue,
+        actual: HashValue,
+    },
+    /// The node is not referenced by the parent it must have in the same version.
+    OrphanedNode { node_key: NodeKey },
+    /// The node is referenced by a readable state tree, but it's gone.
+    MissingNode { node_key: NodeKey },
+    /// The node became stale before what the pruner claims to have pruned, but it's still
+    /// indexed.
+    UnprunedStaleNode { stale_node_index: StaleNodeIndex },
+}
+
+impl Inconsistency {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::TransactionHashMismatch { .. } => "transaction_hash_mismatch",
+            Self::WriteSetHashMismatch { .. } => "write_set_hash_mismatch",
+            Self::EventRootHashMismatch { .. } => "event_root_hash_mismatch",
+            Self::EventAccumulatorMismatch { .. } => "event_accumulator_mismatch",
+            Self::TransactionAccumulatorMismatch { .. } => "transaction_accumulator_mismatch",
+            Self::StateCheckpointHashMismatch { .. } => "state_checkpoint_hash_mismatch",
+            Self::NodeHashMismatch { .. } => "node_hash_mismatch",
+            Self::OrphanedNode { .. } => "orphaned_node",
+            Self::MissingNode { .. } => "missing_node",
+            Self::UnprunedStaleNode { .. } => "unpruned_stale_node",
+        }
+    }
+}
+
+/// What the checks went through and what they found.
+#[derive(Debug, Default)]
+pub struct ConsistencyReport {
+    pub num_transactions_checked: usize,
+    pub num_nodes_checked: usize,
+    pub inconsistencies: Vec<Inconsistency>,
+}
+
+impl ConsistencyReport {
+    pub fn is_consistent(&self) -> bool {
+        self.inconsistencies.is_empty()
+    }
+
+    fn add(&mut self, inconsistency: Inconsistency) {
+        error!(
+            inconsistency = ?inconsistency,
+            "Found inconsistency in AptosDB."
+        );
+        CONSISTENCY_CHECKER_INCONSISTENCIES
+            .with_label_values(&[inconsistency.kind()])
+            .inc();
+        self.inconsistencies.push(inconsistency);
+    }
+}
+
+/// Where the next batch of checks starts, see [`ConsistencyChecker::check_next_batch`].
+#[derive(Clone, Debug)]
+pub struct ConsistencyCheckProgress {
+    /// The next transaction to check.
+    pub next_version: Version,
+    /// The next state tree node to check, or where to look for it.
+    pub next_node_key: NodeKey,
+}
+
+impl ConsistencyCheckProgress {
+    /// Starts from the transactions and the state tree nodes at `start_version`, or the oldest
+    /// ones not pruned if they are later.
+    pub fn new(start_version: Version) -> Self {
+        Self {
+            next_version: start_version,
+            next_node_key: NodeKey::new_empty_path(start_version),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ConsistencyChecker {
+    ledger_db: Arc<DB>,
+    ledger_store: Arc<LedgerStore>,
+    event_store: Arc<EventStore>,
+    transaction_store: Arc<TransactionStore>,
+    state_store: Arc<StateStore>,
+}
+
+impl ConsistencyChecker {
+    pub(crate) fn new(
+        ledger_db: Arc<DB>,
+        ledger_store: Arc<LedgerStore>,
+        event_store: Arc<EventStore>,
+        transaction_store: Arc<TransactionStore>,
+        state_store: Arc<StateStore>,
+    ) -> Self {
+        Self {
+            ledger_db,
+            ledger_store,
+            event_store,
+            transaction_store,
+            state_store,
+        }
+    }
+
+    /// Checks the next `batch_size` transactions and the next `batch_size` state tree nodes. Once
+    /// both are caught up with the DB, checks the stale node indices and returns `true`, after
+    /// which calling it again checks what's committed since.
+    pub fn check_next_batch(
+        &self,
+        progress: &mut ConsistencyCheckProgress,
+        batch_size: usize,
+        report: &mut ConsistencyReport,
+    ) -> Result<bool> {
+        ensure!(batch_size > 0, "Batch size must be positive.");
+        let mut caught_up = true;
+
+        if let Some((latest_version, _)) = self.ledger_store.get_latest_transaction_info_option()? {
+            let first_version = std::cmp::max(
+                progress.next_version,
+                self.get_first_transaction_info_version()?.unwrap_or(0),
+            );
+            if first_version <= latest_version {
+                let num_transactions =
+                    std::cmp::min(batch_size as u64, latest_version - first_version + 1);
+                self.check_transactions(first_version, num_transactions as usize, report)?;
+                progress.next_version = first_version + num_transactions;
+                CONSISTENCY_CHECKER_PROGRESS
+                    .with_label_values(&["ledger"])
+                    .set(progress.next_version as i64);
+                caught_up = progress.next_version > latest_version;
+            }
+        }
+
+        // Nodes older than both pruners' progress are only there as part of later trees, and
+        // those are checked at the versions of their roots.
+        let min_readable_version = std::cmp::min(
+            self.state_store.state_pruner.get_min_readable_version(),
+            self.state_store
+                .epoch_snapshot_pruner
+                .get_min_readable_version(),
+        );
+        if progress.next_node_key.version() < min_readable_version {
+            progress.next_node_key = NodeKey::new_empty_path(min_readable_version);
+        }
+        if let Some(latest_snapshot_version) = self
+            .state_store
+            .state_merkle_db
+            .get_state_snapshot_version_before(Version::max_value())?
+        {
+            if progress.next_node_key.version() <= latest_snapshot_version {
+                match self.check_state_merkle_nodes(&progress.next_node_key, batch_size, report)? {
+                    Some(next_node_key) => {
+                        progress.next_node_key = next_node_key;
+                        caught_up = false;
+                    }
+                    None => {
+                        progress.next_node_key =
+                            NodeKey::new_empty_path(latest_snapshot_version + 1);
+                    }
+                }
+                CONSISTENCY_CHECKER_PROGRESS
+                    .with_label_values(&["state_merkle"])
+                    .set(progress.next_node_key.version() as i64);
+            }
+        }
+
+        if caught_up {
+            self.check_stale_node_indices(batch_size, report)?;
+        }
+        Ok(caught_up)
+    }
+
+    /// Checks `num_transactions` transactions starting from `first_version`, together with their
+    /// write sets and events, against their `TransactionInfo`s, and the `TransactionInfo`s against
+    /// the transaction accumulator.
+    pub fn check_transactions(
+        &self,
+        first_version: Version,
+        num_transactions: usize,
+        report: &mut ConsistencyReport,
+    ) -> Result<()> {
+        let _timer = OTHER_TIMERS_SECONDS
+            .with_label_values(&["consistency_check_transactions"])
+            .start_timer();
+        if num_transactions == 0 {
+            return Ok(());
+        }
+
+        let txns = self
+            .transaction_store
+            .get_transaction_iter(first_version, num_transactions)?;
+        let txn_infos = self
+            .ledger_store
+            .get_transaction_info_iter(first_version, num_transactions)?;
+        let write_sets = self
+            .transaction_store
+            .get_write_set_iter(first_version, num_transactions)?;
+        let event_vecs = self
+            .event_store
+            .get_events_by_version_iter(first_version, num_transactions)?;
+
+        let mut txn_info_hashes = Vec::with_capacity(num_transactions);
+        for (version, txn, txn_info, write_set, events) in
+            izip!(first_version.., txns, txn_infos, write_sets, event_vecs)
+        {
+            let (txn, txn_info, write_set, events) = (txn?, txn_info?, write_set?, events?);
+
+            let txn_hash = txn.hash();
+            if txn_hash != txn_info.transaction_hash() {
+                report.add(Inconsistency::TransactionHashMismatch {
+                    version,
+                    expected: txn_info.transaction_hash(),
+                    actual: txn_hash,
+                });
+            }
+
+            let write_set_hash = CryptoHash::hash(&write_set);
+            if write_set_hash != txn_info.state_change_hash() {
+                report.add(Inconsistency::WriteSetHashMismatch {
+                    version,
+                    expected: txn_info.state_change_hash(),
+                    actual: write_set_hash,
+                });
+            }
+
+            let event_hashes: Vec<_> = events.iter().map(CryptoHash::hash).collect();
+            let event_root_hash =
+                InMemoryAccumulator::<EventAccumulatorHasher>::from_leaves(&event_hashes)
+                    .root_hash();
+            if event_root_hash != txn_info.event_root_hash() {
+                report.add(Inconsistency::EventRootHashMismatch {
+                    version,
+                    expected: txn_info.event_root_hash(),
+                    actual: event_root_hash,
+                });
+            }
+            let persisted_event_root_hash = self
+                .event_store
+                .get_event_root_hash(version, events.len() as u64)?;
+            if persisted_event_root_hash != txn_info.event_root_hash() {
+                report.add(Inconsistency::EventAccumulatorMismatch {
+                    version,
+                    expected: txn_info.event_root_hash(),
+                    actual: persisted_event_root_hash,
+                });
+            }
+
+            txn_info_hashes.push(txn_info.hash());
+            report.num_transactions_checked += 1;
+        }
+        ensure!(
+            txn_info_hashes.len() == num_transactions,
+            "Transaction data is missing after version {}, expected {} transactions, got {}.",
+            first_version,
+            num_transactions,
+            txn_info_hashes.len(),
+        );
+
+        let last_version = first_version + num_transactions as Version - 1;
+        let frozen_subtrees = self.ledger_store.get_frozen_subtree_hashes(first_version)?;
+        let root_hash = InMemoryAccumulator::<TransactionAccumulatorHasher>::new(
+            frozen_subtrees,
+            first_version,
+        )?
+        .append(&txn_info_hashes)
+        .root_hash();
+        let persisted_root_hash = self.ledger_store.get_root_hash(last_version)?;
+        if root_hash != persisted_root_hash {
+            report.add(Inconsistency::TransactionAccumulatorMismatch {
+                version: last_version,
+                expected: persisted_root_hash,
+                actual: root_hash,
+            });
+        }
+
+        if let Some(ledger_info_with_sigs) = self.ledger_store.get_latest_ledger_info_option() {
+            let ledger_info = ledger_info_with_sigs.ledger_info();
+            if (first_version..=last_version).contains(&ledger_info.version()) {
+                let persisted_root_hash = self.ledger_store.get_root_hash(ledger_info.version())?;
+                if persisted_root_hash != ledger_info.transaction_accumulator_hash() {
+                    report.add(Inconsistency::TransactionAccumulatorMismatch {
+                        version: ledger_info.version(),
+                        expected: ledger_info.transaction_accumulator_hash(),
+                        actual: persisted_root_hash,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks up to `limit` state tree nodes starting from `start_key`, in the order they are
+    /// stored, i.e. by version. Returns the key of the next node to check, or `None` if all nodes
+    /// have been checked.
+    ///
+    /// Nodes are only checked at versions with a readable tree: nodes written at a version of which
+    /// the root has been pruned can still be referenced by later trees, but their own parents can
+    /// be gone.
+    pub fn check_state_merkle_nodes(
+        &self,
+        start_key: &NodeKey,
+        limit: usize,
+        report: &mut ConsistencyReport,
+    ) -> Result<Option<NodeKey>> {
+        let _timer = OTHER_TIMERS_SECONDS
+            .with_label_values(&["consistency_check_state_merkle_nodes"])
+            .start_timer();
+
+        let mut iter = self
+            .state_store
+            .state_merkle_db
+            .iter::<JellyfishMerkleNodeSchema>(ReadOptions::default())?;
+        iter.seek(start_key)?;
+
+        let mut readable_version: Option<(Version, bool)> = None;
+        for _ in 0..limit {
+            let (node_key, node) = match iter.next().transpose()? {
+                Some(key_and_node) => key_and_node,
+                None => return Ok(None),
+            };
+            let version = node_key.version();
+            let is_readable = match readable_version {
+                Some((v, is_readable)) if v == version => is_readable,
+                _ => {
+                    let is_readable = self.is_state_tree_readable(version)?;
+                    readable_version = Some((version, is_readable));
+                    is_readable
+                }
+            };
+            if is_readable {
+                self.check_state_merkle_node(&node_key, &node, report)?;
+                report.num_nodes_checked += 1;
+            }
+        }
+
+        Ok(iter.next().transpose()?.map(|(node_key, _node)| node_key))
+    }
+
+    /// Checks that no stale node index is left behind the pruners, looking at up to `limit`
+    /// indices of each kind.
+    pub fn check_stale_node_indices(
+        &self,
+        limit: usize,
+        report: &mut ConsistencyReport,
+    ) -> Result<()> {
+        let _timer = OTHER_TIMERS_SECONDS
+            .with_label_values(&["consistency_check_stale_node_indices"])
+            .start_timer();
+
+        self.check_stale_node_indices_impl::<StaleNodeIndexSchema>(
+            self.state_store.state_pruner.get_min_readable_version(),
+            limit,
+            report,
+        )?;
+        self.check_stale_node_indices_impl::<StaleNodeIndexCrossEpochSchema>(
+            self.state_store
+                .epoch_snapshot_pruner
+                .get_min_readable_version(),
+            limit,
+            report,
+        )
+    }
+
+    fn check_stale_node_indices_impl<S: StaleNodeIndexSchemaTrait>(
+        &self,
+        min_readable_version: Version,
+        limit: usize,
+        report: &mut ConsistencyReport,
+    ) -> Result<()>
+    where
+        StaleNodeIndex: KeyCodec<S>,
+    {
+        let mut iter = self
+            .state_store
+            .state_merkle_db
+            .iter::<S>(ReadOptions::default())?;
+        iter.seek_to_first();
+        // The pruner goes through the indices in the order of `stale_since_version` and deletes
+        // the nodes together with the indices, so none is expected before its progress.
+        for res in iter.take(limit) {
+            let (stale_node_index, _) = res?;
+            if stale_node_index.stale_since_version >= min_readable_version {
+                break;
+            }
+            report.add(Inconsistency::UnprunedStaleNode { stale_node_index });
+        }
+        Ok(())
+    }
+
+    fn check_state_merkle_node(
+        &self,
+        node_key: &NodeKey,
+        node: &Node,
+        report: &mut ConsistencyReport,
+    ) -> Result<()> {
+        let version = node_key.version();
+        let mut inconsistencies = Vec::new();
+
+        if node_key.nibble_path().is_empty() {
+            if let Some(expected) = self
+                .ledger_db
+                .get::<TransactionInfoSchema>(&version)?
+                .and_then(|txn_info| txn_info.state_checkpoint_hash())
+            {
+                let actual = node.hash();
+                if actual != expected {
+                    inconsistencies.push(Inconsistency::StateCheckpointHashMismatch {
+                        version,
+                        expected,
+                        actual,
+                    });
+                }
+            }
+        } else {
+            // All the nodes on the path from the root to a node written at a version are written at
+            // the same version.
+            let parent = self.get_node(&node_key.gen_parent_node_key())?;
+            let child = match &parent {
+                Some(Node::Internal(internal_node)) => node_key
+                    .nibble_path()
+                    .last()
+                    .and_then(|nibble| internal_node.child(nibble)),
+                _ => None,
+            };
+            match child {
+                Some(child) if child.version == version => {
+                    let actual = node.hash();
+                    if actual != child.hash {
+                        inconsistencies.push(Inconsistency::NodeHashMismatch {
+                            node_key: node_key.clone(),
+                            expected: child.hash,
+                            actual,
+                        });
+                    }
+                }
+                _ => inconsistencies.push(Inconsistency::OrphanedNode {
+                    node_key: node_key.clone(),
+                }),
+            }
+        }
+
+        if let Node::Internal(internal_node) = node {
+            for (nibble, child) in internal_node.children_sorted() {
+                let child_key = node_key.gen_child_node_key(child.version, *nibble);
+                if self.get_node(&child_key)?.is_none() {
+                    inconsistencies.push(Inconsistency::MissingNode {
+                        node_key: child_key,
+                    });
+                }
+            }
+        }
+
+        // The pruner might have started on the tree after the node was read.
+        if !inconsistencies.is_empty() && self.is_state_tree_readable(version)? {
+            inconsistencies
+                .into_iter()
+                .for_each(|inconsistency| report.add(inconsistency));
+        }
+        Ok(())
+    }
+
+    /// Whether the state tree at `version` exists and is not being pruned.
+    fn is_state_tree_readable(&self, version: Version) -> Result<bool> {
+        if self.get_node(&NodeKey::new_empty_path(version))?.is_none() {
+            return Ok(false);
+        }
+        if version >= self.state_store.state_pruner.get_min_readable_version() {
+            return Ok(true);
+        }
+        Ok(version
+            >= self
+                .state_store
+                .epoch_snapshot_pruner
+                .get_min_readable_version()
+            && self.ledger_store.ensure_epoch_ending(version).is_ok())
+    }
+
+    /// Where the background checker stopped before the DB was last closed, or the start of the DB
+    /// if it never ran.
+    pub fn load_progress(&self) -> Result<ConsistencyCheckProgress> {
+        let next_version = self
+            .ledger_db
+            .get::<DbMetadataSchema>(&DbMetadataKey::ConsistencyCheckerLedgerProgress)?
+            .map_or(0, DbMetadataValue::expect_version);
+        let next_node_version = self
+            .state_store
+            .state_merkle_db
+            .get::<DbMetadataSchema>(&DbMetadataKey::ConsistencyCheckerStateMerkleProgress)?
+            .map_or(0, DbMetadataValue::expect_version);
+        Ok(ConsistencyCheckProgress {
+            next_version,
+            next_node_key: NodeKey::new_empty_path(next_node_version),
+        })
+    }
+
+    /// Saves `progress` for `load_progress`. Only the version of the next state tree node is kept,
+    /// so the nodes of that version are checked again after a restart.
+    pub fn save_progress(&self, progress: &ConsistencyCheckProgress) -> Result<()> {
+        self.ledger_db.put::<DbMetadataSchema>(
+            &DbMetadataKey::ConsistencyCheckerLedgerProgress,
+            &DbMetadataValue::Version(progress.next_version),
+        )?;
+        self.state_store.state_merkle_db.put::<DbMetadataSchema>(
+            &DbMetadataKey::ConsistencyCheckerStateMerkleProgress,
+            &DbMetadataValue::Version(progress.next_node_key.version()),
+        )
+    }
+
+    fn get_node(&self, node_key: &NodeKey) -> Result<Option<Node>> {
+        self.state_store
+            .state_merkle_db
+            .get::<JellyfishMerkleNodeSchema>(node_key)
+    }
+
+    fn get_first_transaction_info_version(&self) -> Result<Option<Version>> {
+        let mut iter = self
+            .ledger_db
+            .iter::<TransactionInfoSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        Ok(iter.next().transpose()?.map(|(version, _txn_info)| version))
+    }
+
+    fn work(&self, config: ConsistencyCheckerConfig, stop_receiver: mpsc::Receiver<()>) {
+        // Picks up where the checker stopped before the node restarted, instead of going through
+        // the whole DB again.
+        let mut progress = self.load_progress().unwrap_or_else(|e| {
+            warn!(
+                error = ?e,
+                "Failed to load consistency checker progress, starting over."
+            );
+            ConsistencyCheckProgress::new(0)
+        });
+        loop {
+            let mut report = ConsistencyReport::default();
+            let caught_up = self
+                .check_next_batch(&mut progress, config.batch_size, &mut report)
+                .and_then(|caught_up| {
+                    self.save_progress(&progress)?;
+                    Ok(caught_up)
+                })
+                .unwrap_or_else(|e| {
+                    warn!(
+                        error = ?e,
+                        next_version = progress.next_version,
+                        "Consistency check failed, retrying later."
+                    );
+                    true
+                });
+
+            let interval_ms = if caught_up {
+                config.idle_interval_ms
+            } else {
+                config.batch_interval_ms
+            };
+            match stop_receiver.recv_timeout(Duration::from_millis(interval_ms)) {
+                Ok(_) => break,
+                Err(mpsc::RecvTimeoutError::Timeout) => (),
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    }
+}
+
+/// Runs a `ConsistencyChecker` in a thread created on construction and joined on destruction.
+#[derive(Debug)]
+pub(crate) struct BackgroundConsistencyChecker {
+    sender: Mutex<mpsc::Sender<()>>,
+    join_handle: Option<JoinHandle<()>>,
+}
+
+impl BackgroundConsistencyChecker {
+    pub fn new(checker: ConsistencyChecker, config: ConsistencyCheckerConfig) -> Self {
+        let (send, recv) = mpsc::channel();
+        let join_handle = Some(
+            thread::Builder::new()
+                .name("aptosdb_consistency_checker".into())
+                .spawn(move || checker.work(config, recv))
+                .expect("Creating consistency checker thread should succeed."),
+        );
+        Self {
+            sender: Mutex::new(send),
+            join_handle,
+        }
+    }
+}
+
+impl Drop for BackgroundConsistencyChecker {
+    fn drop(&mut self) {
+        // Notify the checker thread to exit, it does so once the batch in progress is done.
+        self.sender.lock().send(()).unwrap();
+        self.join_handle
+            .take()
+            .expect("Consistency checker thread must exist.")
+            .join()
+            .expect("Consistency checker thread should join peacefully.");
+    }
+}
@@ -0,0 +1,323 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{
+    schema::{event_accumulator::EventAccumulatorSchema, transaction::TransactionSchema},
+    test_helper::{arb_blocks_to_commit, update_in_memory_state},
+    AptosDB,
+};
+use aptos_proptest_helpers::Index;
+use aptos_temppath::TempPath;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    nibble::Nibble,
+    proof::position::Position,
+    state_store::state_key::StateKey,
+    transaction::{Transaction, TransactionToCommit},
+};
+use proptest::prelude::*;
+use schemadb::SchemaBatch;
+use storage_interface::DbWriter;
+
+fn save_blocks(db: &AptosDB, input: &[(Vec<TransactionToCommit>, LedgerInfoWithSignatures)]) {
+    let mut in_memory_state = db
+        .state_store
+        .buffered_state()
+        .lock()
+        .current_state()
+        .clone();
+    let _ancester = in_memory_state.current.clone();
+    let mut next_ver: Version = 0;
+    for (txns_to_commit, ledger_info_with_sigs) in input {
+        update_in_memory_state(&mut in_memory_state, txns_to_commit.as_slice());
+        db.save_transactions(
+            txns_to_commit,
+            next_ver,                /* first_version */
+            next_ver.checked_sub(1), /* base_state_version */
+            Some(ledger_info_with_sigs),
+            true, /* sync_commit */
+            in_memory_state.clone(),
+        )
+        .unwrap();
+        next_ver += txns_to_commit.len() as u64;
+    }
+}
+
+fn check_all(db: &AptosDB) -> ConsistencyReport {
+    let checker = db.get_consistency_checker();
+    let mut progress = ConsistencyCheckProgress::new(0);
+    let mut report = ConsistencyReport::default();
+    while !checker
+        .check_next_batch(&mut progress, 3 /* batch_size */, &mut report)
+        .unwrap()
+    {}
+    report
+}
+
+/// A node that hashes differently from any node in the DB.
+fn random_leaf(version: Version) -> Node {
+    Node::new_leaf(
+        HashValue::random(),
+        HashValue::random(),
+        (StateKey::Raw(b"random".to_vec()), version),
+    )
+}
+
+fn jmt_nodes(db: &AptosDB, roots: bool) -> Vec<NodeKey> {
+    db.state_store
+        .get_all_jmt_nodes()
+        .unwrap()
+        .into_iter()
+        .filter(|node_key| node_key.nibble_path().is_empty() == roots)
+        .collect()
+}
+
+#[test]
+fn test_progress() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let checker = db.get_consistency_checker();
+
+    let progress = checker.load_progress().unwrap();
+    assert_eq!(progress.next_version, 0);
+    assert_eq!(progress.next_node_key, NodeKey::new_empty_path(0));
+
+    checker
+        .save_progress(&ConsistencyCheckProgress {
+            next_version: 10,
+            next_node_key: NodeKey::new_empty_path(7).gen_child_node_key(7, Nibble::from(3)),
+        })
+        .unwrap();
+    let progress = checker.load_progress().unwrap();
+    assert_eq!(progress.next_version, 10);
+    assert_eq!(progress.next_node_key, NodeKey::new_empty_path(7));
+}
+
+#[test]
+fn test_unpruned_stale_node() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let stale_node_index = StaleNodeIndex {
+        stale_since_version: 5,
+        node_key: NodeKey::new_empty_path(4),
+    };
+    db.state_merkle_db
+        .put::<StaleNodeIndexSchema>(&stale_node_index, &())
+        .unwrap();
+    let checker = db.get_consistency_checker();
+
+    let mut report = ConsistencyReport::default();
+    checker.check_stale_node_indices(10, &mut report).unwrap();
+    assert!(report.is_consistent());
+
+    db.state_store.state_pruner.testonly_update_min_version(6);
+    checker.check_stale_node_indices(10, &mut report).unwrap();
+    assert_eq!(
+        report.inconsistencies,
+        vec![Inconsistency::UnprunedStaleNode { stale_node_index }]
+    );
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_consistent(input in arb_blocks_to_commit()) {
+        let tmp_dir = TempPath::new();
+        let db = AptosDB::new_for_test(&tmp_dir);
+        save_blocks(&db, &input);
+
+        let report = check_all(&db);
+        prop_assert!(report.is_consistent());
+        prop_assert_eq!(
+            report.num_transactions_checked,
+            input.iter().map(|(txns, _)| txns.len()).sum::<usize>()
+        );
+        prop_assert_eq!(
+            report.num_nodes_checked,
+            db.state_store.get_all_jmt_nodes().unwrap().len()
+        );
+    }
+
+    #[test]
+    fn test_tampered_transaction(input in arb_blocks_to_commit(), index in any::<Index>()) {
+        let tmp_dir = TempPath::new();
+        let db = AptosDB::new_for_test(&tmp_dir);
+        save_blocks(&db, &input);
+
+        let txns: Vec<_> = input.iter().flat_map(|(txns, _)| txns).collect();
+        let version = index.index(txns.len()) as Version;
+        let tampered_txn = Transaction::StateCheckpoint(HashValue::random());
+        db.ledger_db
+            .put::<TransactionSchema>(&version, &tampered_txn)
+            .unwrap();
+
+        let report = check_all(&db);
+        prop_assert_eq!(report.inconsistencies, vec![
+            Inconsistency::TransactionHashMismatch {
+                version,
+                expected: txns[version as usize].transaction_info().transaction_hash(),
+                actual: tampered_txn.hash(),
+            }
+        ]);
+    }
+
+    #[test]
+    fn test_missing_node(input in arb_blocks_to_commit(), index in any::<Index>()) {
+        let tmp_dir = TempPath::new();
+        let db = AptosDB::new_for_test(&tmp_dir);
+        save_blocks(&db, &input);
+
+        let non_root_nodes: Vec<_> = db
+            .state_store
+            .get_all_jmt_nodes()
+            .unwrap()
+            .into_iter()
+            .filter(|node_key| !node_key.nibble_path().is_empty())
+            .collect();
+        prop_assume!(!non_root_nodes.is_empty());
+        let node_key = &non_root_nodes[index.index(non_root_nodes.len())];
+        let batch = SchemaBatch::new();
+        batch.delete::<JellyfishMerkleNodeSchema>(node_key).unwrap();
+        db.state_merkle_db.write_schemas(batch).unwrap();
+
+        let report = check_all(&db);
+        prop_assert!(report
+            .inconsistencies
+            .contains(&Inconsistency::MissingNode { node_key: node_key.clone() }));
+    }
+
+    #[test]
+    fn test_event_accumulator_mismatch(input in arb_blocks_to_commit(), index in any::<Index>()) {
+        let tmp_dir = TempPath::new();
+        let db = AptosDB::new_for_test(&tmp_dir);
+        save_blocks(&db, &input);
+
+        let txns: Vec<_> = input.iter().flat_map(|(txns, _)| txns).collect();
+        let versions_with_events: Vec<_> = (0..txns.len() as Version)
+            .filter(|version| !txns[*version as usize].events().is_empty())
+            .collect();
+        prop_assume!(!versions_with_events.is_empty());
+        let version = versions_with_events[index.index(versions_with_events.len())];
+        let mut iter = db
+            .ledger_db
+            .iter::<EventAccumulatorSchema>(ReadOptions::default())
+            .unwrap();
+        iter.seek(&(version, Position::from_inorder_index(0))).unwrap();
+        let batch = SchemaBatch::new();
+        for res in iter {
+            let ((v, position), _hash) = res.unwrap();
+            if v != version {
+                break;
+            }
+            batch
+                .put::<EventAccumulatorSchema>(&(v, position), &HashValue::random())
+                .unwrap();
+        }
+        db.ledger_db.write_schemas(batch).unwrap();
+
+        let report = check_all(&db);
+        let num_events = txns[version as usize].events().len() as u64;
+        prop_assert_eq!(report.inconsistencies, vec![
+            Inconsistency::EventAccumulatorMismatch {
+                version,
+                expected: txns[version as usize].transaction_info().event_root_hash(),
+                actual: db.event_store.get_event_root_hash(version, num_events).unwrap(),
+            }
+        ]);
+    }
+
+    #[test]
+    fn test_state_checkpoint_hash_mismatch(
+        input in arb_blocks_to_commit(),
+        index in any::<Index>(),
+    ) {
+        let tmp_dir = TempPath::new();
+        let db = AptosDB::new_for_test(&tmp_dir);
+        save_blocks(&db, &input);
+
+        let roots = jmt_nodes(&db, true /* roots */);
+        prop_assume!(!roots.is_empty());
+        let root_key = &roots[index.index(roots.len())];
+        let version = root_key.version();
+        let state_checkpoint_hash = db
+            .ledger_db
+            .get::<TransactionInfoSchema>(&version)
+            .unwrap()
+            .and_then(|txn_info| txn_info.state_checkpoint_hash());
+        prop_assume!(state_checkpoint_hash.is_some());
+        let tampered_root = random_leaf(version);
+        db.state_merkle_db
+            .put::<JellyfishMerkleNodeSchema>(root_key, &tampered_root)
+            .unwrap();
+
+        let report = check_all(&db);
+        prop_assert!(report
+            .inconsistencies
+            .contains(&Inconsistency::StateCheckpointHashMismatch {
+                version,
+                expected: state_checkpoint_hash.unwrap(),
+                actual: tampered_root.hash(),
+            }));
+    }
+
+    #[test]
+    fn test_node_hash_mismatch(input in arb_blocks_to_commit(), index in any::<Index>()) {
+        let tmp_dir = TempPath::new();
+        let db = AptosDB::new_for_test(&tmp_dir);
+        save_blocks(&db, &input);
+
+        let non_root_nodes = jmt_nodes(&db, false /* roots */);
+        prop_assume!(!non_root_nodes.is_empty());
+        let node_key = &non_root_nodes[index.index(non_root_nodes.len())];
+        let node = db
+            .state_merkle_db
+            .get::<JellyfishMerkleNodeSchema>(node_key)
+            .unwrap()
+            .unwrap();
+        let tampered_node = random_leaf(node_key.version());
+        db.state_merkle_db
+            .put::<JellyfishMerkleNodeSchema>(node_key, &tampered_node)
+            .unwrap();
+
+        let report = check_all(&db);
+        prop_assert!(report.inconsistencies.contains(&Inconsistency::NodeHashMismatch {
+            node_key: node_key.clone(),
+            expected: node.hash(),
+            actual: tampered_node.hash(),
+        }));
+    }
+
+    #[test]
+    fn test_orphaned_node(input in arb_blocks_to_commit(), index in any::<Index>()) {
+        let tmp_dir = TempPath::new();
+        let db = AptosDB::new_for_test(&tmp_dir);
+        save_blocks(&db, &input);
+
+        let roots = jmt_nodes(&db, true /* roots */);
+        prop_assume!(!roots.is_empty());
+        let root_key = &roots[index.index(roots.len())];
+        let version = root_key.version();
+        let root = db
+            .state_merkle_db
+            .get::<JellyfishMerkleNodeSchema>(root_key)
+            .unwrap()
+            .unwrap();
+        // A child of the root that the root doesn't point to at the same version.
+        let nibble = (0..16u8).map(Nibble::from).find(|nibble| match &root {
+            Node::Internal(internal_node) => internal_node
+                .child(*nibble)
+                .map_or(true, |child| child.version != version),
+            _ => true,
+        });
+        prop_assume!(nibble.is_some());
+        let node_key = root_key.gen_child_node_key(version, nibble.unwrap());
+        db.state_merkle_db
+            .put::<JellyfishMerkleNodeSchema>(&node_key, &random_leaf(version))
+            .unwrap();
+
+        let report = check_all(&db);
+        prop_assert_eq!(report.inconsistencies, vec![Inconsistency::OrphanedNode { node_key }]);
+    }
+}
@@ -10,14 +10,17 @@ use crate::utils::iterators::EventsByVersionIter;
 use crate::{
     errors::AptosDbError,
     schema::{
-        event::EventSchema, event_accumulator::EventAccumulatorSchema,
-        event_by_key::EventByKeySchema, event_by_version::EventByVersionSchema,
+        event::EventSchema,
+        event_accumulator::EventAccumulatorSchema,
+        event_by_key::EventByKeySchema,
+        event_by_type::{type_tag_hash, EventByTypeSchema},
+        event_by_version::EventByVersionSchema,
     },
 };
 use accumulator::{HashReader, MerkleAccumulator};
 use anyhow::{bail, ensure, format_err, Result};
 use aptos_crypto::{
-    hash::{CryptoHash, EventAccumulatorHasher},
+    hash::{CryptoHash, EventAccumulatorHasher, ACCUMULATOR_PLACEHOLDER_HASH},
     HashValue,
 };
 use aptos_types::{
@@ -28,6 +31,7 @@ use aptos_types::{
     proof::position::Position,
     transaction::Version,
 };
+use move_core_types::language_storage::TypeTag;
 use schemadb::iterator::SchemaIterator;
 use schemadb::{schema::ValueCodec, ReadOptions, SchemaBatch, DB};
 use std::{
@@ -40,11 +44,26 @@ use std::{
 #[derive(Debug)]
 pub struct EventStore {
     db: Arc<DB>,
+    /// Whether new events are also written to `EventByTypeSchema`.
+    index_by_type: bool,
 }
 
 impl EventStore {
     pub fn new(db: Arc<DB>) -> Self {
-        Self { db }
+        Self {
+            db,
+            index_by_type: false,
+        }
+    }
+
+    /// Also maintains the event-by-type index for events written from now on.
+    pub fn with_event_by_type_index(mut self, index_by_type: bool) -> Self {
+        self.index_by_type = index_by_type;
+        self
+    }
+
+    pub fn event_by_type_index_enabled(&self) -> bool {
+        self.index_by_type
     }
 
     /// Get all of the events given a transaction version.
@@ -188,6 +207,59 @@ impl EventStore {
         Ok(result)
     }
 
+    /// Given `type_tag` and `start_version`, returns events of that type identified by transaction
+    /// version and index among all events emitted by the same transaction. Result won't contain
+    /// records with a transaction version > `ledger_version` and is in ascending order.
+    ///
+    /// A page never ends in the middle of a transaction: if `limit` is hit part way through the
+    /// events of a version, that version is left for the next page. The exception is a single
+    /// transaction emitting more than `limit` events of the type, which is returned whole as a page
+    /// of its own so that paging by version can move past it.
+    pub fn lookup_events_by_type(
+        &self,
+        type_tag: &TypeTag,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<
+        Vec<(
+            Version, // transaction version it belongs to
+            u64,     // index among events for the same transaction
+        )>,
+    > {
+        ensure!(
+            self.index_by_type,
+            "Event by type index is not enabled on this node."
+        );
+        let type_tag_hash = type_tag_hash(type_tag)?;
+        let mut iter = self.db.iter::<EventByTypeSchema>(ReadOptions::default())?;
+        iter.seek(&(type_tag_hash, start_version, 0))?;
+
+        let mut result: Vec<(Version, u64)> = Vec::new();
+        while let Some(((hash, ver, idx), ())) = iter.next().transpose()? {
+            if hash != type_tag_hash || ver > ledger_version {
+                break;
+            }
+            if result.len() as u64 >= limit {
+                let first_ver = result.first().map(|(v, _)| *v);
+                let last_ver = result.last().map(|(v, _)| *v);
+                if first_ver == Some(ver) {
+                    // The page only holds this version so far, it has to be returned whole.
+                    result.push((ver, idx));
+                    continue;
+                }
+                // Drop the trailing version if it doesn't fit into this page entirely.
+                if last_ver == Some(ver) {
+                    result.retain(|(v, _)| *v != ver);
+                }
+                break;
+            }
+            result.push((ver, idx));
+        }
+
+        Ok(result)
+    }
+
     fn lookup_event_by_key(
         &self,
         event_key: &EventKey,
@@ -322,7 +394,14 @@ impl EventStore {
                 batch.put::<EventByVersionSchema>(
                     &(*event.key(), version, event.sequence_number()),
                     &(idx as u64),
-                )
+                )?;
+                if self.index_by_type {
+                    batch.put::<EventByTypeSchema>(
+                        &(type_tag_hash(event.type_tag())?, version, idx as u64),
+                        &(),
+                    )?;
+                }
+                Ok(())
             })?;
 
         // EventAccumulatorSchema updates
@@ -339,6 +418,18 @@ impl EventStore {
         Ok(root_hash)
     }
 
+    /// Gets the root hash of the persisted event accumulator of the transaction at `version`,
+    /// which has `num_events` leaves.
+    pub fn get_event_root_hash(&self, version: Version, num_events: u64) -> Result<HashValue> {
+        if num_events == 0 {
+            return Ok(*ACCUMULATOR_PLACEHOLDER_HASH);
+        }
+        MerkleAccumulator::<EventHashReader, EventAccumulatorHasher>::get_root_hash(
+            &EventHashReader::new(self, version),
+            num_events,
+        )
+    }
+
     pub(crate) fn put_events_multiple_versions(
         &self,
         first_version: u64,
@@ -469,6 +560,12 @@ impl EventStore {
                     event.sequence_number(),
                 ))?;
                 db_batch.delete::<EventByKeySchema>(&(*event.key(), event.sequence_number()))?;
+                // The index is optional, deleting absent entries is a no-op.
+                db_batch.delete::<EventByTypeSchema>(&(
+                    type_tag_hash(event.type_tag())?,
+                    current_version as u64,
+                    current_index as u64,
+                ))?;
                 db_batch.delete::<EventSchema>(&(current_version as u64, current_index as u64))?;
             }
             current_version += 1;
@@ -361,3 +361,58 @@ proptest! {
         test_get_last_version_before_timestamp_impl(new_block_events)
     }
 }
+
+#[test]
+fn test_lookup_events_by_type() {
+    let tmp_dir = TempPath::new();
+    let mut db = AptosDB::new_for_test(&tmp_dir);
+    db.enable_event_by_type_index();
+    let store = &db.event_store;
+
+    let event_key = EventKey::new(0, AccountAddress::random());
+    let event = |type_tag: TypeTag| ContractEvent::new(event_key, 0, type_tag, vec![]);
+    save(store, 0, &[event(TypeTag::U64), event(TypeTag::Bool)]);
+    save(store, 1, &[event(TypeTag::Bool)]);
+    save(store, 2, &[event(TypeTag::U64), event(TypeTag::U64)]);
+    save(store, 3, &[event(TypeTag::U64)]);
+
+    assert_eq!(
+        store
+            .lookup_events_by_type(&TypeTag::U64, 0, 10, 3)
+            .unwrap(),
+        vec![(0, 0), (2, 0), (2, 1), (3, 0)]
+    );
+    assert_eq!(
+        store
+            .lookup_events_by_type(&TypeTag::Bool, 1, 10, 3)
+            .unwrap(),
+        vec![(1, 0)]
+    );
+    // Doesn't go beyond the ledger version.
+    assert_eq!(
+        store
+            .lookup_events_by_type(&TypeTag::U64, 0, 10, 2)
+            .unwrap(),
+        vec![(0, 0), (2, 0), (2, 1)]
+    );
+    // A page doesn't end in the middle of a version.
+    assert_eq!(
+        store.lookup_events_by_type(&TypeTag::U64, 0, 2, 3).unwrap(),
+        vec![(0, 0)]
+    );
+    // Unless a single version doesn't fit into the page, which is then returned whole.
+    assert_eq!(
+        store.lookup_events_by_type(&TypeTag::U64, 2, 1, 3).unwrap(),
+        vec![(2, 0), (2, 1)]
+    );
+    assert_eq!(
+        store.lookup_events_by_type(&TypeTag::U64, 1, 1, 3).unwrap(),
+        vec![(2, 0), (2, 1)]
+    );
+
+    // The index is only queryable when it's enabled.
+    let store = EventStore::new(Arc::clone(&store.db));
+    assert!(store
+        .lookup_events_by_type(&TypeTag::U64, 0, 10, 3)
+        .is_err());
+}
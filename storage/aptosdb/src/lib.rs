@@ -14,6 +14,7 @@
 pub mod test_helper;
 
 pub mod backup;
+pub mod consistency_checker;
 pub mod errors;
 pub mod metrics;
 pub mod schema;
@@ -37,6 +38,7 @@ mod aptosdb_test;
 use crate::state_store::buffered_state::BufferedState;
 use crate::{
     backup::{backup_handler::BackupHandler, restore_handler::RestoreHandler, restore_utils},
+    consistency_checker::{BackgroundConsistencyChecker, ConsistencyChecker},
     db_options::{
         gen_ledger_cfds, gen_state_merkle_cfds, ledger_db_column_families,
         state_merkle_db_column_families,
@@ -57,8 +59,8 @@ use anyhow::{bail, ensure, Result};
 #[cfg(any(test, feature = "fuzzing"))]
 use aptos_config::config::DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD;
 use aptos_config::config::{
-    PrunerConfig, RocksdbConfig, RocksdbConfigs, BUFFERED_STATE_TARGET_ITEMS,
-    NO_OP_STORAGE_PRUNER_CONFIG,
+    ConsistencyCheckerConfig, PrunerConfig, RocksdbConfig, RocksdbConfigs,
+    BUFFERED_STATE_TARGET_ITEMS, NO_OP_STORAGE_PRUNER_CONFIG,
 };
 
 use aptos_crypto::hash::HashValue;
@@ -91,10 +93,12 @@ use aptos_types::{
         TransactionOutput, TransactionOutputListWithProof, TransactionToCommit,
         TransactionWithProof, Version,
     },
+    write_set::TransactionWrite,
 };
 use aptos_vm::data_cache::AsMoveResolver;
 use aptosdb_indexer::Indexer;
 use itertools::zip_eq;
+use move_core_types::language_storage::TypeTag;
 use move_resource_viewer::MoveValueAnnotator;
 use once_cell::sync::Lazy;
 use schemadb::{SchemaBatch, DB};
@@ -116,7 +120,7 @@ use crate::stale_node_index::StaleNodeIndexSchema;
 use crate::stale_node_index_cross_epoch::StaleNodeIndexCrossEpochSchema;
 use storage_interface::{
     state_delta::StateDelta, state_view::DbStateView, DbReader, DbWriter, ExecutedTrees, Order,
-    StateSnapshotReceiver, MAX_REQUEST_LIMIT,
+    StateSnapshotReceiver, StateValueChange, MAX_REQUEST_LIMIT,
 };
 
 pub const LEDGER_DB_NAME: &str = "ledger_db";
@@ -252,6 +256,7 @@ pub struct AptosDB {
     _rocksdb_property_reporter: RocksdbPropertyReporter,
     ledger_commit_lock: std::sync::Mutex<()>,
     indexer: Option<Indexer>,
+    consistency_checker: Option<BackgroundConsistencyChecker>,
 }
 
 impl AptosDB {
@@ -302,6 +307,7 @@ impl AptosDB {
             ),
             ledger_commit_lock: std::sync::Mutex::new(()),
             indexer: None,
+            consistency_checker: None,
         }
     }
 
@@ -420,6 +426,33 @@ impl AptosDB {
         Ok(())
     }
 
+    /// Maintains the event-by-type index for transactions committed from now on. Like the
+    /// internal indexer, the index only covers history written while it is enabled, so it is
+    /// meant to be turned on from genesis.
+    pub fn enable_event_by_type_index(&mut self) {
+        self.event_store =
+            Arc::new(EventStore::new(Arc::clone(&self.ledger_db)).with_event_by_type_index(true));
+    }
+
+    pub fn get_consistency_checker(&self) -> ConsistencyChecker {
+        ConsistencyChecker::new(
+            Arc::clone(&self.ledger_db),
+            Arc::clone(&self.ledger_store),
+            Arc::clone(&self.event_store),
+            Arc::clone(&self.transaction_store),
+            Arc::clone(&self.state_store),
+        )
+    }
+
+    /// Keeps checking the DB against itself in a background thread, throttled according to
+    /// `config`. Inconsistencies found are logged and counted in metrics.
+    pub fn start_consistency_checker(&mut self, config: ConsistencyCheckerConfig) {
+        self.consistency_checker = Some(BackgroundConsistencyChecker::new(
+            self.get_consistency_checker(),
+            config,
+        ));
+    }
+
     pub fn open_as_secondary<P: AsRef<Path> + Clone>(
         db_root_path: P,
         secondary_db_root_path: P,
@@ -733,6 +766,69 @@ impl AptosDB {
         Ok(events_with_version)
     }
 
+    fn get_events_by_type_impl(
+        &self,
+        type_tag: &TypeTag,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<EventWithVersion>> {
+        error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
+        self.error_if_ledger_pruned("Event", start_version)?;
+
+        self.event_store
+            .lookup_events_by_type(type_tag, start_version, limit, ledger_version)?
+            .into_iter()
+            .map(|(ver, idx)| {
+                let event = self.event_store.get_event_by_version_and_index(ver, idx)?;
+                Ok(EventWithVersion::new(ver, event))
+            })
+            .collect()
+    }
+
+    fn get_state_value_changes_impl(
+        &self,
+        state_key: &StateKey,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<StateValueChange>> {
+        error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
+        self.error_if_ledger_pruned("Write set", start_version)?;
+
+        if start_version > ledger_version || limit == 0 {
+            return Ok(Vec::new());
+        }
+        let limit = std::cmp::min(limit, ledger_version - start_version + 1);
+
+        let mut changes: Vec<StateValueChange> = Vec::new();
+        let write_sets = self
+            .transaction_store
+            .get_write_set_iter(start_version, limit as usize)?;
+        for (version, write_set) in (start_version..).zip(write_sets) {
+            let write_set = write_set?;
+            let write_op = match write_set.get(state_key) {
+                Some(write_op) => write_op,
+                None => continue,
+            };
+            // Only the first change has to look up the previous value, the others follow on from
+            // the change before them.
+            let before = match changes.last() {
+                Some(change) => change.after.clone(),
+                None if version == 0 => None,
+                None => self
+                    .state_store
+                    .get_state_value_by_version(state_key, version - 1)?,
+            };
+            changes.push(StateValueChange {
+                version,
+                before,
+                after: write_op.extract_raw_bytes().map(StateValue::from),
+            });
+        }
+        Ok(changes)
+    }
+
     fn save_transactions_impl(
         &self,
         txns_to_commit: &[TransactionToCommit],
@@ -1146,6 +1242,30 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_events_by_type(
+        &self,
+        type_tag: &TypeTag,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<EventWithVersion>> {
+        gauged_api("get_events_by_type", || {
+            self.get_events_by_type_impl(type_tag, start_version, limit, ledger_version)
+        })
+    }
+
+    fn get_state_value_changes(
+        &self,
+        state_key: &StateKey,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<StateValueChange>> {
+        gauged_api("get_state_value_changes", || {
+            self.get_state_value_changes_impl(state_key, start_version, limit, ledger_version)
+        })
+    }
+
     /// Gets ledger info at specified version and ensures it's an epoch ending.
     fn get_epoch_ending_ledger_info(&self, version: u64) -> Result<LedgerInfoWithSignatures> {
         gauged_api("get_epoch_ending_ledger_info", || {
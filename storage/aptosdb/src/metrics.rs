@@ -2,8 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_metrics_core::{
-    exponential_buckets, register_histogram_vec, register_int_counter, register_int_gauge,
-    register_int_gauge_vec, HistogramVec, IntCounter, IntGauge, IntGaugeVec,
+    exponential_buckets, register_histogram_vec, register_int_counter, register_int_counter_vec,
+    register_int_gauge, register_int_gauge_vec, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 
@@ -204,3 +205,29 @@ pub(crate) static BACKUP_STATE_SNAPSHOT_LEAF_IDX: Lazy<IntGauge> = Lazy::new(||
     )
     .unwrap()
 });
+
+// Consistency checker metrics:
+
+pub(crate) static CONSISTENCY_CHECKER_PROGRESS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        // metric name
+        "aptos_storage_consistency_checker_progress",
+        // metric description
+        "Version up to which the consistency checker has checked the DB.",
+        // metric labels (dimensions)
+        &["check"]
+    )
+    .unwrap()
+});
+
+pub(crate) static CONSISTENCY_CHECKER_INCONSISTENCIES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        // metric name
+        "aptos_storage_consistency_checker_inconsistencies",
+        // metric description
+        "Number of inconsistencies found by the consistency checker.",
+        // metric labels (dimensions)
+        &["kind"]
+    )
+    .unwrap()
+});
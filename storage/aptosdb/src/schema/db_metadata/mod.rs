@@ -49,6 +49,8 @@ pub enum DbMetadataKey {
     StateMerklePrunerProgress,
     EpochEndingStateMerklePrunerProgress,
     StateSnapshotRestoreProgress(Version),
+    ConsistencyCheckerLedgerProgress,
+    ConsistencyCheckerStateMerkleProgress,
 }
 
 define_schema!(
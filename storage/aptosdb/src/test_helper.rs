@@ -685,6 +685,21 @@ pub fn verify_committed_transactions(
             updates.insert(state_key, state_value);
             let state_value_in_db = db.get_state_value_by_version(state_key, cur_ver).unwrap();
             assert_eq!(state_value_in_db, *state_value);
+
+            // Verify the change is found in the write set.
+            let changes = db
+                .get_state_value_changes(state_key, cur_ver, 1, ledger_version)
+                .unwrap();
+            assert_eq!(changes.len(), 1);
+            assert_eq!(changes[0].version, cur_ver);
+            assert_eq!(changes[0].after, *state_value);
+            if cur_ver > 0 {
+                assert_eq!(
+                    changes[0].before,
+                    db.get_state_value_by_version(state_key, cur_ver - 1)
+                        .unwrap()
+                );
+            }
         }
 
         if !txn_to_commit.is_state_checkpoint() {
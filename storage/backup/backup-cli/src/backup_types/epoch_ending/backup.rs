@@ -6,8 +6,9 @@ use crate::{
     metadata::Metadata,
     storage::{BackupHandleRef, BackupStorage, FileHandle, ShellSafeName},
     utils::{
-        backup_service_client::BackupServiceClient, read_record_bytes::ReadRecordBytes,
-        should_cut_chunk, storage_ext::BackupStorageExt, GlobalBackupOpt,
+        backup_service_client::BackupServiceClient, codec::ChunkCoder,
+        read_record_bytes::ReadRecordBytes, should_cut_chunk, storage_ext::BackupStorageExt,
+        GlobalBackupOpt,
     },
 };
 use anyhow::{anyhow, ensure, Result};
@@ -34,6 +35,7 @@ pub struct EpochEndingBackupController {
     start_epoch: u64,
     end_epoch: u64,
     max_chunk_size: usize,
+    chunk_coder: ChunkCoder,
     client: Arc<BackupServiceClient>,
     storage: Arc<dyn BackupStorage>,
 }
@@ -49,6 +51,7 @@ impl EpochEndingBackupController {
             start_epoch: opt.start_epoch,
             end_epoch: opt.end_epoch,
             max_chunk_size: global_opt.max_chunk_size,
+            chunk_coder: ChunkCoder::new_with_opt(global_opt.codec),
             client,
             storage,
         }
@@ -159,12 +162,15 @@ impl EpochEndingBackupController {
             .storage
             .create_for_write(backup_handle, &Self::chunk_name(first_epoch))
             .await?;
-        chunk_file.write_all(chunk_bytes).await?;
+        chunk_file
+            .write_all(&self.chunk_coder.encode(chunk_bytes)?)
+            .await?;
         chunk_file.shutdown().await?;
         Ok(EpochEndingChunk {
             first_epoch,
             last_epoch,
             ledger_infos: chunk_handle,
+            codec: self.chunk_coder.codec(),
         })
     }
 
@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::storage::FileHandle;
+use crate::{storage::FileHandle, utils::codec::Codec};
 use anyhow::{ensure, Result};
 use aptos_types::waypoint::Waypoint;
 use serde::{Deserialize, Serialize};
@@ -13,6 +13,9 @@ pub struct EpochEndingChunk {
     pub first_epoch: u64,
     pub last_epoch: u64,
     pub ledger_infos: FileHandle,
+    /// How the `ledger_infos` file is encoded.
+    #[serde(default, skip_serializing_if = "Codec::is_plain")]
+    pub codec: Codec,
 }
 
 /// Epoch ending backup manifest, representing epoch ending information in the
@@ -2,15 +2,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    backup_types::epoch_ending::manifest::EpochEndingBackup,
+    backup_types::epoch_ending::manifest::{EpochEndingBackup, EpochEndingChunk},
+    metadata::EpochEndingBackupMeta,
     metrics::{
         restore::{EPOCH_ENDING_EPOCH, EPOCH_ENDING_VERSION},
         verify::{VERIFY_EPOCH_ENDING_EPOCH, VERIFY_EPOCH_ENDING_VERSION},
     },
-    storage::{BackupStorage, FileHandle, FileHandleRef},
+    storage::{BackupStorage, FileHandle},
     utils::{
-        read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, stream::StreamX,
-        GlobalRestoreOptions, RestoreRunMode,
+        codec::ChunkCoder, read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt,
+        stream::StreamX, GlobalRestoreOptions, RestoreRunMode,
     },
 };
 use anyhow::{anyhow, ensure, Result};
@@ -37,6 +38,7 @@ pub struct EpochEndingRestoreController {
     manifest_handle: FileHandle,
     target_version: Version,
     trusted_waypoints: Arc<HashMap<Version, Waypoint>>,
+    chunk_coder: ChunkCoder,
 }
 
 impl EpochEndingRestoreController {
@@ -51,6 +53,7 @@ impl EpochEndingRestoreController {
             manifest_handle: opt.manifest_handle,
             target_version: global_opt.target_version,
             trusted_waypoints: global_opt.trusted_waypoints,
+            chunk_coder: global_opt.chunk_coder,
         }
     }
 
@@ -94,7 +97,7 @@ impl EpochEndingRestoreController {
                 break;
             }
 
-            let lis = self.read_chunk(&chunk.ledger_infos).await?;
+            let lis = self.read_chunk(chunk).await?;
             ensure!(
                 chunk.first_epoch + lis.len() as u64 == chunk.last_epoch + 1,
                 "Number of items in chunks doesn't match that in manifest. \
@@ -157,11 +160,11 @@ impl EpochEndingRestoreController {
         })
     }
 
-    async fn read_chunk(
-        &self,
-        file_handle: &FileHandleRef,
-    ) -> Result<Vec<LedgerInfoWithSignatures>> {
-        let mut file = self.storage.open_for_read(file_handle).await?;
+    async fn read_chunk(&self, chunk: &EpochEndingChunk) -> Result<Vec<LedgerInfoWithSignatures>> {
+        let mut file = self
+            .storage
+            .open_chunk_for_read(&chunk.ledger_infos, &chunk.codec, &self.chunk_coder)
+            .await?;
         let mut chunk = vec![];
 
         while let Some(record_bytes) = file.read_record_bytes().await? {
@@ -413,3 +416,33 @@ impl EpochHistoryRestoreController {
         })
     }
 }
+
+/// Version of the last epoch ending at or before `timestamp_usecs` in `epoch_ending_backups`.
+/// The ledger infos are not verified, they are only used to tell versions by time.
+pub(crate) async fn last_epoch_ending_version_before(
+    storage: &Arc<dyn BackupStorage>,
+    chunk_coder: &ChunkCoder,
+    epoch_ending_backups: &[EpochEndingBackupMeta],
+    timestamp_usecs: u64,
+) -> Result<Option<Version>> {
+    for backup in epoch_ending_backups.iter().rev() {
+        let manifest: EpochEndingBackup = storage.load_json_file(&backup.manifest).await?;
+        for chunk in manifest.chunks.iter().rev() {
+            let mut file = storage
+                .open_chunk_for_read(&chunk.ledger_infos, &chunk.codec, chunk_coder)
+                .await?;
+            let mut last_version = None;
+            while let Some(record_bytes) = file.read_record_bytes().await? {
+                let li: LedgerInfoWithSignatures = bcs::from_bytes(&record_bytes)?;
+                if li.ledger_info().timestamp_usecs() > timestamp_usecs {
+                    break;
+                }
+                last_version = Some(li.ledger_info().version());
+            }
+            if last_version.is_some() {
+                return Ok(last_version);
+            }
+        }
+    }
+    Ok(None)
+}
@@ -11,7 +11,9 @@ use crate::{
     },
     storage::{local_fs::LocalFs, BackupStorage},
     utils::{
-        backup_service_client::BackupServiceClient, test_utils::tmp_db_with_random_content,
+        backup_service_client::BackupServiceClient,
+        codec::{ChunkCodecOpt, EncryptionKeyOpt},
+        test_utils::tmp_db_with_random_content,
         ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, RocksdbOpt, TrustedWaypointOpt,
     },
 };
@@ -67,6 +69,7 @@ fn end_to_end() {
                 },
                 GlobalBackupOpt {
                     max_chunk_size: 1024,
+                    codec: ChunkCodecOpt::default(),
                 },
                 client,
                 Arc::clone(&store),
@@ -86,6 +89,8 @@ fn end_to_end() {
                 rocksdb_opt: RocksdbOpt::default(),
                 concurrent_downloads: ConcurrentDownloadsOpt::default(),
                 replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+                replay_write_sets: false,
+                encryption_key: EncryptionKeyOpt::default(),
             }
             .try_into()
             .unwrap(),
@@ -197,6 +202,7 @@ async fn test_trusted_waypoints_impl(
             },
             GlobalBackupOpt {
                 max_chunk_size: 1024,
+                codec: ChunkCodecOpt::default(),
             },
             client.clone(),
             Arc::clone(&store),
@@ -218,6 +224,8 @@ async fn test_trusted_waypoints_impl(
             rocksdb_opt: RocksdbOpt::default(),
             concurrent_downloads: ConcurrentDownloadsOpt::default(),
             replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+            replay_write_sets: false,
+            encryption_key: EncryptionKeyOpt::default(),
         }
         .try_into()
         .unwrap(),
@@ -239,6 +247,8 @@ async fn test_trusted_waypoints_impl(
             rocksdb_opt: RocksdbOpt::default(),
             concurrent_downloads: ConcurrentDownloadsOpt::default(),
             replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+            replay_write_sets: false,
+            encryption_key: EncryptionKeyOpt::default(),
         }
         .try_into()
         .unwrap(),
@@ -6,8 +6,9 @@ use crate::{
     metadata::Metadata,
     storage::{BackupHandleRef, BackupStorage, FileHandle, ShellSafeName},
     utils::{
-        backup_service_client::BackupServiceClient, read_record_bytes::ReadRecordBytes,
-        should_cut_chunk, storage_ext::BackupStorageExt, GlobalBackupOpt,
+        backup_service_client::BackupServiceClient, codec::ChunkCoder,
+        read_record_bytes::ReadRecordBytes, should_cut_chunk, storage_ext::BackupStorageExt,
+        GlobalBackupOpt,
     },
 };
 use anyhow::{anyhow, Result};
@@ -39,6 +40,7 @@ pub struct StateSnapshotBackupController {
     epoch: u64,
     version: Option<Version>, // initialize before using
     max_chunk_size: usize,
+    chunk_coder: ChunkCoder,
     client: Arc<BackupServiceClient>,
     storage: Arc<dyn BackupStorage>,
 }
@@ -54,6 +56,7 @@ impl StateSnapshotBackupController {
             epoch: opt.epoch,
             version: None,
             max_chunk_size: global_opt.max_chunk_size,
+            chunk_coder: ChunkCoder::new_with_opt(global_opt.codec),
             client,
             storage,
         }
@@ -202,7 +205,9 @@ impl StateSnapshotBackupController {
             .storage
             .create_for_write(backup_handle, &Self::chunk_name(first_idx))
             .await?;
-        chunk_file.write_all(chunk_bytes).await?;
+        chunk_file
+            .write_all(&self.chunk_coder.encode(chunk_bytes)?)
+            .await?;
         chunk_file.shutdown().await?;
         let (proof_handle, mut proof_file) = self
             .storage
@@ -225,6 +230,7 @@ impl StateSnapshotBackupController {
             last_key,
             blobs: chunk_handle,
             proof: proof_handle,
+            codec: self.chunk_coder.codec(),
         })
     }
 
@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::storage::FileHandle;
+use crate::{storage::FileHandle, utils::codec::Codec};
 use aptos_crypto::HashValue;
 use aptos_types::transaction::Version;
 use serde::{Deserialize, Serialize};
@@ -24,6 +24,9 @@ pub struct StateSnapshotChunk {
     /// BCS serialized `SparseMerkleRangeProof` that proves this chunk adds up to the root hash
     /// indicated in the backup (`StateSnapshotBackup::root_hash`).
     pub proof: FileHandle,
+    /// How the `blobs` file is encoded.
+    #[serde(default, skip_serializing_if = "Codec::is_plain")]
+    pub codec: Codec,
 }
 
 /// State snapshot backup manifest, representing a complete state view at specified version.
@@ -5,7 +5,8 @@ use crate::metrics::OTHER_TIMERS_SECONDS;
 use crate::utils::stream::StreamX;
 use crate::{
     backup_types::{
-        epoch_ending::restore::EpochHistory, state_snapshot::manifest::StateSnapshotBackup,
+        epoch_ending::restore::EpochHistory,
+        state_snapshot::manifest::{StateSnapshotBackup, StateSnapshotChunk},
     },
     metrics::{
         restore::{
@@ -18,8 +19,8 @@ use crate::{
     },
     storage::{BackupStorage, FileHandle},
     utils::{
-        read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, GlobalRestoreOptions,
-        RestoreRunMode,
+        codec::ChunkCoder, read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt,
+        GlobalRestoreOptions, RestoreRunMode,
     },
 };
 use anyhow::{anyhow, ensure, Result};
@@ -56,6 +57,7 @@ pub struct StateSnapshotRestoreController {
     target_version: Version,
     epoch_history: Option<Arc<EpochHistory>>,
     concurrent_downloads: usize,
+    chunk_coder: ChunkCoder,
 }
 
 impl StateSnapshotRestoreController {
@@ -73,6 +75,7 @@ impl StateSnapshotRestoreController {
             target_version: global_opt.target_version,
             epoch_history,
             concurrent_downloads: global_opt.concurrent_downloads,
+            chunk_coder: global_opt.chunk_coder,
         }
     }
 
@@ -166,11 +169,13 @@ impl StateSnapshotRestoreController {
         let start_idx = chunks.first().map_or(0, |chunk| chunk.first_idx);
 
         let storage = self.storage.clone();
+        let chunk_coder = self.chunk_coder.clone();
         let futs_iter = chunks.into_iter().enumerate().map(|(chunk_idx, chunk)| {
             let storage = storage.clone();
+            let chunk_coder = chunk_coder.clone();
             async move {
                 tokio::spawn(async move {
-                    let blobs = Self::read_state_value(&storage, chunk.blobs.clone()).await?;
+                    let blobs = Self::read_state_value(&storage, &chunk, &chunk_coder).await?;
                     let proof = storage.load_bcs_file(&chunk.proof).await?;
                     Result::<_>::Ok((chunk_idx, chunk, blobs, proof))
                 })
@@ -209,9 +214,12 @@ impl StateSnapshotRestoreController {
 
     async fn read_state_value(
         storage: &Arc<dyn BackupStorage>,
-        file_handle: FileHandle,
+        chunk: &StateSnapshotChunk,
+        chunk_coder: &ChunkCoder,
     ) -> Result<Vec<(StateKey, StateValue)>> {
-        let mut file = storage.open_for_read(&file_handle).await?;
+        let mut file = storage
+            .open_chunk_for_read(&chunk.blobs, &chunk.codec, chunk_coder)
+            .await?;
 
         let mut chunk = vec![];
 
@@ -10,6 +10,7 @@ use crate::{
     storage::{local_fs::LocalFs, BackupStorage},
     utils::{
         backup_service_client::BackupServiceClient,
+        codec::{ChunkCodecOpt, EncryptionKeyOpt},
         test_utils::{start_local_backup_service, tmp_db_with_random_content},
         ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, RocksdbOpt, TrustedWaypointOpt,
     },
@@ -63,6 +64,7 @@ fn end_to_end() {
                 StateSnapshotBackupOpt { epoch },
                 GlobalBackupOpt {
                     max_chunk_size: 500,
+                    codec: ChunkCodecOpt::default(),
                 },
                 client,
                 Arc::clone(&store),
@@ -85,6 +87,8 @@ fn end_to_end() {
                 rocksdb_opt: RocksdbOpt::default(),
                 concurrent_downloads: ConcurrentDownloadsOpt::default(),
                 replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+                replay_write_sets: false,
+                encryption_key: EncryptionKeyOpt::default(),
             }
             .try_into()
             .unwrap(),
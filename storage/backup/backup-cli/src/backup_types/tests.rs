@@ -15,7 +15,9 @@ use crate::{
     },
     storage::{local_fs::LocalFs, BackupStorage},
     utils::{
-        backup_service_client::BackupServiceClient, test_utils::start_local_backup_service,
+        backup_service_client::BackupServiceClient,
+        codec::{ChunkCodecOpt, Compression, EncryptionKey, EncryptionKeyOpt},
+        test_utils::start_local_backup_service,
         ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, GlobalRestoreOptions,
         RocksdbOpt, TrustedWaypointOpt,
     },
@@ -36,6 +38,7 @@ struct TestData {
     state_snapshot_epoch: Option<u64>,
     state_snapshot_ver: Option<u64>,
     target_ver: Version,
+    replay_write_sets: bool,
 }
 
 fn test_data_strategy() -> impl Strategy<Value = TestData> {
@@ -58,15 +61,23 @@ fn test_data_strategy() -> impl Strategy<Value = TestData> {
                 prop_oneof![Just(Some(state_snapshot_epoch)), Just(None)],
                 Just(state_snapshot_ver),
                 state_snapshot_ver..=latest_ver,
+                any::<bool>(),
             )
         })
         .prop_map(
-            move |(txn_start_ver, state_snapshot_epoch, state_snapshot_ver, target_ver)| TestData {
+            move |(
+                txn_start_ver,
+                state_snapshot_epoch,
+                state_snapshot_ver,
+                target_ver,
+                replay_write_sets,
+            )| TestData {
                 db: Arc::clone(&db),
                 txn_start_ver,
                 state_snapshot_epoch,
                 state_snapshot_ver: state_snapshot_epoch.map(|_| state_snapshot_ver),
                 target_ver,
+                replay_write_sets,
             },
         )
 }
@@ -85,8 +96,16 @@ fn test_end_to_end_impl(d: TestData) {
     let num_txns_to_backup = d.target_ver - d.txn_start_ver + 1;
 
     // Backup
+    // Chunk files are compressed and encrypted, to cover decoding them on restore.
+    let encryption_key = EncryptionKey::new([7; 32]);
     let global_backup_opt = GlobalBackupOpt {
         max_chunk_size: 2048,
+        codec: ChunkCodecOpt {
+            compression: Compression::Lz4,
+            encryption_key: EncryptionKeyOpt {
+                encryption_key: Some(encryption_key.clone()),
+            },
+        },
     };
     let state_snapshot_manifest = d.state_snapshot_epoch.map(|epoch| {
         rt.block_on(
@@ -124,6 +143,10 @@ fn test_end_to_end_impl(d: TestData) {
         rocksdb_opt: RocksdbOpt::default(),
         concurrent_downloads: ConcurrentDownloadsOpt::default(),
         replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+        replay_write_sets: d.replay_write_sets,
+        encryption_key: EncryptionKeyOpt {
+            encryption_key: Some(encryption_key),
+        },
     }
     .try_into()
     .unwrap();
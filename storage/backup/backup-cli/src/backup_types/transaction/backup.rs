@@ -6,8 +6,9 @@ use crate::{
     metadata::Metadata,
     storage::{BackupHandleRef, BackupStorage, FileHandle, ShellSafeName},
     utils::{
-        backup_service_client::BackupServiceClient, read_record_bytes::ReadRecordBytes,
-        should_cut_chunk, storage_ext::BackupStorageExt, GlobalBackupOpt,
+        backup_service_client::BackupServiceClient, codec::ChunkCoder,
+        read_record_bytes::ReadRecordBytes, should_cut_chunk, storage_ext::BackupStorageExt,
+        GlobalBackupOpt,
     },
 };
 use anyhow::{anyhow, ensure, Result};
@@ -31,6 +32,7 @@ pub struct TransactionBackupController {
     start_version: u64,
     num_transactions: usize,
     max_chunk_size: usize,
+    chunk_coder: ChunkCoder,
     client: Arc<BackupServiceClient>,
     storage: Arc<dyn BackupStorage>,
 }
@@ -46,6 +48,7 @@ impl TransactionBackupController {
             start_version: opt.start_version,
             num_transactions: opt.num_transactions,
             max_chunk_size: global_opt.max_chunk_size,
+            chunk_coder: ChunkCoder::new_with_opt(global_opt.codec),
             client,
             storage,
         }
@@ -128,7 +131,7 @@ impl TransactionBackupController {
         format!("transaction_{}-", self.start_version)
     }
 
-    fn manifest_name() -> &'static ShellSafeName {
+    pub(crate) fn manifest_name() -> &'static ShellSafeName {
         static NAME: Lazy<ShellSafeName> =
             Lazy::new(|| ShellSafeName::from_str("transaction.manifest").unwrap());
         &NAME
@@ -172,7 +175,9 @@ impl TransactionBackupController {
             .storage
             .create_for_write(backup_handle, &Self::chunk_name(first_version))
             .await?;
-        chunk_file.write_all(chunk_bytes).await?;
+        chunk_file
+            .write_all(&self.chunk_coder.encode(chunk_bytes)?)
+            .await?;
         chunk_file.shutdown().await?;
 
         Ok(TransactionChunk {
@@ -180,6 +185,7 @@ impl TransactionBackupController {
             last_version,
             transactions: chunk_handle,
             proof: proof_handle,
+            codec: self.chunk_coder.codec(),
         })
     }
 
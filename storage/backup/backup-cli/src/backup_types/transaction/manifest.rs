@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::storage::FileHandle;
+use crate::{storage::FileHandle, utils::codec::Codec};
 use anyhow::{ensure, Result};
 use aptos_types::transaction::Version;
 use serde::{Deserialize, Serialize};
@@ -21,6 +21,9 @@ pub struct TransactionChunk {
     /// signatures it carries, against the validator set in the epoch. (Hence proper
     /// `EpochEndingBackup` is needed for verification.)
     pub proof: FileHandle,
+    /// How the `transactions` file is encoded.
+    #[serde(default, skip_serializing_if = "Codec::is_plain")]
+    pub codec: Codec,
 }
 
 /// Transaction backup manifest, representing transactions in the
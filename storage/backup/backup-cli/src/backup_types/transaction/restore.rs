@@ -7,12 +7,14 @@ use crate::{
         epoch_ending::restore::EpochHistory,
         transaction::manifest::{TransactionBackup, TransactionChunk},
     },
+    metadata::TransactionBackupMeta,
     metrics::{
         restore::{TRANSACTION_REPLAY_VERSION, TRANSACTION_SAVE_VERSION},
         verify::VERIFY_TRANSACTION_VERSION,
     },
     storage::{BackupStorage, FileHandle},
     utils::{
+        codec::ChunkCoder,
         error_notes::ErrorNotes,
         read_record_bytes::ReadRecordBytes,
         storage_ext::BackupStorageExt,
@@ -41,7 +43,7 @@ use futures::{
     stream::{Peekable, Stream, TryStreamExt},
     StreamExt,
 };
-use itertools::zip_eq;
+use itertools::izip;
 use std::{
     cmp::{max, min},
     pin::Pin,
@@ -53,6 +55,8 @@ use tokio::io::BufReader;
 
 const BATCH_SIZE: usize = if cfg!(test) { 2 } else { 10000 };
 
+type TransactionToReplay = (Transaction, TransactionInfo, WriteSet, Vec<ContractEvent>);
+
 #[derive(Parser)]
 pub struct TransactionRestoreOpt {
     #[clap(long = "transaction-manifest")]
@@ -82,6 +86,7 @@ struct LoadedChunk {
     pub txns: Vec<Transaction>,
     pub txn_infos: Vec<TransactionInfo>,
     pub event_vecs: Vec<Vec<ContractEvent>>,
+    pub write_sets: Vec<WriteSet>,
     pub range_proof: TransactionAccumulatorRangeProof,
     pub ledger_info: LedgerInfoWithSignatures,
 }
@@ -90,19 +95,25 @@ impl LoadedChunk {
     async fn load(
         manifest: TransactionChunk,
         storage: &Arc<dyn BackupStorage>,
+        chunk_coder: &ChunkCoder,
         epoch_history: Option<&Arc<EpochHistory>>,
     ) -> Result<Self> {
-        let mut file = BufReader::new(storage.open_for_read(&manifest.transactions).await?);
+        let mut file = BufReader::new(
+            storage
+                .open_chunk_for_read(&manifest.transactions, &manifest.codec, chunk_coder)
+                .await?,
+        );
         let mut txns = Vec::new();
         let mut txn_infos = Vec::new();
         let mut event_vecs = Vec::new();
+        let mut write_sets = Vec::new();
 
         while let Some(record_bytes) = file.read_record_bytes().await? {
-            let (txn, txn_info, events, _write_set): (_, _, _, WriteSet) =
-                bcs::from_bytes(&record_bytes)?;
+            let (txn, txn_info, events, write_set) = bcs::from_bytes(&record_bytes)?;
             txns.push(txn);
             txn_infos.push(txn_info);
             event_vecs.push(events);
+            write_sets.push(write_set);
         }
 
         ensure!(
@@ -143,6 +154,7 @@ impl LoadedChunk {
             txns,
             txn_infos,
             event_vecs,
+            write_sets,
             range_proof,
             ledger_info,
         })
@@ -281,14 +293,17 @@ impl TransactionRestoreBatchController {
             });
 
         let storage = self.storage.clone();
+        let chunk_coder = self.global_opt.chunk_coder.clone();
         let epoch_history = self.epoch_history.clone();
         chunk_manifest_stream
             .and_then(move |chunk| {
                 let storage = storage.clone();
+                let chunk_coder = chunk_coder.clone();
                 let epoch_history = epoch_history.clone();
                 future::ok(async move {
                     tokio::task::spawn(async move {
-                        LoadedChunk::load(chunk, &storage, epoch_history.as_ref()).await
+                        LoadedChunk::load(chunk, &storage, &chunk_coder, epoch_history.as_ref())
+                            .await
                     })
                     .err_into::<anyhow::Error>()
                     .await
@@ -325,7 +340,7 @@ impl TransactionRestoreBatchController {
         global_first_version: Version,
         loaded_chunk_stream: impl Stream<Item = Result<LoadedChunk>> + Unpin,
         restore_handler: &RestoreHandler,
-    ) -> Result<Option<impl Stream<Item = Result<(Transaction, TransactionInfo)>>>> {
+    ) -> Result<Option<impl Stream<Item = Result<TransactionToReplay>>>> {
         let next_expected_version = self
             .global_opt
             .run_mode
@@ -355,10 +370,12 @@ impl TransactionRestoreBatchController {
                                 mut last_version,
                                 transactions: _,
                                 proof: _,
+                                codec: _,
                             },
                         mut txns,
                         mut txn_infos,
                         mut event_vecs,
+                        mut write_sets,
                         range_proof: _,
                         ledger_info: _,
                     } = chunk;
@@ -368,6 +385,7 @@ impl TransactionRestoreBatchController {
                         txns.drain(num_to_keep..);
                         txn_infos.drain(num_to_keep..);
                         event_vecs.drain(num_to_keep..);
+                        write_sets.drain(num_to_keep..);
                         last_version = target_version;
                     }
 
@@ -377,6 +395,7 @@ impl TransactionRestoreBatchController {
                         let txns_to_save: Vec<_> = txns.drain(..num_to_save).collect();
                         let txn_infos_to_save: Vec<_> = txn_infos.drain(..num_to_save).collect();
                         let event_vecs_to_save: Vec<_> = event_vecs.drain(..num_to_save).collect();
+                        write_sets.drain(..num_to_save);
 
                         tokio::task::spawn_blocking(move || {
                             restore_handler.save_transactions(
@@ -398,7 +417,7 @@ impl TransactionRestoreBatchController {
                     }
 
                     Ok(stream::iter(
-                        zip_eq(txns, txn_infos).into_iter().map(Result::<_>::Ok),
+                        izip!(txns, txn_infos, write_sets, event_vecs).map(Result::<_>::Ok),
                     ))
                 })
             })
@@ -422,27 +441,44 @@ impl TransactionRestoreBatchController {
     async fn replay_transactions(
         &self,
         restore_handler: &RestoreHandler,
-        txns_to_execute_stream: impl Stream<Item = Result<(Transaction, TransactionInfo)>>,
+        txns_to_execute_stream: impl Stream<Item = Result<TransactionToReplay>>,
     ) -> Result<()> {
         let first_version = self.replay_from_version.unwrap();
         restore_handler.reset_state_store();
         let replay_start = Instant::now();
         let db = DbReaderWriter::from_arc(Arc::clone(&restore_handler.aptosdb));
         let chunk_replayer = Arc::new(ChunkExecutor::<AptosVM>::new(db));
+        let replay_write_sets = self.global_opt.replay_write_sets;
 
         let db_commit_stream = txns_to_execute_stream
             .try_chunks(BATCH_SIZE)
             .err_into::<anyhow::Error>()
             .map_ok(|chunk| {
-                let (txns, txn_infos): (Vec<_>, Vec<_>) = chunk.into_iter().unzip();
+                let mut txns = Vec::with_capacity(chunk.len());
+                let mut txn_infos = Vec::with_capacity(chunk.len());
+                let mut write_sets = Vec::with_capacity(chunk.len());
+                let mut event_vecs = Vec::with_capacity(chunk.len());
+                for (txn, txn_info, write_set, events) in chunk {
+                    txns.push(txn);
+                    txn_infos.push(txn_info);
+                    write_sets.push(write_set);
+                    event_vecs.push(events);
+                }
                 let chunk_replayer = chunk_replayer.clone();
                 async move {
                     let _timer = OTHER_TIMERS_SECONDS
                         .with_label_values(&["replay_txn_chunk"])
                         .start_timer();
-                    tokio::task::spawn_blocking(move || chunk_replayer.replay(txns, txn_infos))
-                        .err_into::<anyhow::Error>()
-                        .await
+                    tokio::task::spawn_blocking(move || {
+                        if replay_write_sets {
+                            chunk_replayer
+                                .replay_write_sets(txns, txn_infos, write_sets, event_vecs)
+                        } else {
+                            chunk_replayer.replay(txns, txn_infos)
+                        }
+                    })
+                    .err_into::<anyhow::Error>()
+                    .await
                 }
             })
             .try_buffered_x(self.global_opt.concurrent_downloads, 1)
@@ -501,3 +537,82 @@ impl TransactionRestoreBatchController {
             .await
     }
 }
+
+/// Finds the last version in a block with a timestamp at or before `timestamp_usecs`, i.e. the
+/// one right before the first block after it, or the last one backed up if there's no such block.
+/// The chunks of `transaction_backups`, continuous and in order, are binary searched. `None` if
+/// the backups start after the timestamp.
+pub(crate) async fn last_version_before(
+    storage: &Arc<dyn BackupStorage>,
+    chunk_coder: &ChunkCoder,
+    transaction_backups: &[TransactionBackupMeta],
+    timestamp_usecs: u64,
+) -> Result<Option<Version>> {
+    let mut chunks = Vec::new();
+    for backup in transaction_backups {
+        let manifest: TransactionBackup = storage.load_json_file(&backup.manifest).await?;
+        manifest.verify()?;
+        chunks.extend(manifest.chunks);
+    }
+    let (first_version, last_version) = match (chunks.first(), chunks.last()) {
+        (Some(first), Some(last)) => (first.first_version, last.last_version),
+        _ => return Ok(None),
+    };
+
+    // Finds the first chunk where the last block started up until its end is after the
+    // timestamp. A chunk starting no block is within the block started before it, so that
+    // chunk always starts blocks, the first of them after the timestamp found below.
+    let (mut lo, mut hi) = (0, chunks.len());
+    while lo < hi {
+        let mid = (lo + hi) / 2;
+        let mut after = false;
+        for chunk in chunks[..=mid].iter().rev() {
+            if let Some((_, block_timestamp_usecs)) =
+                load_block_starts(storage, chunk_coder, chunk).await?.last()
+            {
+                after = *block_timestamp_usecs > timestamp_usecs;
+                break;
+            }
+        }
+        if after {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    if lo == chunks.len() {
+        return Ok(Some(last_version));
+    }
+    let (first_block_after, _) = load_block_starts(storage, chunk_coder, &chunks[lo])
+        .await?
+        .into_iter()
+        .find(|(_, block_timestamp_usecs)| *block_timestamp_usecs > timestamp_usecs)
+        .ok_or_else(|| anyhow!("Block timestamps not increasing in the transaction backups."))?;
+    Ok(first_block_after
+        .checked_sub(1)
+        .filter(|version| *version >= first_version))
+}
+
+/// The versions and timestamps of the blocks started in `chunk`.
+async fn load_block_starts(
+    storage: &Arc<dyn BackupStorage>,
+    chunk_coder: &ChunkCoder,
+    chunk: &TransactionChunk,
+) -> Result<Vec<(Version, u64)>> {
+    let mut file = BufReader::new(
+        storage
+            .open_chunk_for_read(&chunk.transactions, &chunk.codec, chunk_coder)
+            .await?,
+    );
+    let mut block_starts = Vec::new();
+    let mut version = chunk.first_version;
+    while let Some(record_bytes) = file.read_record_bytes().await? {
+        let (txn, _, _, _): (Transaction, TransactionInfo, Vec<ContractEvent>, WriteSet) =
+            bcs::from_bytes(&record_bytes)?;
+        if let Transaction::BlockMetadata(block_metadata) = txn {
+            block_starts.push((version, block_metadata.timestamp_usecs()));
+        }
+        version += 1;
+    }
+    Ok(block_starts)
+}
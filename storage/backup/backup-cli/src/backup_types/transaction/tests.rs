@@ -10,6 +10,7 @@ use crate::{
     storage::{local_fs::LocalFs, BackupStorage},
     utils::{
         backup_service_client::BackupServiceClient,
+        codec::{ChunkCodecOpt, EncryptionKeyOpt},
         test_utils::{start_local_backup_service, tmp_db_with_random_content},
         ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, RocksdbOpt, TrustedWaypointOpt,
     },
@@ -63,7 +64,10 @@ fn end_to_end() {
                     start_version: first_ver_to_backup,
                     num_transactions: num_txns_to_backup,
                 },
-                GlobalBackupOpt { max_chunk_size },
+                GlobalBackupOpt {
+                    max_chunk_size,
+                    codec: ChunkCodecOpt::default(),
+                },
                 client,
                 Arc::clone(&store),
             )
@@ -85,6 +89,8 @@ fn end_to_end() {
                 rocksdb_opt: RocksdbOpt::default(),
                 concurrent_downloads: ConcurrentDownloadsOpt::default(),
                 replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+                replay_write_sets: false,
+                encryption_key: EncryptionKeyOpt::default(),
             }
             .try_into()
             .unwrap(),
@@ -8,7 +8,7 @@ use backup_cli::{
     coordinators::verify::VerifyCoordinator,
     metadata::cache::MetadataCacheOpt,
     storage::StorageOpt,
-    utils::{ConcurrentDownloadsOpt, TrustedWaypointOpt},
+    utils::{codec::EncryptionKeyOpt, ConcurrentDownloadsOpt, TrustedWaypointOpt},
 };
 use clap::Parser;
 
@@ -18,6 +18,8 @@ struct Opt {
     metadata_cache_opt: MetadataCacheOpt,
     #[clap(flatten)]
     trusted_waypoints_opt: TrustedWaypointOpt,
+    #[clap(flatten)]
+    encryption_key_opt: EncryptionKeyOpt,
     #[clap(subcommand)]
     storage: StorageOpt,
     #[clap(flatten)]
@@ -43,6 +45,7 @@ async fn main_impl() -> Result<()> {
         opt.storage.init_storage().await?,
         opt.metadata_cache_opt,
         opt.trusted_waypoints_opt,
+        opt.encryption_key_opt,
         opt.concurrent_downloads.get(),
     )?
     .run()
@@ -14,7 +14,10 @@ use backup_cli::{
         state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
         transaction::backup::{TransactionBackupController, TransactionBackupOpt},
     },
-    coordinators::backup::{BackupCoordinator, BackupCoordinatorOpt},
+    coordinators::{
+        backup::{BackupCoordinator, BackupCoordinatorOpt},
+        retention::{RetentionCoordinator, RetentionCoordinatorOpt},
+    },
     metadata::{cache, cache::MetadataCacheOpt},
     storage::StorageOpt,
     utils::{
@@ -116,6 +119,12 @@ enum CoordinatorCommand {
     a Aptos Node."
     )]
     Run(CoordinatorRunOpt),
+    #[clap(
+        about = "Apply a retention policy to the existing backups once: delete old state \
+        snapshots and transactions, merge small transaction backups and compact the metadata \
+        files."
+    )]
+    Retention(CoordinatorRetentionOpt),
 }
 
 #[derive(Parser)]
@@ -133,6 +142,15 @@ struct CoordinatorRunOpt {
     storage: StorageOpt,
 }
 
+#[derive(Parser)]
+struct CoordinatorRetentionOpt {
+    #[clap(flatten)]
+    retention: RetentionCoordinatorOpt,
+
+    #[clap(subcommand)]
+    storage: StorageOpt,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     main_impl().await.map_err(|e| {
@@ -213,7 +231,15 @@ async fn main_impl() -> Result<()> {
                     opt.global,
                     Arc::new(BackupServiceClient::new_with_opt(opt.client)),
                     opt.storage.init_storage().await?,
-                )
+                )?
+                .run()
+                .await?;
+            }
+            CoordinatorCommand::Retention(opt) => {
+                RetentionCoordinator::new_with_opt(
+                    opt.retention,
+                    opt.storage.init_storage().await?,
+                )?
                 .run()
                 .await?;
             }
@@ -0,0 +1,107 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{ensure, Result};
+use aptos_config::config::{
+    BUFFERED_STATE_TARGET_ITEMS, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_logger::{prelude::*, Level, Logger};
+use aptos_types::transaction::Version;
+use aptosdb::{
+    consistency_checker::{ConsistencyCheckProgress, ConsistencyReport},
+    AptosDB,
+};
+use backup_cli::utils::RocksdbOpt;
+use clap::Parser;
+use std::{path::PathBuf, time::Duration};
+
+#[derive(Parser)]
+#[clap(
+    name = "db-check",
+    about = "Check an AptosDB against the authenticated data structures it holds: the \
+    transactions, write sets and events against the transaction infos and the transaction \
+    accumulator, and the state trees against the state checkpoint hashes and themselves."
+)]
+struct Opt {
+    #[clap(
+        long = "db-dir",
+        parse(from_os_str),
+        help = "DB to check, e.g. /opt/aptos/data/db"
+    )]
+    db_dir: PathBuf,
+
+    #[clap(
+        long,
+        default_value = "0",
+        help = "Check the transactions and the state tree nodes from this version on, or from the \
+        oldest ones not pruned if they are later."
+    )]
+    start_version: Version,
+
+    #[clap(
+        long,
+        default_value = "1000",
+        help = "Number of transactions, or state tree nodes, to check a time."
+    )]
+    batch_size: usize,
+
+    #[clap(
+        long,
+        default_value = "0",
+        help = "Pause between batches in milliseconds, which throttles the reads on a DB shared \
+        with a running node."
+    )]
+    batch_interval_ms: u64,
+
+    #[clap(flatten)]
+    rocksdb_opt: RocksdbOpt,
+}
+
+fn main() -> Result<()> {
+    Logger::new().level(Level::Info).init();
+    let opt = Opt::from_args();
+
+    // The DB is opened read only, so it can be checked while a node is running on it, as of the
+    // time it is opened.
+    let db = AptosDB::open(
+        &opt.db_dir,
+        true,                        /* readonly */
+        NO_OP_STORAGE_PRUNER_CONFIG, /* pruner */
+        opt.rocksdb_opt.into(),
+        false, /* indexer */
+        BUFFERED_STATE_TARGET_ITEMS,
+        DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    )?;
+    let checker = db.get_consistency_checker();
+
+    let mut progress = ConsistencyCheckProgress::new(opt.start_version);
+    let mut report = ConsistencyReport::default();
+    let mut num_batches: u64 = 0;
+    while !checker.check_next_batch(&mut progress, opt.batch_size, &mut report)? {
+        num_batches += 1;
+        if num_batches % 100 == 0 {
+            info!(
+                next_version = progress.next_version,
+                next_node_version = progress.next_node_key.version(),
+                num_inconsistencies = report.inconsistencies.len(),
+                "Checking DB."
+            );
+        }
+        std::thread::sleep(Duration::from_millis(opt.batch_interval_ms));
+    }
+
+    println!(
+        "Checked {} transactions and {} state tree nodes.",
+        report.num_transactions_checked, report.num_nodes_checked,
+    );
+    for inconsistency in &report.inconsistencies {
+        println!("{:?}", inconsistency);
+    }
+    ensure!(
+        report.is_consistent(),
+        "Found {} inconsistencies.",
+        report.inconsistencies.len(),
+    );
+    Ok(())
+}
@@ -14,7 +14,7 @@ use backup_cli::{
     coordinators::replay_verify::ReplayVerifyCoordinator,
     metadata::cache::MetadataCacheOpt,
     storage::StorageOpt,
-    utils::{ConcurrentDownloadsOpt, RocksdbOpt, TrustedWaypointOpt},
+    utils::{codec::EncryptionKeyOpt, ConcurrentDownloadsOpt, RocksdbOpt, TrustedWaypointOpt},
 };
 use clap::Parser;
 use std::{path::PathBuf, sync::Arc};
@@ -25,6 +25,8 @@ struct Opt {
     metadata_cache_opt: MetadataCacheOpt,
     #[clap(flatten)]
     trusted_waypoints_opt: TrustedWaypointOpt,
+    #[clap(flatten)]
+    encryption_key_opt: EncryptionKeyOpt,
     #[clap(subcommand)]
     storage: StorageOpt,
     #[clap(flatten)]
@@ -74,6 +76,7 @@ async fn main_impl() -> Result<()> {
         opt.storage.init_storage().await?,
         opt.metadata_cache_opt,
         opt.trusted_waypoints_opt,
+        opt.encryption_key_opt,
         opt.concurrent_downloads.get(),
         opt.replay_concurrency_level.get(),
         restore_handler,
@@ -7,6 +7,7 @@ use crate::{
         state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
         transaction::backup::{TransactionBackupController, TransactionBackupOpt},
     },
+    coordinators::retention::{RetentionCoordinator, RetentionPolicyOpt},
     metadata,
     metadata::cache::MetadataCacheOpt,
     metrics::backup::{
@@ -14,8 +15,9 @@ use crate::{
     },
     storage::BackupStorage,
     utils::{
-        backup_service_client::BackupServiceClient, unix_timestamp_sec, ConcurrentDownloadsOpt,
-        GlobalBackupOpt,
+        backup_service_client::BackupServiceClient,
+        codec::{ChunkCoder, Compression},
+        unix_timestamp_sec, ConcurrentDownloadsOpt, GlobalBackupOpt,
     },
 };
 use anyhow::{anyhow, ensure, Result};
@@ -27,7 +29,7 @@ use futures::{stream, Future, StreamExt};
 use std::{fmt::Debug, sync::Arc};
 use tokio::{
     sync::watch,
-    time::{interval, Duration},
+    time::{interval, interval_at, Duration, Instant, MissedTickBehavior},
 };
 use tokio_stream::wrappers::IntervalStream;
 
@@ -65,6 +67,14 @@ pub struct BackupCoordinatorOpt {
     pub transaction_batch_size: usize,
     #[clap(flatten)]
     pub concurrent_downloads: ConcurrentDownloadsOpt,
+    #[clap(flatten)]
+    pub retention_policy: RetentionPolicyOpt,
+    #[clap(
+        long = "retention-interval-secs",
+        help = "Apply the retention policy every this many seconds, while backing up. The first \
+        time is after one interval. [Defaults to never applying it]"
+    )]
+    pub retention_interval_secs: Option<u64>,
 }
 
 impl BackupCoordinatorOpt {
@@ -73,7 +83,11 @@ impl BackupCoordinatorOpt {
             self.state_snapshot_interval_epochs > 0 && self.transaction_batch_size > 0,
             "Backup interval and batch size must be greater than 0."
         );
-        Ok(())
+        ensure!(
+            self.retention_interval_secs != Some(0),
+            "Retention interval must be greater than 0."
+        );
+        self.retention_policy.validate()
     }
 }
 
@@ -85,6 +99,7 @@ pub struct BackupCoordinator {
     state_snapshot_interval_epochs: usize,
     transaction_batch_size: usize,
     concurrent_downloads: usize,
+    retention: Option<(Duration, RetentionCoordinator)>,
 }
 
 impl BackupCoordinator {
@@ -93,17 +108,36 @@ impl BackupCoordinator {
         global_opt: GlobalBackupOpt,
         client: Arc<BackupServiceClient>,
         storage: Arc<dyn BackupStorage>,
-    ) -> Self {
-        opt.validate().unwrap();
-        Self {
+    ) -> Result<Self> {
+        opt.validate()?;
+        let concurrent_downloads = opt.concurrent_downloads.get();
+        let retention = opt
+            .retention_interval_secs
+            .map(|secs| -> Result<_> {
+                let coordinator = RetentionCoordinator::new(
+                    Arc::clone(&storage),
+                    opt.metadata_cache_opt.clone(),
+                    opt.retention_policy,
+                    false, /* dry_run */
+                    ChunkCoder::new(
+                        Compression::None,
+                        global_opt.codec.encryption_key.encryption_key.clone(),
+                    ),
+                    concurrent_downloads,
+                )?;
+                Ok((Duration::from_secs(secs), coordinator))
+            })
+            .transpose()?;
+        Ok(Self {
             client,
             storage,
             global_opt,
             metadata_cache_opt: opt.metadata_cache_opt,
             state_snapshot_interval_epochs: opt.state_snapshot_interval_epochs,
             transaction_batch_size: opt.transaction_batch_size,
-            concurrent_downloads: opt.concurrent_downloads.get(),
-        }
+            concurrent_downloads,
+            retention,
+        })
     }
 
     pub async fn run(&self) -> Result<()> {
@@ -153,13 +187,27 @@ impl BackupCoordinator {
             )
             .boxed_local();
 
-        info!("Backup coordinator started.");
-        let mut all_work = stream::select_all(vec![
+        let mut work_streams = vec![
             watch_db_state,
             backup_epoch_endings,
             backup_state_snapshots,
             backup_transactions,
-        ]);
+        ];
+        if let Some((period, retention)) = &self.retention {
+            let mut retention_interval = interval_at(Instant::now() + *period, *period);
+            retention_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            let apply_retention = IntervalStream::new(retention_interval)
+                .then(move |_| async move {
+                    if let Err(e) = retention.run().await {
+                        warn!("Applying retention policy failed: {}. Will retry later.", e);
+                    }
+                })
+                .boxed_local();
+            work_streams.push(apply_retention);
+        }
+
+        info!("Backup coordinator started.");
+        let mut all_work = stream::select_all(work_streams);
 
         loop {
             all_work
@@ -4,4 +4,5 @@
 pub mod backup;
 pub mod replay_verify;
 pub mod restore;
+pub mod retention;
 pub mod verify;
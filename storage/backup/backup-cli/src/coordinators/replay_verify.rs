@@ -9,7 +9,10 @@ use crate::{
     metadata,
     metadata::cache::MetadataCacheOpt,
     storage::BackupStorage,
-    utils::{GlobalRestoreOptions, RestoreRunMode, TrustedWaypointOpt},
+    utils::{
+        codec::{ChunkCoder, Compression, EncryptionKeyOpt},
+        GlobalRestoreOptions, RestoreRunMode, TrustedWaypointOpt,
+    },
 };
 use anyhow::{ensure, Result};
 use aptos_logger::prelude::*;
@@ -22,6 +25,7 @@ pub struct ReplayVerifyCoordinator {
     storage: Arc<dyn BackupStorage>,
     metadata_cache_opt: MetadataCacheOpt,
     trusted_waypoints_opt: TrustedWaypointOpt,
+    encryption_key_opt: EncryptionKeyOpt,
     concurrent_downloads: usize,
     replay_concurrency_level: usize,
     restore_handler: RestoreHandler,
@@ -34,6 +38,7 @@ impl ReplayVerifyCoordinator {
         storage: Arc<dyn BackupStorage>,
         metadata_cache_opt: MetadataCacheOpt,
         trusted_waypoints_opt: TrustedWaypointOpt,
+        encryption_key_opt: EncryptionKeyOpt,
         concurrent_downloads: usize,
         replay_concurrency_level: usize,
         restore_handler: RestoreHandler,
@@ -44,6 +49,7 @@ impl ReplayVerifyCoordinator {
             storage,
             metadata_cache_opt,
             trusted_waypoints_opt,
+            encryption_key_opt,
             concurrent_downloads,
             replay_concurrency_level,
             restore_handler,
@@ -105,6 +111,8 @@ impl ReplayVerifyCoordinator {
             }),
             concurrent_downloads: self.concurrent_downloads,
             replay_concurrency_level: 0, // won't replay, doesn't matter
+            replay_write_sets: false,
+            chunk_coder: ChunkCoder::new(Compression::None, self.encryption_key_opt.encryption_key),
         };
 
         if let Some(backup) = state_snapshot {
@@ -3,12 +3,12 @@
 
 use crate::{
     backup_types::{
-        epoch_ending::restore::EpochHistoryRestoreController,
+        epoch_ending::restore::{EpochHistory, EpochHistoryRestoreController},
         state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
-        transaction::restore::TransactionRestoreBatchController,
+        transaction::restore::{last_version_before, TransactionRestoreBatchController},
     },
     metadata,
-    metadata::{cache::MetadataCacheOpt, TransactionBackupMeta},
+    metadata::{cache::MetadataCacheOpt, view::MetadataView, TransactionBackupMeta},
     metrics::restore::{
         COORDINATOR_FAIL_TS, COORDINATOR_START_TS, COORDINATOR_SUCC_TS, COORDINATOR_TARGET_VERSION,
     },
@@ -19,7 +19,10 @@ use anyhow::{anyhow, bail, Result};
 use aptos_logger::prelude::*;
 use aptos_types::transaction::Version;
 use clap::Parser;
-use std::sync::Arc;
+use std::{
+    cmp::{max, min},
+    sync::Arc,
+};
 
 #[derive(Parser)]
 pub struct RestoreCoordinatorOpt {
@@ -39,6 +42,20 @@ pub struct RestoreCoordinatorOpt {
     pub ledger_history_start_version: Option<Version>,
     #[clap(long, help = "Skip restoring epoch ending info, used for debugging.")]
     pub skip_epoch_endings: bool,
+    #[clap(
+        long,
+        help = "Restore to the last transaction in a block at or before this unix timestamp in \
+        seconds. If --target-version is also given, the earlier of the two is used."
+    )]
+    pub target_time_secs: Option<u64>,
+    #[clap(
+        long,
+        help = "Restore only the state snapshot closest to the target, with the transaction at \
+        its version, skipping the transactions between the snapshot and the target. The DB ends \
+        at the version of the snapshot instead of the target, which takes minutes instead of \
+        hours of replaying."
+    )]
+    pub state_only: bool,
 }
 
 pub struct RestoreCoordinator {
@@ -48,6 +65,8 @@ pub struct RestoreCoordinator {
     replay_all: bool,
     ledger_history_start_version: Option<Version>,
     skip_epoch_endings: bool,
+    target_time_secs: Option<u64>,
+    state_only: bool,
 }
 
 impl RestoreCoordinator {
@@ -63,6 +82,8 @@ impl RestoreCoordinator {
             replay_all: opt.replay_all,
             ledger_history_start_version: opt.ledger_history_start_version,
             skip_epoch_endings: opt.skip_epoch_endings,
+            target_time_secs: opt.target_time_secs,
+            state_only: opt.state_only,
         }
     }
 
@@ -94,7 +115,11 @@ impl RestoreCoordinator {
         //   2. a only transaction and its output, at the state snapshot version
         //   3. the epoch history from 0 up until the latest closed epoch preceding the state
         //      snapshot version.
-        // And it does so in a resume-able way.
+        //   4. unless --state-only, the transactions after the state snapshot up until the target
+        //      version, replayed.
+        // An interrupted state snapshot restore is picked up where it stopped, and once the DB
+        // holds a state snapshot, an interrupted replay continues from the next version in the DB
+        // up until the target version.
 
         if self.replay_all {
             bail!("--replay--all not supported in this version.");
@@ -110,19 +135,46 @@ impl RestoreCoordinator {
         )
         .await?;
 
+        let max_txn_ver = metadata_view
+            .max_transaction_version()?
+            .ok_or_else(|| anyhow!("No transaction backup found."))?;
+        let target_version = min(
+            self.resolve_target_version(&metadata_view).await?,
+            max_txn_ver,
+        );
+
         let next_txn_version = self
             .global_opt
             .run_mode
             .get_next_expected_transaction_version()?;
         if next_txn_version != 0 {
-            // DB is already in workable state
+            if self.state_only || next_txn_version > target_version {
+                // DB is already in workable state
+                info!(
+                    next_txn_version = next_txn_version,
+                    "DB is ready to accept transactions, start the node to catch up with the \
+                    chain. If the node is unable to catch up because the DB is too old, delete \
+                    the data folder and bootstrap again.",
+                );
+                return Ok(());
+            }
+            COORDINATOR_TARGET_VERSION.set(target_version as i64);
             info!(
                 next_txn_version = next_txn_version,
-                "DB is ready to accept transactions, start the node to catch up with the chain. \
-                If the node is unable to catch up because the DB is too old, delete the data folder \
-                and bootstrap again.",
+                target_version = target_version,
+                "Resuming replay."
             );
-            return Ok(());
+            let version = next_txn_version - 1;
+            let global_opt = GlobalRestoreOptions {
+                target_version,
+                ..self.global_opt.clone()
+            };
+            let epoch_history = self
+                .restore_epoch_history(&metadata_view, version, &global_opt)
+                .await?;
+            return self
+                .restore_transactions(&metadata_view, version, global_opt, epoch_history)
+                .await;
         }
 
         let state_snapshot_backup =
@@ -133,63 +185,94 @@ impl RestoreCoordinator {
                 );
                 metadata_view.expect_state_snapshot(version)?
             } else {
-                let max_txn_ver = metadata_view
-                    .max_transaction_version()?
-                    .ok_or_else(|| anyhow!("No transaction backup found."))?;
                 metadata_view
-                    .select_state_snapshot(std::cmp::min(self.target_version(), max_txn_ver))?
+                    .select_state_snapshot(target_version)?
                     .ok_or_else(|| anyhow!("No usable state snapshot."))?
             };
         let version = state_snapshot_backup.version;
-        let epoch_ending_backups = metadata_view.select_epoch_ending_backups(version)?;
-        let transaction_backup = metadata_view
-            .select_transaction_backups(version, version)?
-            .pop()
-            .unwrap();
-        COORDINATOR_TARGET_VERSION.set(version as i64);
-        info!(version = version, "Restore target decided.");
-
-        let epoch_history = if !self.skip_epoch_endings {
-            Some(Arc::new(
-                EpochHistoryRestoreController::new(
-                    epoch_ending_backups
-                        .into_iter()
-                        .map(|backup| backup.manifest)
-                        .collect(),
-                    self.global_opt.clone(),
-                    self.storage.clone(),
-                )
-                .run()
-                .await?,
-            ))
+        let target_version = if self.state_only {
+            version
         } else {
-            None
+            max(target_version, version)
         };
+        COORDINATOR_TARGET_VERSION.set(target_version as i64);
+        info!(
+            state_snapshot_version = version,
+            target_version = target_version,
+            "Restore target decided."
+        );
 
+        let global_opt = GlobalRestoreOptions {
+            target_version,
+            ..self.global_opt.clone()
+        };
+        let epoch_history = self
+            .restore_epoch_history(&metadata_view, version, &global_opt)
+            .await?;
         StateSnapshotRestoreController::new(
             StateSnapshotRestoreOpt {
                 manifest_handle: state_snapshot_backup.manifest,
                 version,
             },
-            self.global_opt.clone(),
+            global_opt.clone(),
             Arc::clone(&self.storage),
             epoch_history.clone(),
         )
         .run()
         .await?;
 
-        let txn_manifests = vec![transaction_backup.manifest];
+        self.restore_transactions(&metadata_view, version, global_opt, epoch_history)
+            .await
+    }
+
+    /// Restores the epoch endings up until `version`, unless --skip-epoch-endings.
+    async fn restore_epoch_history(
+        &self,
+        metadata_view: &MetadataView,
+        version: Version,
+        global_opt: &GlobalRestoreOptions,
+    ) -> Result<Option<Arc<EpochHistory>>> {
+        if self.skip_epoch_endings {
+            return Ok(None);
+        }
+        let epoch_ending_backups = metadata_view.select_epoch_ending_backups(version)?;
+        Ok(Some(Arc::new(
+            EpochHistoryRestoreController::new(
+                epoch_ending_backups
+                    .into_iter()
+                    .map(|backup| backup.manifest)
+                    .collect(),
+                global_opt.clone(),
+                Arc::clone(&self.storage),
+            )
+            .run()
+            .await?,
+        )))
+    }
+
+    /// Saves the transactions from `version`, the last one already in the DB, and replays them
+    /// from `version + 1` up until the target version.
+    async fn restore_transactions(
+        &self,
+        metadata_view: &MetadataView,
+        version: Version,
+        global_opt: GlobalRestoreOptions,
+        epoch_history: Option<Arc<EpochHistory>>,
+    ) -> Result<()> {
+        let txn_manifests = metadata_view
+            .select_transaction_backups(version, global_opt.target_version)?
+            .into_iter()
+            .map(|backup| backup.manifest)
+            .collect();
         TransactionRestoreBatchController::new(
-            self.global_opt,
-            self.storage,
+            global_opt,
+            Arc::clone(&self.storage),
             txn_manifests,
             Some(version + 1),
             epoch_history,
         )
         .run()
-        .await?;
-
-        Ok(())
+        .await
     }
 }
 
@@ -198,6 +281,30 @@ impl RestoreCoordinator {
         self.global_opt.target_version
     }
 
+    /// The target version, brought forward to the last transaction in a block at or before
+    /// `--target-time-secs` if given.
+    async fn resolve_target_version(&self, metadata_view: &MetadataView) -> Result<Version> {
+        let target_version = self.target_version();
+        match self.target_time_secs {
+            Some(secs) => {
+                let transaction_backups = metadata_view.select_transaction_backups(
+                    metadata_view.min_transaction_version().unwrap_or(0),
+                    target_version,
+                )?;
+                let version = last_version_before(
+                    &self.storage,
+                    &self.global_opt.chunk_coder,
+                    &transaction_backups,
+                    secs * 1_000_000,
+                )
+                .await?
+                .ok_or_else(|| anyhow!("No transaction at or before {} in the backups.", secs))?;
+                Ok(min(target_version, version))
+            }
+            None => Ok(target_version),
+        }
+    }
+
     #[allow(dead_code)]
     fn get_actual_target_version(
         &self,
@@ -218,3 +325,221 @@ impl RestoreCoordinator {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        backup_types::{
+            epoch_ending::backup::{EpochEndingBackupController, EpochEndingBackupOpt},
+            state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+            transaction::backup::{TransactionBackupController, TransactionBackupOpt},
+        },
+        storage::local_fs::LocalFs,
+        utils::{
+            backup_service_client::BackupServiceClient,
+            codec::{ChunkCodecOpt, ChunkCoder},
+            test_utils::start_local_backup_service,
+            ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, ReplayConcurrencyLevelOpt,
+            RocksdbOpt, TrustedWaypointOpt,
+        },
+    };
+    use aptos_temppath::TempPath;
+    use aptos_types::{ledger_info::LedgerInfo, transaction::Transaction};
+    use aptosdb::AptosDB;
+    use executor_test_helpers::integration_test_impl::test_execution_with_storage_impl;
+    use std::convert::TryInto;
+    use storage_interface::DbReader;
+    use tokio::{runtime::Runtime, time::Duration};
+
+    /// Backs up all the epoch endings of `db`, a state snapshot at each of them and all the
+    /// transactions.
+    fn backup(rt: &Runtime, port: u16, db: &AptosDB, storage: &Arc<dyn BackupStorage>) {
+        let client = Arc::new(BackupServiceClient::new(format!(
+            "http://localhost:{}",
+            port
+        )));
+        let global_opt = GlobalBackupOpt {
+            max_chunk_size: 2048,
+            codec: ChunkCodecOpt::default(),
+        };
+        let latest_epoch = db.get_latest_epoch_state().unwrap().epoch;
+        rt.block_on(
+            EpochEndingBackupController::new(
+                EpochEndingBackupOpt {
+                    start_epoch: 0,
+                    end_epoch: latest_epoch,
+                },
+                global_opt.clone(),
+                Arc::clone(&client),
+                Arc::clone(storage),
+            )
+            .run(),
+        )
+        .unwrap();
+        for epoch in 0..latest_epoch {
+            rt.block_on(
+                StateSnapshotBackupController::new(
+                    StateSnapshotBackupOpt { epoch },
+                    global_opt.clone(),
+                    Arc::clone(&client),
+                    Arc::clone(storage),
+                )
+                .run(),
+            )
+            .unwrap();
+        }
+        rt.block_on(
+            TransactionBackupController::new(
+                TransactionBackupOpt {
+                    start_version: 0,
+                    num_transactions: db.get_latest_version().unwrap() as usize + 1,
+                },
+                global_opt,
+                client,
+                Arc::clone(storage),
+            )
+            .run(),
+        )
+        .unwrap();
+    }
+
+    fn restore(
+        rt: &Runtime,
+        storage: &Arc<dyn BackupStorage>,
+        db_dir: &TempPath,
+        target_time_secs: Option<u64>,
+        state_only: bool,
+    ) -> Version {
+        let cache_dir = TempPath::new();
+        let global_opt: GlobalRestoreOptions = GlobalRestoreOpt {
+            dry_run: false,
+            db_dir: Some(db_dir.path().to_path_buf()),
+            target_version: None,
+            trusted_waypoints: TrustedWaypointOpt::default(),
+            rocksdb_opt: RocksdbOpt::default(),
+            concurrent_downloads: ConcurrentDownloadsOpt::default(),
+            replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+            replay_write_sets: false,
+            encryption_key: Default::default(),
+        }
+        .try_into()
+        .unwrap();
+        rt.block_on(
+            RestoreCoordinator::new(
+                RestoreCoordinatorOpt {
+                    metadata_cache_opt: MetadataCacheOpt::new(Some(cache_dir.path().to_path_buf())),
+                    replay_all: false,
+                    ledger_history_start_version: None,
+                    skip_epoch_endings: false,
+                    target_time_secs,
+                    state_only,
+                },
+                global_opt,
+                Arc::clone(storage),
+            )
+            .run(),
+        )
+        .unwrap();
+
+        AptosDB::new_readonly_for_test(db_dir)
+            .get_latest_transaction_info_option()
+            .unwrap()
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn test_restore() {
+        let db = test_execution_with_storage_impl();
+        let latest_version = db.get_latest_version().unwrap();
+        let latest_epoch = db.get_latest_epoch_state().unwrap().epoch;
+        let epoch_endings: Vec<LedgerInfo> = db
+            .get_epoch_ending_ledger_infos(0, latest_epoch)
+            .unwrap()
+            .ledger_info_with_sigs
+            .iter()
+            .map(|li| li.ledger_info().clone())
+            .collect();
+        let backup_dir = TempPath::new();
+        backup_dir.create_as_dir().unwrap();
+        let storage: Arc<dyn BackupStorage> =
+            Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+        let (rt, port) = start_local_backup_service(Arc::clone(&db));
+        backup(&rt, port, &db, &storage);
+        let last_epoch_ending_version = epoch_endings.last().unwrap().version();
+        assert!(last_epoch_ending_version < latest_version);
+
+        // --state-only stops at the latest state snapshot, short of the latest version.
+        let db_dir = TempPath::new();
+        assert_eq!(
+            restore(&rt, &storage, &db_dir, None, true),
+            last_epoch_ending_version
+        );
+        assert_eq!(
+            restore(&rt, &storage, &db_dir, None, true),
+            last_epoch_ending_version
+        );
+        // Without it, the replay continues from there up until the target.
+        assert_eq!(restore(&rt, &storage, &db_dir, None, false), latest_version);
+
+        // --target-time-secs goes back to the last transaction in a block at or before it. The
+        // blocks of the test DB are at microseconds 1, 2 and 3, so second 0 ends right before the
+        // first block, and second 1 is after all of them.
+        let txns = db
+            .get_transactions(0, latest_version + 1, latest_version, false)
+            .unwrap()
+            .transactions;
+        let expected_version = |timestamp_usecs: u64| {
+            txns.iter()
+                .enumerate()
+                .find_map(|(version, txn)| match txn {
+                    Transaction::BlockMetadata(block_metadata)
+                        if block_metadata.timestamp_usecs() > timestamp_usecs =>
+                    {
+                        Some(version as Version - 1)
+                    }
+                    _ => None,
+                })
+                .unwrap_or(latest_version)
+        };
+        let db_dir = TempPath::new();
+        assert_eq!(
+            restore(&rt, &storage, &db_dir, Some(0), false),
+            expected_version(0)
+        );
+        let db_dir = TempPath::new();
+        assert_eq!(
+            restore(&rt, &storage, &db_dir, Some(1), false),
+            latest_version
+        );
+
+        // Within the first second, the search lands between the blocks.
+        let cache_dir = TempPath::new();
+        let metadata_view = rt
+            .block_on(metadata::cache::sync_and_load(
+                &MetadataCacheOpt::new(Some(cache_dir.path().to_path_buf())),
+                Arc::clone(&storage),
+                1,
+            ))
+            .unwrap();
+        let transaction_backups = metadata_view
+            .select_transaction_backups(0, latest_version)
+            .unwrap();
+        let chunk_coder = ChunkCoder::new_with_opt(ChunkCodecOpt::default());
+        for timestamp_usecs in 0..4 {
+            assert_eq!(
+                rt.block_on(last_version_before(
+                    &storage,
+                    &chunk_coder,
+                    &transaction_backups,
+                    timestamp_usecs
+                ))
+                .unwrap(),
+                Some(expected_version(timestamp_usecs))
+            );
+        }
+
+        rt.shutdown_timeout(Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,972 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        epoch_ending::restore::last_epoch_ending_version_before,
+        state_snapshot::manifest::StateSnapshotBackup,
+        transaction::{backup::TransactionBackupController, manifest::TransactionBackup},
+    },
+    metadata,
+    metadata::{
+        cache::MetadataCacheOpt, view::MetadataView, Metadata, StateSnapshotBackupMeta,
+        TransactionBackupMeta,
+    },
+    metrics::retention::{
+        RETENTION_COORDINATOR_FAIL_TS, RETENTION_COORDINATOR_START_TS,
+        RETENTION_COORDINATOR_SUCC_TS, RETENTION_DELETED_FILES,
+    },
+    storage::{BackupStorage, FileHandle, ShellSafeName},
+    utils::{
+        codec::{ChunkCoder, Compression, EncryptionKeyOpt},
+        storage_ext::BackupStorageExt,
+        stream::StreamX,
+        unix_timestamp_sec, ConcurrentDownloadsOpt,
+    },
+};
+use anyhow::{ensure, Result};
+use aptos_logger::prelude::*;
+use aptos_types::transaction::Version;
+use clap::Parser;
+use itertools::Itertools;
+use rand::random;
+use std::{cmp::Reverse, collections::HashSet, convert::TryInto, fmt, sync::Arc};
+use tokio::io::AsyncWriteExt;
+use tokio_stream::StreamExt;
+
+const SECONDS_PER_DAY: u64 = 24 * 3600;
+
+#[derive(Clone, Parser)]
+pub struct RetentionPolicyOpt {
+    #[clap(
+        long = "keep-state-snapshot-every-epochs",
+        help = "Only keep the state snapshots taken at epochs that are multiples of this, plus the \
+        latest one. [Defaults to keeping all state snapshots]"
+    )]
+    pub keep_state_snapshot_every_epochs: Option<u64>,
+    #[clap(
+        long = "keep-transaction-days",
+        help = "Only keep the transactions of about the last this many days, according to the \
+        timestamps of the epoch endings. Transactions are kept from the latest kept state snapshot \
+        before that, so they can be replayed on top of it, and the transaction backups containing \
+        the versions of the kept state snapshots are kept as well, so the snapshots can be \
+        restored. [Defaults to keeping all transactions]"
+    )]
+    pub keep_transaction_days: Option<u64>,
+    #[clap(
+        long = "merge-transaction-backups-up-to-versions",
+        help = "Merge consecutive transaction backups into one, as long as it doesn't span more \
+        than this many versions. The transaction chunks are reused, only the manifests are \
+        rewritten. [Defaults to not merging]"
+    )]
+    pub merge_transaction_backups_up_to_versions: Option<u64>,
+    #[clap(
+        long = "compact-metadata-files-above",
+        default_value = "100",
+        help = "Merge all the metadata files into one when there are more than this many, even if \
+        nothing is deleted. They are always merged when a backup is deleted or merged."
+    )]
+    pub compact_metadata_files_above: usize,
+}
+
+impl RetentionPolicyOpt {
+    pub(crate) fn validate(&self) -> Result<()> {
+        ensure!(
+            self.keep_state_snapshot_every_epochs != Some(0)
+                && self.merge_transaction_backups_up_to_versions != Some(0),
+            "State snapshot interval and merged transaction backup size must be greater than 0."
+        );
+        Ok(())
+    }
+}
+
+#[derive(Parser)]
+pub struct RetentionCoordinatorOpt {
+    #[clap(flatten)]
+    pub metadata_cache_opt: MetadataCacheOpt,
+    #[clap(flatten)]
+    pub policy: RetentionPolicyOpt,
+    #[clap(
+        long = "dry-run",
+        help = "Print what would be deleted and merged, without changing anything."
+    )]
+    pub dry_run: bool,
+    #[clap(flatten)]
+    pub encryption_key_opt: EncryptionKeyOpt,
+    #[clap(flatten)]
+    pub concurrent_downloads: ConcurrentDownloadsOpt,
+}
+
+/// Applies a `RetentionPolicyOpt` to the backups in a storage.
+///
+/// New manifests and metadata are written before anything is deleted, and metadata before the
+/// files it points to, so an interrupted run leaves unreferenced files behind at worst, never
+/// metadata pointing to missing files.
+pub struct RetentionCoordinator {
+    storage: Arc<dyn BackupStorage>,
+    metadata_cache_opt: MetadataCacheOpt,
+    policy: RetentionPolicyOpt,
+    dry_run: bool,
+    chunk_coder: ChunkCoder,
+    concurrent_downloads: usize,
+}
+
+impl RetentionCoordinator {
+    pub fn new(
+        storage: Arc<dyn BackupStorage>,
+        metadata_cache_opt: MetadataCacheOpt,
+        policy: RetentionPolicyOpt,
+        dry_run: bool,
+        chunk_coder: ChunkCoder,
+        concurrent_downloads: usize,
+    ) -> Result<Self> {
+        policy.validate()?;
+        ensure!(
+            dry_run || storage.supports_retention(),
+            "The storage doesn't support deleting files, can't apply a retention policy. For a \
+            command adapter, add a delete_file command to its config."
+        );
+        Ok(Self {
+            storage,
+            metadata_cache_opt,
+            policy,
+            dry_run,
+            chunk_coder,
+            concurrent_downloads,
+        })
+    }
+
+    pub fn new_with_opt(
+        opt: RetentionCoordinatorOpt,
+        storage: Arc<dyn BackupStorage>,
+    ) -> Result<Self> {
+        Self::new(
+            storage,
+            opt.metadata_cache_opt,
+            opt.policy,
+            opt.dry_run,
+            ChunkCoder::new(Compression::None, opt.encryption_key_opt.encryption_key),
+            opt.concurrent_downloads.get(),
+        )
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        info!("Retention coordinator started.");
+        RETENTION_COORDINATOR_START_TS.set(unix_timestamp_sec());
+
+        let ret = self.run_impl().await;
+
+        if let Err(e) = &ret {
+            error!(
+                error = ?e,
+                "Retention coordinator failed."
+            );
+            RETENTION_COORDINATOR_FAIL_TS.set(unix_timestamp_sec());
+        } else {
+            info!("Retention coordinator exiting with success.");
+            RETENTION_COORDINATOR_SUCC_TS.set(unix_timestamp_sec());
+        }
+
+        ret
+    }
+
+    async fn run_impl(&self) -> Result<()> {
+        let (metadata_files, metadata_view) = metadata::cache::sync_and_load_with_file_handles(
+            &self.metadata_cache_opt,
+            Arc::clone(&self.storage),
+            self.concurrent_downloads,
+        )
+        .await?;
+
+        let transaction_cutoff = match self.policy.keep_transaction_days {
+            Some(days) => {
+                let cutoff_secs =
+                    (unix_timestamp_sec() as u64).saturating_sub(days * SECONDS_PER_DAY);
+                last_epoch_ending_version_before(
+                    &self.storage,
+                    &self.chunk_coder,
+                    metadata_view.epoch_ending_backups(),
+                    cutoff_secs * 1_000_000,
+                )
+                .await?
+            }
+            None => None,
+        };
+        let plan = plan(
+            &metadata_view,
+            metadata_files.len(),
+            &self.policy,
+            transaction_cutoff,
+        );
+
+        if self.dry_run {
+            println!("{}", plan);
+            return Ok(());
+        }
+        info!("Retention plan:\n{}", plan);
+        self.execute(plan, &metadata_view, &metadata_files).await
+    }
+
+    async fn execute(
+        &self,
+        plan: RetentionPlan,
+        metadata_view: &MetadataView,
+        metadata_files: &[FileHandle],
+    ) -> Result<()> {
+        if !plan.compact_metadata {
+            info!("Nothing to delete, merge or compact.");
+            return Ok(());
+        }
+
+        let mut merged = Vec::new();
+        for backups in &plan.transaction_backups_to_merge {
+            merged.push(self.merge_transaction_backups(backups).await?);
+        }
+
+        // All the files to delete are listed before any is deleted, since a redundant backup is
+        // compared with the backup covering it, whose manifest might be deleted as well.
+        let mut files_to_delete = Vec::new();
+        for backup in &plan.state_snapshots_to_delete {
+            let manifest: StateSnapshotBackup =
+                self.storage.load_json_file(&backup.manifest).await?;
+            for chunk in manifest.chunks {
+                files_to_delete.push(chunk.blobs);
+                files_to_delete.push(chunk.proof);
+            }
+            files_to_delete.push(manifest.proof);
+            files_to_delete.push(backup.manifest.clone());
+        }
+        for backup in &plan.transaction_backups_to_delete {
+            files_to_delete.extend(self.transaction_backup_files(backup).await?);
+        }
+        for (backup, covering) in &plan.redundant_transaction_backups {
+            let used_by_covering: HashSet<_> = self
+                .transaction_backup_files(covering)
+                .await?
+                .into_iter()
+                .collect();
+            files_to_delete.extend(
+                self.transaction_backup_files(backup)
+                    .await?
+                    .into_iter()
+                    .filter(|file| !used_by_covering.contains(file)),
+            );
+        }
+        // Merged backups reuse the chunks, only their manifests are dropped.
+        files_to_delete.extend(
+            plan.transaction_backups_to_merge
+                .iter()
+                .flatten()
+                .map(|backup| backup.manifest.clone()),
+        );
+
+        self.compact_metadata(&plan, metadata_view, merged, metadata_files)
+            .await?;
+
+        let num_files = files_to_delete.len();
+        self.delete_files(files_to_delete).await?;
+        RETENTION_DELETED_FILES.set(num_files as i64);
+        info!(num_files = num_files, "Backup files deleted.");
+
+        Ok(())
+    }
+
+    /// Writes a manifest with the chunks of all the `backups`, which must be continuous.
+    async fn merge_transaction_backups(
+        &self,
+        backups: &[TransactionBackupMeta],
+    ) -> Result<TransactionBackupMeta> {
+        let mut chunks = Vec::new();
+        for backup in backups {
+            let manifest: TransactionBackup = self.storage.load_json_file(&backup.manifest).await?;
+            manifest.verify()?;
+            ensure!(
+                manifest.first_version == backup.first_version
+                    && manifest.last_version == backup.last_version,
+                "Manifest {} doesn't match its metadata.",
+                backup.manifest,
+            );
+            chunks.extend(manifest.chunks);
+        }
+        let manifest = TransactionBackup {
+            first_version: backups[0].first_version,
+            last_version: backups[backups.len() - 1].last_version,
+            chunks,
+        };
+        manifest.verify()?;
+
+        let backup_handle = self
+            .storage
+            .create_backup_with_random_suffix(&format!("transaction_{}-", manifest.first_version))
+            .await?;
+        let (manifest_handle, mut manifest_file) = self
+            .storage
+            .create_for_write(&backup_handle, TransactionBackupController::manifest_name())
+            .await?;
+        manifest_file
+            .write_all(&serde_json::to_vec(&manifest)?)
+            .await?;
+        manifest_file.shutdown().await?;
+        info!(
+            first_version = manifest.first_version,
+            last_version = manifest.last_version,
+            num_backups = backups.len(),
+            "Transaction backups merged."
+        );
+
+        Ok(TransactionBackupMeta {
+            first_version: manifest.first_version,
+            last_version: manifest.last_version,
+            manifest: manifest_handle,
+        })
+    }
+
+    /// Replaces all the `metadata_files` with a single one holding the entries that are kept.
+    async fn compact_metadata(
+        &self,
+        plan: &RetentionPlan,
+        metadata_view: &MetadataView,
+        merged: Vec<TransactionBackupMeta>,
+        metadata_files: &[FileHandle],
+    ) -> Result<()> {
+        let dropped_manifests = plan.dropped_manifests();
+        let entries = metadata_view
+            .identity()
+            .cloned()
+            .map(Metadata::Identity)
+            .into_iter()
+            .chain(
+                metadata_view
+                    .epoch_ending_backups()
+                    .iter()
+                    .cloned()
+                    .map(Metadata::EpochEndingBackup),
+            )
+            .chain(
+                metadata_view
+                    .state_snapshot_backups()
+                    .iter()
+                    .filter(|backup| !dropped_manifests.contains(&backup.manifest))
+                    .cloned()
+                    .map(Metadata::StateSnapshotBackup),
+            )
+            .chain(
+                metadata_view
+                    .transaction_backups()
+                    .iter()
+                    .filter(|backup| !dropped_manifests.contains(&backup.manifest))
+                    .cloned()
+                    .chain(merged)
+                    .map(Metadata::TransactionBackup),
+            );
+        let lines = entries
+            .map(|entry| entry.to_text_line())
+            .collect::<Result<Vec<_>>>()?;
+
+        let name: ShellSafeName = format!(
+            "compacted_{}_{:04x}.meta",
+            unix_timestamp_sec(),
+            random::<u16>()
+        )
+        .try_into()?;
+        self.storage.save_metadata_lines(&name, &lines).await?;
+        // Only the files listed before are deleted, those saved since by a running backup
+        // coordinator are left alone.
+        self.delete_files(metadata_files.to_vec()).await?;
+        info!(
+            num_files = metadata_files.len(),
+            num_entries = lines.len(),
+            "Metadata files compacted."
+        );
+
+        Ok(())
+    }
+
+    async fn transaction_backup_files(
+        &self,
+        backup: &TransactionBackupMeta,
+    ) -> Result<Vec<FileHandle>> {
+        let manifest: TransactionBackup = self.storage.load_json_file(&backup.manifest).await?;
+        let mut files = Vec::new();
+        for chunk in manifest.chunks {
+            files.push(chunk.transactions);
+            files.push(chunk.proof);
+        }
+        files.push(backup.manifest.clone());
+        Ok(files)
+    }
+
+    async fn delete_files(&self, files: Vec<FileHandle>) -> Result<()> {
+        let con = self.concurrent_downloads;
+        let futs = files.into_iter().map(|file_handle| {
+            let storage = Arc::clone(&self.storage);
+            async move { storage.delete_file(&file_handle).await }
+        });
+        futures::stream::iter(futs)
+            .buffered_x(con * 2, con)
+            .collect::<Result<Vec<_>>>()
+            .await?;
+        Ok(())
+    }
+}
+
+/// What a retention run deletes and merges.
+#[derive(Debug, Eq, PartialEq)]
+pub struct RetentionPlan {
+    num_metadata_files: usize,
+    state_snapshots_to_delete: Vec<StateSnapshotBackupMeta>,
+    transaction_backups_to_delete: Vec<TransactionBackupMeta>,
+    /// Transaction backups covered by a longer one, like the ones left by an interrupted merge,
+    /// along with the backup covering them.
+    redundant_transaction_backups: Vec<(TransactionBackupMeta, TransactionBackupMeta)>,
+    /// Runs of continuous transaction backups, each replaced by a single backup.
+    transaction_backups_to_merge: Vec<Vec<TransactionBackupMeta>>,
+    /// Always set when anything is deleted or merged, since the metadata entries can only be
+    /// dropped by rewriting the metadata files.
+    compact_metadata: bool,
+}
+
+impl RetentionPlan {
+    fn dropped_manifests(&self) -> HashSet<FileHandle> {
+        self.state_snapshots_to_delete
+            .iter()
+            .map(|backup| backup.manifest.clone())
+            .chain(
+                self.transaction_backups_to_delete
+                    .iter()
+                    .chain(
+                        self.redundant_transaction_backups
+                            .iter()
+                            .map(|(backup, _)| backup),
+                    )
+                    .chain(self.transaction_backups_to_merge.iter().flatten())
+                    .map(|backup| backup.manifest.clone()),
+            )
+            .collect()
+    }
+}
+
+impl fmt::Display for RetentionPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "State snapshots to delete: {}",
+            self.state_snapshots_to_delete.len()
+        )?;
+        for backup in &self.state_snapshots_to_delete {
+            writeln!(
+                f,
+                "  epoch {} at version {}: {}",
+                backup.epoch, backup.version, backup.manifest
+            )?;
+        }
+        writeln!(
+            f,
+            "Transaction backups to delete: {}",
+            self.transaction_backups_to_delete.len()
+        )?;
+        for backup in &self.transaction_backups_to_delete {
+            writeln!(
+                f,
+                "  versions [{}, {}]: {}",
+                backup.first_version, backup.last_version, backup.manifest
+            )?;
+        }
+        writeln!(
+            f,
+            "Redundant transaction backups to delete: {}",
+            self.redundant_transaction_backups.len()
+        )?;
+        for (backup, covering) in &self.redundant_transaction_backups {
+            writeln!(
+                f,
+                "  versions [{}, {}]: {}, covered by {}",
+                backup.first_version, backup.last_version, backup.manifest, covering.manifest
+            )?;
+        }
+        writeln!(
+            f,
+            "Transaction backups to merge: {}",
+            self.transaction_backups_to_merge
+                .iter()
+                .map(Vec::len)
+                .sum::<usize>()
+        )?;
+        for backups in &self.transaction_backups_to_merge {
+            writeln!(
+                f,
+                "  versions [{}, {}] from {} backups",
+                backups[0].first_version,
+                backups[backups.len() - 1].last_version,
+                backups.len()
+            )?;
+        }
+        if self.compact_metadata {
+            write!(
+                f,
+                "Metadata files to compact into one: {}",
+                self.num_metadata_files
+            )
+        } else {
+            write!(
+                f,
+                "Metadata files: {}, not compacted",
+                self.num_metadata_files
+            )
+        }
+    }
+}
+
+/// Decides what to delete and merge. `transaction_cutoff` is the version before which
+/// transactions are no longer needed, if any.
+fn plan(
+    metadata_view: &MetadataView,
+    num_metadata_files: usize,
+    policy: &RetentionPolicyOpt,
+    transaction_cutoff: Option<Version>,
+) -> RetentionPlan {
+    let latest_state_snapshot = metadata_view.state_snapshot_backups().last();
+    let (kept_state_snapshots, state_snapshots_to_delete): (Vec<_>, Vec<_>) = metadata_view
+        .state_snapshot_backups()
+        .iter()
+        .cloned()
+        .partition(|backup| {
+            policy
+                .keep_state_snapshot_every_epochs
+                .map_or(true, |n| backup.epoch % n == 0)
+                || Some(backup) == latest_state_snapshot
+        });
+
+    // Visiting longer backups first, a backup is covered by another one if one of the backups
+    // visited before ends at or after it.
+    let mut transaction_backups = Vec::new();
+    let mut redundant_transaction_backups = Vec::new();
+    let mut covering: Option<&TransactionBackupMeta> = None;
+    for backup in metadata_view
+        .transaction_backups()
+        .iter()
+        .sorted_by_key(|b| (b.first_version, Reverse(b.last_version)))
+    {
+        match covering {
+            Some(c) if c.last_version >= backup.last_version => {
+                redundant_transaction_backups.push((backup.clone(), c.clone()))
+            }
+            _ => {
+                covering = Some(backup);
+                transaction_backups.push(backup.clone());
+            }
+        }
+    }
+
+    // Kept transactions are replayed on top of the latest kept state snapshot before them.
+    let first_kept_version = transaction_cutoff
+        .and_then(|cutoff| {
+            kept_state_snapshots
+                .iter()
+                .rev()
+                .find(|backup| backup.version <= cutoff)
+        })
+        .map_or(0, |backup| backup.version);
+    let (kept_transaction_backups, transaction_backups_to_delete): (Vec<_>, Vec<_>) =
+        transaction_backups.into_iter().partition(|backup| {
+            backup.last_version >= first_kept_version
+                || kept_state_snapshots.iter().any(|snapshot| {
+                    (backup.first_version..=backup.last_version).contains(&snapshot.version)
+                })
+        });
+
+    let mut transaction_backups_to_merge = Vec::new();
+    if let Some(max_versions) = policy.merge_transaction_backups_up_to_versions {
+        let mut run: Vec<TransactionBackupMeta> = Vec::new();
+        for backup in kept_transaction_backups {
+            let extends_run = match (run.first(), run.last()) {
+                (Some(first), Some(last)) => {
+                    last.last_version + 1 == backup.first_version
+                        && backup.last_version - first.first_version < max_versions
+                }
+                _ => true,
+            };
+            if !extends_run {
+                transaction_backups_to_merge.push(std::mem::take(&mut run));
+            }
+            run.push(backup);
+        }
+        transaction_backups_to_merge.push(run);
+        transaction_backups_to_merge.retain(|run| run.len() > 1);
+    }
+
+    let compact_metadata = !state_snapshots_to_delete.is_empty()
+        || !transaction_backups_to_delete.is_empty()
+        || !redundant_transaction_backups.is_empty()
+        || !transaction_backups_to_merge.is_empty()
+        || num_metadata_files > policy.compact_metadata_files_above;
+
+    RetentionPlan {
+        num_metadata_files,
+        state_snapshots_to_delete,
+        transaction_backups_to_delete,
+        redundant_transaction_backups,
+        transaction_backups_to_merge,
+        compact_metadata,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        backup_types::transaction::manifest::TransactionChunk,
+        metadata::cache::sync_and_load_with_file_handles,
+        storage::{local_fs::LocalFs, BackupHandle, BackupHandleRef, FileHandleRef, TextLine},
+    };
+    use aptos_temppath::TempPath;
+    use std::str::FromStr;
+    use tokio::{
+        io::{AsyncRead, AsyncWrite},
+        runtime::Runtime,
+    };
+
+    fn policy(
+        keep_state_snapshot_every_epochs: Option<u64>,
+        merge_transaction_backups_up_to_versions: Option<u64>,
+    ) -> RetentionPolicyOpt {
+        RetentionPolicyOpt {
+            keep_state_snapshot_every_epochs,
+            keep_transaction_days: None,
+            merge_transaction_backups_up_to_versions,
+            compact_metadata_files_above: 100,
+        }
+    }
+
+    fn snapshot(epoch: u64, version: Version) -> StateSnapshotBackupMeta {
+        StateSnapshotBackupMeta {
+            epoch,
+            version,
+            manifest: format!("state_ver_{}/state.manifest", version),
+        }
+    }
+
+    fn transactions(first_version: Version, last_version: Version) -> TransactionBackupMeta {
+        TransactionBackupMeta {
+            first_version,
+            last_version,
+            manifest: format!(
+                "transaction_{}-{}/transaction.manifest",
+                first_version, last_version
+            ),
+        }
+    }
+
+    fn view(
+        snapshots: &[StateSnapshotBackupMeta],
+        transaction_backups: &[TransactionBackupMeta],
+    ) -> MetadataView {
+        snapshots
+            .iter()
+            .cloned()
+            .map(Metadata::StateSnapshotBackup)
+            .chain(
+                transaction_backups
+                    .iter()
+                    .cloned()
+                    .map(Metadata::TransactionBackup),
+            )
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    #[test]
+    fn test_plan() {
+        let snapshots = [
+            snapshot(0, 0),
+            snapshot(1, 10),
+            snapshot(2, 20),
+            snapshot(3, 30),
+        ];
+        let txns = [
+            transactions(0, 0),
+            transactions(1, 10),
+            transactions(11, 20),
+            transactions(21, 30),
+            transactions(31, 40),
+        ];
+
+        // Nothing to do by default.
+        let res = plan(&view(&snapshots, &txns), 10, &policy(None, None), None);
+        assert!(!res.compact_metadata);
+        assert!(res.dropped_manifests().is_empty());
+        // Unless there are too many metadata files.
+        assert!(plan(&view(&snapshots, &txns), 101, &policy(None, None), None).compact_metadata);
+
+        // Snapshots at even epochs and the latest one are kept.
+        let res = plan(&view(&snapshots, &txns), 10, &policy(Some(2), None), None);
+        assert_eq!(res.state_snapshots_to_delete, vec![snapshot(1, 10)]);
+        assert!(res.transaction_backups_to_delete.is_empty());
+        assert!(res.compact_metadata);
+
+        // Transactions are kept from the kept snapshot before the cutoff, and at the kept
+        // snapshots.
+        let res = plan(
+            &view(&snapshots, &txns),
+            10,
+            &policy(Some(2), None),
+            Some(25),
+        );
+        assert_eq!(res.transaction_backups_to_delete, vec![transactions(1, 10)]);
+        let res = plan(
+            &view(&snapshots[3..], &txns),
+            10,
+            &policy(None, None),
+            Some(35),
+        );
+        assert_eq!(
+            res.transaction_backups_to_delete,
+            vec![
+                transactions(0, 0),
+                transactions(1, 10),
+                transactions(11, 20)
+            ]
+        );
+        // Without a snapshot before the cutoff, all transactions are kept.
+        let res = plan(
+            &view(&snapshots[1..], &txns),
+            10,
+            &policy(None, None),
+            Some(5),
+        );
+        assert!(res.transaction_backups_to_delete.is_empty());
+
+        // Continuous backups are merged up to the size.
+        let res = plan(&view(&[], &txns), 10, &policy(None, Some(20)), None);
+        assert_eq!(
+            res.transaction_backups_to_merge,
+            vec![
+                vec![transactions(0, 0), transactions(1, 10)],
+                vec![transactions(11, 20), transactions(21, 30)],
+            ]
+        );
+        let res = plan(&view(&[], &txns[..3]), 10, &policy(None, Some(9)), None);
+        assert!(res.transaction_backups_to_merge.is_empty());
+        let res = plan(
+            &view(&[], &[txns[0].clone(), txns[2].clone()]),
+            10,
+            &policy(None, Some(100)),
+            None,
+        );
+        assert!(res.transaction_backups_to_merge.is_empty());
+
+        // Backups covered by a longer one are redundant.
+        let merged = transactions(1, 20);
+        let res = plan(
+            &view(&[], &[txns[1].clone(), txns[2].clone(), merged.clone()]),
+            10,
+            &policy(None, None),
+            None,
+        );
+        assert_eq!(
+            res.redundant_transaction_backups,
+            vec![(txns[1].clone(), merged.clone()), (txns[2].clone(), merged),]
+        );
+        assert!(res.compact_metadata);
+    }
+
+    async fn save_transaction_backup(
+        storage: &Arc<dyn BackupStorage>,
+        first_version: Version,
+        last_version: Version,
+    ) -> TransactionBackupMeta {
+        let backup_handle = storage
+            .create_backup_with_random_suffix(&format!("transaction_{}-", first_version))
+            .await
+            .unwrap();
+        let mut chunks = Vec::new();
+        for name in ["chunk", "proof"] {
+            let (file_handle, mut file) = storage
+                .create_for_write(&backup_handle, &ShellSafeName::from_str(name).unwrap())
+                .await
+                .unwrap();
+            file.write_all(name.as_bytes()).await.unwrap();
+            file.shutdown().await.unwrap();
+            chunks.push(file_handle);
+        }
+        let manifest = TransactionBackup {
+            first_version,
+            last_version,
+            chunks: vec![TransactionChunk {
+                first_version,
+                last_version,
+                proof: chunks.pop().unwrap(),
+                transactions: chunks.pop().unwrap(),
+                codec: Default::default(),
+            }],
+        };
+        let (manifest_handle, mut file) = storage
+            .create_for_write(&backup_handle, TransactionBackupController::manifest_name())
+            .await
+            .unwrap();
+        file.write_all(&serde_json::to_vec(&manifest).unwrap())
+            .await
+            .unwrap();
+        file.shutdown().await.unwrap();
+
+        let metadata =
+            Metadata::new_transaction_backup(first_version, last_version, manifest_handle);
+        storage
+            .save_metadata_line(&metadata.name(), &metadata.to_text_line().unwrap())
+            .await
+            .unwrap();
+        match metadata {
+            Metadata::TransactionBackup(backup) => backup,
+            _ => unreachable!(),
+        }
+    }
+
+    async fn test_execute_impl() {
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+        let storage: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(tmpdir.path().to_path_buf()));
+        let cache_dir = TempPath::new();
+        let cache_opt = MetadataCacheOpt::new(Some(cache_dir.path().to_path_buf()));
+        let coordinator = RetentionCoordinator::new(
+            Arc::clone(&storage),
+            cache_opt.clone(),
+            policy(None, Some(100)),
+            false, /* dry_run */
+            ChunkCoder::default(),
+            4, /* concurrent_downloads */
+        )
+        .unwrap();
+
+        let mut backups = Vec::new();
+        for (first, last) in [(0, 0), (1, 10), (11, 20), (21, 30)] {
+            backups.push(save_transaction_backup(&storage, first, last).await);
+        }
+        let snapshot = Metadata::new_state_snapshot_backup(1, 1, "state/state.manifest".into());
+        storage
+            .save_metadata_line(&snapshot.name(), &snapshot.to_text_line().unwrap())
+            .await
+            .unwrap();
+        let deleted_files = coordinator
+            .transaction_backup_files(&backups[0])
+            .await
+            .unwrap();
+
+        let (metadata_files, view) =
+            sync_and_load_with_file_handles(&cache_opt, Arc::clone(&storage), 4)
+                .await
+                .unwrap();
+        assert_eq!(metadata_files.len(), 5);
+        let res = plan(&view, metadata_files.len(), &coordinator.policy, Some(5));
+        assert_eq!(res.transaction_backups_to_delete, vec![backups[0].clone()]);
+        assert_eq!(
+            res.transaction_backups_to_merge,
+            vec![backups[1..].to_vec()]
+        );
+        coordinator
+            .execute(res, &view, &metadata_files)
+            .await
+            .unwrap();
+
+        let (metadata_files, view) =
+            sync_and_load_with_file_handles(&cache_opt, Arc::clone(&storage), 4)
+                .await
+                .unwrap();
+        assert_eq!(metadata_files.len(), 1);
+        assert_eq!(view.state_snapshot_backups().len(), 1);
+        let merged = view.transaction_backups();
+        assert_eq!(merged.len(), 1);
+        assert_eq!((merged[0].first_version, merged[0].last_version), (1, 30));
+        let manifest: TransactionBackup =
+            storage.load_json_file(&merged[0].manifest).await.unwrap();
+        manifest.verify().unwrap();
+        for chunk in manifest.chunks {
+            storage.read_all(&chunk.transactions).await.unwrap();
+            storage.read_all(&chunk.proof).await.unwrap();
+        }
+        for backup in &backups {
+            assert!(storage.read_all(&backup.manifest).await.is_err());
+        }
+        for file_handle in &deleted_files {
+            assert!(storage.read_all(file_handle).await.is_err());
+        }
+
+        // Nothing left to do.
+        coordinator.run().await.unwrap();
+        let (metadata_files, _) =
+            sync_and_load_with_file_handles(&cache_opt, Arc::clone(&storage), 4)
+                .await
+                .unwrap();
+        assert_eq!(metadata_files.len(), 1);
+    }
+
+    #[test]
+    fn test_execute() {
+        Runtime::new().unwrap().block_on(test_execute_impl());
+    }
+
+    /// A storage implementing only what backing up and restoring need.
+    struct AppendOnlyStorage(LocalFs);
+
+    #[async_trait::async_trait]
+    impl BackupStorage for AppendOnlyStorage {
+        async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+            self.0.create_backup(name).await
+        }
+
+        async fn create_for_write(
+            &self,
+            backup_handle: &BackupHandleRef,
+            name: &ShellSafeName,
+        ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+            self.0.create_for_write(backup_handle, name).await
+        }
+
+        async fn open_for_read(
+            &self,
+            file_handle: &FileHandleRef,
+        ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+            self.0.open_for_read(file_handle).await
+        }
+
+        async fn save_metadata_line(&self, name: &ShellSafeName, content: &TextLine) -> Result<()> {
+            self.0.save_metadata_line(name, content).await
+        }
+
+        async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+            self.0.list_metadata_files().await
+        }
+    }
+
+    #[test]
+    fn test_storage_without_deletion() {
+        Runtime::new().unwrap().block_on(async {
+            let tmpdir = TempPath::new();
+            tmpdir.create_as_dir().unwrap();
+            let storage: Arc<dyn BackupStorage> =
+                Arc::new(AppendOnlyStorage(LocalFs::new(tmpdir.path().to_path_buf())));
+            let name = ShellSafeName::from_str("name").unwrap();
+            assert!(storage
+                .save_metadata_lines(&name, &[TextLine::new("line").unwrap()])
+                .await
+                .is_err());
+            assert!(storage.delete_file("metadata/name").await.is_err());
+
+            let cache_dir = TempPath::new();
+            let cache_opt = MetadataCacheOpt::new(Some(cache_dir.path().to_path_buf()));
+            for dry_run in [false, true] {
+                assert_eq!(
+                    RetentionCoordinator::new(
+                        Arc::clone(&storage),
+                        cache_opt.clone(),
+                        policy(None, Some(100)),
+                        dry_run,
+                        ChunkCoder::default(),
+                        4, /* concurrent_downloads */
+                    )
+                    .is_ok(),
+                    dry_run
+                );
+            }
+        });
+    }
+}
@@ -13,7 +13,10 @@ use crate::{
         VERIFY_COORDINATOR_FAIL_TS, VERIFY_COORDINATOR_START_TS, VERIFY_COORDINATOR_SUCC_TS,
     },
     storage::BackupStorage,
-    utils::{unix_timestamp_sec, GlobalRestoreOptions, RestoreRunMode, TrustedWaypointOpt},
+    utils::{
+        codec::{ChunkCoder, Compression, EncryptionKeyOpt},
+        unix_timestamp_sec, GlobalRestoreOptions, RestoreRunMode, TrustedWaypointOpt,
+    },
 };
 use anyhow::Result;
 use aptos_logger::prelude::*;
@@ -24,6 +27,7 @@ pub struct VerifyCoordinator {
     storage: Arc<dyn BackupStorage>,
     metadata_cache_opt: MetadataCacheOpt,
     trusted_waypoints_opt: TrustedWaypointOpt,
+    encryption_key_opt: EncryptionKeyOpt,
     concurrent_downloads: usize,
 }
 
@@ -32,12 +36,14 @@ impl VerifyCoordinator {
         storage: Arc<dyn BackupStorage>,
         metadata_cache_opt: MetadataCacheOpt,
         trusted_waypoints_opt: TrustedWaypointOpt,
+        encryption_key_opt: EncryptionKeyOpt,
         concurrent_downloads: usize,
     ) -> Result<Self> {
         Ok(Self {
             storage,
             metadata_cache_opt,
             trusted_waypoints_opt,
+            encryption_key_opt,
             concurrent_downloads,
         })
     }
@@ -71,7 +77,11 @@ impl VerifyCoordinator {
         .await?;
         let ver_max = Version::max_value();
         let state_snapshot = metadata_view.select_state_snapshot(ver_max)?;
-        let transactions = metadata_view.select_transaction_backups(0, ver_max)?;
+        // Older transactions might have been deleted by a retention policy.
+        let transactions = metadata_view.select_transaction_backups(
+            metadata_view.min_transaction_version().unwrap_or(0),
+            ver_max,
+        )?;
         let epoch_endings = metadata_view.select_epoch_ending_backups(ver_max)?;
 
         let global_opt = GlobalRestoreOptions {
@@ -80,6 +90,8 @@ impl VerifyCoordinator {
             run_mode: Arc::new(RestoreRunMode::Verify),
             concurrent_downloads: self.concurrent_downloads,
             replay_concurrency_level: 0, // won't replay, doesn't matter
+            replay_write_sets: false,
+            chunk_coder: ChunkCoder::new(Compression::None, self.encryption_key_opt.encryption_key),
         };
 
         let epoch_history = Arc::new(
@@ -33,7 +33,7 @@ static TEMP_METADATA_CACHE_DIR: Lazy<TempPath> = Lazy::new(|| {
     dir
 });
 
-#[derive(Parser)]
+#[derive(Clone, Parser)]
 pub struct MetadataCacheOpt {
     #[clap(
         long = "metadata-cache-dir",
@@ -50,6 +50,10 @@ impl MetadataCacheOpt {
     // in cache we save things other than the cached files.
     const SUB_DIR: &'static str = "cache";
 
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        Self { dir }
+    }
+
     fn cache_dir(&self) -> PathBuf {
         self.dir
             .clone()
@@ -72,6 +76,20 @@ pub async fn sync_and_load(
     storage: Arc<dyn BackupStorage>,
     concurrent_downloads: usize,
 ) -> Result<MetadataView> {
+    Ok(
+        sync_and_load_with_file_handles(opt, storage, concurrent_downloads)
+            .await?
+            .1,
+    )
+}
+
+/// Like `sync_and_load`, but also returns the handles of the metadata files the entries were
+/// loaded from.
+pub async fn sync_and_load_with_file_handles(
+    opt: &MetadataCacheOpt,
+    storage: Arc<dyn BackupStorage>,
+    concurrent_downloads: usize,
+) -> Result<(Vec<FileHandle>, MetadataView)> {
     let timer = Instant::now();
     let cache_dir = opt.cache_dir();
     create_dir_all(&cache_dir).await.err_notes(&cache_dir)?; // create if not present already
@@ -194,7 +212,10 @@ pub async fn sync_and_load(
         total_time = timer.elapsed().as_secs(),
         "Metadata cache loaded.",
     );
-    Ok(metadata_vec.into())
+    Ok((
+        remote_file_handle_by_hash.into_values().collect(),
+        metadata_vec.into(),
+    ))
 }
 
 trait FileHandleHash {
@@ -7,16 +7,34 @@ use crate::metadata::{
 use anyhow::{anyhow, ensure, Result};
 use aptos_types::transaction::Version;
 use itertools::Itertools;
-use std::{fmt, str::FromStr};
+use std::{cmp::Reverse, fmt, str::FromStr};
 
 pub struct MetadataView {
     epoch_ending_backups: Vec<EpochEndingBackupMeta>,
     state_snapshot_backups: Vec<StateSnapshotBackupMeta>,
     transaction_backups: Vec<TransactionBackupMeta>,
-    _identity: Option<IdentityMeta>,
+    identity: Option<IdentityMeta>,
 }
 
 impl MetadataView {
+    pub fn epoch_ending_backups(&self) -> &[EpochEndingBackupMeta] {
+        &self.epoch_ending_backups
+    }
+
+    /// Sorted by epoch.
+    pub fn state_snapshot_backups(&self) -> &[StateSnapshotBackupMeta] {
+        &self.state_snapshot_backups
+    }
+
+    /// Sorted by first version.
+    pub fn transaction_backups(&self) -> &[TransactionBackupMeta] {
+        &self.transaction_backups
+    }
+
+    pub fn identity(&self) -> Option<&IdentityMeta> {
+        self.identity.as_ref()
+    }
+
     pub fn get_storage_state(&self) -> Result<BackupStorageState> {
         let latest_epoch_ending_epoch =
             self.epoch_ending_backups.iter().map(|e| e.last_epoch).max();
@@ -66,31 +84,53 @@ impl MetadataView {
         start_version: Version,
         target_version: Version,
     ) -> Result<Vec<TransactionBackupMeta>> {
-        // This can be more flexible, but for now we assume and check backups are continuous in
-        // range (which is always true when we backup from a single backup coordinator)
-        let mut next_ver = 0;
+        // Backups are continuous in range when taken by a single backup coordinator. However a
+        // retention policy can delete the older ones, so only the range from `start_version` is
+        // checked. And an interrupted merge can leave backups covered by the merged one, which are
+        // skipped by visiting longer backups first.
+        let mut next_ver = None;
         let mut res = Vec::new();
-        for backup in self.transaction_backups.iter().sorted() {
+        for backup in self
+            .transaction_backups
+            .iter()
+            .sorted_by_key(|b| (b.first_version, Reverse(b.last_version)))
+        {
             if backup.first_version > target_version {
                 break;
             }
-            ensure!(
-                backup.first_version == next_ver,
-                "Transaction backup ranges not continuous, expecting version {}, got {}.",
-                next_ver,
-                backup.first_version,
-            );
-
-            if backup.last_version >= start_version {
-                res.push(backup.clone());
+            if backup.last_version < start_version {
+                continue;
+            }
+            match next_ver {
+                None => ensure!(
+                    backup.first_version <= start_version,
+                    "No transaction backup has version {}, the first one after it starts at {}.",
+                    start_version,
+                    backup.first_version,
+                ),
+                Some(next_ver) if backup.last_version < next_ver => continue,
+                Some(next_ver) => ensure!(
+                    backup.first_version == next_ver,
+                    "Transaction backup ranges not continuous, expecting version {}, got {}.",
+                    next_ver,
+                    backup.first_version,
+                ),
             }
 
-            next_ver = backup.last_version + 1;
+            res.push(backup.clone());
+            next_ver = Some(backup.last_version + 1);
         }
 
         Ok(res)
     }
 
+    pub fn min_transaction_version(&self) -> Option<Version> {
+        self.transaction_backups
+            .iter()
+            .map(|backup| backup.first_version)
+            .min()
+    }
+
     pub fn max_transaction_version(&self) -> Result<Option<Version>> {
         Ok(self
             .transaction_backups
@@ -144,11 +184,19 @@ impl From<Vec<Metadata>> for MetadataView {
             }
         }
 
+        // The same entry can be in several metadata files, when compacting them was interrupted.
+        epoch_ending_backups.sort();
+        epoch_ending_backups.dedup();
+        state_snapshot_backups.sort();
+        state_snapshot_backups.dedup();
+        transaction_backups.sort();
+        transaction_backups.dedup();
+
         Self {
             epoch_ending_backups,
             state_snapshot_backups,
             transaction_backups,
-            _identity: identity,
+            identity,
         }
     }
 }
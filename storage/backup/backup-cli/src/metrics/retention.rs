@@ -0,0 +1,37 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_push_metrics::{register_int_gauge, IntGauge};
+use once_cell::sync::Lazy;
+
+pub static RETENTION_DELETED_FILES: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_backup_retention_deleted_files",
+        "Number of files deleted by the last retention run."
+    )
+    .unwrap()
+});
+
+pub static RETENTION_COORDINATOR_START_TS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_backup_retention_coordinator_start_timestamp_s",
+        "Timestamp when the retention coordinator starts."
+    )
+    .unwrap()
+});
+
+pub static RETENTION_COORDINATOR_SUCC_TS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_backup_retention_coordinator_succeed_timestamp_s",
+        "Timestamp when the retention coordinator succeeds."
+    )
+    .unwrap()
+});
+
+pub static RETENTION_COORDINATOR_FAIL_TS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_backup_retention_coordinator_fail_timestamp_s",
+        "Timestamp when the retention coordinator fails."
+    )
+    .unwrap()
+});
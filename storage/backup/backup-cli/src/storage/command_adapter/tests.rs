@@ -6,7 +6,7 @@ use crate::storage::{
     command_adapter::config::Commands,
     test_util::{
         arb_backups, arb_metadata_files, test_save_and_list_metadata_files_impl,
-        test_write_and_read_impl,
+        test_save_lines_and_delete_impl, test_write_and_read_impl,
     },
 };
 use aptos_temppath::TempPath;
@@ -29,6 +29,7 @@ commands:
   open_for_read: 'cat "$FOLDER/$FILE_HANDLE"'
   save_metadata_line: 'cd "$FOLDER" && mkdir -p metadata && cd metadata && cat > $FILE_NAME'
   list_metadata_files: 'cd "$FOLDER" && (test -d metadata && cd metadata && ls -1 || exec) | while read f; do echo metadata/$f; done'
+  delete_file: 'rm -f "$FOLDER/$FILE_HANDLE"'
 "#, tmpdir.path().to_str().unwrap()),
     ).unwrap();
 
@@ -59,6 +60,12 @@ proptest! {
     }
 }
 
+#[test]
+fn test_save_lines_and_delete() {
+    let tmpdir = TempPath::new();
+    block_on(test_save_lines_and_delete_impl(get_store(&tmpdir)));
+}
+
 fn dummy_store(cmd: &str) -> CommandAdapter {
     CommandAdapter::new(CommandAdapterConfig {
         commands: Commands {
@@ -67,11 +74,20 @@ fn dummy_store(cmd: &str) -> CommandAdapter {
             open_for_read: cmd.to_string(),
             save_metadata_line: cmd.to_string(),
             list_metadata_files: cmd.to_string(),
+            delete_file: Some(cmd.to_string()),
         },
         env_vars: Vec::new(),
     })
 }
 
+#[test]
+fn test_supports_retention() {
+    let mut store = dummy_store("true");
+    assert!(store.supports_retention());
+    store.config.commands.delete_file = None;
+    assert!(!store.supports_retention());
+}
+
 async fn assert_commands_error(cmd: &str) {
     let name = ShellSafeName::from_str("name").unwrap();
 
@@ -113,6 +129,9 @@ async fn assert_commands_error(cmd: &str) {
 
     // list_metadata_files
     assert!(store.list_metadata_files().await.is_err());
+
+    // delete_file
+    assert!(store.delete_file(handle).await.is_err());
 }
 
 async fn assert_commands_okay(cmd: &str) {
@@ -148,7 +167,10 @@ async fn assert_commands_okay(cmd: &str) {
         .unwrap();
 
     // list_metadata_files
-    assert_eq!(store.list_metadata_files().await.unwrap(), vec!["okay"])
+    assert_eq!(store.list_metadata_files().await.unwrap(), vec!["okay"]);
+
+    // delete_file
+    store.delete_file(handle).await.unwrap();
 }
 
 #[test]
@@ -3,6 +3,7 @@
 
 pub mod command_adapter;
 pub mod local_fs;
+pub mod s3;
 
 #[cfg(test)]
 mod test_util;
@@ -12,8 +13,9 @@ mod tests;
 use crate::storage::{
     command_adapter::{CommandAdapter, CommandAdapterOpt},
     local_fs::{LocalFs, LocalFsOpt},
+    s3::{S3Opt, S3},
 };
-use anyhow::{ensure, Result};
+use anyhow::{bail, ensure, Result};
 use async_trait::async_trait;
 use clap::Parser;
 use once_cell::sync::Lazy;
@@ -44,6 +46,8 @@ pub type FileHandleRef = str;
 /// `create_backup()` and `create_for_write()` don't contain funny characters tricky to deal with
 /// in shell commands.
 /// Specifically, names follow the pattern "\A[a-zA-Z0-9][a-zA-Z0-9._-]{2,126}\z"
+/// Storages that don't go through a shell, like `S3`, escape names themselves and don't rely on
+/// this.
 #[cfg_attr(test, derive(Hash, Eq, PartialEq))]
 #[derive(Debug)]
 pub struct ShellSafeName(String);
@@ -160,6 +164,15 @@ pub trait BackupStorage: Send + Sync {
     /// is straightforward and acceptable.
     /// See `list_metadata_files`.
     async fn save_metadata_line(&self, name: &ShellSafeName, content: &TextLine) -> Result<()>;
+    /// Like `save_metadata_line`, but saves several metadata entries in the same file. Used to
+    /// compact the metadata files into fewer ones.
+    async fn save_metadata_lines(&self, name: &ShellSafeName, _lines: &[TextLine]) -> Result<()> {
+        bail!(
+            "This storage doesn't support saving several metadata entries in one file, can't \
+            save {}.",
+            name.as_ref(),
+        )
+    }
     /// The backup system always asks for all metadata files and cache and build index on top of
     /// the content of them. This means:
     ///   1. The storage is free to reorganise the metadata files, like combining multiple ones to
@@ -167,6 +180,20 @@ pub trait BackupStorage: Send + Sync {
     ///   2. But the cache does expect the content stays the same for a file handle, so when
     /// reorganising metadata files, give them new unique names.
     async fn list_metadata_files(&self) -> Result<Vec<FileHandle>>;
+    /// Deletes a file returned by `create_for_write()` or `list_metadata_files()`, to apply a
+    /// retention policy. Deleting a file that doesn't exist is not an error, so an interrupted
+    /// deletion can be retried.
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        bail!(
+            "This storage doesn't support deleting files, can't delete {}.",
+            file_handle
+        )
+    }
+    /// Whether `save_metadata_lines()` and `delete_file()` are supported, which applying a
+    /// retention policy needs.
+    fn supports_retention(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Parser)]
@@ -180,6 +207,11 @@ pub enum StorageOpt {
     https://github.com/aptos-labs/aptos-core/tree/main/storage/backup/backup-cli/src/storage/command_adapter/sample_configs/"
     )]
     CommandAdapter(CommandAdapterOpt),
+    #[clap(
+        about = "Select the S3 backup storage type, which talks to AWS S3 or another S3 compatible \
+    object store directly, uploading and downloading files in parts."
+    )]
+    S3(S3Opt),
 }
 
 impl StorageOpt {
@@ -187,6 +219,7 @@ impl StorageOpt {
         Ok(match self {
             StorageOpt::LocalFs(opt) => Arc::new(LocalFs::new_with_opt(opt)),
             StorageOpt::CommandAdapter(opt) => Arc::new(CommandAdapter::new_with_opt(opt).await?),
+            StorageOpt::S3(opt) => Arc::new(S3::new_with_opt(opt)?),
         })
     }
 }
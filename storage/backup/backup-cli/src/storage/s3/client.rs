@@ -0,0 +1,443 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::storage::s3::{
+    credentials::{Credentials, CredentialsProvider},
+    S3Opt,
+};
+use anyhow::{anyhow, bail, ensure, Result};
+use aptos_logger::prelude::*;
+use bytes::Bytes;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use itertools::Itertools;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::{header::HeaderMap, Method, StatusCode, Url};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, time::Duration};
+
+/// Characters left as is when encoding a URI for signing, see
+/// https://docs.aws.amazon.com/AmazonS3/latest/API/sig-v4-header-based-auth.html
+const URI_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+const KEY_ENCODE_SET: &AsciiSet = &URI_ENCODE_SET.remove(b'/');
+const EMPTY_PAYLOAD_SHA256: &str =
+    "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(200);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// A minimal client of the S3 REST API, signing requests with AWS Signature Version 4.
+/// Keys are percent-encoded, so they can hold any character.
+pub(super) struct S3Client {
+    http: reqwest::Client,
+    endpoint: Url,
+    bucket: String,
+    region: String,
+    path_style: bool,
+    credentials: CredentialsProvider,
+    max_retries: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListBucketResult {
+    #[serde(default)]
+    contents: Vec<ListedObject>,
+    is_truncated: bool,
+    next_continuation_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListedObject {
+    key: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct InitiateMultipartUploadResult {
+    upload_id: String,
+}
+
+impl S3Client {
+    pub fn new(opt: &S3Opt) -> Result<Self> {
+        let endpoint = match &opt.endpoint {
+            Some(endpoint) => endpoint.clone(),
+            None => Url::parse(&format!("https://s3.{}.amazonaws.com", opt.region))?,
+        };
+        ensure!(
+            endpoint.host_str().is_some(),
+            "Endpoint has no host: {}",
+            endpoint
+        );
+        // Requests are made to the root of the endpoint, so anything after the host would be lost.
+        ensure!(
+            endpoint.path() == "/" && endpoint.query().is_none() && endpoint.fragment().is_none(),
+            "Endpoint must not have a path or a query: {}",
+            endpoint
+        );
+        Ok(Self {
+            http: reqwest::Client::builder()
+                .connect_timeout(Duration::from_secs(opt.connect_timeout_secs))
+                .timeout(Duration::from_secs(opt.timeout_secs))
+                .build()?,
+            endpoint,
+            bucket: opt.bucket.clone(),
+            region: opt.region.clone(),
+            path_style: opt.path_style,
+            credentials: CredentialsProvider::new(opt)?,
+            max_retries: opt.max_retries,
+        })
+    }
+
+    pub async fn put_object(&self, key: &str, body: Bytes) -> Result<()> {
+        self.call(Method::PUT, key, &[], None, body).await?;
+        Ok(())
+    }
+
+    /// Succeeds if the object doesn't exist.
+    pub async fn delete_object(&self, key: &str) -> Result<()> {
+        self.call(Method::DELETE, key, &[], None, Bytes::new())
+            .await?;
+        Ok(())
+    }
+
+    /// Returns the size of the object.
+    pub async fn head_object(&self, key: &str) -> Result<u64> {
+        let (headers, _) = self
+            .call(Method::HEAD, key, &[], None, Bytes::new())
+            .await?;
+        Ok(headers
+            .get(reqwest::header::CONTENT_LENGTH)
+            .ok_or_else(|| anyhow!("No content length for {}", key))?
+            .to_str()?
+            .parse()?)
+    }
+
+    /// Reads bytes `start` to `end` of the object, both inclusive.
+    pub async fn get_object_range(&self, key: &str, start: u64, end: u64) -> Result<Bytes> {
+        let range = format!("bytes={}-{}", start, end);
+        let (_, body) = self
+            .call(Method::GET, key, &[], Some(range), Bytes::new())
+            .await?;
+        ensure!(
+            body.len() as u64 == end - start + 1,
+            "Expected {} bytes of {}, got {}.",
+            end - start + 1,
+            key,
+            body.len(),
+        );
+        Ok(body)
+    }
+
+    /// Lists the keys of all objects whose key starts with `prefix`.
+    pub async fn list_objects(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let mut query = vec![
+                ("list-type", "2".to_string()),
+                ("prefix", prefix.to_string()),
+            ];
+            if let Some(token) = continuation_token.take() {
+                query.push(("continuation-token", token));
+            }
+            let (_, body) = self
+                .call(Method::GET, "", &query, None, Bytes::new())
+                .await?;
+            let result: ListBucketResult = quick_xml::de::from_str(std::str::from_utf8(&body)?)?;
+            keys.extend(result.contents.into_iter().map(|object| object.key));
+            match result.next_continuation_token {
+                Some(token) if result.is_truncated => continuation_token = Some(token),
+                _ => break,
+            }
+        }
+        Ok(keys)
+    }
+
+    pub async fn create_multipart_upload(&self, key: &str) -> Result<String> {
+        let query = [("uploads", String::new())];
+        let (_, body) = self
+            .call(Method::POST, key, &query, None, Bytes::new())
+            .await?;
+        let result: InitiateMultipartUploadResult =
+            quick_xml::de::from_str(std::str::from_utf8(&body)?)?;
+        Ok(result.upload_id)
+    }
+
+    /// Returns the ETag of the part, needed to complete the upload.
+    pub async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_number: usize,
+        body: Bytes,
+    ) -> Result<String> {
+        let query = [
+            ("partNumber", part_number.to_string()),
+            ("uploadId", upload_id.to_string()),
+        ];
+        let (headers, _) = self.call(Method::PUT, key, &query, None, body).await?;
+        Ok(headers
+            .get(reqwest::header::ETAG)
+            .ok_or_else(|| anyhow!("No ETag for part {} of {}", part_number, key))?
+            .to_str()?
+            .to_string())
+    }
+
+    /// `etags` are the ETags of the parts, in order.
+    pub async fn complete_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        etags: &[String],
+    ) -> Result<()> {
+        let query = [("uploadId", upload_id.to_string())];
+        let parts = etags
+            .iter()
+            .enumerate()
+            .map(|(i, etag)| {
+                format!(
+                    "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                    i + 1,
+                    etag
+                )
+            })
+            .join("");
+        let body = format!(
+            "<CompleteMultipartUpload>{}</CompleteMultipartUpload>",
+            parts
+        );
+        let (_, response) = self
+            .call(Method::POST, key, &query, None, body.into())
+            .await?;
+        // Failures after the upload started are reported with a 200 status and an error body.
+        ensure!(
+            !String::from_utf8_lossy(&response).contains("<Error>"),
+            "Failed to complete upload of {}: {}",
+            key,
+            String::from_utf8_lossy(&response),
+        );
+        Ok(())
+    }
+
+    pub async fn abort_multipart_upload(&self, key: &str, upload_id: &str) -> Result<()> {
+        let query = [("uploadId", upload_id.to_string())];
+        self.call(Method::DELETE, key, &query, None, Bytes::new())
+            .await?;
+        Ok(())
+    }
+
+    /// Sends a request, retrying with backoff on network errors, throttling and server errors,
+    /// and returns the response headers and body.
+    async fn call(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, String)],
+        range: Option<String>,
+        body: Bytes,
+    ) -> Result<(HeaderMap, Bytes)> {
+        let mut delay = INITIAL_RETRY_DELAY;
+        let mut attempt = 0;
+        loop {
+            let err = match self
+                .call_once(method.clone(), key, query, range.clone(), body.clone())
+                .await
+            {
+                Ok(CallResult::Done(headers, body)) => return Ok((headers, body)),
+                Ok(CallResult::Failed(status, body)) => {
+                    let err = anyhow!(
+                        "{} {} failed with {}: {}",
+                        method,
+                        key,
+                        status,
+                        String::from_utf8_lossy(&body),
+                    );
+                    if !is_retryable(status) {
+                        return Err(err);
+                    }
+                    err
+                }
+                Err(err) => err,
+            };
+            if attempt >= self.max_retries {
+                bail!("Giving up after {} retries: {}", attempt, err);
+            }
+            attempt += 1;
+            warn!(
+                error = %err,
+                attempt = attempt,
+                "S3 request failed, retrying in {:?}.",
+                delay,
+            );
+            tokio::time::sleep(delay).await;
+            delay = std::cmp::min(delay * 2, MAX_RETRY_DELAY);
+        }
+    }
+
+    async fn call_once(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, String)],
+        range: Option<String>,
+        body: Bytes,
+    ) -> Result<CallResult> {
+        let canonical_uri = if self.path_style && key.is_empty() {
+            format!("/{}", uri_encode(&self.bucket, false))
+        } else if self.path_style {
+            format!(
+                "/{}/{}",
+                uri_encode(&self.bucket, false),
+                uri_encode(key, true)
+            )
+        } else {
+            format!("/{}", uri_encode(key, true))
+        };
+        let canonical_query = query
+            .iter()
+            .map(|(k, v)| (uri_encode(k, false), uri_encode(v, false)))
+            .sorted()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .join("&");
+        let mut host = self
+            .endpoint
+            .host_str()
+            .expect("Checked on creation.")
+            .to_string();
+        if !self.path_style {
+            host = format!("{}.{}", self.bucket, host);
+        }
+        if let Some(port) = self.endpoint.port() {
+            host = format!("{}:{}", host, port);
+        }
+        let mut url = format!("{}://{}{}", self.endpoint.scheme(), host, canonical_uri);
+        if !canonical_query.is_empty() {
+            url = format!("{}?{}", url, canonical_query);
+        }
+
+        let payload_hash = if body.is_empty() {
+            EMPTY_PAYLOAD_SHA256.to_string()
+        } else {
+            hex::encode(Sha256::digest(&body))
+        };
+        let mut headers = BTreeMap::new();
+        headers.insert("host".to_string(), host);
+        headers.insert("x-amz-content-sha256".to_string(), payload_hash.clone());
+        headers.insert(
+            "x-amz-date".to_string(),
+            Utc::now().format("%Y%m%dT%H%M%SZ").to_string(),
+        );
+        if let Some(range) = range {
+            headers.insert("range".to_string(), range);
+        }
+        let credentials = self.credentials.get(&self.http).await?;
+        if let Some(token) = &credentials.session_token {
+            headers.insert("x-amz-security-token".to_string(), token.clone());
+        }
+        let authorization = authorization(
+            &credentials,
+            &self.region,
+            method.as_str(),
+            &canonical_uri,
+            &canonical_query,
+            &headers,
+            &payload_hash,
+        );
+
+        let mut request = self
+            .http
+            .request(method, Url::parse(&url)?)
+            .header(reqwest::header::AUTHORIZATION, authorization)
+            .body(body);
+        for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.bytes().await?;
+        Ok(if status.is_success() {
+            CallResult::Done(headers, body)
+        } else {
+            CallResult::Failed(status, body)
+        })
+    }
+}
+
+enum CallResult {
+    Done(HeaderMap, Bytes),
+    Failed(StatusCode, Bytes),
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+}
+
+pub(super) fn uri_encode(value: &str, is_key: bool) -> String {
+    let set = if is_key {
+        KEY_ENCODE_SET
+    } else {
+        URI_ENCODE_SET
+    };
+    utf8_percent_encode(value, set).to_string()
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any size.");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Computes the `Authorization` header of a request. `headers` are the headers to sign with
+/// lowercase names, and must include `x-amz-date`.
+pub(super) fn authorization(
+    credentials: &Credentials,
+    region: &str,
+    method: &str,
+    canonical_uri: &str,
+    canonical_query: &str,
+    headers: &BTreeMap<String, String>,
+    payload_hash: &str,
+) -> String {
+    let amz_date = &headers["x-amz-date"];
+    let date = &amz_date[..8];
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let signed_headers = headers.keys().join(";");
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        method, canonical_uri, canonical_query, canonical_headers, signed_headers, payload_hash
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date, region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let mut signing_key = hmac_sha256(
+        format!("AWS4{}", credentials.secret_access_key).as_bytes(),
+        date,
+    );
+    for data in [region, "s3", "aws4_request"] {
+        signing_key = hmac_sha256(&signing_key, data);
+    }
+    let signature = hex::encode(hmac_sha256(&signing_key, &string_to_sign));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        credentials.access_key_id, scope, signed_headers, signature
+    )
+}
@@ -0,0 +1,241 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::storage::s3::S3Opt;
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use reqwest::Url;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+
+/// Temporary credentials are refreshed this long before they expire.
+const REFRESH_MARGIN_SECS: i64 = 300;
+/// Lifetime of the session tokens asked of the instance metadata service.
+const IMDS_TOKEN_TTL_SECS: u32 = 21600;
+
+#[derive(Clone)]
+pub(super) struct Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+/// Where the credentials come from when no access key is given.
+enum Source {
+    Static(Credentials),
+    /// Assumes a role with a web identity token, as given to pods by EKS.
+    WebIdentity {
+        sts_endpoint: Url,
+        role_arn: String,
+        role_session_name: String,
+        token_file: PathBuf,
+    },
+    /// The role of the EC2 instance, read from the instance metadata service (IMDSv2).
+    InstanceProfile {
+        imds_endpoint: Url,
+    },
+}
+
+/// Hands out the credentials to sign requests with, fetching temporary ones again when they
+/// are about to expire.
+pub(super) struct CredentialsProvider {
+    source: Source,
+    cached: Mutex<Option<(Credentials, DateTime<Utc>)>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct AssumeRoleWithWebIdentityResponse {
+    assume_role_with_web_identity_result: AssumeRoleWithWebIdentityResult,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct AssumeRoleWithWebIdentityResult {
+    credentials: StsCredentials,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct StsCredentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: String,
+    expiration: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct InstanceProfileCredentials {
+    access_key_id: String,
+    secret_access_key: String,
+    token: String,
+    expiration: String,
+}
+
+impl CredentialsProvider {
+    pub fn new(opt: &S3Opt) -> Result<Self> {
+        let source = match (&opt.access_key_id, &opt.secret_access_key) {
+            (Some(access_key_id), Some(secret_access_key)) => Source::Static(Credentials {
+                access_key_id: access_key_id.clone(),
+                secret_access_key: secret_access_key.clone(),
+                session_token: opt.session_token.clone(),
+            }),
+            (None, None) => match (&opt.web_identity_token_file, &opt.role_arn) {
+                (Some(token_file), Some(role_arn)) => Source::WebIdentity {
+                    sts_endpoint: match &opt.sts_endpoint {
+                        Some(endpoint) => endpoint.clone(),
+                        None => Url::parse(&format!("https://sts.{}.amazonaws.com", opt.region))?,
+                    },
+                    role_arn: role_arn.clone(),
+                    role_session_name: opt.role_session_name.clone(),
+                    token_file: token_file.clone(),
+                },
+                (None, None) => Source::InstanceProfile {
+                    imds_endpoint: opt.imds_endpoint.clone(),
+                },
+                _ => bail!("A web identity token file and a role ARN must be given together."),
+            },
+            _ => bail!("An access key id and a secret access key must be given together."),
+        };
+        Ok(Self {
+            source,
+            cached: Mutex::new(None),
+        })
+    }
+
+    pub async fn get(&self, http: &reqwest::Client) -> Result<Credentials> {
+        if let Source::Static(credentials) = &self.source {
+            return Ok(credentials.clone());
+        }
+        let mut cached = self.cached.lock().await;
+        if let Some((credentials, expiration)) = cached.as_ref() {
+            if *expiration - Duration::seconds(REFRESH_MARGIN_SECS) > Utc::now() {
+                return Ok(credentials.clone());
+            }
+        }
+        let (credentials, expiration) = match &self.source {
+            Source::Static(_) => unreachable!("Returned above."),
+            Source::WebIdentity {
+                sts_endpoint,
+                role_arn,
+                role_session_name,
+                token_file,
+            } => {
+                assume_role_with_web_identity(
+                    http,
+                    sts_endpoint,
+                    role_arn,
+                    role_session_name,
+                    token_file,
+                )
+                .await?
+            }
+            Source::InstanceProfile { imds_endpoint } => {
+                instance_profile_credentials(http, imds_endpoint).await?
+            }
+        };
+        *cached = Some((credentials.clone(), expiration));
+        Ok(credentials)
+    }
+}
+
+async fn assume_role_with_web_identity(
+    http: &reqwest::Client,
+    sts_endpoint: &Url,
+    role_arn: &str,
+    role_session_name: &str,
+    token_file: &Path,
+) -> Result<(Credentials, DateTime<Utc>)> {
+    // The token is rotated on disk, so it's read again every time.
+    let token = tokio::fs::read_to_string(token_file)
+        .await
+        .with_context(|| format!("Failed to read web identity token {:?}", token_file))?;
+    let response = http
+        .get(sts_endpoint.clone())
+        .query(&[
+            ("Action", "AssumeRoleWithWebIdentity"),
+            ("Version", "2011-06-15"),
+            ("RoleArn", role_arn),
+            ("RoleSessionName", role_session_name),
+            ("WebIdentityToken", token.trim()),
+        ])
+        .send()
+        .await?;
+    let status = response.status();
+    let body = response.text().await?;
+    ensure!(
+        status.is_success(),
+        "AssumeRoleWithWebIdentity failed with {}: {}",
+        status,
+        body
+    );
+    let credentials = quick_xml::de::from_str::<AssumeRoleWithWebIdentityResponse>(&body)?
+        .assume_role_with_web_identity_result
+        .credentials;
+    Ok((
+        Credentials {
+            access_key_id: credentials.access_key_id,
+            secret_access_key: credentials.secret_access_key,
+            session_token: Some(credentials.session_token),
+        },
+        parse_expiration(&credentials.expiration)?,
+    ))
+}
+
+async fn instance_profile_credentials(
+    http: &reqwest::Client,
+    imds_endpoint: &Url,
+) -> Result<(Credentials, DateTime<Utc>)> {
+    let response = http
+        .put(imds_endpoint.join("latest/api/token")?)
+        .header("x-aws-ec2-metadata-token-ttl-seconds", IMDS_TOKEN_TTL_SECS)
+        .send()
+        .await
+        .context("No credentials given and the instance metadata service is unreachable")?;
+    ensure!(
+        response.status().is_success(),
+        "Failed to get an instance metadata token: {}",
+        response.status()
+    );
+    let token = response.text().await?;
+
+    let roles_url = imds_endpoint.join("latest/meta-data/iam/security-credentials/")?;
+    let roles = imds_get(http, roles_url.clone(), &token).await?;
+    let role = roles
+        .lines()
+        .next()
+        .ok_or_else(|| anyhow!("The instance has no IAM role."))?;
+    let credentials: InstanceProfileCredentials =
+        serde_json::from_str(&imds_get(http, roles_url.join(role)?, &token).await?)?;
+    Ok((
+        Credentials {
+            access_key_id: credentials.access_key_id,
+            secret_access_key: credentials.secret_access_key,
+            session_token: Some(credentials.token),
+        },
+        parse_expiration(&credentials.expiration)?,
+    ))
+}
+
+async fn imds_get(http: &reqwest::Client, url: Url, token: &str) -> Result<String> {
+    let response = http
+        .get(url.clone())
+        .header("x-aws-ec2-metadata-token", token)
+        .send()
+        .await?;
+    ensure!(
+        response.status().is_success(),
+        "GET {} failed with {}",
+        url,
+        response.status()
+    );
+    Ok(response.text().await?)
+}
+
+fn parse_expiration(expiration: &str) -> Result<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(expiration)
+        .with_context(|| format!("Bad expiration time {}", expiration))?
+        .with_timezone(&Utc))
+}
@@ -0,0 +1,403 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+mod client;
+mod credentials;
+#[cfg(test)]
+mod tests;
+
+use super::{BackupHandle, BackupHandleRef, FileHandle, FileHandleRef};
+
+use crate::{
+    storage::{s3::client::S3Client, BackupStorage, ShellSafeName, TextLine},
+    utils::error_notes::ErrorNotes,
+};
+use anyhow::{ensure, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use clap::Parser;
+use futures::{
+    ready,
+    stream::{self, FuturesOrdered},
+    task::{Context, Poll},
+    Future, StreamExt,
+};
+use reqwest::Url;
+use std::{path::PathBuf, pin::Pin, sync::Arc};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, DuplexStream},
+    task::JoinHandle,
+};
+use tokio_util::io::StreamReader;
+
+/// S3 doesn't accept smaller parts in multipart uploads, except for the last one.
+const MIN_PART_SIZE: usize = 5 * 1024 * 1024;
+/// Size of the buffer between a file being written and its upload.
+const PIPE_BUFFER_SIZE: usize = 64 * 1024;
+
+#[derive(Parser)]
+pub struct S3Opt {
+    #[clap(long = "bucket", help = "Bucket holding the backups.")]
+    pub bucket: String,
+    #[clap(
+        long = "prefix",
+        default_value = "",
+        help = "Prefix of the keys of everything stored, like a folder in the bucket."
+    )]
+    pub prefix: String,
+    #[clap(
+        long = "region",
+        default_value = "us-east-1",
+        help = "Region of the bucket."
+    )]
+    pub region: String,
+    #[clap(
+        long = "endpoint",
+        help = "Endpoint of an S3 compatible store, like http://localhost:9000, with no \
+        path. [Defaults to AWS S3 in the region]"
+    )]
+    pub endpoint: Option<Url>,
+    #[clap(
+        long = "path-style",
+        help = "Address the bucket as <endpoint>/<bucket> instead of <bucket>.<endpoint host>. Most \
+        S3 compatible stores other than AWS need this."
+    )]
+    pub path_style: bool,
+    #[clap(
+        long = "access-key-id",
+        env = "AWS_ACCESS_KEY_ID",
+        hide_env_values = true,
+        help = "Without access keys, credentials come from a web identity token if one is given, \
+        otherwise from the instance profile."
+    )]
+    pub access_key_id: Option<String>,
+    #[clap(
+        long = "secret-access-key",
+        env = "AWS_SECRET_ACCESS_KEY",
+        hide_env_values = true
+    )]
+    pub secret_access_key: Option<String>,
+    #[clap(
+        long = "session-token",
+        env = "AWS_SESSION_TOKEN",
+        hide_env_values = true
+    )]
+    pub session_token: Option<String>,
+    #[clap(
+        long = "web-identity-token-file",
+        env = "AWS_WEB_IDENTITY_TOKEN_FILE",
+        help = "File holding a web identity token to assume --role-arn with."
+    )]
+    pub web_identity_token_file: Option<PathBuf>,
+    #[clap(long = "role-arn", env = "AWS_ROLE_ARN")]
+    pub role_arn: Option<String>,
+    #[clap(
+        long = "role-session-name",
+        env = "AWS_ROLE_SESSION_NAME",
+        default_value = "db-backup"
+    )]
+    pub role_session_name: String,
+    #[clap(long = "sts-endpoint", help = "[Defaults to AWS STS in the region]")]
+    pub sts_endpoint: Option<Url>,
+    #[clap(
+        long = "imds-endpoint",
+        default_value = "http://169.254.169.254",
+        help = "Instance metadata service to get instance profile credentials from."
+    )]
+    pub imds_endpoint: Url,
+    #[clap(
+        long = "part-size",
+        default_value = "16777216",
+        help = "Files are uploaded and downloaded in parts of this many bytes. At least 5MiB."
+    )]
+    pub part_size: usize,
+    #[clap(
+        long = "concurrent-parts",
+        default_value = "4",
+        help = "Number of parts of a single file uploaded or downloaded at the same time."
+    )]
+    pub concurrent_parts: usize,
+    #[clap(
+        long = "max-retries",
+        default_value = "5",
+        help = "Number of times a failed request is retried, with exponential backoff."
+    )]
+    pub max_retries: usize,
+    #[clap(
+        long = "connect-timeout-secs",
+        default_value = "10",
+        help = "Timeout of connecting to the store."
+    )]
+    pub connect_timeout_secs: u64,
+    #[clap(
+        long = "timeout-secs",
+        default_value = "300",
+        help = "Timeout of a single request, including transferring its body."
+    )]
+    pub timeout_secs: u64,
+}
+
+/// A storage backend that stores everything in an S3 compatible object store, talking to it
+/// directly instead of through shell commands.
+pub struct S3 {
+    client: Arc<S3Client>,
+    prefix: String,
+    part_size: usize,
+    concurrent_parts: usize,
+}
+
+impl S3 {
+    const METADATA_DIR: &'static str = "metadata";
+
+    pub fn new_with_opt(opt: S3Opt) -> Result<Self> {
+        ensure!(
+            opt.part_size >= MIN_PART_SIZE,
+            "Part size must be at least {} bytes.",
+            MIN_PART_SIZE,
+        );
+        Self::new(opt)
+    }
+
+    fn new(opt: S3Opt) -> Result<Self> {
+        ensure!(
+            opt.concurrent_parts > 0,
+            "Concurrent parts must be positive."
+        );
+        Ok(Self {
+            client: Arc::new(S3Client::new(&opt)?),
+            prefix: opt.prefix.trim_matches('/').to_string(),
+            part_size: opt.part_size,
+            concurrent_parts: opt.concurrent_parts,
+        })
+    }
+
+    fn key(&self, file_handle: &FileHandleRef) -> String {
+        if self.prefix.is_empty() {
+            file_handle.to_string()
+        } else {
+            format!("{}/{}", self.prefix, file_handle)
+        }
+    }
+}
+
+#[async_trait]
+impl BackupStorage for S3 {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        // There are no folders to create in an object store.
+        Ok(name.to_string())
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let file_handle = format!("{}/{}", backup_handle, name.as_ref());
+        let (pipe, reader) = tokio::io::duplex(PIPE_BUFFER_SIZE);
+        let upload = tokio::spawn(upload(
+            self.client.clone(),
+            self.key(&file_handle),
+            reader,
+            self.part_size,
+            self.concurrent_parts,
+        ));
+        Ok((file_handle, Box::new(UploadWriter::new(pipe, upload))))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let key = self.key(file_handle);
+        let size = self.client.head_object(&key).await.err_notes(&key)?;
+        let client = self.client.clone();
+        let part_size = self.part_size as u64;
+        let parts = stream::iter((0..size).step_by(self.part_size))
+            .map(move |start| {
+                let client = client.clone();
+                let key = key.clone();
+                async move {
+                    let end = std::cmp::min(start + part_size, size) - 1;
+                    client
+                        .get_object_range(&key, start, end)
+                        .await
+                        .err_notes(&key)
+                        .map_err(|e| tokio::io::Error::new(tokio::io::ErrorKind::Other, e))
+                }
+            })
+            .buffered(self.concurrent_parts);
+        Ok(Box::new(StreamReader::new(parts)))
+    }
+
+    async fn save_metadata_line(&self, name: &ShellSafeName, content: &TextLine) -> Result<()> {
+        self.save_metadata_lines(name, std::slice::from_ref(content))
+            .await
+    }
+
+    async fn save_metadata_lines(&self, name: &ShellSafeName, lines: &[TextLine]) -> Result<()> {
+        let key = self.key(&format!("{}/{}", Self::METADATA_DIR, name.as_ref()));
+        let content = lines.iter().map(AsRef::as_ref).collect::<String>();
+        self.client
+            .put_object(&key, content.into())
+            .await
+            .err_notes(&key)
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        let prefix = self.key(&format!("{}/", Self::METADATA_DIR));
+        let keys = self.client.list_objects(&prefix).await.err_notes(&prefix)?;
+        let key_prefix_len = self.key("").len();
+        Ok(keys
+            .into_iter()
+            .map(|key| key[key_prefix_len..].to_string())
+            .collect())
+    }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let key = self.key(file_handle);
+        self.client.delete_object(&key).await.err_notes(&key)
+    }
+
+    fn supports_retention(&self) -> bool {
+        true
+    }
+}
+
+/// Uploads everything read from `reader` to `key`, with a multipart upload if it's bigger than
+/// a part.
+async fn upload(
+    client: Arc<S3Client>,
+    key: String,
+    mut reader: DuplexStream,
+    part_size: usize,
+    concurrent_parts: usize,
+) -> Result<()> {
+    let first_part = read_part(&mut reader, part_size).await?;
+    if first_part.len() < part_size {
+        return client.put_object(&key, first_part).await.err_notes(&key);
+    }
+
+    let upload_id = client.create_multipart_upload(&key).await.err_notes(&key)?;
+    let res = upload_parts(
+        &client,
+        &key,
+        &upload_id,
+        first_part,
+        reader,
+        part_size,
+        concurrent_parts,
+    )
+    .await;
+    match res {
+        Ok(etags) => client
+            .complete_multipart_upload(&key, &upload_id, &etags)
+            .await
+            .err_notes(&key),
+        Err(e) => {
+            // Otherwise the uploaded parts are kept, and billed, until the bucket's lifecycle
+            // rules clean them up.
+            let _ = client
+                .abort_multipart_upload(&key, &upload_id)
+                .await
+                .err_notes(&key);
+            Err(e)
+        }
+    }
+}
+
+async fn upload_parts(
+    client: &S3Client,
+    key: &str,
+    upload_id: &str,
+    first_part: Bytes,
+    mut reader: DuplexStream,
+    part_size: usize,
+    concurrent_parts: usize,
+) -> Result<Vec<String>> {
+    let mut etags = Vec::new();
+    let mut in_flight = FuturesOrdered::new();
+    let mut part = first_part;
+    let mut part_number = 1;
+    while !part.is_empty() {
+        in_flight.push_back(client.upload_part(key, upload_id, part_number, part));
+        if in_flight.len() >= concurrent_parts {
+            etags.push(in_flight.next().await.expect("Not empty.").err_notes(key)?);
+        }
+        part = read_part(&mut reader, part_size).await?;
+        part_number += 1;
+    }
+    while let Some(etag) = in_flight.next().await {
+        etags.push(etag.err_notes(key)?);
+    }
+    Ok(etags)
+}
+
+/// Reads `part_size` bytes, or less if the end of the input is reached.
+async fn read_part(reader: &mut DuplexStream, part_size: usize) -> Result<Bytes> {
+    let mut buf = Vec::with_capacity(part_size);
+    reader.take(part_size as u64).read_to_end(&mut buf).await?;
+    Ok(buf.into())
+}
+
+/// Feeds the upload task of a file, and waits for the upload to finish on shutdown.
+struct UploadWriter {
+    pipe: DuplexStream,
+    upload: JoinHandle<Result<()>>,
+    pipe_shutdown: bool,
+    done: bool,
+}
+
+impl UploadWriter {
+    fn new(pipe: DuplexStream, upload: JoinHandle<Result<()>>) -> Self {
+        Self {
+            pipe,
+            upload,
+            pipe_shutdown: false,
+            done: false,
+        }
+    }
+}
+
+impl AsyncWrite for UploadWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, tokio::io::Error>> {
+        Pin::new(&mut self.pipe).poll_write(cx, buf)
+    }
+
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), tokio::io::Error>> {
+        Pin::new(&mut self.pipe).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), tokio::io::Error>> {
+        if !self.pipe_shutdown {
+            ready!(Pin::new(&mut self.pipe).poll_shutdown(cx))?;
+            self.pipe_shutdown = true;
+        }
+
+        let res = ready!(Pin::new(&mut self.upload).poll(cx));
+        self.done = true;
+        Poll::Ready(match res {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(e)) => Err(tokio::io::Error::new(tokio::io::ErrorKind::Other, e)),
+            Err(e) => Err(tokio::io::Error::new(tokio::io::ErrorKind::Other, e)),
+        })
+    }
+}
+
+impl Drop for UploadWriter {
+    fn drop(&mut self) {
+        // A file dropped before shutdown is incomplete, so it must not be uploaded.
+        if !self.done {
+            self.upload.abort();
+        }
+    }
+}
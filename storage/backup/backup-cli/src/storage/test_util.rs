@@ -11,7 +11,7 @@ use proptest::{
     collection::{hash_map, vec},
     prelude::*,
 };
-use std::{collections::HashMap, path::Path};
+use std::{collections::HashMap, path::Path, str::FromStr};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 fn to_file_name(backup_name: &str, file_name: &str) -> String {
@@ -102,3 +102,26 @@ pub fn arb_metadata_files() -> impl Strategy<Value = Vec<(ShellSafeName, TextLin
         .prop_map(HashMap::into_iter)
         .prop_map(Iterator::collect)
 }
+
+pub async fn test_save_lines_and_delete_impl(store: Box<dyn BackupStorage>) {
+    let name = ShellSafeName::from_str("compacted.meta").unwrap();
+    let lines = vec![TextLine::new("1").unwrap(), TextLine::new("2").unwrap()];
+    store.save_metadata_lines(&name, &lines).await.unwrap();
+
+    let file_handles = store.list_metadata_files().await.unwrap();
+    assert_eq!(file_handles.len(), 1);
+    let mut buf = String::new();
+    store
+        .open_for_read(&file_handles[0])
+        .await
+        .unwrap()
+        .read_to_string(&mut buf)
+        .await
+        .unwrap();
+    assert_eq!(buf, "1\n2\n");
+
+    store.delete_file(&file_handles[0]).await.unwrap();
+    assert!(store.list_metadata_files().await.unwrap().is_empty());
+    // Deleting again, like a retried deletion would, is fine.
+    store.delete_file(&file_handles[0]).await.unwrap();
+}
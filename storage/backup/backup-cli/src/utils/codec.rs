@@ -0,0 +1,328 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Optional compression and encryption of the chunk files of backups, i.e. the files holding the
+//! transactions, state values and ledger infos. Proofs and manifests are not encoded, they only
+//! hold hashes and signatures, and the manifests tell how their chunks are encoded.
+
+use aes_gcm::{
+    aead::{Aead, NewAead},
+    Aes256Gcm, Key, Nonce,
+};
+use anyhow::{anyhow, bail, ensure, Result};
+use aptos_compression::metrics::CompressionClient;
+use clap::Parser;
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{borrow::Cow, ffi::OsStr, fmt, str::FromStr};
+
+/// Largest block LZ4 compresses.
+const MAX_LZ4_BLOCK_BYTES: usize = 0x7E00_0000;
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Compression {
+    None,
+    Lz4,
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+impl FromStr for Compression {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "none" => Ok(Self::None),
+            "lz4" => Ok(Self::Lz4),
+            _ => bail!("Unknown compression: {}, expecting none or lz4.", s),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "algorithm", rename_all = "snake_case")]
+pub enum Encryption {
+    /// Each file is encrypted with its own random key, which is stored in front of it, encrypted
+    /// with the key identified by `key_id`.
+    Aes256GcmEnvelope { key_id: String },
+}
+
+/// How a chunk file is encoded, recorded in the manifest next to its file handle. Manifests
+/// written before chunk files could be encoded don't have it, and their chunk files are plain.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Codec {
+    #[serde(default)]
+    pub compression: Compression,
+    #[serde(default)]
+    pub encryption: Option<Encryption>,
+}
+
+impl Codec {
+    pub fn is_plain(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A key chunk files are encrypted with.
+#[derive(Clone, Eq, PartialEq)]
+pub struct EncryptionKey([u8; KEY_LEN]);
+
+impl EncryptionKey {
+    pub fn new(bytes: [u8; KEY_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn load_from_file(path: &OsStr) -> Result<Self, String> {
+        std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed reading {:?}: {}", path, e))?
+            .parse()
+            .map_err(|e| format!("Bad key in {:?}: {}", path, e))
+    }
+
+    /// Identifies the key in manifests, without revealing it.
+    pub fn id(&self) -> String {
+        hex::encode(&Sha256::digest(&self.0)[..8])
+    }
+
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut data_key = [0u8; KEY_LEN];
+        let mut key_nonce = [0u8; NONCE_LEN];
+        let mut data_nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut data_key);
+        OsRng.fill_bytes(&mut key_nonce);
+        OsRng.fill_bytes(&mut data_nonce);
+
+        let wrapped_key = seal(&self.0, &key_nonce, &data_key)?;
+        let ciphertext = seal(&data_key, &data_nonce, plaintext)?;
+        Ok([&key_nonce[..], &wrapped_key, &data_nonce, &ciphertext].concat())
+    }
+
+    fn decrypt(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        ensure!(
+            bytes.len() >= NONCE_LEN + KEY_LEN + TAG_LEN + NONCE_LEN + TAG_LEN,
+            "Encrypted chunk too short: {} bytes.",
+            bytes.len(),
+        );
+        let (key_nonce, rest) = bytes.split_at(NONCE_LEN);
+        let (wrapped_key, rest) = rest.split_at(KEY_LEN + TAG_LEN);
+        let (data_nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+        let data_key = open(&self.0, key_nonce, wrapped_key)?;
+        open(&data_key, data_nonce, ciphertext)
+    }
+}
+
+impl FromStr for EncryptionKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let bytes = hex::decode(s.trim())?;
+        Ok(Self(bytes.try_into().map_err(|bytes: Vec<u8>| {
+            anyhow!("Expecting {} bytes, got {}.", KEY_LEN, bytes.len())
+        })?))
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EncryptionKey({})", self.id())
+    }
+}
+
+fn seal(key: &[u8], nonce: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+    Aes256Gcm::new(Key::from_slice(key))
+        .encrypt(Nonce::from_slice(nonce), plaintext)
+        .map_err(|_| anyhow!("Encryption failed."))
+}
+
+fn open(key: &[u8], nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+    Aes256Gcm::new(Key::from_slice(key))
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow!("Decryption failed, the chunk is corrupted."))
+}
+
+#[derive(Clone, Default, Parser)]
+pub struct EncryptionKeyOpt {
+    #[clap(
+        long = "encryption-key-file",
+        parse(try_from_os_str = EncryptionKey::load_from_file),
+        help = "File holding a hex encoded 32 bytes key, as generated by `openssl rand -hex 32`. \
+        Chunk files of new backups are encrypted with it, and encrypted chunk files are decrypted \
+        with it. Losing it means losing the backups encrypted with it."
+    )]
+    pub encryption_key: Option<EncryptionKey>,
+}
+
+#[derive(Clone, Default, Parser)]
+pub struct ChunkCodecOpt {
+    #[clap(
+        long = "compression",
+        default_value = "none",
+        help = "Compression of the chunk files of new backups, none or lz4."
+    )]
+    pub compression: Compression,
+
+    #[clap(flatten)]
+    pub encryption_key: EncryptionKeyOpt,
+}
+
+/// Encodes the chunk files of new backups, and decodes chunk files according to the codec
+/// recorded in their manifests.
+#[derive(Clone, Debug, Default)]
+pub struct ChunkCoder {
+    compression: Compression,
+    encryption_key: Option<EncryptionKey>,
+}
+
+impl ChunkCoder {
+    pub fn new(compression: Compression, encryption_key: Option<EncryptionKey>) -> Self {
+        Self {
+            compression,
+            encryption_key,
+        }
+    }
+
+    pub fn new_with_opt(opt: ChunkCodecOpt) -> Self {
+        Self::new(opt.compression, opt.encryption_key.encryption_key)
+    }
+
+    /// The codec of the chunk files returned by `encode()`.
+    pub fn codec(&self) -> Codec {
+        Codec {
+            compression: self.compression,
+            encryption: self
+                .encryption_key
+                .as_ref()
+                .map(|key| Encryption::Aes256GcmEnvelope { key_id: key.id() }),
+        }
+    }
+
+    pub fn encode<'a>(&self, bytes: &'a [u8]) -> Result<Cow<'a, [u8]>> {
+        let compressed = match self.compression {
+            Compression::None => Cow::Borrowed(bytes),
+            Compression::Lz4 => Cow::Owned(aptos_compression::compress(
+                bytes.to_vec(),
+                CompressionClient::Backup,
+                MAX_LZ4_BLOCK_BYTES,
+            )?),
+        };
+        match &self.encryption_key {
+            None => Ok(compressed),
+            Some(key) => Ok(Cow::Owned(key.encrypt(&compressed)?)),
+        }
+    }
+
+    pub fn decode(&self, codec: &Codec, bytes: Vec<u8>) -> Result<Vec<u8>> {
+        let decrypted = match &codec.encryption {
+            None => bytes,
+            Some(Encryption::Aes256GcmEnvelope { key_id }) => {
+                let key = self.encryption_key.as_ref().ok_or_else(|| {
+                    anyhow!(
+                        "Chunk is encrypted with key {}, but no encryption key is given.",
+                        key_id
+                    )
+                })?;
+                ensure!(
+                    key.id() == *key_id,
+                    "Chunk is encrypted with key {}, not with the given key {}.",
+                    key_id,
+                    key.id(),
+                );
+                key.decrypt(&bytes)?
+            }
+        };
+        match codec.compression {
+            Compression::None => Ok(decrypted),
+            Compression::Lz4 => Ok(aptos_compression::decompress(
+                &decrypted,
+                CompressionClient::Backup,
+                MAX_LZ4_BLOCK_BYTES,
+            )?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{collection::vec, prelude::*};
+
+    fn key(byte: u8) -> EncryptionKey {
+        EncryptionKey::new([byte; KEY_LEN])
+    }
+
+    fn coders() -> Vec<ChunkCoder> {
+        vec![
+            ChunkCoder::default(),
+            ChunkCoder::new(Compression::Lz4, None),
+            ChunkCoder::new(Compression::None, Some(key(1))),
+            ChunkCoder::new(Compression::Lz4, Some(key(1))),
+        ]
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10))]
+
+        #[test]
+        fn test_encode_decode(bytes in vec(any::<u8>(), 0..10000)) {
+            for coder in coders() {
+                let encoded = coder.encode(&bytes).unwrap().into_owned();
+                let decoded = coder.decode(&coder.codec(), encoded).unwrap();
+                prop_assert_eq!(&decoded, &bytes);
+            }
+        }
+    }
+
+    #[test]
+    fn test_codec() {
+        assert!(ChunkCoder::default().codec().is_plain());
+        assert!(!ChunkCoder::new(Compression::Lz4, None).codec().is_plain());
+
+        // Old manifests don't have a codec.
+        let codec: Codec = serde_json::from_str("{}").unwrap();
+        assert!(codec.is_plain());
+        let codec = ChunkCoder::new(Compression::Lz4, Some(key(1))).codec();
+        assert_eq!(
+            serde_json::from_str::<Codec>(&serde_json::to_string(&codec).unwrap()).unwrap(),
+            codec
+        );
+    }
+
+    #[test]
+    fn test_wrong_key() {
+        let coder = ChunkCoder::new(Compression::None, Some(key(1)));
+        let encoded = coder.encode(b"chunk").unwrap().into_owned();
+
+        assert!(ChunkCoder::default()
+            .decode(&coder.codec(), encoded.clone())
+            .is_err());
+        assert!(ChunkCoder::new(Compression::None, Some(key(2)))
+            .decode(&coder.codec(), encoded.clone())
+            .is_err());
+        // Tampered with.
+        let mut tampered = encoded;
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(coder.decode(&coder.codec(), tampered).is_err());
+    }
+
+    #[test]
+    fn test_parse_key() {
+        let hex = "01".repeat(KEY_LEN);
+        assert_eq!(EncryptionKey::from_str(&hex).unwrap(), key(1));
+        assert_eq!(
+            EncryptionKey::from_str(&format!("{}\n", hex)).unwrap(),
+            key(1)
+        );
+        assert!(EncryptionKey::from_str("0101").is_err());
+        assert!(EncryptionKey::from_str("not hex").is_err());
+    }
+}
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod backup_service_client;
+pub mod codec;
 pub(crate) mod error_notes;
 pub mod read_record_bytes;
 pub mod storage_ext;
@@ -10,6 +11,7 @@ pub(crate) mod stream;
 #[cfg(test)]
 pub mod test_utils;
 
+use crate::utils::codec::{ChunkCodecOpt, ChunkCoder, Compression, EncryptionKeyOpt};
 use anyhow::{anyhow, Result};
 use aptos_config::config::{
     RocksdbConfig, RocksdbConfigs, BUFFERED_STATE_TARGET_ITEMS,
@@ -50,6 +52,9 @@ pub struct GlobalBackupOpt {
         help = "Maximum chunk file size in bytes."
     )]
     pub max_chunk_size: usize,
+
+    #[clap(flatten)]
+    pub codec: ChunkCodecOpt,
 }
 
 #[derive(Clone, Parser)]
@@ -132,6 +137,16 @@ pub struct GlobalRestoreOpt {
 
     #[clap(flatten)]
     pub replay_concurrency_level: ReplayConcurrencyLevelOpt,
+
+    #[clap(
+        long,
+        help = "Replay transactions by applying the write sets in the backup instead of executing \
+        them, which is much faster. The results are still checked against the transaction infos."
+    )]
+    pub replay_write_sets: bool,
+
+    #[clap(flatten)]
+    pub encryption_key: EncryptionKeyOpt,
 }
 
 pub enum RestoreRunMode {
@@ -240,6 +255,8 @@ pub struct GlobalRestoreOptions {
     pub run_mode: Arc<RestoreRunMode>,
     pub concurrent_downloads: usize,
     pub replay_concurrency_level: usize,
+    pub replay_write_sets: bool,
+    pub chunk_coder: ChunkCoder,
 }
 
 impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
@@ -270,6 +287,8 @@ impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
             run_mode: Arc::new(run_mode),
             concurrent_downloads,
             replay_concurrency_level,
+            replay_write_sets: opt.replay_write_sets,
+            chunk_coder: ChunkCoder::new(Compression::None, opt.encryption_key.encryption_key),
         })
     }
 }
@@ -1,19 +1,33 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::storage::{BackupHandle, BackupStorage, FileHandleRef};
+use crate::{
+    storage::{BackupHandle, BackupStorage, FileHandleRef},
+    utils::{
+        codec::{ChunkCoder, Codec},
+        error_notes::ErrorNotes,
+    },
+};
 use anyhow::Result;
 use async_trait::async_trait;
 use rand::random;
 use serde::de::DeserializeOwned;
-use std::{convert::TryInto, sync::Arc};
-use tokio::io::AsyncReadExt;
+use std::{convert::TryInto, io::Cursor, sync::Arc};
+use tokio::io::{AsyncRead, AsyncReadExt};
 
 #[async_trait]
 pub trait BackupStorageExt {
     async fn read_all(&self, file_handle: &FileHandleRef) -> Result<Vec<u8>>;
     async fn load_json_file<T: DeserializeOwned>(&self, file_handle: &FileHandleRef) -> Result<T>;
     async fn load_bcs_file<T: DeserializeOwned>(&self, file_handle: &FileHandleRef) -> Result<T>;
+    /// Opens a chunk file encoded with `codec`, decoding it with `coder`. Encoded files are read
+    /// and decoded as a whole, plain files are streamed.
+    async fn open_chunk_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+        codec: &Codec,
+        coder: &ChunkCoder,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>>;
     /// Adds a random suffix ".XXXX" to the backup name, so a retry won't pass a same backup name to
     /// the storage.
     async fn create_backup_with_random_suffix(&self, name: &str) -> Result<BackupHandle>;
@@ -36,6 +50,21 @@ impl BackupStorageExt for Arc<dyn BackupStorage> {
         Ok(serde_json::from_slice(&self.read_all(file_handle).await?)?)
     }
 
+    async fn open_chunk_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+        codec: &Codec,
+        coder: &ChunkCoder,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        if codec.is_plain() {
+            return self.open_for_read(file_handle).await;
+        }
+        let bytes = coder
+            .decode(codec, self.read_all(file_handle).await?)
+            .err_notes(file_handle)?;
+        Ok(Box::new(Cursor::new(bytes)))
+    }
+
     async fn create_backup_with_random_suffix(&self, name: &str) -> Result<BackupHandle> {
         self.create_backup(&format!("{}.{:04x}", name, random::<u16>()).try_into()?)
             .await
@@ -32,6 +32,7 @@ use aptos_types::{
         TransactionOutputListWithProof, TransactionToCommit, TransactionWithProof, Version,
     },
 };
+use move_core_types::language_storage::TypeTag;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, sync::Arc};
 use thiserror::Error;
@@ -99,6 +100,15 @@ pub enum Order {
     Descending,
 }
 
+/// A write to a state key, with the values before and after the transaction at `version`.
+/// `None` means the key didn't exist.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StateValueChange {
+    pub version: Version,
+    pub before: Option<StateValue>,
+    pub after: Option<StateValue>,
+}
+
 /// Trait that is implemented by a DB that supports certain public (to client) read APIs
 /// expected of an Aptos DB
 #[allow(unused_variables)]
@@ -206,6 +216,30 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Returns events of the given Move type emitted at or after `start_version` and no later
+    /// than `ledger_version`, in ascending order. Requires the optional event-by-type index.
+    fn get_events_by_type(
+        &self,
+        type_tag: &TypeTag,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<EventWithVersion>> {
+        unimplemented!()
+    }
+
+    /// Returns the writes to `state_key` made by the transactions in
+    /// `[start_version, start_version + limit)`, capped at `ledger_version`, in ascending order.
+    fn get_state_value_changes(
+        &self,
+        state_key: &StateKey,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<StateValueChange>> {
+        unimplemented!()
+    }
+
     /// See [AptosDB::get_block_timestamp].
     ///
     /// [AptosDB::get_block_timestamp]:
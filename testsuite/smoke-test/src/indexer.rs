@@ -2,23 +2,47 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_indexer::{
-    database::{new_db_pool, PgDbPool, PgPoolConnection},
+    database::{new_db_pool, ArcDbPool, DbConnection},
     models::transactions::TransactionQuery,
 };
 use aptos_sdk::types::LocalAccount;
 use cached_packages::aptos_stdlib::aptos_token_stdlib;
-use diesel::RunQueryDsl;
+use diesel::{sql_types::Text, QueryableByName, RunQueryDsl};
 use forge::{AptosPublicInfo, Result, Swarm};
 use std::sync::Arc;
 
-pub fn wipe_database(conn: &mut PgPoolConnection) {
-    for command in [
-        "DROP SCHEMA public CASCADE",
-        "CREATE SCHEMA public",
-        "GRANT ALL ON SCHEMA public TO postgres",
-        "GRANT ALL ON SCHEMA public TO public",
-    ] {
-        diesel::sql_query(command).execute(conn).unwrap();
+pub fn wipe_database(conn: &mut DbConnection) {
+    match conn {
+        DbConnection::Postgres(conn) => {
+            for command in [
+                "DROP SCHEMA public CASCADE",
+                "CREATE SCHEMA public",
+                "GRANT ALL ON SCHEMA public TO postgres",
+                "GRANT ALL ON SCHEMA public TO public",
+            ] {
+                diesel::sql_query(command).execute(conn).unwrap();
+            }
+        }
+        DbConnection::Sqlite(conn) => {
+            #[derive(QueryableByName)]
+            struct SchemaObject {
+                #[diesel(sql_type = Text)]
+                type_: String,
+                #[diesel(sql_type = Text)]
+                name: String,
+            }
+            let objects = diesel::sql_query(
+                "SELECT type AS type_, name FROM sqlite_master \
+                 WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%'",
+            )
+            .load::<SchemaObject>(conn)
+            .unwrap();
+            for object in objects {
+                diesel::sql_query(format!("DROP {} \"{}\"", object.type_, object.name))
+                    .execute(conn)
+                    .unwrap();
+            }
+        }
     }
 }
 
@@ -26,7 +50,7 @@ pub fn get_database_url() -> String {
     std::env::var("INDEXER_DATABASE_URL").expect("must set 'INDEXER_DATABASE_URL' to run tests!")
 }
 
-pub fn setup_indexer() -> anyhow::Result<PgDbPool> {
+pub fn setup_indexer() -> anyhow::Result<ArcDbPool> {
     let conn_pool = new_db_pool(get_database_url().as_str())?;
     wipe_database(&mut conn_pool.get()?);
     Ok(conn_pool)
@@ -105,8 +129,9 @@ async fn test_old_indexer() {
 
             config.indexer.enabled = true;
             config.indexer.postgres_uri = Some(get_database_url());
-            config.indexer.processor =
-                Some(aptos_indexer::processors::default_processor::NAME.to_string());
+            config.indexer.processor = Some(vec![
+                aptos_indexer::processors::default_processor::NAME.to_string(),
+            ]);
         }))
         .build()
         .await;